@@ -0,0 +1,201 @@
+use serde::Serialize;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, State};
+
+use crate::packages::xarm::client::{ArmPosition, ControllerErrors};
+use crate::packages::xarm::report::{ArmReport, ReportKind};
+use crate::packages::xarm::session::{SessionInfo, SessionOptions};
+use crate::packages::xarm::stream::{SubscriptionId, DEFAULT_RATE_HZ};
+use crate::packages::xarm::ArmAddress;
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 连接成功后返回的基本信息
+#[derive(Serialize, Clone, Debug)]
+pub struct ArmInfo {
+    pub version: String,
+    pub arm_sn: String,
+    pub state: u8,
+    pub errors: ControllerErrors,
+}
+
+/// 连接机械臂指令端口
+#[tauri::command]
+pub async fn arm_connect(state: State<'_, AppState>, arm: ArmAddress) -> Result<ArmInfo, AppError> {
+    let client = state.arms.get(&arm)?;
+    let mut client = client.lock().await;
+    client.connect().await?;
+    Ok(ArmInfo {
+        version: client.get_version().await?,
+        arm_sn: client.get_robot_sn().await?,
+        state: client.get_state().await?,
+        errors: client.get_error().await?,
+    })
+}
+
+/// 断开机械臂指令端口
+#[tauri::command]
+pub async fn arm_disconnect(state: State<'_, AppState>, arm: ArmAddress) -> Result<bool, AppError> {
+    state.arms.remove(&arm).await
+}
+
+#[tauri::command]
+pub async fn arm_get_version(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+) -> Result<String, AppError> {
+    state.arms.get(&arm)?.lock().await.get_version().await
+}
+
+/// 运动状态：1 运动中，2 就绪，3 暂停，4 停止
+#[tauri::command]
+pub async fn arm_get_state(state: State<'_, AppState>, arm: ArmAddress) -> Result<u8, AppError> {
+    state.arms.get(&arm)?.lock().await.get_state().await
+}
+
+#[tauri::command]
+pub async fn arm_get_error(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+) -> Result<ControllerErrors, AppError> {
+    state.arms.get(&arm)?.lock().await.get_error().await
+}
+
+/// 关节角和 TCP 位姿
+#[tauri::command]
+pub async fn arm_get_position(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+) -> Result<ArmPosition, AppError> {
+    state.arms.get(&arm)?.lock().await.get_position().await
+}
+
+#[tauri::command]
+pub async fn arm_motion_enable(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    enable: bool,
+) -> Result<(), AppError> {
+    state
+        .arms
+        .get(&arm)?
+        .lock()
+        .await
+        .motion_enable(enable)
+        .await
+}
+
+#[tauri::command]
+pub async fn arm_set_mode(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    mode: u8,
+) -> Result<(), AppError> {
+    state.arms.get(&arm)?.lock().await.set_mode(mode).await
+}
+
+#[tauri::command]
+pub async fn arm_set_state(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    value: u8,
+) -> Result<(), AppError> {
+    state.arms.get(&arm)?.lock().await.set_state(value).await
+}
+
+/// 清除错误和警告
+#[tauri::command]
+pub async fn arm_clean_error(state: State<'_, AppState>, arm: ArmAddress) -> Result<(), AppError> {
+    let client = state.arms.get(&arm)?;
+    let mut client = client.lock().await;
+    client.clean_error().await?;
+    client.clean_warn().await
+}
+
+/// 读任意寄存器，返回原始数据
+#[tauri::command]
+pub async fn arm_read_register(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    register: u8,
+    params: Option<Vec<u8>>,
+) -> Result<Vec<u8>, AppError> {
+    let params = params.unwrap_or_default();
+    state
+        .arms
+        .get(&arm)?
+        .lock()
+        .await
+        .read_register(register, &params)
+        .await
+}
+
+/// 写任意寄存器，返回状态字节
+#[tauri::command]
+pub async fn arm_write_register(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    register: u8,
+    params: Vec<u8>,
+) -> Result<u8, AppError> {
+    state
+        .arms
+        .get(&arm)?
+        .lock()
+        .await
+        .write_register(register, &params)
+        .await
+}
+
+/// 订阅上报数据，按 `rate_hz`（默认 30，0 表示不限频）推送到 `on_report`
+#[tauri::command]
+pub fn arm_subscribe_report(
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    kind: Option<ReportKind>,
+    rate_hz: Option<f64>,
+    on_report: Channel<ArmReport>,
+) -> Result<SubscriptionId, AppError> {
+    let kind = kind.unwrap_or_default();
+    state.reports.subscribe(
+        arm.report_addr(kind)?,
+        kind,
+        rate_hz.unwrap_or(DEFAULT_RATE_HZ),
+        move |_, report| on_report.send(report.clone()).is_ok(),
+    )
+}
+
+/// 取消上报订阅
+#[tauri::command]
+pub fn arm_unsubscribe_report(state: State<'_, AppState>, subscription_id: SubscriptionId) -> bool {
+    state.reports.unsubscribe(subscription_id)
+}
+
+/// 打开会话：连接机械臂并开始心跳，断线后自动重连，状态通过 `arm_connection_state` 事件通知
+#[tauri::command]
+pub async fn arm_open_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    options: Option<SessionOptions>,
+) -> Result<SessionInfo, AppError> {
+    state
+        .sessions
+        .open(&app, &state.arms, arm, options.unwrap_or_default())
+        .await
+}
+
+/// 关闭会话并断开连接
+#[tauri::command]
+pub async fn arm_close_session(
+    state: State<'_, AppState>,
+    arm_sn: String,
+) -> Result<bool, AppError> {
+    state.sessions.close(&state.arms, &arm_sn).await
+}
+
+/// 所有会话的当前状态
+#[tauri::command]
+pub fn list_arm_sessions(state: State<'_, AppState>) -> Vec<SessionInfo> {
+    state.sessions.list()
+}
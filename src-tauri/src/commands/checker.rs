@@ -0,0 +1,71 @@
+use tauri::AppHandle;
+
+use crate::packages::checker::zones::{self, SafetyZone};
+use crate::packages::checker::{self, CheckOptions, CheckReport};
+use crate::packages::trajectory::Trajectory;
+use crate::packages::xarm::model::ArmIdentity;
+use crate::utils::error::AppError;
+
+/// 某台机械臂保存的安全区
+#[tauri::command]
+pub fn get_safety_zones(app: AppHandle, arm_sn: String) -> Result<Vec<SafetyZone>, AppError> {
+    zones::load(&app, &arm_sn)
+}
+
+/// 保存某台机械臂的安全区，传空列表即清除
+#[tauri::command]
+pub fn set_safety_zones(
+    app: AppHandle,
+    arm_sn: String,
+    zones: Vec<SafetyZone>,
+) -> Result<(), AppError> {
+    zones::save(&app, &arm_sn, &zones)
+}
+
+/// 未直接传入安全区时，按 `arm_sn` 读取保存的安全区
+fn resolve_zones(
+    app: &AppHandle,
+    arm_sn: Option<String>,
+    zones: Option<Vec<SafetyZone>>,
+) -> Result<Vec<SafetyZone>, AppError> {
+    let zones = match (zones, arm_sn) {
+        (Some(zones), _) => zones,
+        (None, Some(arm_sn)) => zones::load(app, &arm_sn)?,
+        (None, None) => Vec::new(),
+    };
+    zones::validate(&zones)?;
+    Ok(zones)
+}
+
+/// 检查单个关节角（rad）
+#[tauri::command]
+pub fn check_configuration(
+    app: AppHandle,
+    arm: ArmIdentity,
+    arm_sn: Option<String>,
+    joints: Vec<f64>,
+    zones: Option<Vec<SafetyZone>>,
+    options: Option<CheckOptions>,
+) -> Result<CheckReport, AppError> {
+    let zones = resolve_zones(&app, arm_sn, zones)?;
+    checker::check_configuration(arm.model()?, &joints, &zones, &options.unwrap_or_default())
+}
+
+/// 检查整条轨迹，问题带源文件行号
+#[tauri::command]
+pub fn check_trajectory(
+    app: AppHandle,
+    trajectory: Trajectory,
+    arm: ArmIdentity,
+    arm_sn: Option<String>,
+    zones: Option<Vec<SafetyZone>>,
+    options: Option<CheckOptions>,
+) -> Result<CheckReport, AppError> {
+    let zones = resolve_zones(&app, arm_sn, zones)?;
+    checker::check_trajectory(
+        arm.model()?,
+        &trajectory,
+        &zones,
+        &options.unwrap_or_default(),
+    )
+}
@@ -0,0 +1,24 @@
+use tauri::{AppHandle, State};
+
+use crate::packages::controller_logs::{self, LogArchive, LogSource};
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 下载机械臂的控制器日志（可直接传 `ArmIpIntro`），进度见 `controller_log_progress` 事件
+#[tauri::command]
+pub async fn download_controller_logs(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    arm: LogSource,
+) -> Result<LogArchive, AppError> {
+    controller_logs::download(&app, &state.client, &arm).await
+}
+
+/// 已下载的日志归档，最新的在前；`arm_sn` 为空时列出所有机械臂
+#[tauri::command]
+pub fn list_controller_logs(
+    app: AppHandle,
+    arm_sn: Option<String>,
+) -> Result<Vec<LogArchive>, AppError> {
+    controller_logs::list(&app, arm_sn.as_deref())
+}
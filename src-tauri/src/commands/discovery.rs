@@ -0,0 +1,70 @@
+use log::info;
+use tauri::{AppHandle, Emitter, Runtime, State, Window};
+
+use crate::packages::discovery::presence::{DeviceRecord, PresenceOptions};
+use crate::packages::discovery::range::{ProbeRangeRequest, RangeProbe};
+use crate::packages::discovery::{ScanId, EVENT_XARM_IP};
+use crate::state::app_state::AppState;
+
+/// 获取当前在线设备（新打开的窗口无需重新扫描）
+#[tauri::command]
+pub fn list_devices<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Vec<DeviceRecord> {
+    let mut devices = state.discovery.list_devices();
+    // 备注名可能在设备上线后被修改，返回前重新补充
+    for device in devices.iter_mut() {
+        state.registry.annotate(&app, &mut device.intro);
+    }
+    devices
+}
+
+/// 以新参数重启后台在线检测
+#[tauri::command]
+pub async fn start_presence_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    options: Option<PresenceOptions>,
+) -> Result<(), String> {
+    state
+        .discovery
+        .start_presence_monitor(&app, options.unwrap_or_default())
+        .await
+}
+
+/// 停止后台在线检测
+#[tauri::command]
+pub async fn stop_presence_monitor(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.discovery.stop_presence_monitor().await)
+}
+
+/// 单播探测 IP 范围或 CIDR 网段（网络屏蔽广播时使用），返回扫描 ID
+#[tauri::command]
+pub async fn probe_range<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, AppState>,
+    request: ProbeRangeRequest,
+) -> Result<ScanId, String> {
+    let probe = RangeProbe::from_request(&request).map_err(|e| e.to_string())?;
+    // 与广播扫描一致，先发送扫描开始标记
+    let _ = app.emit_to(window.label(), EVENT_XARM_IP, "begin");
+    info!(
+        "Probe range {} ({} hosts, {} pkt/s)",
+        probe.label,
+        probe.hosts.len(),
+        probe.rate
+    );
+    state.discovery.start_probe(&app, window.label(), probe)
+}
+
+/// 取消单播探测
+#[tauri::command]
+pub async fn stop_probe_range(state: State<'_, AppState>, scan_id: ScanId) -> Result<(), String> {
+    if state.discovery.stop_scan(scan_id).await {
+        Ok(())
+    } else {
+        Err(format!("Probe {} is not running", scan_id))
+    }
+}
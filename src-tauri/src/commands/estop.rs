@@ -0,0 +1,23 @@
+use tauri::{AppHandle, State};
+
+use crate::packages::keyboard::estop::{self, StopReport};
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 当前急停快捷键
+#[tauri::command]
+pub fn get_estop_shortcut(state: State<'_, AppState>) -> Option<String> {
+    state.estop.shortcut()
+}
+
+/// 修改急停快捷键，例如 `Ctrl+Shift+E`，返回规范化后的写法
+#[tauri::command]
+pub fn set_estop_shortcut(app: AppHandle, shortcut: String) -> Result<String, AppError> {
+    estop::set_binding(&app, &shortcut)
+}
+
+/// 停止所有已连接的机械臂（与急停快捷键相同）
+#[tauri::command]
+pub async fn emergency_stop(app: AppHandle) -> StopReport {
+    estop::run(&app, "command").await
+}
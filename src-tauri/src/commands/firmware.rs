@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tauri::{async_runtime, AppHandle, State};
+
+use crate::packages::firmware::package::{self, FirmwarePackage};
+use crate::packages::firmware::transport::UnverifiedHttpUpload;
+use crate::packages::firmware::{BatchInfo, FirmwareTarget, UpdateOptions};
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 读取固件包并校验清单和镜像，`path` 为固件包目录或清单文件
+#[tauri::command]
+pub async fn inspect_firmware_package(path: PathBuf) -> Result<FirmwarePackage, AppError> {
+    async_runtime::spawn_blocking(move || {
+        let package = package::load(&path)?;
+        package.read_image()?;
+        Ok(package)
+    })
+    .await
+    .map_err(|e| AppError::Io(e.to_string()))?
+}
+
+/// 开始批量升级选中的机械臂（可直接传 `ArmIpIntro` 列表），
+/// 进度见 `firmware_update_progress`，结果见 `firmware_update_finished`
+#[tauri::command]
+pub async fn start_firmware_update(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    arms: Vec<FirmwareTarget>,
+    options: Option<UpdateOptions>,
+) -> Result<BatchInfo, AppError> {
+    state
+        .firmware
+        .start(
+            &app,
+            Arc::new(UnverifiedHttpUpload::new(state.client.clone())),
+            &path,
+            arms,
+            options.unwrap_or_default(),
+        )
+        .await
+}
+
+/// 中止批次：不再开始新的升级，正在上传的断开，已在安装的继续等待验证
+#[tauri::command]
+pub fn abort_firmware_update(state: State<'_, AppState>, batch_id: u64) -> bool {
+    state.firmware.abort(batch_id)
+}
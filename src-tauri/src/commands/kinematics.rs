@@ -0,0 +1,54 @@
+use crate::packages::kinematics::ik::{self, IkMethod, IkSolution};
+use crate::packages::kinematics::rotation::{Orientation, OrientationKind};
+use crate::packages::kinematics::{self, Link, Pose, Singularity};
+use crate::packages::xarm::model::ArmIdentity;
+use crate::utils::error::AppError;
+
+/// 机型的 MDH 参数
+#[tauri::command]
+pub fn get_dh_parameters(arm: ArmIdentity) -> Result<Vec<Link>, AppError> {
+    Ok(kinematics::links(arm.model()?).to_vec())
+}
+
+/// 正解，关节角单位 rad
+#[tauri::command]
+pub fn forward_kinematics(arm: ArmIdentity, joints: Vec<f64>) -> Result<Pose, AppError> {
+    kinematics::forward(arm.model()?, &joints)
+}
+
+/// 逆解，返回关节范围内的所有分支，按与 `reference` 的距离排序
+#[tauri::command]
+pub fn inverse_kinematics(
+    arm: ArmIdentity,
+    pose: Pose,
+    method: Option<IkMethod>,
+    reference: Option<Vec<f64>>,
+) -> Result<Vec<IkSolution>, AppError> {
+    ik::solve(
+        arm.model()?,
+        &pose,
+        method.unwrap_or_default(),
+        reference.as_deref(),
+    )
+}
+
+/// 雅可比（6 × n，按行）
+#[tauri::command]
+pub fn get_jacobian(arm: ArmIdentity, joints: Vec<f64>) -> Result<Vec<Vec<f64>>, AppError> {
+    kinematics::jacobian(arm.model()?, &joints)
+}
+
+/// 奇异检测
+#[tauri::command]
+pub fn check_singularity(arm: ArmIdentity, joints: Vec<f64>) -> Result<Singularity, AppError> {
+    kinematics::singularity(arm.model()?, &joints)
+}
+
+/// RPY、四元数、轴角互转
+#[tauri::command]
+pub fn convert_orientation(
+    orientation: Orientation,
+    to: OrientationKind,
+) -> Result<Orientation, AppError> {
+    orientation.convert(to)
+}
@@ -1,4 +1,21 @@
+pub mod arm;
+pub mod checker;
+#[cfg(feature = "controller-log-download")]
+pub mod controller_logs;
+pub mod discovery;
+pub mod estop;
+pub mod firmware;
 pub mod http;
+pub mod kinematics;
+pub mod modbus;
+pub mod parameters;
+pub mod product_config;
+pub mod recorder;
+pub mod registry;
 pub mod request;
+pub mod service;
+pub mod simulator;
 pub mod system;
 pub mod tools;
+pub mod trajectory;
+pub mod websocket;
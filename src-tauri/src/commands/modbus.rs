@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::packages::modbus::map::{PointValue, RegisterMap};
+use crate::packages::modbus::{DeviceInfo, RawReply, RawRequest};
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 添加 Modbus 设备并启动轮询组，寄存器表直接传入或从 JSON 文件读取
+#[tauri::command]
+pub async fn modbus_add_device(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    map: Option<RegisterMap>,
+    path: Option<PathBuf>,
+) -> Result<DeviceInfo, AppError> {
+    let map = match (map, path) {
+        (Some(map), _) => map,
+        (None, Some(path)) => RegisterMap::load(&path)?,
+        (None, None) => {
+            return Err(AppError::InvalidArgument(
+                "either map or path is required".to_string(),
+            ))
+        }
+    };
+    state.modbus.add(&app, map).await
+}
+
+/// 移除设备并停止轮询
+#[tauri::command]
+pub async fn modbus_remove_device(
+    state: State<'_, AppState>,
+    device: String,
+) -> Result<bool, AppError> {
+    state.modbus.remove(&device).await
+}
+
+/// 已添加的设备和最近读取的值
+#[tauri::command]
+pub fn modbus_list_devices(state: State<'_, AppState>) -> Vec<DeviceInfo> {
+    state.modbus.list()
+}
+
+/// 读取一个点（工程值）
+#[tauri::command]
+pub async fn modbus_read_point(
+    state: State<'_, AppState>,
+    device: String,
+    point: String,
+) -> Result<PointValue, AppError> {
+    state.modbus.read_point(&device, &point).await
+}
+
+/// 写入一个点（工程值，按寄存器表换算）
+#[tauri::command]
+pub async fn modbus_write_point(
+    state: State<'_, AppState>,
+    device: String,
+    point: String,
+    value: PointValue,
+) -> Result<(), AppError> {
+    state.modbus.write_point(&device, &point, value).await
+}
+
+/// 按功能码（1~6、15、16、23）直接读写，不经过寄存器表
+#[tauri::command]
+pub async fn modbus_raw(
+    state: State<'_, AppState>,
+    device: String,
+    request: RawRequest,
+) -> Result<RawReply, AppError> {
+    state.modbus.raw(&device, request).await
+}
@@ -0,0 +1,134 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+
+use crate::packages::parameters::{
+    self, ArmParameters, ParameterChange, RestoreReport, SnapshotFile,
+};
+use crate::packages::xarm::report::ReportKind;
+use crate::packages::xarm::ArmAddress;
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 目标机械臂，可以直接传 `ArmIpIntro` 对象
+#[derive(Deserialize, Clone, Debug)]
+pub struct ParameterTarget {
+    #[serde(flatten)]
+    pub address: ArmAddress,
+    pub arm_sn: String,
+    pub device_type: String,
+    /// 详细上报端口，默认 30002（模拟器可能使用其他端口）
+    #[serde(default)]
+    pub report_port: Option<u16>,
+}
+
+impl ParameterTarget {
+    fn report_addr(&self) -> Result<SocketAddr, AppError> {
+        let addr = self.address.report_addr(ReportKind::Rich)?;
+        Ok(SocketAddr::new(
+            addr.ip(),
+            self.report_port.unwrap_or(addr.port()),
+        ))
+    }
+}
+
+/// 比较的一方：快照文件或在线机械臂
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParameterSource {
+    File { path: PathBuf },
+    Arm(ParameterTarget),
+}
+
+async fn read_live(state: &AppState, target: &ParameterTarget) -> Result<ArmParameters, AppError> {
+    let report_addr = target.report_addr()?;
+    let client = state.arms.get(&target.address)?;
+    let mut client = client.lock().await;
+    parameters::read(&mut client, report_addr).await
+}
+
+async fn read_source(
+    state: &AppState,
+    source: &ParameterSource,
+) -> Result<ArmParameters, AppError> {
+    match source {
+        ParameterSource::File { path } => Ok(parameters::load(path)?.parameters),
+        ParameterSource::Arm(target) => read_live(state, target).await,
+    }
+}
+
+/// 备份机械臂参数到本地快照，旧固件读不到的项（如世界坐标偏移）列在返回的 `missing` 中
+#[tauri::command]
+pub async fn backup_parameters(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    arm: ParameterTarget,
+    note: Option<String>,
+) -> Result<SnapshotFile, AppError> {
+    let report_addr = arm.report_addr()?;
+    let snapshot = {
+        let client = state.arms.get(&arm.address)?;
+        let mut client = client.lock().await;
+        parameters::snapshot(
+            &mut client,
+            report_addr,
+            &arm.arm_sn,
+            &arm.device_type,
+            note.unwrap_or_default(),
+        )
+        .await?
+    };
+    let path = parameters::save(&parameters::snapshots_dir(&app)?, &snapshot)?;
+    Ok(SnapshotFile::new(path, snapshot))
+}
+
+/// 列出快照，最新的在前；`arm_sn` 为空时列出所有机械臂
+#[tauri::command]
+pub fn list_parameter_snapshots(
+    app: AppHandle,
+    arm_sn: Option<String>,
+) -> Result<Vec<SnapshotFile>, AppError> {
+    parameters::list(&parameters::snapshots_dir(&app)?, arm_sn.as_deref())
+}
+
+/// 预览把快照恢复到机械臂会修改的项
+#[tauri::command]
+pub async fn preview_parameter_restore(
+    state: State<'_, AppState>,
+    arm: ParameterTarget,
+    path: PathBuf,
+) -> Result<Vec<ParameterChange>, AppError> {
+    let snapshot = parameters::load(&path)?;
+    parameters::check_target(&snapshot, &arm.device_type)?;
+    let current = read_live(&state, &arm).await?;
+    Ok(parameters::preview(&current, &snapshot))
+}
+
+/// 把快照恢复到机械臂（可以是更换控制器后的另一台），只写入有差异的项
+#[tauri::command]
+pub async fn restore_parameters(
+    state: State<'_, AppState>,
+    arm: ParameterTarget,
+    path: PathBuf,
+) -> Result<RestoreReport, AppError> {
+    let snapshot = parameters::load(&path)?;
+    parameters::check_target(&snapshot, &arm.device_type)?;
+    let report_addr = arm.report_addr()?;
+    let client = state.arms.get(&arm.address)?;
+    let mut client = client.lock().await;
+    parameters::restore(&mut client, report_addr, &snapshot).await
+}
+
+/// 比较两份参数，`before`/`after` 可以是快照文件或在线机械臂
+#[tauri::command]
+pub async fn diff_parameters(
+    state: State<'_, AppState>,
+    before: ParameterSource,
+    after: ParameterSource,
+) -> Result<Vec<ParameterChange>, AppError> {
+    let before = read_source(&state, &before).await?;
+    let after = read_source(&state, &after).await?;
+    Ok(parameters::diff(&before, &after))
+}
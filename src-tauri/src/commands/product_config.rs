@@ -0,0 +1,18 @@
+use tauri::{AppHandle, State};
+
+use crate::packages::product_config::{self, ProductConfig, CONFIG_FILE};
+use crate::state::app_state::AppState;
+use crate::state::threads;
+use crate::utils::error::AppError;
+
+/// 当前产品配置，监控线程尚未读取时直接读文件；之后的变化见 `product_config_changed` 事件
+#[tauri::command]
+pub fn get_product_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ProductConfig, AppError> {
+    if let Some(config) = state.product_config.get() {
+        return Ok(config);
+    }
+    product_config::load(&threads::resolve_config_path(&app, CONFIG_FILE)?)
+}
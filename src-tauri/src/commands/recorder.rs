@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use tauri::{async_runtime, AppHandle, State};
+
+use crate::packages::recorder::export::{self, ExportFormat};
+use crate::packages::recorder::{self, RecordingFile, RecordingInfo, ReplayInfo};
+use crate::packages::xarm::report::ReportKind;
+use crate::packages::xarm::ArmAddress;
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 开始录制机械臂上报数据，默认录制实时上报
+#[tauri::command]
+pub fn start_recording(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    arm: ArmAddress,
+    arm_sn: Option<String>,
+    kind: Option<ReportKind>,
+) -> Result<RecordingInfo, AppError> {
+    state
+        .recorder
+        .start_recording(&app, &state.reports, arm, arm_sn, kind.unwrap_or_default())
+}
+
+/// 停止录制，文件在任务结束时落盘
+#[tauri::command]
+pub fn stop_recording(state: State<'_, AppState>, id: u64) -> bool {
+    state.recorder.stop_recording(id)
+}
+
+/// 进行中的录制
+#[tauri::command]
+pub fn get_recordings(state: State<'_, AppState>) -> Vec<RecordingInfo> {
+    state.recorder.recordings()
+}
+
+/// 已保存的录制文件
+#[tauri::command]
+pub fn list_recording_files(app: AppHandle) -> Result<Vec<RecordingFile>, AppError> {
+    recorder::list_files(&app)
+}
+
+/// 导出录制文件，`output` 为空时导出到录制文件旁边，返回导出文件路径
+#[tauri::command]
+pub async fn export_recording(
+    app: AppHandle,
+    file: PathBuf,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<PathBuf, AppError> {
+    let source = recorder::resolve(&app, &file)?;
+    let target = output.unwrap_or_else(|| source.with_extension(format.extension()));
+    async_runtime::spawn_blocking(move || {
+        export::export(&source, &target, format)?;
+        Ok(target)
+    })
+    .await
+    .map_err(|e| AppError::Io(e.to_string()))?
+}
+
+/// 开始回放，`speed` 默认 1 倍速；`arm` 为空时推送到录制时的地址
+#[tauri::command]
+pub fn start_replay(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    file: PathBuf,
+    speed: Option<f64>,
+    arm: Option<ArmAddress>,
+) -> Result<ReplayInfo, AppError> {
+    let path = recorder::resolve(&app, &file)?;
+    state
+        .recorder
+        .start_replay(&app, &state.reports, path, speed.unwrap_or(1.0), arm)
+}
+
+#[tauri::command]
+pub fn stop_replay(state: State<'_, AppState>, id: u64) -> bool {
+    state.recorder.stop_replay(id)
+}
+
+/// 进行中的回放
+#[tauri::command]
+pub fn get_replays(state: State<'_, AppState>) -> Vec<ReplayInfo> {
+    state.recorder.replays()
+}
@@ -0,0 +1,56 @@
+use tauri::{AppHandle, Runtime, State};
+
+use crate::packages::registry::RegistryEntry;
+use crate::state::app_state::AppState;
+
+/// 获取设备登记表
+#[tauri::command]
+pub fn list_registry<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RegistryEntry>, String> {
+    state.registry.list(&app)
+}
+
+/// 修改设备备注名
+#[tauri::command]
+pub fn rename_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    nickname: String,
+) -> Result<RegistryEntry, String> {
+    state.registry.rename(&app, &id, &nickname)
+}
+
+/// 设置设备标签
+#[tauri::command]
+pub fn tag_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+    tags: Vec<String>,
+) -> Result<RegistryEntry, String> {
+    state.registry.set_tags(&app, &id, tags)
+}
+
+/// 从登记表中删除设备
+#[tauri::command]
+pub fn forget_device<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<bool, String> {
+    state.registry.forget(&app, &id)
+}
+
+/// 合并两条登记记录，`from` 合并进 `into` 后被删除
+#[tauri::command]
+pub fn merge_devices<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    from: String,
+    into: String,
+) -> Result<RegistryEntry, String> {
+    state.registry.merge(&app, &from, &into)
+}
@@ -0,0 +1,17 @@
+use tauri::State;
+
+use crate::packages::services::ServiceEndpoint;
+use crate::packages::sidecar::ServiceState;
+use crate::state::app_state::AppState;
+
+/// tool_service 当前状态，从未启动时为空；之后的变化见 `service_state` 事件
+#[tauri::command]
+pub fn get_service_state(state: State<'_, AppState>) -> Option<ServiceState> {
+    state.tool_service.state()
+}
+
+/// 本地服务地址（tool_service、WebSocket 桥、本地 API），端口启动时分配，前端不应写死
+#[tauri::command]
+pub fn get_service_endpoints(state: State<'_, AppState>) -> Vec<ServiceEndpoint> {
+    state.services.list()
+}
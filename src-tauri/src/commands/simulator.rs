@@ -0,0 +1,28 @@
+use tauri::State;
+
+use crate::packages::simulator::{SimConfig, SimulatorStatus};
+use crate::state::app_state::AppState;
+
+/// 启动（或以新参数重启）虚拟机械臂
+#[tauri::command]
+pub async fn start_simulator(
+    state: State<'_, AppState>,
+    config: Option<SimConfig>,
+) -> Result<SimulatorStatus, String> {
+    state
+        .simulator
+        .start(config.unwrap_or_default(), &state.discovery)
+        .await
+}
+
+/// 停止虚拟机械臂
+#[tauri::command]
+pub async fn stop_simulator(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.simulator.stop(&state.discovery).await)
+}
+
+/// 虚拟机械臂运行状态，未运行时为空（前端据此判断是否处于演示模式）
+#[tauri::command]
+pub fn get_simulator(state: State<'_, AppState>) -> Option<SimulatorStatus> {
+    state.simulator.status()
+}
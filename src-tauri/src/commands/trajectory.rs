@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::packages::trajectory::export::{self, ExportOptions};
+use crate::packages::trajectory::import::{self, ImportOptions};
+use crate::packages::trajectory::{self, Trajectory, TrajectoryFormat, Violation};
+use crate::packages::xarm::model::ArmIdentity;
+use crate::utils::error::AppError;
+
+/// 导入轨迹文件，`format` 为空时按扩展名判断；格式错误时返回带行号的错误
+#[tauri::command]
+pub fn import_trajectory(
+    path: PathBuf,
+    format: Option<TrajectoryFormat>,
+    options: Option<ImportOptions>,
+) -> Result<Trajectory, AppError> {
+    import::load(&path, format, &options.unwrap_or_default())
+}
+
+/// 按目标机械臂（可直接传 `ArmIpIntro`）校验轨迹，返回所有问题
+#[tauri::command]
+pub fn validate_trajectory(
+    trajectory: Trajectory,
+    arm: ArmIdentity,
+) -> Result<Vec<Violation>, AppError> {
+    Ok(trajectory::validate(&trajectory, arm.model()?))
+}
+
+/// 校验通过后导出，`format` 为空时按扩展名判断
+#[tauri::command]
+pub fn export_trajectory(
+    trajectory: Trajectory,
+    arm: ArmIdentity,
+    path: PathBuf,
+    format: Option<TrajectoryFormat>,
+    options: Option<ExportOptions>,
+) -> Result<(), AppError> {
+    let format = match format {
+        Some(format) => format,
+        None => TrajectoryFormat::from_path(&path)?,
+    };
+    let text = export::render(
+        &trajectory,
+        arm.model()?,
+        format,
+        &options.unwrap_or_default(),
+    )?;
+    fs::write(&path, text)?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use tauri::State;
+
+use crate::packages::services::WEBSOCKET_BRIDGE;
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+use crate::utils::websocket::{BridgeConfig, BridgeStatus};
+
+/// 启动（或以新参数重启）本地 WebSocket 桥，默认只监听 127.0.0.1，
+/// 未传 `config` 时使用登记的端口（见 `get_service_endpoints`）
+#[tauri::command]
+pub async fn start_websocket_bridge(
+    state: State<'_, AppState>,
+    config: Option<BridgeConfig>,
+) -> Result<BridgeStatus, AppError> {
+    let config = config.unwrap_or_else(|| {
+        let mut config = BridgeConfig::default();
+        if let Some(endpoint) = state.services.get(WEBSOCKET_BRIDGE) {
+            config.port = endpoint.port;
+        }
+        config
+    });
+    let status = state.bridge.start(config, state.bridge_context()).await?;
+    if let Ok(addr) = status.addr.parse() {
+        state.services.update(WEBSOCKET_BRIDGE, addr);
+    }
+    Ok(status)
+}
+
+/// 停止 WebSocket 桥并断开所有连接
+#[tauri::command]
+pub fn stop_websocket_bridge(state: State<'_, AppState>) -> bool {
+    state.bridge.stop()
+}
+
+/// WebSocket 桥运行状态，未运行时为空
+#[tauri::command]
+pub fn get_websocket_bridge(state: State<'_, AppState>) -> Option<BridgeStatus> {
+    state.bridge.status()
+}
+
+/// 为外部工具生成新的访问令牌
+#[tauri::command]
+pub fn create_websocket_token(state: State<'_, AppState>) -> Result<String, AppError> {
+    state.bridge.create_token()
+}
+
+/// 吊销令牌，使用该令牌的连接会被断开
+#[tauri::command]
+pub fn revoke_websocket_token(state: State<'_, AppState>, token: String) -> bool {
+    state.bridge.revoke_token(&token)
+}
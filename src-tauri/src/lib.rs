@@ -2,7 +2,7 @@ mod commands;
 mod packages;
 mod state;
 mod utils;
-use tauri::{LogicalSize, Manager};
+use tauri::{LogicalSize, Manager, RunEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -16,6 +16,88 @@ pub fn run() {
             commands::system::start_udp_broadcast,
             commands::system::stop_udp_broadcast,
             commands::system::ping,
+            commands::system::latency_pong,
+            commands::system::get_latency_stats,
+            commands::discovery::list_devices,
+            commands::discovery::start_presence_monitor,
+            commands::discovery::stop_presence_monitor,
+            commands::discovery::probe_range,
+            commands::discovery::stop_probe_range,
+            commands::registry::list_registry,
+            commands::registry::rename_device,
+            commands::registry::tag_device,
+            commands::registry::forget_device,
+            commands::registry::merge_devices,
+            commands::simulator::start_simulator,
+            commands::simulator::stop_simulator,
+            commands::simulator::get_simulator,
+            commands::arm::arm_connect,
+            commands::arm::arm_disconnect,
+            commands::arm::arm_get_version,
+            commands::arm::arm_get_state,
+            commands::arm::arm_get_error,
+            commands::arm::arm_get_position,
+            commands::arm::arm_motion_enable,
+            commands::arm::arm_set_mode,
+            commands::arm::arm_set_state,
+            commands::arm::arm_clean_error,
+            commands::arm::arm_read_register,
+            commands::arm::arm_write_register,
+            commands::arm::arm_subscribe_report,
+            commands::arm::arm_unsubscribe_report,
+            commands::arm::arm_open_session,
+            commands::arm::arm_close_session,
+            commands::arm::list_arm_sessions,
+            commands::websocket::start_websocket_bridge,
+            commands::websocket::stop_websocket_bridge,
+            commands::websocket::get_websocket_bridge,
+            commands::websocket::create_websocket_token,
+            commands::websocket::revoke_websocket_token,
+            commands::modbus::modbus_add_device,
+            commands::modbus::modbus_remove_device,
+            commands::modbus::modbus_list_devices,
+            commands::modbus::modbus_read_point,
+            commands::modbus::modbus_write_point,
+            commands::modbus::modbus_raw,
+            commands::estop::get_estop_shortcut,
+            commands::estop::set_estop_shortcut,
+            commands::estop::emergency_stop,
+            commands::kinematics::get_dh_parameters,
+            commands::kinematics::forward_kinematics,
+            commands::kinematics::inverse_kinematics,
+            commands::kinematics::get_jacobian,
+            commands::kinematics::check_singularity,
+            commands::kinematics::convert_orientation,
+            commands::recorder::start_recording,
+            commands::recorder::stop_recording,
+            commands::recorder::get_recordings,
+            commands::recorder::list_recording_files,
+            commands::recorder::export_recording,
+            commands::recorder::start_replay,
+            commands::recorder::stop_replay,
+            commands::recorder::get_replays,
+            commands::trajectory::import_trajectory,
+            commands::trajectory::validate_trajectory,
+            commands::trajectory::export_trajectory,
+            commands::checker::get_safety_zones,
+            commands::checker::set_safety_zones,
+            commands::checker::check_configuration,
+            commands::checker::check_trajectory,
+            #[cfg(feature = "controller-log-download")]
+            commands::controller_logs::download_controller_logs,
+            #[cfg(feature = "controller-log-download")]
+            commands::controller_logs::list_controller_logs,
+            commands::firmware::inspect_firmware_package,
+            commands::firmware::start_firmware_update,
+            commands::firmware::abort_firmware_update,
+            commands::parameters::backup_parameters,
+            commands::parameters::list_parameter_snapshots,
+            commands::parameters::preview_parameter_restore,
+            commands::parameters::restore_parameters,
+            commands::parameters::diff_parameters,
+            commands::service::get_service_state,
+            commands::service::get_service_endpoints,
+            commands::product_config::get_product_config,
             commands::request::fetch_history_releases,
             commands::http::fetch_with_timeout,
             commands::tools::set_beta_updater,
@@ -39,6 +121,63 @@ pub fn run() {
             window.set_size(LogicalSize::new(1280.0, 768.0)).unwrap();
             // window.reload().unwrap();
 
+            // 监控产品配置文件
+            let monitor = state::threads::spawn_state_monitor(
+                app.handle(),
+                state::threads::MonitorConfig::default(),
+            );
+            app.state::<state::app_state::AppState>()
+                .product_config
+                .set_monitor(monitor);
+
+            // 演示模式：先启动虚拟机械臂，再启动设备在线检测
+            let demo = packages::simulator::demo_config();
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = handle.state::<state::app_state::AppState>();
+                // 先分配本地服务端口，被占用的首选端口记录为冲突
+                state.services.allocate_defaults();
+                let tool_service = state
+                    .services
+                    .get(packages::sidecar::TOOL_SERVICE)
+                    .map(|endpoint| endpoint.url)
+                    .unwrap_or_else(|| {
+                        format!("http://127.0.0.1:{}", packages::xarm::CONTROLLER_HTTP_PORT)
+                    });
+                state
+                    .latency
+                    .start(&handle, state.client.clone(), tool_service);
+                // 找不到 tool_service 时只记录日志，其他功能照常使用
+                match packages::sidecar::SidecarConfig::tool_service() {
+                    Ok(mut config) => {
+                        config.envs.extend(state.services.env());
+                        // tool_service 应在分配的端口上提供 HTTP 接口和本地 API
+                        config.ready_addrs = [
+                            packages::sidecar::TOOL_SERVICE,
+                            packages::services::LOCAL_API,
+                        ]
+                        .iter()
+                        .filter_map(|name| state.services.get(name))
+                        .map(|endpoint| endpoint.addr())
+                        .collect();
+                        let _ = state.tool_service.start(&handle, config).await;
+                    }
+                    Err(e) => log::error!("Failed to locate tool_service: {}", e),
+                }
+                if let Some(config) = demo {
+                    if let Err(e) = state.simulator.start(config, &state.discovery).await {
+                        log::error!("Failed to start simulator: {}", e);
+                    }
+                }
+                if let Err(e) = state
+                    .discovery
+                    .start_presence_monitor(&handle, Default::default())
+                    .await
+                {
+                    log::error!("Failed to start presence monitor: {}", e);
+                }
+            });
+
             // 获取version版本
             let version = env!("CARGO_PKG_VERSION");
             log::info!(
@@ -49,6 +188,29 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("run fail");
+        .build(tauri::generate_context!())
+        .expect("run fail")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                // 退出前停止 WebSocket 桥、所有扫描任务、会话心跳、上报连接、录制回放、固件升级、本地服务、配置监控和虚拟机械臂
+                let state = app.state::<state::app_state::AppState>();
+                state.bridge.stop();
+                state.latency.stop();
+                state.modbus.stop_all();
+                state.recorder.stop_all();
+                state.firmware.stop_all();
+                state.product_config.stop_monitor();
+                state.sessions.stop_all();
+                state.reports.stop_all();
+                tauri::async_runtime::block_on(async {
+                    state.tool_service.stop().await;
+                    state.discovery.stop_all().await;
+                    state.simulator.stop(&state.discovery).await;
+                });
+                // 扫描停止后写入尚未落盘的设备登记
+                if let Err(e) = state.registry.flush(app) {
+                    log::error!("Failed to save device registry: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,226 @@
+//! 胶囊体连杆模型
+//!
+//! 每个机型用 5 个胶囊体近似：底座、大臂、肘部、小臂和腕部（含法兰）。
+//! 端点挂在某个关节坐标系上（0 为基座），半径按外壳尺寸取整并略微放大。
+
+use nalgebra::{Point3, Vector3};
+
+use crate::packages::kinematics::{frames, links};
+use crate::packages::xarm::model::ArmModel;
+
+/// 胶囊体端点：关节坐标系序号和该坐标系下的偏移（mm）
+#[derive(Clone, Copy, Debug)]
+struct Anchor {
+    frame: usize,
+    offset: [f64; 3],
+}
+
+const fn at(frame: usize) -> Anchor {
+    Anchor {
+        frame,
+        offset: [0.0; 3],
+    }
+}
+
+const fn offset(frame: usize, x: f64, y: f64) -> Anchor {
+    Anchor {
+        frame,
+        offset: [x, y, 0.0],
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CapsuleSpec {
+    name: &'static str,
+    from: Anchor,
+    to: Anchor,
+    radius: f64,
+}
+
+const fn spec(name: &'static str, from: Anchor, to: Anchor, radius: f64) -> CapsuleSpec {
+    CapsuleSpec {
+        name,
+        from,
+        to,
+        radius,
+    }
+}
+
+/// 需要检查自碰撞的胶囊体对，相邻连杆在关节处必然接触，不参与检查
+pub const COLLISION_PAIRS: [(&str, &str); 4] = [
+    ("base", "forearm"),
+    ("base", "wrist"),
+    ("upper_arm", "wrist"),
+    ("elbow", "wrist"),
+];
+
+const XARM5: [CapsuleSpec; 5] = [
+    spec("base", at(0), at(1), 65.0),
+    spec("upper_arm", at(2), at(3), 50.0),
+    // xArm5 的 J3 坐标系转向了腕部中心，肘部拐点换算到该坐标系下
+    spec("elbow", at(3), offset(3, 17.104, -75.589), 45.0),
+    spec("forearm", offset(3, 17.104, -75.589), at(4), 45.0),
+    spec("wrist", at(4), at(5), 40.0),
+];
+
+const XARM6: [CapsuleSpec; 5] = [
+    spec("base", at(0), at(1), 65.0),
+    spec("upper_arm", at(2), at(3), 50.0),
+    spec("elbow", at(3), offset(3, 77.5, 0.0), 45.0),
+    spec("forearm", offset(3, 77.5, 0.0), at(4), 45.0),
+    spec("wrist", at(4), at(6), 40.0),
+];
+
+const XARM7: [CapsuleSpec; 5] = [
+    spec("base", at(0), at(1), 65.0),
+    spec("upper_arm", at(2), at(3), 50.0),
+    spec("elbow", at(3), offset(4, 77.5, 0.0), 45.0),
+    spec("forearm", offset(4, 77.5, 0.0), at(5), 45.0),
+    spec("wrist", at(5), at(7), 40.0),
+];
+
+const LITE6: [CapsuleSpec; 5] = [
+    spec("base", at(0), at(1), 50.0),
+    spec("upper_arm", at(2), at(3), 40.0),
+    spec("elbow", at(3), offset(3, 87.0, 0.0), 35.0),
+    spec("forearm", offset(3, 87.0, 0.0), at(4), 35.0),
+    spec("wrist", at(4), at(6), 32.0),
+];
+
+const XARM850: [CapsuleSpec; 5] = [
+    spec("base", at(0), at(1), 75.0),
+    spec("upper_arm", at(2), at(3), 60.0),
+    spec("elbow", at(3), offset(3, 150.0, 0.0), 55.0),
+    spec("forearm", offset(3, 150.0, 0.0), at(4), 50.0),
+    spec("wrist", at(4), at(6), 45.0),
+];
+
+fn specs(model: ArmModel) -> &'static [CapsuleSpec] {
+    match model {
+        ArmModel::Xarm5 => &XARM5,
+        ArmModel::Xarm6 => &XARM6,
+        ArmModel::Xarm7 => &XARM7,
+        ArmModel::Lite6 => &LITE6,
+        ArmModel::Xarm850 => &XARM850,
+    }
+}
+
+/// 基座坐标系下的胶囊体
+#[derive(Clone, Debug)]
+pub struct Capsule {
+    pub name: &'static str,
+    pub start: Point3<f64>,
+    pub end: Point3<f64>,
+    pub radius: f64,
+}
+
+/// 按关节角计算各胶囊体位置，关节数需事先校验
+pub fn capsules(model: ArmModel, joints: &[f64]) -> Vec<Capsule> {
+    let frames = frames(links(model), joints);
+    let locate = |anchor: Anchor| {
+        let local = Point3::from(Vector3::from(anchor.offset));
+        match anchor.frame {
+            0 => local,
+            frame => frames[frame - 1] * local,
+        }
+    };
+    specs(model)
+        .iter()
+        .map(|spec| Capsule {
+            name: spec.name,
+            start: locate(spec.from),
+            end: locate(spec.to),
+            radius: spec.radius,
+        })
+        .collect()
+}
+
+/// 两条线段间的最短距离
+pub fn segment_distance(
+    p1: &Point3<f64>,
+    q1: &Point3<f64>,
+    p2: &Point3<f64>,
+    q2: &Point3<f64>,
+) -> f64 {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.norm_squared();
+    let e = d2.norm_squared();
+    let f = d2.dot(&r);
+
+    let (s, t) = if a <= f64::EPSILON && e <= f64::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f64::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e <= f64::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denominator = a * e - b * b;
+            // 平行时任取一点
+            let mut s = if denominator > f64::EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    ((p1 + d1 * s) - (p2 + d2 * t)).norm()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_distances() {
+        let p = |x: f64, y: f64, z: f64| Point3::new(x, y, z);
+        // 交叉
+        let crossing = segment_distance(
+            &p(-1.0, 0.0, 0.0),
+            &p(1.0, 0.0, 0.0),
+            &p(0.0, -1.0, 2.0),
+            &p(0.0, 1.0, 2.0),
+        );
+        assert!((crossing - 2.0).abs() < 1e-12);
+        // 平行
+        let parallel = segment_distance(
+            &p(0.0, 0.0, 0.0),
+            &p(10.0, 0.0, 0.0),
+            &p(5.0, 3.0, 0.0),
+            &p(15.0, 3.0, 0.0),
+        );
+        assert!((parallel - 3.0).abs() < 1e-12);
+        // 端点最近
+        let apart = segment_distance(
+            &p(0.0, 0.0, 0.0),
+            &p(1.0, 0.0, 0.0),
+            &p(4.0, 4.0, 0.0),
+            &p(4.0, 8.0, 0.0),
+        );
+        assert!((apart - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zero_pose_anchors() {
+        let capsules = capsules(ArmModel::Xarm6, &[0.0; 6]);
+        let wrist = capsules.iter().find(|c| c.name == "wrist").unwrap();
+        assert!((wrist.end - Point3::new(207.0, 0.0, 112.0)).norm() < 1e-6);
+        // xArm5 换算后的肘部拐点与 xArm6 相同
+        let xarm5 = super::capsules(ArmModel::Xarm5, &[0.0; 5]);
+        let elbow = |c: &[Capsule]| c.iter().find(|c| c.name == "elbow").unwrap().end;
+        assert!((elbow(&xarm5) - elbow(&capsules)).norm() < 1e-2);
+    }
+}
@@ -0,0 +1,462 @@
+//! 离线安全检查
+//!
+//! 对单个关节角或整条轨迹检查：关节超限、连杆离开安全区（见 `zones`）、
+//! 连杆之间的自碰撞（胶囊体近似，见 `capsule`），并给出自碰撞和各安全区的最小间距。
+//! 笛卡尔轨迹逐点求逆解（以上一点为参考），无解的点单独报告。
+
+pub mod capsule;
+pub mod zones;
+
+use serde::{Deserialize, Serialize};
+
+use self::capsule::{capsules, segment_distance, Capsule, COLLISION_PAIRS};
+use self::zones::SafetyZone;
+use crate::packages::kinematics::ik::{self, IkMethod};
+use crate::packages::kinematics::{check_joints, Pose};
+use crate::packages::trajectory::{Space, Trajectory};
+use crate::packages::xarm::model::ArmModel;
+use crate::utils::error::AppError;
+
+/// 最多报告的问题数，超过后停止检查
+const MAX_ISSUES: usize = 1000;
+/// 底座固定在安装面上，不参与安全区检查
+const FIXED_CAPSULE: &str = "base";
+
+/// 检查选项
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CheckOptions {
+    /// 间距小于该值（mm）即报告，默认 0 表示只报告接触或越界
+    pub margin: f64,
+}
+
+/// 问题类型
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    JointLimit,
+    /// 离开安全区
+    Zone,
+    SelfCollision,
+    /// 笛卡尔点无逆解
+    Unreachable,
+}
+
+/// 检查发现的问题
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// 轨迹源文件行号，单个关节角时为 0
+    pub line: usize,
+    /// 点序号（从 0 开始）
+    pub index: usize,
+    /// 关节号（从 1 开始）
+    pub joint: Option<usize>,
+    pub zone: Option<String>,
+    /// 涉及的连杆
+    pub links: Vec<String>,
+    /// 关节角（rad）或间距（mm）
+    pub value: Option<f64>,
+    pub limit: Option<f64>,
+    pub message: String,
+}
+
+/// 最小间距及其出现位置
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Clearance {
+    /// 安全区名称，自碰撞时为空
+    pub zone: Option<String>,
+    /// 间距（mm），负值表示穿透或越界的深度
+    pub distance: f64,
+    pub line: usize,
+    pub index: usize,
+    pub links: Vec<String>,
+}
+
+/// 检查结果
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CheckReport {
+    pub issues: Vec<Issue>,
+    /// 连杆之间的最小间距
+    pub self_clearance: Option<Clearance>,
+    /// 每个启用的安全区的最小间距
+    pub zone_clearances: Vec<Clearance>,
+}
+
+impl CheckReport {
+    fn push(&mut self, issue: Issue) {
+        if self.issues.len() < MAX_ISSUES {
+            self.issues.push(issue);
+        }
+    }
+
+    fn full(&self) -> bool {
+        self.issues.len() >= MAX_ISSUES
+    }
+
+    /// 记录更小的间距
+    fn track(slot: &mut Option<Clearance>, candidate: Clearance) {
+        if slot
+            .as_ref()
+            .is_none_or(|current| candidate.distance < current.distance)
+        {
+            *slot = Some(candidate);
+        }
+    }
+}
+
+/// 检查单个关节角
+pub fn check_configuration(
+    model: ArmModel,
+    joints: &[f64],
+    zones: &[SafetyZone],
+    options: &CheckOptions,
+) -> Result<CheckReport, AppError> {
+    check_joints(model, joints)?;
+    let mut checker = Checker::new(model, zones, options);
+    checker.check(0, 0, joints);
+    Ok(checker.finish())
+}
+
+/// 检查整条轨迹，关节轨迹多出的列（旧版文件固定 7 列）被忽略
+pub fn check_trajectory(
+    model: ArmModel,
+    trajectory: &Trajectory,
+    zones: &[SafetyZone],
+    options: &CheckOptions,
+) -> Result<CheckReport, AppError> {
+    let axis = model.axis();
+    let mut checker = Checker::new(model, zones, options);
+    let mut previous: Option<Vec<f64>> = None;
+    for (index, point) in trajectory.points.iter().enumerate() {
+        if checker.report.full() {
+            break;
+        }
+        let joints = match trajectory.space {
+            Space::Joint => {
+                let joints = point.values.get(..axis).ok_or_else(|| {
+                    AppError::InvalidArgument(format!(
+                        "line {}: expected {} joints, got {}",
+                        point.line,
+                        axis,
+                        point.values.len()
+                    ))
+                })?;
+                check_joints(model, joints)?;
+                joints.to_vec()
+            }
+            Space::Cartesian => {
+                let [x, y, z, roll, pitch, yaw] = point.values[..] else {
+                    return Err(AppError::InvalidArgument(format!(
+                        "line {}: expected 6 pose values, got {}",
+                        point.line,
+                        point.values.len()
+                    )));
+                };
+                let pose = Pose {
+                    x,
+                    y,
+                    z,
+                    roll,
+                    pitch,
+                    yaw,
+                };
+                match ik::solve(model, &pose, IkMethod::Auto, previous.as_deref()) {
+                    Ok(solutions) => solutions[0].joints.clone(),
+                    Err(e) => {
+                        checker.report.push(Issue {
+                            kind: IssueKind::Unreachable,
+                            line: point.line,
+                            index,
+                            joint: None,
+                            zone: None,
+                            links: Vec::new(),
+                            value: None,
+                            limit: None,
+                            message: format!("no inverse kinematics solution: {}", e),
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
+        checker.check(point.line, index, &joints);
+        previous = Some(joints);
+    }
+    Ok(checker.finish())
+}
+
+struct Checker<'a> {
+    model: ArmModel,
+    zones: Vec<&'a SafetyZone>,
+    margin: f64,
+    report: CheckReport,
+    /// 与 `zones` 一一对应
+    zone_clearances: Vec<Option<Clearance>>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(model: ArmModel, zones: &'a [SafetyZone], options: &CheckOptions) -> Self {
+        let zones: Vec<&SafetyZone> = zones.iter().filter(|zone| zone.enabled).collect();
+        Self {
+            model,
+            margin: options.margin,
+            report: CheckReport::default(),
+            zone_clearances: vec![None; zones.len()],
+            zones,
+        }
+    }
+
+    fn check(&mut self, line: usize, index: usize, joints: &[f64]) {
+        let issue = |kind, message: String| Issue {
+            kind,
+            line,
+            index,
+            joint: None,
+            zone: None,
+            links: Vec::new(),
+            value: None,
+            limit: None,
+            message,
+        };
+
+        for (joint, (value, (min, max))) in
+            joints.iter().zip(self.model.limits().joints).enumerate()
+        {
+            let limit = if *value < min {
+                min
+            } else if *value > max {
+                max
+            } else {
+                continue;
+            };
+            self.report.push(Issue {
+                joint: Some(joint + 1),
+                value: Some(*value),
+                limit: Some(limit),
+                ..issue(
+                    IssueKind::JointLimit,
+                    format!(
+                        "J{} = {:.2}° is outside [{:.2}°, {:.2}°]",
+                        joint + 1,
+                        value.to_degrees(),
+                        min.to_degrees(),
+                        max.to_degrees()
+                    ),
+                )
+            });
+        }
+
+        let capsules = capsules(self.model, joints);
+        let find = |name: &str| capsules.iter().find(|capsule| capsule.name == name);
+        for (a, b) in COLLISION_PAIRS {
+            let (Some(a), Some(b)) = (find(a), find(b)) else {
+                continue;
+            };
+            let distance = capsule_distance(a, b);
+            let links = vec![a.name.to_string(), b.name.to_string()];
+            if distance < self.margin {
+                self.report.push(Issue {
+                    links: links.clone(),
+                    value: Some(distance),
+                    limit: Some(self.margin),
+                    ..issue(
+                        IssueKind::SelfCollision,
+                        format!("{} and {} are {:.1} mm apart", a.name, b.name, distance),
+                    )
+                });
+            }
+            CheckReport::track(
+                &mut self.report.self_clearance,
+                Clearance {
+                    zone: None,
+                    distance,
+                    line,
+                    index,
+                    links,
+                },
+            );
+        }
+
+        for (zone, slot) in self.zones.iter().zip(self.zone_clearances.iter_mut()) {
+            let Some((capsule, distance)) = capsules
+                .iter()
+                .filter(|capsule| capsule.name != FIXED_CAPSULE)
+                .map(|capsule| (capsule, zone.clearance(capsule)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                continue;
+            };
+            if distance < self.margin {
+                self.report.push(Issue {
+                    zone: Some(zone.name.clone()),
+                    links: vec![capsule.name.to_string()],
+                    value: Some(distance),
+                    limit: Some(self.margin),
+                    ..issue(
+                        IssueKind::Zone,
+                        format!(
+                            "{} is {:.1} mm from the boundary of zone {}",
+                            capsule.name, distance, zone.name
+                        ),
+                    )
+                });
+            }
+            CheckReport::track(
+                slot,
+                Clearance {
+                    zone: Some(zone.name.clone()),
+                    distance,
+                    line,
+                    index,
+                    links: vec![capsule.name.to_string()],
+                },
+            );
+        }
+    }
+
+    fn finish(self) -> CheckReport {
+        let mut report = self.report;
+        report.zone_clearances = self.zone_clearances.into_iter().flatten().collect();
+        report
+    }
+}
+
+/// 两个胶囊体表面之间的距离，穿透时为负
+fn capsule_distance(a: &Capsule, b: &Capsule) -> f64 {
+    segment_distance(&a.start, &a.end, &b.start, &b.end) - a.radius - b.radius
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::zones::ZoneShape;
+    use super::*;
+    use crate::packages::kinematics::forward;
+    use crate::packages::trajectory::TrajectoryPoint;
+
+    const MODELS: [ArmModel; 5] = [
+        ArmModel::Xarm5,
+        ArmModel::Xarm6,
+        ArmModel::Xarm7,
+        ArmModel::Lite6,
+        ArmModel::Xarm850,
+    ];
+
+    fn table() -> SafetyZone {
+        SafetyZone {
+            name: "table".to_string(),
+            enabled: true,
+            shape: ZoneShape::Plane {
+                point: [0.0, 0.0, 0.0],
+                normal: [0.0, 0.0, 1.0],
+            },
+        }
+    }
+
+    #[test]
+    fn zero_pose_is_clear() {
+        for model in MODELS {
+            let joints = vec![0.0; model.axis()];
+            let report =
+                check_configuration(model, &joints, &[table()], &CheckOptions::default()).unwrap();
+            assert!(report.issues.is_empty(), "{:?}: {:?}", model, report.issues);
+            assert!(report.self_clearance.unwrap().distance > 0.0);
+            assert_eq!(report.zone_clearances.len(), 1);
+        }
+    }
+
+    #[test]
+    fn reports_joint_limits() {
+        let joints = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+        let report =
+            check_configuration(ArmModel::Xarm6, &joints, &[], &CheckOptions::default()).unwrap();
+        let issue = &report.issues[0];
+        assert_eq!(issue.kind, IssueKind::JointLimit);
+        assert_eq!(issue.joint, Some(3));
+    }
+
+    #[test]
+    fn detects_self_collision() {
+        // J5 折回，腕部打到肘部
+        let joints = [0.0, 0.0, 0.0, 0.0, 3.1, 0.0];
+        let report =
+            check_configuration(ArmModel::Xarm6, &joints, &[], &CheckOptions::default()).unwrap();
+        let issue = report
+            .issues
+            .iter()
+            .find(|issue| issue.kind == IssueKind::SelfCollision)
+            .expect("self collision");
+        assert!(issue.links.contains(&"wrist".to_string()));
+        assert!(report.self_clearance.unwrap().distance < 0.0);
+    }
+
+    #[test]
+    fn trajectory_leaves_zone() {
+        // 零位附近向下压，法兰低于 150 mm 的台面
+        let mut zone = table();
+        zone.shape = ZoneShape::Plane {
+            point: [0.0, 0.0, 150.0],
+            normal: [0.0, 0.0, 1.0],
+        };
+        let disabled = SafetyZone {
+            name: "disabled".to_string(),
+            enabled: false,
+            ..table()
+        };
+        let points = [0.0, 0.2, 0.4]
+            .iter()
+            .enumerate()
+            .map(|(i, j2)| TrajectoryPoint {
+                line: i + 2,
+                time: i as f64,
+                values: vec![0.0, *j2, -0.2, 0.0, 0.0, 0.0],
+            })
+            .collect();
+        let trajectory = Trajectory {
+            name: "test".to_string(),
+            space: Space::Joint,
+            points,
+        };
+        let report = check_trajectory(
+            ArmModel::Xarm6,
+            &trajectory,
+            &[zone, disabled],
+            &CheckOptions::default(),
+        )
+        .unwrap();
+        assert!(report
+            .issues
+            .iter()
+            .all(|issue| issue.kind == IssueKind::Zone));
+        assert!(report.issues.iter().any(|issue| issue.line == 4));
+        assert_eq!(report.zone_clearances.len(), 1);
+        assert_eq!(report.zone_clearances[0].index, 2);
+    }
+
+    #[test]
+    fn cartesian_trajectory_uses_inverse_kinematics() {
+        let reachable = forward(ArmModel::Xarm6, &[FRAC_PI_2, -0.3, -1.0, 0.0, 1.2, 0.0]).unwrap();
+        let point = |line, pose: Pose| TrajectoryPoint {
+            line,
+            time: 0.0,
+            values: vec![pose.x, pose.y, pose.z, pose.roll, pose.pitch, pose.yaw],
+        };
+        let far = Pose {
+            x: 3000.0,
+            ..reachable
+        };
+        let trajectory = Trajectory {
+            name: "test".to_string(),
+            space: Space::Cartesian,
+            points: vec![point(2, reachable), point(3, far)],
+        };
+        let report =
+            check_trajectory(ArmModel::Xarm6, &trajectory, &[], &CheckOptions::default()).unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::Unreachable);
+        assert_eq!(report.issues[0].line, 3);
+        assert_eq!(report.self_clearance.unwrap().index, 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! 用户定义的安全区
+//!
+//! 安全区描述机械臂允许活动的范围（基座坐标系，mm）：长方体要求连杆留在盒内，
+//! 平面要求连杆留在法向一侧。按 arm_sn 保存在应用数据目录的 `safety_zones.json` 中。
+
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use super::capsule::Capsule;
+use crate::utils::error::AppError;
+
+/// 安全区文件（位于应用数据目录）
+const ZONES_STORE: &str = "safety_zones.json";
+
+/// 安全区形状
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ZoneShape {
+    /// 与坐标轴对齐的长方体，连杆必须在盒内
+    Box { min: [f64; 3], max: [f64; 3] },
+    /// 平面，连杆必须在 `normal` 指向的一侧，例如桌面取 `normal = [0, 0, 1]`
+    Plane { point: [f64; 3], normal: [f64; 3] },
+}
+
+/// 安全区
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SafetyZone {
+    pub name: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub shape: ZoneShape,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl SafetyZone {
+    /// 点到边界的有向距离，区域内为正
+    fn signed_distance(&self, point: &Point3<f64>) -> f64 {
+        match &self.shape {
+            ZoneShape::Box { min, max } => {
+                let min = Point3::from(*min);
+                let max = Point3::from(*max);
+                let inside = (0..3)
+                    .map(|i| (point[i] - min[i]).min(max[i] - point[i]))
+                    .fold(f64::INFINITY, f64::min);
+                if inside >= 0.0 {
+                    inside
+                } else {
+                    let nearest =
+                        Point3::from(Vector3::from_fn(|i, _| point[i].clamp(min[i], max[i])));
+                    -(point - nearest).norm()
+                }
+            }
+            ZoneShape::Plane {
+                point: origin,
+                normal,
+            } => {
+                let normal = Vector3::from(*normal).normalize();
+                (point - Point3::from(*origin)).dot(&normal)
+            }
+        }
+    }
+
+    /// 胶囊体到边界的最小间距，越界时为负。区域是凸的，最小值必在线段端点处取得
+    pub fn clearance(&self, capsule: &Capsule) -> f64 {
+        self.signed_distance(&capsule.start)
+            .min(self.signed_distance(&capsule.end))
+            - capsule.radius
+    }
+}
+
+/// 检查名称唯一、形状有效
+pub fn validate(zones: &[SafetyZone]) -> Result<(), AppError> {
+    for (i, zone) in zones.iter().enumerate() {
+        if zone.name.trim().is_empty() {
+            return Err(AppError::InvalidArgument(format!(
+                "zone {} has no name",
+                i + 1
+            )));
+        }
+        if zones[..i].iter().any(|other| other.name == zone.name) {
+            return Err(AppError::InvalidArgument(format!(
+                "duplicate zone name {:?}",
+                zone.name
+            )));
+        }
+        let valid = match &zone.shape {
+            ZoneShape::Box { min, max } => min
+                .iter()
+                .zip(max)
+                .all(|(min, max)| min.is_finite() && max.is_finite() && min < max),
+            ZoneShape::Plane { point, normal } => {
+                point.iter().all(|v| v.is_finite()) && Vector3::from(*normal).norm() > f64::EPSILON
+            }
+        };
+        if !valid {
+            return Err(AppError::InvalidArgument(format!(
+                "zone {:?} has an invalid shape",
+                zone.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 读取某台机械臂的安全区，未设置时为空
+pub fn load<R: Runtime>(app: &AppHandle<R>, arm_sn: &str) -> Result<Vec<SafetyZone>, AppError> {
+    let store = app
+        .store(ZONES_STORE)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    match store.get(arm_sn) {
+        Some(value) => serde_json::from_value(value)
+            .map_err(|e| AppError::Io(format!("invalid safety zones for {}: {}", arm_sn, e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 保存某台机械臂的安全区，空列表时删除记录
+pub fn save<R: Runtime>(
+    app: &AppHandle<R>,
+    arm_sn: &str,
+    zones: &[SafetyZone],
+) -> Result<(), AppError> {
+    if arm_sn.trim().is_empty() {
+        return Err(AppError::InvalidArgument("arm_sn is empty".to_string()));
+    }
+    validate(zones)?;
+    let store = app
+        .store(ZONES_STORE)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    if zones.is_empty() {
+        store.delete(arm_sn);
+    } else {
+        store.set(arm_sn, json!(zones));
+    }
+    store.save().map_err(|e| AppError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capsule(start: [f64; 3], end: [f64; 3], radius: f64) -> Capsule {
+        Capsule {
+            name: "test",
+            start: Point3::from(start),
+            end: Point3::from(end),
+            radius,
+        }
+    }
+
+    #[test]
+    fn box_clearance() {
+        let zone = SafetyZone {
+            name: "cell".to_string(),
+            enabled: true,
+            shape: ZoneShape::Box {
+                min: [-500.0, -500.0, 0.0],
+                max: [500.0, 500.0, 1000.0],
+            },
+        };
+        let inside = capsule([0.0, 0.0, 100.0], [300.0, 0.0, 400.0], 20.0);
+        assert!((zone.clearance(&inside) - 80.0).abs() < 1e-9);
+        let outside = capsule([0.0, 0.0, 100.0], [600.0, 0.0, 400.0], 20.0);
+        assert!((zone.clearance(&outside) + 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn plane_clearance() {
+        let zone: SafetyZone = serde_json::from_value(json!({
+            "name": "table",
+            "type": "plane",
+            "point": [0.0, 0.0, 10.0],
+            "normal": [0.0, 0.0, 2.0],
+        }))
+        .unwrap();
+        assert!(zone.enabled);
+        let capsule = capsule([0.0, 0.0, 200.0], [100.0, 0.0, 40.0], 20.0);
+        assert!((zone.clearance(&capsule) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_invalid_zones() {
+        let zone = |name: &str, shape| SafetyZone {
+            name: name.to_string(),
+            enabled: true,
+            shape,
+        };
+        let flat = ZoneShape::Box {
+            min: [0.0, 0.0, 0.0],
+            max: [100.0, 100.0, 0.0],
+        };
+        assert!(validate(&[zone("a", flat)]).is_err());
+        let plane = ZoneShape::Plane {
+            point: [0.0; 3],
+            normal: [0.0, 0.0, 1.0],
+        };
+        assert!(validate(&[zone("a", plane.clone())]).is_ok());
+        assert!(validate(&[zone("a", plane.clone()), zone("a", plane)]).is_err());
+    }
+}
@@ -0,0 +1,486 @@
+//! 控制器日志下载与归档
+//!
+//! 通过控制器的 HTTP 服务（`CONTROLLER_HTTP_PORT`）拉取日志：先请求 `GET /controller/logs` 取得文件列表
+//! （`[{ "name": ..., "size": ... }]`），再逐个请求 `GET /controller/logs/<name>`。
+//! 不提供该接口的控制器（如旧固件）列表请求返回 404，会报 "not found" 错误而不是静默返回空列表。
+//! 每次下载保存为 `{app_log_dir}/controllers/<arm_sn>/<时间戳>/` 下的一个归档，
+//! 下载失败时删除不完整的归档。进度通过 `controller_log_progress` 事件推送。
+//!
+//! 这两个接口没有公开文档，路径和列表格式是本模块假设的，尚未在真实控制器上确认。
+//! 确认前整个模块放在 `controller-log-download` feature 后面，默认不编译、不注册命令。
+
+use std::fs::{self, File};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{info, warn};
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::time::{self, Instant};
+
+use crate::packages::xarm::CONTROLLER_HTTP_PORT;
+use crate::utils::error::AppError;
+use crate::utils::files::{self, is_plain_name, plain_name};
+
+/// 下载进度推送
+pub const EVENT_CONTROLLER_LOG_PROGRESS: &str = "controller_log_progress";
+
+/// 日志列表接口，单个文件为 `{LIST_PATH}/<name>`
+const LIST_PATH: &str = "/controller/logs";
+/// 归档根目录名
+const DIR_NAME: &str = "controllers";
+/// 归档目录名格式，按字符串排序即按时间排序
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// 请求文件列表的超时
+const LIST_TIMEOUT: Duration = Duration::from_secs(10);
+/// 连续多久收不到数据视为超时
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(30);
+/// 进度推送间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 要下载日志的机械臂，可以直接传 `ArmIpIntro` 对象（只用到 `ip` 和 `arm_sn`）
+#[derive(Deserialize, Clone, Debug)]
+pub struct LogSource {
+    pub ip: String,
+    pub arm_sn: String,
+    /// 控制器 HTTP 端口，默认 18333
+    #[serde(default)]
+    pub http_port: Option<u16>,
+}
+
+impl LogSource {
+    fn base_url(&self) -> Result<Url, AppError> {
+        let ip: IpAddr =
+            self.ip.trim().parse().map_err(|_| {
+                AppError::InvalidArgument(format!("invalid ip address: {}", self.ip))
+            })?;
+        let addr = SocketAddr::new(ip, self.http_port.unwrap_or(CONTROLLER_HTTP_PORT));
+        Url::parse(&format!("http://{}", addr))
+            .map_err(|e| AppError::InvalidArgument(e.to_string()))
+    }
+}
+
+/// 控制器上的日志文件
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RemoteLog {
+    pub name: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// 下载进度，同时作为 `controller_log_progress` 事件内容
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DownloadProgress {
+    pub arm_sn: String,
+    /// 当前文件
+    pub file: String,
+    /// 当前文件序号，从 0 开始
+    pub index: usize,
+    pub files: usize,
+    /// 已接收的总字节数
+    pub received: u64,
+    /// 总字节数，控制器未给出所有文件大小时为空
+    pub total: Option<u64>,
+    /// 全部文件下载完成
+    pub done: bool,
+}
+
+/// 归档中的文件
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ArchivedLog {
+    pub name: String,
+    pub size: u64,
+}
+
+/// 一次下载的归档
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LogArchive {
+    pub arm_sn: String,
+    /// 下载时间，即目录名
+    pub timestamp: String,
+    pub path: PathBuf,
+    pub files: Vec<ArchivedLog>,
+    pub size: u64,
+}
+
+/// 归档根目录，不存在时创建
+pub fn archive_root<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    files::log_dir(app, DIR_NAME)
+}
+
+/// 下载一台机械臂的全部日志并归档
+pub async fn download<R: Runtime>(
+    app: &AppHandle<R>,
+    client: &Client,
+    source: &LogSource,
+) -> Result<LogArchive, AppError> {
+    let base = source.base_url()?;
+    let arm_sn = plain_name(&source.arm_sn, "arm_sn")?;
+    let dir = create_archive_dir(&archive_root(app)?.join(arm_sn))?;
+    info!(
+        "Downloading controller logs of {} into {}",
+        arm_sn,
+        dir.display()
+    );
+
+    let result = fetch_logs(client, &base, &dir, arm_sn, |progress| {
+        let _ = app.emit(EVENT_CONTROLLER_LOG_PROGRESS, progress.clone());
+    })
+    .await;
+    if let Err(e) = result {
+        warn!("Controller log download of {} failed: {}", arm_sn, e);
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            warn!("Failed to remove {}: {}", dir.display(), e);
+        }
+        return Err(e);
+    }
+    read_archive(arm_sn, &dir)
+}
+
+/// 列出已下载的归档，最新的在前；`arm_sn` 为空时列出所有机械臂
+pub fn list<R: Runtime>(
+    app: &AppHandle<R>,
+    arm_sn: Option<&str>,
+) -> Result<Vec<LogArchive>, AppError> {
+    list_archives(&archive_root(app)?, arm_sn)
+}
+
+/// 按时间戳新建归档目录，同一秒内重复下载时追加序号
+fn create_archive_dir(parent: &Path) -> Result<PathBuf, AppError> {
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    files::create_new_dir(parent, &timestamp)
+}
+
+fn request_error(url: &Url, e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::Timeout(url.to_string())
+    } else if e.is_connect() {
+        AppError::Connect {
+            addr: url.to_string(),
+            message: e.to_string(),
+        }
+    } else {
+        AppError::Io(e.to_string())
+    }
+}
+
+fn check_status(url: &Url, response: Response) -> Result<Response, AppError> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(AppError::Protocol(format!(
+            "{} not found, the controller firmware may not support log download",
+            url
+        ))),
+        status => Err(AppError::Protocol(format!("HTTP {} for {}", status, url))),
+    }
+}
+
+/// 下载日志到 `dir`，返回写入的文件
+pub async fn fetch_logs(
+    client: &Client,
+    base: &Url,
+    dir: &Path,
+    arm_sn: &str,
+    mut on_progress: impl FnMut(&DownloadProgress),
+) -> Result<Vec<ArchivedLog>, AppError> {
+    let list_url = base
+        .join(LIST_PATH)
+        .map_err(|e| AppError::InvalidArgument(e.to_string()))?;
+    let response = client
+        .get(list_url.clone())
+        .timeout(LIST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| request_error(&list_url, e))?;
+    let logs: Vec<RemoteLog> = check_status(&list_url, response)?
+        .json()
+        .await
+        .map_err(|e| AppError::Protocol(format!("invalid log list: {}", e)))?;
+    if let Some(log) = logs.iter().find(|log| !is_plain_name(&log.name)) {
+        return Err(AppError::Protocol(format!(
+            "invalid log file name: {:?}",
+            log.name
+        )));
+    }
+
+    let mut progress = DownloadProgress {
+        arm_sn: arm_sn.to_string(),
+        file: String::new(),
+        index: 0,
+        files: logs.len(),
+        received: 0,
+        total: logs.iter().map(|log| log.size).sum(),
+        done: false,
+    };
+    let mut archived = Vec::with_capacity(logs.len());
+    for (index, log) in logs.iter().enumerate() {
+        progress.file = log.name.clone();
+        progress.index = index;
+        on_progress(&progress);
+
+        let mut url = list_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| AppError::InvalidArgument(list_url.to_string()))?
+            .push(&log.name);
+        let mut response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| request_error(&url, e))
+            .and_then(|response| check_status(&url, response))?;
+
+        let mut writer = BufWriter::new(File::create(dir.join(&log.name))?);
+        let mut size = 0;
+        let mut reported = Instant::now();
+        loop {
+            let chunk = time::timeout(CHUNK_TIMEOUT, response.chunk())
+                .await
+                .map_err(|_| AppError::Timeout(url.to_string()))?
+                .map_err(|e| request_error(&url, e))?;
+            let Some(chunk) = chunk else {
+                break;
+            };
+            writer.write_all(&chunk)?;
+            size += chunk.len() as u64;
+            progress.received += chunk.len() as u64;
+            if reported.elapsed() >= PROGRESS_INTERVAL {
+                reported = Instant::now();
+                on_progress(&progress);
+            }
+        }
+        writer.flush()?;
+        archived.push(ArchivedLog {
+            name: log.name.clone(),
+            size,
+        });
+    }
+
+    progress.done = true;
+    on_progress(&progress);
+    Ok(archived)
+}
+
+fn read_archive(arm_sn: &str, dir: &Path) -> Result<LogArchive, AppError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            files.push(ArchivedLog {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                size: metadata.len(),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(LogArchive {
+        arm_sn: arm_sn.to_string(),
+        timestamp: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: dir.to_path_buf(),
+        size: files.iter().map(|file| file.size).sum(),
+        files,
+    })
+}
+
+/// 扫描归档根目录，最新的在前
+pub fn list_archives(root: &Path, arm_sn: Option<&str>) -> Result<Vec<LogArchive>, AppError> {
+    let arms = match arm_sn {
+        Some(arm_sn) => vec![arm_sn.trim().to_string()],
+        None => match fs::read_dir(root) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        },
+    };
+
+    let mut archives = Vec::new();
+    for arm_sn in arms.iter().filter(|arm_sn| is_plain_name(arm_sn)) {
+        let entries = match fs::read_dir(root.join(arm_sn)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                archives.push(read_archive(arm_sn, &path)?);
+            }
+        }
+    }
+    archives.sort_by(|a, b| {
+        b.timestamp
+            .cmp(&a.timestamp)
+            .then_with(|| a.arm_sn.cmp(&b.arm_sn))
+    });
+    Ok(archives)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::utils::http_stand_in::http_stand_in;
+
+    /// 只支持不带请求体的 GET，按路径返回固定内容，未知路径返回 404
+    async fn serve_routes(routes: HashMap<&'static str, Vec<u8>>) -> Url {
+        let addr = http_stand_in(move |request| {
+            if request.method != "GET" || !request.body.is_empty() {
+                return ("400 Bad Request", Vec::new());
+            }
+            match routes.get(request.path.as_str()) {
+                Some(body) => ("200 OK", body.clone()),
+                None => ("404 Not Found", Vec::new()),
+            }
+        })
+        .await;
+        Url::parse(&format!("http://{}", addr)).unwrap()
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("controller-logs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn downloads_listed_files() {
+        let large = vec![b'x'; 256 * 1024];
+        let base = serve_routes(HashMap::from([
+            (
+                LIST_PATH,
+                br#"[{"name":"xarm.log","size":262144},{"name":"error log.txt","size":5}]"#
+                    .to_vec(),
+            ),
+            ("/controller/logs/xarm.log", large.clone()),
+            ("/controller/logs/error%20log.txt", b"E0001".to_vec()),
+        ]))
+        .await;
+        let root = scratch_dir("download");
+        let dir = create_archive_dir(&root.join("XS1234")).unwrap();
+
+        let mut events = Vec::new();
+        let files = fetch_logs(&Client::new(), &base, &dir, "XS1234", |p| {
+            events.push(p.clone())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![
+                ArchivedLog {
+                    name: "xarm.log".to_string(),
+                    size: large.len() as u64
+                },
+                ArchivedLog {
+                    name: "error log.txt".to_string(),
+                    size: 5
+                },
+            ]
+        );
+        assert_eq!(fs::read(dir.join("error log.txt")).unwrap(), b"E0001");
+        let last = events.last().unwrap();
+        assert!(last.done);
+        assert_eq!(last.received, 262149);
+        assert_eq!(last.total, Some(262149));
+        assert!(events.windows(2).all(|w| w[0].received <= w[1].received));
+
+        let archives = list_archives(&root, None).unwrap();
+        assert_eq!(archives.len(), 1);
+        assert_eq!(archives[0].arm_sn, "XS1234");
+        assert_eq!(archives[0].size, 262149);
+        assert_eq!(archives[0].files.len(), 2);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_unsafe_names_and_missing_files() {
+        let client = Client::new();
+        let root = scratch_dir("errors");
+
+        let base = serve_routes(HashMap::from([(
+            LIST_PATH,
+            br#"[{"name":"../../evil"}]"#.to_vec(),
+        )]))
+        .await;
+        let result = fetch_logs(&client, &base, &root, "XS1234", |_| {}).await;
+        assert!(matches!(result, Err(AppError::Protocol(_))));
+
+        let base = serve_routes(HashMap::from([(
+            LIST_PATH,
+            br#"[{"name":"gone.log"}]"#.to_vec(),
+        )]))
+        .await;
+        let result = fetch_logs(&client, &base, &root, "XS1234", |_| {}).await;
+        assert!(matches!(result, Err(AppError::Protocol(_))));
+
+        // 旧固件没有日志接口
+        let base = serve_routes(HashMap::new()).await;
+        let result = fetch_logs(&client, &base, &root, "XS1234", |_| {}).await;
+        assert!(
+            matches!(result, Err(AppError::Protocol(message)) if message.contains("not found"))
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn lists_archives_newest_first() {
+        let root = scratch_dir("list");
+        for (arm_sn, timestamp) in [
+            ("XS0001", "20240101-080000"),
+            ("XS0001", "20240301-080000"),
+            ("XS0002", "20240201-080000"),
+        ] {
+            let dir = root.join(arm_sn).join(timestamp);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("xarm.log"), b"log").unwrap();
+        }
+
+        let all = list_archives(&root, None).unwrap();
+        let timestamps: Vec<_> = all.iter().map(|a| a.timestamp.as_str()).collect();
+        assert_eq!(
+            timestamps,
+            ["20240301-080000", "20240201-080000", "20240101-080000"]
+        );
+        assert_eq!(list_archives(&root, Some("XS0002")).unwrap().len(), 1);
+        assert!(list_archives(&root, Some("XS9999")).unwrap().is_empty());
+        assert!(list_archives(&root, Some("..")).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn builds_base_url() {
+        let source = LogSource {
+            ip: "192.168.1.20".to_string(),
+            arm_sn: "XS1234".to_string(),
+            http_port: None,
+        };
+        assert_eq!(
+            source.base_url().unwrap().as_str(),
+            "http://192.168.1.20:18333/"
+        );
+        let source = LogSource {
+            ip: "fe80::1".to_string(),
+            http_port: Some(8080),
+            ..source
+        };
+        assert_eq!(
+            source.base_url().unwrap().as_str(),
+            "http://[fe80::1]:8080/"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::sync::{mpsc, watch};
+
+use super::ArmIpIntro;
+
+/// 后端上报给扫描任务的事件
+#[derive(Debug)]
+pub enum BackendEvent {
+    /// 发现一台设备（尚未补充登记信息）
+    Found(Box<ArmIpIntro>),
+    /// 收到无法解析的回复
+    Invalid(String),
+    /// 单播探测进度
+    Progress { sent: usize, total: usize },
+}
+
+pub type BackendFuture = Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>>;
+
+/// 设备发现后端
+///
+/// 每个后端在独立任务中运行，把结果发送到 `events`；多个后端的结果由扫描任务
+/// 合并并按序列号去重。后端在 `stop_rx` 置位或 `events` 关闭时应尽快返回，
+/// 也可以在自身工作完成后提前返回（例如单播探测发送完毕）。
+pub trait DiscoveryBackend: Send {
+    /// 后端名称，写入 `ArmIpIntro.source`
+    fn name(&self) -> &'static str;
+
+    fn run(
+        self: Box<Self>,
+        events: mpsc::Sender<BackendEvent>,
+        stop_rx: watch::Receiver<bool>,
+    ) -> BackendFuture;
+}
+
+/// 一次扫描要运行的后端
+pub struct ScanPlan {
+    pub backends: Vec<Box<dyn DiscoveryBackend>>,
+    /// 覆盖的子网（单播探测时为探测范围），写入扫描汇总
+    pub subnets: Vec<String>,
+    /// 扫描时长，为空时一直运行直到手动停止或后端全部结束
+    pub duration: Option<std::time::Duration>,
+}
@@ -0,0 +1,69 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use if_addrs::IfAddr;
+use log::warn;
+use serde::Serialize;
+
+/// 本机 IPv4 网卡信息
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct LocalInterface {
+    pub name: String,
+    pub ip: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+    pub prefix_len: u8,
+}
+
+impl LocalInterface {
+    /// 定向广播地址，例如 192.168.1.10/24 -> 192.168.1.255
+    pub fn broadcast(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.ip) | !u32::from(self.netmask))
+    }
+
+    /// 地址是否位于该网卡的子网内
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match ip.to_canonical() {
+            IpAddr::V4(ip) => {
+                u32::from(ip) & u32::from(self.netmask)
+                    == u32::from(self.ip) & u32::from(self.netmask)
+            }
+            IpAddr::V6(_) => false,
+        }
+    }
+
+    /// 子网的 CIDR 表示，例如 192.168.1.0/24
+    pub fn subnet(&self) -> String {
+        let network = Ipv4Addr::from(u32::from(self.ip) & u32::from(self.netmask));
+        format!("{}/{}", network, self.prefix_len)
+    }
+}
+
+/// 列出本机所有可用于广播的 IPv4 网卡
+///
+/// 保留回环网卡，方便发现本机运行的控制器（回复中的 LOCAL 类型）。
+pub fn list_ipv4_interfaces() -> Vec<LocalInterface> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            warn!("Failed to enumerate network interfaces: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut result: Vec<LocalInterface> = interfaces
+        .into_iter()
+        .filter_map(|iface| match iface.addr {
+            IfAddr::V4(addr) if !addr.ip.is_unspecified() => Some(LocalInterface {
+                name: iface.name,
+                ip: addr.ip,
+                netmask: addr.netmask,
+                prefix_len: addr.prefixlen,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    // 同一网卡可能出现多次（多地址），按 IP 去重
+    result.sort_by_key(|iface| iface.ip);
+    result.dedup_by_key(|iface| iface.ip);
+    result
+}
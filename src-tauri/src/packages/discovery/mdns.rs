@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use log::{debug, info, warn};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+
+use super::backend::{BackendEvent, BackendFuture, DiscoveryBackend};
+use super::interfaces::{self, LocalInterface};
+use super::protocol::PROTOCOL_STANDARD;
+use super::ArmIpIntro;
+
+/// 后端名称
+pub const MDNS_BACKEND: &str = "mdns";
+
+/// 默认浏览的服务类型
+pub const DEFAULT_SERVICE_TYPES: [&str; 2] = ["_xarm._tcp.local.", "_ufactory._tcp.local."];
+
+/// mDNS / DNS-SD 浏览后端，适用于屏蔽 UDP 18355 广播的网络
+pub struct MdnsBackend {
+    service_types: Vec<String>,
+}
+
+impl Default for MdnsBackend {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SERVICE_TYPES
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect(),
+        )
+    }
+}
+
+impl MdnsBackend {
+    pub fn new(service_types: Vec<String>) -> Self {
+        Self { service_types }
+    }
+
+    async fn run_loop(
+        self,
+        events: mpsc::Sender<BackendEvent>,
+        mut stop_rx: watch::Receiver<bool>,
+    ) -> std::io::Result<()> {
+        let daemon = ServiceDaemon::new().map_err(std::io::Error::other)?;
+        // 网卡列表在一次扫描内只枚举一次，供所有解析结果匹配
+        let local_interfaces = Arc::new(interfaces::list_ipv4_interfaces());
+
+        let mut browsers = JoinSet::new();
+        for service_type in &self.service_types {
+            match daemon.browse(service_type) {
+                Ok(receiver) => {
+                    let events = events.clone();
+                    let local_interfaces = local_interfaces.clone();
+                    browsers.spawn(async move {
+                        while let Ok(event) = receiver.recv_async().await {
+                            let ServiceEvent::ServiceResolved(info) = event else {
+                                continue;
+                            };
+                            let event = match intro_from_service(&info, &local_interfaces) {
+                                Some(intro) => BackendEvent::Found(Box::new(intro)),
+                                None => BackendEvent::Invalid(format!(
+                                    "{} has no IP address",
+                                    info.get_fullname()
+                                )),
+                            };
+                            if events.send(event).await.is_err() {
+                                return;
+                            }
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to browse {}: {}", service_type, e),
+            }
+        }
+
+        if browsers.is_empty() {
+            let _ = daemon.shutdown();
+            return Err(std::io::Error::other(
+                "no mDNS service type could be browsed",
+            ));
+        }
+        info!("mDNS browsing {:?}", self.service_types);
+
+        tokio::select! {
+            _ = stop_rx.changed() => {}
+            _ = async { while browsers.join_next().await.is_some() {} } => {}
+        }
+
+        if let Err(e) = daemon.shutdown() {
+            debug!("Failed to shut down mDNS daemon: {}", e);
+        }
+        Ok(())
+    }
+}
+
+impl DiscoveryBackend for MdnsBackend {
+    fn name(&self) -> &'static str {
+        MDNS_BACKEND
+    }
+
+    fn run(
+        self: Box<Self>,
+        events: mpsc::Sender<BackendEvent>,
+        stop_rx: watch::Receiver<bool>,
+    ) -> BackendFuture {
+        Box::pin(self.run_loop(events, stop_rx))
+    }
+}
+
+/// 把 DNS-SD 记录转换为发现结果，TXT 字段沿用 UDP 回复中的名称
+fn intro_from_service(
+    info: &ServiceInfo,
+    local_interfaces: &[LocalInterface],
+) -> Option<ArmIpIntro> {
+    let addresses = info.get_addresses();
+    let ip = addresses
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.iter().next())
+        .copied()?;
+    let txt = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| info.get_property_val_str(key))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    // 实例名一般是序列号，TXT 中没有序列号时使用实例名
+    let instance = info
+        .get_fullname()
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let arm_sn = Some(txt(&["arm_sn", "sn"]))
+        .filter(|sn| !sn.is_empty())
+        .unwrap_or(instance);
+
+    let iface = local_interfaces.iter().find(|iface| iface.contains(ip));
+    let intro = ArmIpIntro {
+        addr_type: MDNS_BACKEND.to_string(),
+        source: MDNS_BACKEND.to_string(),
+        axis: txt(&["axis"]),
+        device_type: txt(&["device_type", "type"]),
+        version: txt(&["version", "ver"]),
+        arm_sn,
+        control_sn: txt(&["control_sn"]),
+        protocol_version: PROTOCOL_STANDARD,
+        ip: ip.to_canonical().to_string(),
+        port: info.get_port().to_string(),
+        ..Default::default()
+    };
+    Some(intro.with_interface(iface))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn service(name: &str, ip: &str, properties: &[(&str, &str)]) -> ServiceInfo {
+        ServiceInfo::new(
+            "_xarm._tcp.local.",
+            name,
+            "xarm-test.local.",
+            ip,
+            502,
+            properties,
+        )
+        .unwrap()
+    }
+
+    fn lan() -> LocalInterface {
+        LocalInterface {
+            name: "eth0".to_string(),
+            ip: Ipv4Addr::new(192, 168, 1, 100),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            prefix_len: 24,
+        }
+    }
+
+    #[test]
+    fn converts_txt_records() {
+        let info = service(
+            "XI130607B22C18",
+            "192.168.1.180",
+            &[
+                ("sn", "XI130607B22C18"),
+                ("control_sn", " XS1306C2203A07 "),
+                ("axis", "6"),
+                ("type", "6"),
+                ("ver", "2.3.0"),
+            ],
+        );
+        let intro = intro_from_service(&info, &[lan()]).unwrap();
+        assert_eq!(intro.ip, "192.168.1.180");
+        assert_eq!(intro.port, "502");
+        assert_eq!(intro.arm_sn, "XI130607B22C18");
+        assert_eq!(intro.control_sn, "XS1306C2203A07");
+        assert_eq!(intro.axis, "6");
+        assert_eq!(intro.device_type, "6");
+        assert_eq!(intro.version, "2.3.0");
+        assert_eq!(intro.source, MDNS_BACKEND);
+        assert_eq!(intro.interface, "eth0");
+        assert_eq!(intro.subnet, "192.168.1.0/24");
+    }
+
+    #[test]
+    fn falls_back_to_instance_name() {
+        let info = service("LI1006B22K0203", "10.0.0.5", &[]);
+        let intro = intro_from_service(&info, &[lan()]).unwrap();
+        assert_eq!(intro.arm_sn, "LI1006B22K0203");
+        assert_eq!(intro.interface, "");
+    }
+
+    /// 本机注册一个 DNS-SD 服务，后端应能浏览并解析到它；
+    /// 需要可收发组播的非回环网卡，用 `cargo test -- --ignored` 运行
+    #[tokio::test]
+    #[ignore = "needs a non-loopback interface with multicast"]
+    async fn discovers_local_responder() {
+        let iface = interfaces::list_ipv4_interfaces()
+            .into_iter()
+            .find(|iface| !iface.ip.is_loopback())
+            .expect("no non-loopback interface");
+
+        let service_type = "_uftest._tcp.local.";
+        let responder = ServiceDaemon::new().unwrap();
+        let info = ServiceInfo::new(
+            service_type,
+            "XI130507A21K01",
+            "uftest-responder.local.",
+            iface.ip.to_string().as_str(),
+            18333,
+            &[("control_sn", "XS1305C2101K01"), ("version", "1.12.10")][..],
+        )
+        .unwrap();
+        responder.register(info).unwrap();
+
+        let (events_tx, mut events_rx) = mpsc::channel(16);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let backend = Box::new(MdnsBackend::new(vec![service_type.to_string()]));
+        let task = tokio::spawn(backend.run(events_tx, stop_rx));
+
+        let found = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                match events_rx.recv().await {
+                    Some(BackendEvent::Found(intro)) => return *intro,
+                    Some(_) => continue,
+                    None => panic!("backend stopped"),
+                }
+            }
+        })
+        .await
+        .expect("responder was not discovered");
+
+        assert_eq!(found.arm_sn, "XI130507A21K01");
+        assert_eq!(found.control_sn, "XS1305C2101K01");
+        assert_eq!(found.version, "1.12.10");
+        assert_eq!(found.ip, iface.ip.to_string());
+        assert_eq!(found.port, "18333");
+        assert_eq!(found.interface, iface.name);
+
+        stop_tx.send(true).unwrap();
+        task.await.unwrap().unwrap();
+        let _ = responder.shutdown();
+    }
+}
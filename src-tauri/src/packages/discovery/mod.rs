@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::{self, JoinHandle};
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+use tokio::time;
+
+use crate::packages::registry::DeviceRegistry;
+
+pub mod backend;
+pub mod interfaces;
+pub mod mdns;
+pub mod presence;
+pub mod protocol;
+pub mod range;
+pub mod udp;
+
+use backend::{BackendEvent, DiscoveryBackend, ScanPlan};
+use interfaces::LocalInterface;
+use mdns::{MdnsBackend, MDNS_BACKEND};
+use presence::{device_key, DeviceRecord, PresenceOptions, PresenceTracker};
+use range::{ProbeProgress, RangeProbe, UdpRangeBackend, EVENT_PROBE_PROGRESS};
+use udp::{UdpBroadcastBackend, UDP_BACKEND};
+
+/// 机械臂发现协议端口
+pub const DISCOVERY_PORT: u16 = 18355;
+/// 发现请求报文
+pub const DISCOVERY_MESSAGE: &str = "get_xarm_addr";
+
+/// 单个设备回复事件
+pub const EVENT_XARM_IP: &str = "xarm_ip";
+/// 扫描结束事件，携带 `ScanSummary`
+pub const EVENT_SCAN_FINISHED: &str = "xarm_scan_finished";
+
+pub type ScanId = u64;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ArmIpIntro {
+    pub addr_type: String,
+    pub ip: String,
+    pub port: String,
+    pub axis: String,
+    pub device_type: String,
+    pub version: String,
+    pub arm_sn: String,
+    pub control_sn: String,
+    /// 回复的协议版本，见 `protocol` 模块
+    pub protocol_version: u8,
+    /// 新固件追加的未知字段
+    pub extra_fields: Vec<String>,
+    /// 设备登记表中的备注名
+    pub nickname: String,
+    /// 设备登记表中的标签
+    pub tags: Vec<String>,
+    /// 收到回复的本机网卡名称
+    pub interface: String,
+    /// 本机网卡 IP
+    pub local_ip: String,
+    /// 本机网卡所在子网（CIDR）
+    pub subnet: String,
+    /// 发现该设备的后端（`udp` / `mdns`）
+    pub source: String,
+}
+
+impl ArmIpIntro {
+    /// 记录回复来自哪个本机网卡
+    fn with_interface(mut self, iface: Option<&LocalInterface>) -> Self {
+        if let Some(iface) = iface {
+            self.interface = iface.name.clone();
+            self.local_ip = iface.ip.to_string();
+            self.subnet = iface.subnet();
+        }
+        self
+    }
+}
+
+/// 扫描参数（前端可选传入）
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// 扫描时长（毫秒），为空时一直运行直到手动停止
+    pub duration_ms: Option<u64>,
+    /// 重复广播间隔（毫秒），为空时只广播一次
+    pub interval_ms: Option<u64>,
+    /// 启用的发现后端（`udp` / `mdns`），为空时只用 UDP 广播；
+    /// mDNS 会在本机启动组播守护进程，需显式指定
+    pub backends: Vec<String>,
+}
+
+impl ScanOptions {
+    /// 按参数创建发现后端，`targets` 为额外单播的地址
+    fn plan(&self, targets: &[SocketAddr]) -> Result<ScanPlan, String> {
+        let names: Vec<&str> = if self.backends.is_empty() {
+            vec![UDP_BACKEND]
+        } else {
+            self.backends.iter().map(String::as_str).collect()
+        };
+
+        let mut backends: Vec<Box<dyn DiscoveryBackend>> = Vec::new();
+        let mut subnets = Vec::new();
+        for name in names {
+            match name {
+                UDP_BACKEND => {
+                    let interval = self.interval_ms.map(Duration::from_millis);
+                    let udp =
+                        UdpBroadcastBackend::bind(interval, targets).map_err(|e| e.to_string())?;
+                    subnets = udp.subnets();
+                    backends.push(Box::new(udp));
+                }
+                MDNS_BACKEND => backends.push(Box::new(MdnsBackend::default())),
+                other => return Err(format!("Unknown discovery backend: {}", other)),
+            }
+        }
+        Ok(ScanPlan {
+            backends,
+            subnets,
+            duration: self.duration_ms.map(Duration::from_millis),
+        })
+    }
+}
+
+/// 扫描结束原因
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// 到达设定时长
+    Completed,
+    /// 被手动停止
+    Stopped,
+    /// socket 出错
+    Error,
+}
+
+/// 扫描结束时发送的汇总信息
+#[derive(Serialize, Clone, Debug)]
+pub struct ScanSummary {
+    pub scan_id: ScanId,
+    pub window: String,
+    pub reason: FinishReason,
+    pub error: Option<String>,
+    /// 收到的有效回复数
+    pub replies: u32,
+    /// 无法解析的回复数
+    pub invalid: u32,
+    /// 去重后的设备数（按序列号）
+    pub devices: usize,
+    /// 本次扫描使用的网卡子网（单播探测时为探测范围）
+    pub subnets: Vec<String>,
+    /// 单播探测发送的主机数，广播扫描为 0
+    pub probed: usize,
+    pub duration_ms: u64,
+}
+
+/// 后台任务句柄：停止信号 + 任务本身
+struct TaskHandle {
+    stop_tx: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl TaskHandle {
+    /// 通知任务退出并等待其结束（调用时不能持有锁）
+    async fn finish(self) {
+        let _ = self.stop_tx.send(true);
+        if let Err(e) = self.task.await {
+            error!("Failed to join discovery task: {}", e);
+        }
+    }
+}
+
+struct ScanHandle {
+    window: String,
+    handle: TaskHandle,
+}
+
+/// 基于 tokio 的发现服务，每次扫描分配一个 ID，可并发运行
+#[derive(Clone, Default)]
+pub struct DiscoveryService {
+    next_id: Arc<AtomicU64>,
+    scans: Arc<Mutex<HashMap<ScanId, ScanHandle>>>,
+    monitor: Arc<Mutex<Option<TaskHandle>>>,
+    presence: PresenceTracker,
+    /// 广播之外额外单播的地址
+    unicast_targets: Arc<Mutex<Vec<SocketAddr>>>,
+    /// 发现的设备写入登记表
+    registry: DeviceRegistry,
+}
+
+impl DiscoveryService {
+    pub fn new(registry: DeviceRegistry) -> Self {
+        Self {
+            registry,
+            ..Default::default()
+        }
+    }
+
+    /// 启动一次扫描，结果发送到 `window` 对应的窗口
+    ///
+    /// 各后端的结果合并为一路，按序列号去重。必须在 tokio 运行时内调用（异步命令中）。
+    pub fn start_scan<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        window: &str,
+        options: ScanOptions,
+    ) -> Result<ScanId, String> {
+        let plan = options.plan(&self.unicast_targets())?;
+        self.spawn_scan(app, window, plan)
+    }
+
+    /// 启动单播范围探测，用于屏蔽广播的网络
+    ///
+    /// 回复与广播扫描走同一条 `xarm_ip` 事件通道，进度通过 `xarm_probe_progress` 上报。
+    pub fn start_probe<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        window: &str,
+        probe: RangeProbe,
+    ) -> Result<ScanId, String> {
+        let subnets = vec![probe.label.clone()];
+        let backend = UdpRangeBackend::bind(probe).map_err(|e| e.to_string())?;
+        let plan = ScanPlan {
+            backends: vec![Box::new(backend)],
+            subnets,
+            duration: None,
+        };
+        self.spawn_scan(app, window, plan)
+    }
+
+    fn spawn_scan<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        window: &str,
+        plan: ScanPlan,
+    ) -> Result<ScanId, String> {
+        let scan_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (stop_tx, stop_rx) = watch::channel(false);
+
+        // 在锁内 spawn，保证任务结束时移除自身一定发生在插入之后
+        let mut scans = self.scans.lock().map_err(|e| e.to_string())?;
+        let task = async_runtime::spawn(run_scan(
+            app.clone(),
+            self.clone(),
+            scan_id,
+            window.to_string(),
+            plan,
+            stop_rx,
+        ));
+        scans.insert(
+            scan_id,
+            ScanHandle {
+                window: window.to_string(),
+                handle: TaskHandle { stop_tx, task },
+            },
+        );
+
+        Ok(scan_id)
+    }
+
+    /// 停止指定扫描，返回是否存在该扫描
+    pub async fn stop_scan(&self, scan_id: ScanId) -> bool {
+        let handle = self
+            .scans
+            .lock()
+            .ok()
+            .and_then(|mut scans| scans.remove(&scan_id));
+
+        match handle {
+            Some(scan) => {
+                scan.handle.finish().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 停止某个窗口发起的所有扫描，返回停止的数量
+    pub async fn stop_window_scans(&self, window: &str) -> usize {
+        self.stop_where(|handle| handle.window == window).await
+    }
+
+    /// 停止所有扫描及后台在线检测
+    pub async fn stop_all(&self) -> usize {
+        self.stop_presence_monitor().await;
+        self.stop_where(|_| true).await
+    }
+
+    async fn stop_where(&self, pred: impl Fn(&ScanHandle) -> bool) -> usize {
+        // 先在锁内摘出句柄，再在锁外等待任务退出
+        let handles: Vec<ScanHandle> = match self.scans.lock() {
+            Ok(mut scans) => {
+                let ids: Vec<ScanId> = scans
+                    .iter()
+                    .filter(|(_, handle)| pred(handle))
+                    .map(|(id, _)| *id)
+                    .collect();
+                ids.iter().filter_map(|id| scans.remove(id)).collect()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        let count = handles.len();
+        for scan in handles {
+            scan.handle.finish().await;
+        }
+        count
+    }
+
+    /// 启动（或以新参数重启）后台在线检测
+    pub async fn start_presence_monitor<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        options: PresenceOptions,
+    ) -> Result<(), String> {
+        self.stop_presence_monitor().await;
+
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let task = async_runtime::spawn(run_presence_monitor(
+            app.clone(),
+            self.clone(),
+            options,
+            stop_rx,
+        ));
+
+        let previous = self
+            .monitor
+            .lock()
+            .map_err(|e| e.to_string())?
+            .replace(TaskHandle { stop_tx, task });
+        // 并发调用时可能已有新的监控任务，停掉旧的
+        if let Some(previous) = previous {
+            previous.finish().await;
+        }
+        Ok(())
+    }
+
+    /// 停止后台在线检测，返回之前是否在运行
+    pub async fn stop_presence_monitor(&self) -> bool {
+        let handle = self
+            .monitor
+            .lock()
+            .ok()
+            .and_then(|mut monitor| monitor.take());
+        match handle {
+            Some(handle) => {
+                handle.finish().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 当前在线设备
+    pub fn list_devices(&self) -> Vec<DeviceRecord> {
+        self.presence.list()
+    }
+
+    /// 添加额外单播的发现地址，广播扫描和在线检测都会向其发送请求
+    pub fn add_unicast_target(&self, target: SocketAddr) {
+        if let Ok(mut targets) = self.unicast_targets.lock() {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+
+    pub fn remove_unicast_target(&self, target: SocketAddr) {
+        if let Ok(mut targets) = self.unicast_targets.lock() {
+            targets.retain(|t| *t != target);
+        }
+    }
+
+    fn unicast_targets(&self) -> Vec<SocketAddr> {
+        self.unicast_targets
+            .lock()
+            .map(|targets| targets.clone())
+            .unwrap_or_default()
+    }
+
+    fn forget(&self, scan_id: ScanId) {
+        if let Ok(mut scans) = self.scans.lock() {
+            scans.remove(&scan_id);
+        }
+    }
+}
+
+/// 扫描任务：运行各后端并合并结果，结束时发送汇总事件
+async fn run_scan<R: Runtime>(
+    app: AppHandle<R>,
+    service: DiscoveryService,
+    scan_id: ScanId,
+    window: String,
+    plan: ScanPlan,
+    stop_rx: watch::Receiver<bool>,
+) {
+    let started = Instant::now();
+    let mut summary = ScanSummary {
+        scan_id,
+        window: window.clone(),
+        reason: FinishReason::Completed,
+        error: None,
+        replies: 0,
+        invalid: 0,
+        devices: 0,
+        subnets: plan.subnets,
+        probed: 0,
+        duration_ms: 0,
+    };
+    // 本次扫描已上报的设备，键为序列号
+    let mut seen: HashMap<String, ArmIpIntro> = HashMap::new();
+
+    let deadline = plan.duration.map(|d| time::Instant::now() + d);
+    let (reason, error) = run_backends(plan.backends, stop_rx, deadline, |event| match event {
+        BackendEvent::Found(arm_ip_intro) => {
+            summary.replies += 1;
+            let key = device_key(&arm_ip_intro);
+            // 同一台设备被多个后端或多次广播发现时，只在首次发现或地址变化时上报
+            let arm_ip_intro = match seen.get(&key) {
+                Some(known) if known.ip == arm_ip_intro.ip => known.clone(),
+                _ => {
+                    let arm_ip_intro = service.registry.enrich(&app, *arm_ip_intro);
+                    seen.insert(key, arm_ip_intro.clone());
+                    if let Err(e) = app.emit_to(window.as_str(), EVENT_XARM_IP, &arm_ip_intro) {
+                        error!("Failed to emit event: {}", e);
+                    }
+                    arm_ip_intro
+                }
+            };
+            service.presence.observe(&app, &arm_ip_intro);
+        }
+        BackendEvent::Invalid(e) => {
+            summary.invalid += 1;
+            error!("Invalid discovery reply: {}", e);
+        }
+        BackendEvent::Progress { sent, total } => {
+            summary.probed = sent;
+            let progress = ProbeProgress {
+                scan_id,
+                sent,
+                total,
+            };
+            if let Err(e) = app.emit_to(window.as_str(), EVENT_PROBE_PROGRESS, progress) {
+                error!("Failed to emit event: {}", e);
+            }
+        }
+    })
+    .await;
+
+    if let Some(e) = &error {
+        error!("UDP scan {} failed: {}", scan_id, e);
+    }
+    summary.reason = reason;
+    summary.error = error;
+    summary.devices = seen.len();
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+
+    service.forget(scan_id);
+    info!(
+        "UDP scan {} finished: {:?}, {} replies, {} devices",
+        scan_id, summary.reason, summary.replies, summary.devices
+    );
+    if let Err(e) = app.emit_to(window.as_str(), EVENT_SCAN_FINISHED, summary) {
+        error!("Failed to emit event: {}", e);
+    }
+}
+
+/// 并发运行一组后端，直到全部结束、到达 `deadline` 或收到停止信号
+///
+/// 返回结束原因和后端错误；只有全部后端都出错时才视为扫描失败。
+async fn run_backends(
+    backends: Vec<Box<dyn DiscoveryBackend>>,
+    mut stop_rx: watch::Receiver<bool>,
+    deadline: Option<time::Instant>,
+    mut on_event: impl FnMut(BackendEvent),
+) -> (FinishReason, Option<String>) {
+    let total = backends.len();
+    let (events_tx, mut events_rx) = mpsc::channel(64);
+    let (backend_stop_tx, backend_stop_rx) = watch::channel(false);
+
+    let mut tasks = JoinSet::new();
+    for backend in backends {
+        let name = backend.name();
+        let run = backend.run(events_tx.clone(), backend_stop_rx.clone());
+        tasks.spawn(async move { (name, run.await) });
+    }
+    drop(events_tx);
+
+    let mut reason = FinishReason::Completed;
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => {
+                reason = FinishReason::Stopped;
+                break;
+            }
+            _ = sleep_until(deadline) => break,
+            event = events_rx.recv() => match event {
+                Some(event) => on_event(event),
+                None => break,
+            },
+        }
+    }
+
+    // 先关闭接收端，避免后端阻塞在发送上
+    let _ = backend_stop_tx.send(true);
+    drop(events_rx);
+
+    let mut errors = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((_, Ok(()))) => {}
+            Ok((name, Err(e))) => {
+                warn!("Discovery backend {} failed: {}", name, e);
+                errors.push(format!("{}: {}", name, e));
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    if total > 0 && errors.len() == total && reason == FinishReason::Completed {
+        reason = FinishReason::Error;
+    }
+    let error = (!errors.is_empty()).then(|| errors.join("; "));
+    (reason, error)
+}
+
+/// 在线检测任务：周期性探测，刷新设备最近响应时间并清理超时设备
+async fn run_presence_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    service: DiscoveryService,
+    options: PresenceOptions,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    info!("Presence monitor started: {:?}", options);
+    let ttl = Duration::from_millis(options.ttl_ms);
+    let listen = Duration::from_millis(options.listen_ms);
+    let mut ticker = time::interval(Duration::from_millis(options.probe_interval_ms.max(1)));
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = ticker.tick() => {}
+        }
+
+        // 周期探测只用 UDP 广播，mDNS 守护进程启动开销较大
+        match UdpBroadcastBackend::bind(None, &service.unicast_targets()) {
+            Ok(backend) => {
+                let deadline = Some(time::Instant::now() + listen);
+                let (reason, error) = run_backends(
+                    vec![Box::new(backend)],
+                    stop_rx.clone(),
+                    deadline,
+                    |event| match event {
+                        BackendEvent::Found(arm_ip_intro) => {
+                            let arm_ip_intro = service.registry.enrich(&app, *arm_ip_intro);
+                            service.presence.observe(&app, &arm_ip_intro)
+                        }
+                        BackendEvent::Invalid(e) => error!("Invalid discovery reply: {}", e),
+                        BackendEvent::Progress { .. } => {}
+                    },
+                )
+                .await;
+                if reason == FinishReason::Stopped {
+                    break;
+                }
+                if let Some(e) = error {
+                    warn!("Presence probe failed: {}", e);
+                }
+            }
+            Err(e) => warn!("Presence probe failed: {}", e),
+        }
+
+        service.presence.expire(&app, ttl);
+    }
+
+    info!("Presence monitor stopped");
+}
+
+async fn sleep_until(deadline: Option<time::Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use super::ArmIpIntro;
+
+/// 新设备上线
+pub const EVENT_DEVICE_APPEARED: &str = "device_appeared";
+/// 设备 IP 或固件版本变化
+pub const EVENT_DEVICE_CHANGED: &str = "device_changed";
+/// 设备超过 TTL 未响应
+pub const EVENT_DEVICE_LOST: &str = "device_lost";
+
+/// 在线检测参数
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct PresenceOptions {
+    /// 两次探测之间的间隔（毫秒）
+    pub probe_interval_ms: u64,
+    /// 每次探测等待回复的时长（毫秒）
+    pub listen_ms: u64,
+    /// 超过该时长未响应视为离线（毫秒）
+    pub ttl_ms: u64,
+}
+
+impl Default for PresenceOptions {
+    fn default() -> Self {
+        Self {
+            probe_interval_ms: 5000,
+            listen_ms: 1500,
+            ttl_ms: 15000,
+        }
+    }
+}
+
+/// 在线设备记录
+#[derive(Serialize, Clone, Debug)]
+pub struct DeviceRecord {
+    #[serde(flatten)]
+    pub intro: ArmIpIntro,
+    /// 首次发现时间（毫秒时间戳）
+    pub first_seen: i64,
+    /// 最近一次响应时间（毫秒时间戳）
+    pub last_seen: i64,
+    /// 设备可达的各条路径（本机网卡）最近一次回复，`intro` 取自其中的首选路径
+    #[serde(skip)]
+    routes: HashMap<String, (ArmIpIntro, Instant)>,
+}
+
+/// `device_changed` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct DeviceChange {
+    pub device: DeviceRecord,
+    pub previous_ip: String,
+    pub previous_version: String,
+}
+
+/// 设备唯一键：优先 arm_sn，其次 control_sn，最后退回到 IP
+pub fn device_key(intro: &ArmIpIntro) -> String {
+    [&intro.arm_sn, &intro.control_sn, &intro.ip]
+        .into_iter()
+        .find(|value| !value.is_empty())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 回复路径：同一台设备可能经由多个本机网卡、以不同 IP 回复。
+/// 只按网卡区分，同一网卡上 IP 变化（如 DHCP 续租换了地址）仍是同一条路径
+fn route_key(intro: &ArmIpIntro) -> &str {
+    &intro.interface
+}
+
+/// 记录每台设备最近一次响应，并据此发送上线/变化/离线事件
+#[derive(Clone, Default)]
+pub struct PresenceTracker {
+    devices: Arc<Mutex<HashMap<String, DeviceRecord>>>,
+}
+
+impl PresenceTracker {
+    /// 处理一条发现回复
+    pub fn observe<R: Runtime>(&self, app: &AppHandle<R>, intro: &ArmIpIntro) {
+        if let Some(event) = self.record(intro, Instant::now()) {
+            event.emit(app);
+        }
+    }
+
+    /// 移除超过 `ttl` 未响应的设备并发送离线事件
+    pub fn expire<R: Runtime>(&self, app: &AppHandle<R>, ttl: Duration) {
+        for event in self.sweep(ttl, Instant::now()) {
+            event.emit(app);
+        }
+    }
+
+    /// 按序列号合并回复；设备同时出现在多个网卡上时保持首选路径不变，
+    /// 只有首选路径在 `sweep` 中超时后才切换，避免 IP 来回跳变。首选路径上的 IP 或版本变化立即通知
+    fn record(&self, intro: &ArmIpIntro, now: Instant) -> Option<PresenceEvent> {
+        let key = device_key(intro);
+        let route = route_key(intro).to_string();
+        let timestamp = Local::now().timestamp_millis();
+
+        let mut devices = self.devices.lock().ok()?;
+        match devices.get_mut(&key) {
+            Some(record) => {
+                record.routes.insert(route.clone(), (intro.clone(), now));
+                record.last_seen = timestamp;
+                if route != route_key(&record.intro) {
+                    return None;
+                }
+                let previous = std::mem::replace(&mut record.intro, intro.clone());
+                (previous.ip != intro.ip || previous.version != intro.version).then(|| {
+                    PresenceEvent::Changed(DeviceChange {
+                        device: record.clone(),
+                        previous_ip: previous.ip,
+                        previous_version: previous.version,
+                    })
+                })
+            }
+            None => {
+                let record = DeviceRecord {
+                    intro: intro.clone(),
+                    first_seen: timestamp,
+                    last_seen: timestamp,
+                    routes: HashMap::from([(route, (intro.clone(), now))]),
+                };
+                devices.insert(key, record.clone());
+                Some(PresenceEvent::Appeared(record))
+            }
+        }
+    }
+
+    /// 清理超时的路径；首选路径超时但仍有其他路径时切换过去，全部超时则视为离线
+    fn sweep(&self, ttl: Duration, now: Instant) -> Vec<PresenceEvent> {
+        let Ok(mut devices) = self.devices.lock() else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        devices.retain(|_, record| {
+            record
+                .routes
+                .retain(|_, (_, at)| now.saturating_duration_since(*at) <= ttl);
+            if !record.routes.contains_key(route_key(&record.intro)) {
+                // 切换到最近仍有回复的路径
+                let next = record
+                    .routes
+                    .values()
+                    .max_by_key(|(_, at)| *at)
+                    .map(|(intro, _)| intro.clone());
+                match next {
+                    Some(intro) => {
+                        let previous = std::mem::replace(&mut record.intro, intro);
+                        events.push(PresenceEvent::Changed(DeviceChange {
+                            device: record.clone(),
+                            previous_ip: previous.ip,
+                            previous_version: previous.version,
+                        }));
+                    }
+                    None => {
+                        events.push(PresenceEvent::Lost(record.clone()));
+                        return false;
+                    }
+                }
+            }
+            true
+        });
+        events
+    }
+
+    /// 当前在线设备，按首次发现时间排序
+    pub fn list(&self) -> Vec<DeviceRecord> {
+        let mut devices: Vec<DeviceRecord> = self
+            .devices
+            .lock()
+            .map(|devices| devices.values().cloned().collect())
+            .unwrap_or_default();
+        devices.sort_by_key(|record| record.first_seen);
+        devices
+    }
+}
+
+enum PresenceEvent {
+    Appeared(DeviceRecord),
+    Changed(DeviceChange),
+    Lost(DeviceRecord),
+}
+
+impl PresenceEvent {
+    fn emit<R: Runtime>(self, app: &AppHandle<R>) {
+        let result = match self {
+            PresenceEvent::Appeared(record) => {
+                info!(
+                    "Device appeared: {} ({})",
+                    record.intro.arm_sn, record.intro.ip
+                );
+                app.emit(EVENT_DEVICE_APPEARED, record)
+            }
+            PresenceEvent::Changed(change) => {
+                info!(
+                    "Device changed: {} {} -> {}, {} -> {}",
+                    change.device.intro.arm_sn,
+                    change.previous_ip,
+                    change.device.intro.ip,
+                    change.previous_version,
+                    change.device.intro.version
+                );
+                app.emit(EVENT_DEVICE_CHANGED, change)
+            }
+            PresenceEvent::Lost(record) => {
+                info!("Device lost: {} ({})", record.intro.arm_sn, record.intro.ip);
+                app.emit(EVENT_DEVICE_LOST, record)
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intro(ip: &str, interface: &str, version: &str) -> ArmIpIntro {
+        ArmIpIntro {
+            ip: ip.to_string(),
+            interface: interface.to_string(),
+            version: version.to_string(),
+            arm_sn: "XI130607B22C18".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn kinds(events: &[PresenceEvent]) -> Vec<&'static str> {
+        events
+            .iter()
+            .map(|event| match event {
+                PresenceEvent::Appeared(_) => "appeared",
+                PresenceEvent::Changed(_) => "changed",
+                PresenceEvent::Lost(_) => "lost",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_appear_version_change_and_loss() {
+        let tracker = PresenceTracker::default();
+        let start = Instant::now();
+        let ttl = Duration::from_secs(15);
+
+        let first = tracker.record(&intro("192.168.1.10", "eth0", "2.3.0"), start);
+        assert!(matches!(first, Some(PresenceEvent::Appeared(_))));
+        assert!(tracker
+            .record(&intro("192.168.1.10", "eth0", "2.3.0"), start)
+            .is_none());
+
+        let changed = tracker.record(
+            &intro("192.168.1.10", "eth0", "2.4.0"),
+            start + Duration::from_secs(1),
+        );
+        match changed {
+            Some(PresenceEvent::Changed(change)) => {
+                assert_eq!(change.previous_ip, "192.168.1.10");
+                assert_eq!(change.previous_version, "2.3.0");
+                assert_eq!(change.device.intro.version, "2.4.0");
+            }
+            _ => panic!("expected device_changed"),
+        }
+
+        assert!(tracker
+            .sweep(ttl, start + Duration::from_secs(10))
+            .is_empty());
+        let lost = tracker.sweep(ttl, start + Duration::from_secs(20));
+        assert_eq!(kinds(&lost), ["lost"]);
+        assert!(tracker.list().is_empty());
+    }
+
+    #[test]
+    fn reports_ip_change_on_same_interface_at_once() {
+        let tracker = PresenceTracker::default();
+        let start = Instant::now();
+        let ttl = Duration::from_secs(15);
+
+        tracker.record(&intro("192.168.1.10", "eth0", "2.3.0"), start);
+        // DHCP 续租换了地址
+        let renewed = start + Duration::from_secs(5);
+        match tracker.record(&intro("192.168.1.23", "eth0", "2.3.0"), renewed) {
+            Some(PresenceEvent::Changed(change)) => {
+                assert_eq!(change.previous_ip, "192.168.1.10");
+                assert_eq!(change.device.intro.ip, "192.168.1.23");
+            }
+            _ => panic!("expected device_changed"),
+        }
+        assert_eq!(tracker.list()[0].intro.ip, "192.168.1.23");
+        // 旧地址不会作为单独的路径在超时时再切换一次
+        tracker.record(
+            &intro("192.168.1.23", "eth0", "2.3.0"),
+            start + Duration::from_secs(15),
+        );
+        assert!(tracker
+            .sweep(ttl, start + Duration::from_secs(25))
+            .is_empty());
+    }
+
+    #[test]
+    fn second_interface_does_not_flip_ip() {
+        let tracker = PresenceTracker::default();
+        let start = Instant::now();
+        let ttl = Duration::from_secs(15);
+
+        tracker.record(&intro("192.168.1.10", "eth0", "2.3.0"), start);
+        for round in 1..5 {
+            let at = start + Duration::from_secs(round * 5);
+            assert!(tracker
+                .record(&intro("10.0.0.10", "wlan0", "2.3.0"), at)
+                .is_none());
+            assert!(tracker
+                .record(&intro("192.168.1.10", "eth0", "2.3.0"), at)
+                .is_none());
+            assert!(tracker.sweep(ttl, at).is_empty());
+        }
+
+        let devices = tracker.list();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].intro.ip, "192.168.1.10");
+        assert_eq!(devices[0].intro.interface, "eth0");
+    }
+
+    #[test]
+    fn switches_route_when_preferred_expires() {
+        let tracker = PresenceTracker::default();
+        let start = Instant::now();
+        let ttl = Duration::from_secs(15);
+
+        tracker.record(&intro("192.168.1.10", "eth0", "2.3.0"), start);
+        tracker.record(&intro("10.0.0.10", "wlan0", "2.3.0"), start);
+        // 有线断开，只剩无线回复
+        let later = start + Duration::from_secs(10);
+        tracker.record(&intro("10.0.0.10", "wlan0", "2.3.0"), later);
+
+        let events = tracker.sweep(ttl, start + Duration::from_secs(20));
+        match events.as_slice() {
+            [PresenceEvent::Changed(change)] => {
+                assert_eq!(change.previous_ip, "192.168.1.10");
+                assert_eq!(change.device.intro.ip, "10.0.0.10");
+            }
+            _ => panic!("expected a single device_changed"),
+        }
+
+        // 之后有线恢复也不再跳回
+        let back = start + Duration::from_secs(21);
+        assert!(tracker
+            .record(&intro("192.168.1.10", "eth0", "2.3.0"), back)
+            .is_none());
+        assert_eq!(tracker.list()[0].intro.ip, "10.0.0.10");
+
+        let lost = tracker.sweep(ttl, start + Duration::from_secs(60));
+        assert_eq!(kinds(&lost), ["lost"]);
+    }
+}
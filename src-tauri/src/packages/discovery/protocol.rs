@@ -0,0 +1,291 @@
+//! 发现协议回复解析
+//!
+//! 回复格式：`<prefix>:<axis>,<device_type>,<arm_sn>,<control_sn>,<version>[,...]:<addr_type>[:...]`
+//!
+//! * 旧固件没有 `addr_type` 段（协议版本 0）
+//! * 标准回复为 5 个字段 + `addr_type`（协议版本 1）
+//! * 新固件可能在字段或段末尾追加内容，解析时保留在 `extra_fields` 中（协议版本 2）
+
+use std::fmt;
+use std::net::SocketAddr;
+
+use super::ArmIpIntro;
+
+/// 合法的回复前缀（不区分大小写）
+const PREFIXES: [&str; 2] = ["xarm", "uf"];
+
+/// 旧固件，无 addr_type 段
+pub const PROTOCOL_LEGACY: u8 = 0;
+/// 标准格式
+pub const PROTOCOL_STANDARD: u8 = 1;
+/// 含有未知的扩展字段
+pub const PROTOCOL_EXTENDED: u8 = 2;
+
+/// 解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// 空报文
+    Empty,
+    /// 前缀不是 xarm/uf
+    UnknownPrefix(String),
+    /// 缺少设备信息段
+    MissingIntro,
+    /// 设备信息字段不足
+    MissingField(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty reply"),
+            ParseError::UnknownPrefix(prefix) => write!(f, "unknown reply prefix: {:?}", prefix),
+            ParseError::MissingIntro => write!(f, "missing device intro section"),
+            ParseError::MissingField(field) => write!(f, "missing field: {}", field),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 解析后的发现回复
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryReply {
+    pub prefix: String,
+    pub protocol_version: u8,
+    /// 轴数与设备类型按原样保留，旧固件可能回复非数字或空值
+    pub axis: String,
+    pub device_type: String,
+    pub arm_sn: String,
+    pub control_sn: String,
+    pub version: String,
+    /// 原始 addr_type，`LOCAL` 会被转换为 `localhost`，缺失时为 `unknown`
+    pub addr_type: String,
+    /// 未识别的附加字段（按出现顺序）
+    pub extra_fields: Vec<String>,
+}
+
+impl DiscoveryReply {
+    /// 解析一条回复报文
+    pub fn parse(data: &str) -> Result<Self, ParseError> {
+        let data = data.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        if data.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let mut sections = data.split(':');
+        let prefix = sections.next().unwrap_or_default().trim();
+        if !PREFIXES.iter().any(|p| p.eq_ignore_ascii_case(prefix)) {
+            return Err(ParseError::UnknownPrefix(prefix.to_string()));
+        }
+
+        let intro = sections.next().ok_or(ParseError::MissingIntro)?;
+        let addr_type = sections.next().map(str::trim);
+        let mut extra_fields: Vec<String> = Vec::new();
+
+        let mut fields = intro.split(',').map(str::trim);
+        let axis = fields.next().ok_or(ParseError::MissingField("axis"))?;
+        let device_type = fields
+            .next()
+            .ok_or(ParseError::MissingField("device_type"))?;
+        let mut next_field = |name: &'static str| {
+            fields
+                .next()
+                .filter(|value| !value.is_empty())
+                .ok_or(ParseError::MissingField(name))
+        };
+        let arm_sn = next_field("arm_sn")?.to_string();
+        let control_sn = next_field("control_sn")?.to_string();
+        let version = next_field("version")?.to_string();
+        extra_fields.extend(fields.filter(|v| !v.is_empty()).map(str::to_string));
+        extra_fields.extend(sections.map(|v| v.trim().to_string()));
+
+        let protocol_version = match addr_type {
+            None => PROTOCOL_LEGACY,
+            Some(_) if !extra_fields.is_empty() => PROTOCOL_EXTENDED,
+            Some(_) => PROTOCOL_STANDARD,
+        };
+        let addr_type = match addr_type {
+            Some(t) if t.eq_ignore_ascii_case("LOCAL") => "localhost".to_string(),
+            Some(t) if !t.is_empty() => t.to_string(),
+            _ => "unknown".to_string(),
+        };
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            protocol_version,
+            axis: axis.to_string(),
+            device_type: device_type.to_string(),
+            arm_sn,
+            control_sn,
+            version,
+            addr_type,
+            extra_fields,
+        })
+    }
+
+    /// 结合来源地址转换为前端使用的 `ArmIpIntro`
+    pub fn into_intro(self, src: &SocketAddr) -> ArmIpIntro {
+        // IPv4 映射的 IPv6 地址（::ffff:a.b.c.d）还原为 IPv4
+        let ip = src.ip().to_canonical();
+        ArmIpIntro {
+            addr_type: self.addr_type,
+            ip: ip.to_string(),
+            port: src.port().to_string(),
+            axis: self.axis,
+            device_type: self.device_type,
+            version: self.version,
+            arm_sn: self.arm_sn,
+            control_sn: self.control_sn,
+            protocol_version: self.protocol_version,
+            extra_fields: self.extra_fields,
+            nickname: String::new(),
+            tags: Vec::new(),
+            interface: String::new(),
+            local_ip: String::new(),
+            subnet: String::new(),
+            source: String::new(),
+        }
+    }
+}
+
+/// 解析接收到的数据
+pub fn parse_reply(data: &str, src: &SocketAddr) -> Result<ArmIpIntro, ParseError> {
+    DiscoveryReply::parse(data).map(|reply| reply.into_intro(src))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    fn src(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    /// 回复样例，见 `replies.json`：`source` 为 `captured` 的是现场抓包，
+    /// `synthetic` 是按回复格式构造的，抓到对应型号的真实回复后替换
+    #[derive(Deserialize)]
+    struct Sample {
+        case: String,
+        source: String,
+        data: String,
+        axis: String,
+        device_type: String,
+        arm_sn: String,
+        control_sn: String,
+        version: String,
+        addr_type: String,
+    }
+
+    fn corpus() -> Vec<Sample> {
+        serde_json::from_str(include_str!("replies.json")).unwrap()
+    }
+
+    #[test]
+    fn parses_corpus() {
+        for sample in corpus() {
+            let data = &sample.data;
+            let reply = DiscoveryReply::parse(data).unwrap_or_else(|e| panic!("{}: {}", data, e));
+            assert_eq!(reply.axis, sample.axis, "{}", data);
+            assert_eq!(reply.device_type, sample.device_type, "{}", data);
+            assert_eq!(reply.arm_sn, sample.arm_sn, "{}", data);
+            assert_eq!(reply.control_sn, sample.control_sn, "{}", data);
+            assert_eq!(reply.version, sample.version, "{}", data);
+            assert_eq!(reply.addr_type, sample.addr_type, "{}", data);
+            assert_eq!(reply.protocol_version, PROTOCOL_STANDARD, "{}", data);
+            assert!(reply.extra_fields.is_empty(), "{}", data);
+        }
+    }
+
+    #[test]
+    #[ignore = "replies.json only has synthetic samples so far"]
+    fn has_captured_reply_for_every_model() {
+        let corpus = corpus();
+        for case in ["xarm5", "xarm6", "xarm7", "lite6", "xarm850", "local"] {
+            assert!(
+                corpus
+                    .iter()
+                    .any(|sample| sample.case == case && sample.source == "captured"),
+                "no captured reply for {}",
+                case
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_trailing_fields() {
+        let reply =
+            DiscoveryReply::parse("xarm:6,6,XI130607B22C18,XS1306C2203A07,2.5.0,1,ext:LAN:v3")
+                .unwrap();
+        assert_eq!(reply.version, "2.5.0");
+        assert_eq!(reply.addr_type, "LAN");
+        assert_eq!(reply.protocol_version, PROTOCOL_EXTENDED);
+        assert_eq!(reply.extra_fields, vec!["1", "ext", "v3"]);
+    }
+
+    #[test]
+    fn accepts_legacy_reply_without_addr_type() {
+        let reply = DiscoveryReply::parse("xarm:5,5,XI130507A21K01,XS1305C2101K01,1.5.0").unwrap();
+        assert_eq!(reply.protocol_version, PROTOCOL_LEGACY);
+        assert_eq!(reply.addr_type, "unknown");
+    }
+
+    #[test]
+    fn accepts_non_numeric_axis_and_device_type() {
+        // 旧解析器对这两个字段不做校验，老固件的回复不能因此被丢弃
+        let reply =
+            DiscoveryReply::parse("xarm:xArm6,,XI130607B22C18,XS1306C2203A07,1.2.0:LAN").unwrap();
+        assert_eq!(reply.axis, "xArm6");
+        assert_eq!(reply.device_type, "");
+        assert_eq!(reply.arm_sn, "XI130607B22C18");
+
+        let intro = parse_reply(
+            "XARM:6,0x06,XI130607B22C18,XS1306C2203A07,1.2.0",
+            &src("192.168.1.180:18355"),
+        )
+        .unwrap();
+        assert_eq!(intro.axis, "6");
+        assert_eq!(intro.device_type, "0x06");
+        assert_eq!(intro.addr_type, "unknown");
+    }
+
+    #[test]
+    fn rejects_malformed_replies() {
+        assert_eq!(DiscoveryReply::parse(""), Err(ParseError::Empty));
+        assert_eq!(DiscoveryReply::parse(" \0"), Err(ParseError::Empty));
+        assert_eq!(
+            DiscoveryReply::parse("get_xarm_addr"),
+            Err(ParseError::UnknownPrefix("get_xarm_addr".to_string()))
+        );
+        assert_eq!(DiscoveryReply::parse("xarm"), Err(ParseError::MissingIntro));
+        assert_eq!(
+            DiscoveryReply::parse("xarm:6,6,XI130607B22C18:LAN"),
+            Err(ParseError::MissingField("control_sn"))
+        );
+        assert_eq!(
+            DiscoveryReply::parse("xarm:6,6,XI130607B22C18,,2.3.0:LAN"),
+            Err(ParseError::MissingField("control_sn"))
+        );
+        assert_eq!(
+            DiscoveryReply::parse("xarm:6"),
+            Err(ParseError::MissingField("device_type"))
+        );
+    }
+
+    #[test]
+    fn converts_ipv4_and_ipv6_sources() {
+        let data = "xarm:6,9,LI1006B22K0203,LS1006C2211A03,2.1.0:LAN";
+
+        let intro = parse_reply(data, &src("192.168.1.180:18355")).unwrap();
+        assert_eq!(intro.ip, "192.168.1.180");
+        assert_eq!(intro.port, "18355");
+
+        let intro = parse_reply(data, &src("[fe80::1:2ff:fe3a:4b5c]:18355")).unwrap();
+        assert_eq!(intro.ip, "fe80::1:2ff:fe3a:4b5c");
+        assert_eq!(intro.port, "18355");
+
+        let intro = parse_reply(data, &src("[::ffff:10.0.0.7]:18355")).unwrap();
+        assert_eq!(intro.ip, "10.0.0.7");
+    }
+}
@@ -0,0 +1,381 @@
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+use tokio::time;
+
+use super::backend::{BackendEvent, BackendFuture, DiscoveryBackend};
+use super::interfaces::{self, LocalInterface};
+use super::udp::{bind_broadcast_socket, spawn_receivers, to_event, ScanSocket, UDP_BACKEND};
+use super::{sleep_until, ScanId, DISCOVERY_MESSAGE, DISCOVERY_PORT};
+
+/// 单播探测进度事件
+pub const EVENT_PROBE_PROGRESS: &str = "xarm_probe_progress";
+
+/// 单次探测允许的最大主机数（/16）
+pub const MAX_RANGE_HOSTS: usize = 65536;
+/// 默认发送速率（包/秒）
+const DEFAULT_RATE: u32 = 200;
+/// 最大发送速率（包/秒）
+const MAX_RATE: u32 = 1000;
+/// 默认在最后一个包发出后继续等待回复的时长
+const DEFAULT_WAIT_MS: u64 = 2000;
+/// 每发送多少个包上报一次进度
+const PROGRESS_STEP: usize = 32;
+
+/// 前端传入的单播探测参数，`cidr` 与 `start`/`end` 二选一
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ProbeRangeRequest {
+    /// 例如 `192.168.1.0/24`
+    pub cidr: Option<String>,
+    /// 起始地址（含）
+    pub start: Option<String>,
+    /// 结束地址（含）
+    pub end: Option<String>,
+    /// 发送速率（包/秒）
+    pub rate: Option<u32>,
+    /// 发送完毕后等待回复的时长（毫秒）
+    pub wait_ms: Option<u64>,
+}
+
+/// 探测进度
+#[derive(Serialize, Clone, Debug)]
+pub struct ProbeProgress {
+    pub scan_id: ScanId,
+    pub sent: usize,
+    pub total: usize,
+}
+
+/// 地址范围解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeError {
+    /// 既没有 cidr 也没有 start/end
+    Missing,
+    InvalidAddress(String),
+    InvalidCidr(String),
+    /// start 大于 end
+    Reversed,
+    /// 超过 `MAX_RANGE_HOSTS`
+    TooLarge(usize),
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Missing => write!(f, "either cidr or start/end is required"),
+            RangeError::InvalidAddress(addr) => write!(f, "invalid IPv4 address: {}", addr),
+            RangeError::InvalidCidr(cidr) => write!(f, "invalid CIDR: {}", cidr),
+            RangeError::Reversed => write!(f, "start address is greater than end address"),
+            RangeError::TooLarge(count) => write!(
+                f,
+                "range has {} hosts, at most {} are allowed",
+                count, MAX_RANGE_HOSTS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// 解析后的单播探测计划
+#[derive(Clone, Debug)]
+pub struct RangeProbe {
+    /// 用于日志和汇总的范围描述
+    pub label: String,
+    pub hosts: Vec<Ipv4Addr>,
+    pub rate: u32,
+    pub wait: Duration,
+}
+
+impl RangeProbe {
+    pub fn from_request(request: &ProbeRangeRequest) -> Result<Self, RangeError> {
+        let (label, hosts) = match (&request.cidr, &request.start, &request.end) {
+            (Some(cidr), _, _) => (cidr.trim().to_string(), parse_cidr(cidr)?),
+            (None, Some(start), Some(end)) => (
+                format!("{}-{}", start.trim(), end.trim()),
+                parse_range(start, end)?,
+            ),
+            _ => return Err(RangeError::Missing),
+        };
+
+        Ok(Self {
+            label,
+            hosts,
+            rate: request.rate.unwrap_or(DEFAULT_RATE).clamp(1, MAX_RATE),
+            wait: Duration::from_millis(request.wait_ms.unwrap_or(DEFAULT_WAIT_MS)),
+        })
+    }
+}
+
+fn parse_addr(addr: &str) -> Result<Ipv4Addr, RangeError> {
+    addr.trim()
+        .parse()
+        .map_err(|_| RangeError::InvalidAddress(addr.trim().to_string()))
+}
+
+/// 解析 CIDR，/31 和 /32 以外的网段去掉网络地址和广播地址
+pub fn parse_cidr(cidr: &str) -> Result<Vec<Ipv4Addr>, RangeError> {
+    let invalid = || RangeError::InvalidCidr(cidr.trim().to_string());
+    let (addr, prefix) = cidr.trim().split_once('/').ok_or_else(invalid)?;
+    let addr = u32::from(parse_addr(addr)?);
+    let prefix: u32 = prefix.trim().parse().map_err(|_| invalid())?;
+    if prefix > 32 {
+        return Err(invalid());
+    }
+
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    let network = addr & mask;
+    let broadcast = network | !mask;
+    let (first, last) = if prefix >= 31 {
+        (network, broadcast)
+    } else {
+        (network + 1, broadcast - 1)
+    };
+    collect_hosts(first, last)
+}
+
+/// 解析闭区间 `start..=end`
+pub fn parse_range(start: &str, end: &str) -> Result<Vec<Ipv4Addr>, RangeError> {
+    let first = u32::from(parse_addr(start)?);
+    let last = u32::from(parse_addr(end)?);
+    if first > last {
+        return Err(RangeError::Reversed);
+    }
+    collect_hosts(first, last)
+}
+
+fn collect_hosts(first: u32, last: u32) -> Result<Vec<Ipv4Addr>, RangeError> {
+    let count = (last - first) as usize + 1;
+    if count > MAX_RANGE_HOSTS {
+        return Err(RangeError::TooLarge(count));
+    }
+    Ok((first..=last).map(Ipv4Addr::from).collect())
+}
+
+/// 单播范围探测后端，发送完毕并等待 `wait` 后自行结束
+pub struct UdpRangeBackend {
+    socket: ScanSocket,
+    probe: RangeProbe,
+    /// socket 绑定在 0.0.0.0 上，按来源地址匹配本机网卡
+    local_interfaces: Vec<LocalInterface>,
+}
+
+impl UdpRangeBackend {
+    pub fn bind(probe: RangeProbe) -> std::io::Result<Self> {
+        let socket = bind_broadcast_socket(Ipv4Addr::UNSPECIFIED)?;
+        Ok(Self {
+            socket: ScanSocket {
+                iface: None,
+                socket: Arc::new(socket),
+                target: SocketAddr::from((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)),
+            },
+            probe,
+            local_interfaces: interfaces::list_ipv4_interfaces(),
+        })
+    }
+
+    /// 按速率逐个单播发现请求
+    async fn run_loop(
+        self,
+        events: mpsc::Sender<BackendEvent>,
+        mut stop_rx: watch::Receiver<bool>,
+    ) -> std::io::Result<()> {
+        let sockets = std::slice::from_ref(&self.socket);
+        let (_receivers, mut reply_rx) = spawn_receivers(sockets);
+        let socket = &self.socket.socket;
+
+        let total = self.probe.hosts.len();
+        let mut hosts = self.probe.hosts.iter();
+        let mut sent = 0;
+        let mut ticker = time::interval(Duration::from_secs(1) / self.probe.rate);
+        let mut deadline = None;
+        let mut event = Some(BackendEvent::Progress { sent, total });
+
+        loop {
+            if let Some(event) = event.take() {
+                if events.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+
+            tokio::select! {
+                _ = stop_rx.changed() => return Ok(()),
+                _ = sleep_until(deadline) => return Ok(()),
+                _ = ticker.tick(), if deadline.is_none() => {
+                    if let Some(host) = hosts.next() {
+                        let target = SocketAddr::from((*host, DISCOVERY_PORT));
+                        // 不可达等错误只影响单个主机
+                        if let Err(e) = socket.send_to(DISCOVERY_MESSAGE.as_bytes(), target).await {
+                            debug!("Failed to probe {}: {}", target, e);
+                        }
+                        sent += 1;
+                        if sent % PROGRESS_STEP == 0 || sent == total {
+                            event = Some(BackendEvent::Progress { sent, total });
+                        }
+                    }
+                    if sent == total {
+                        deadline = Some(time::Instant::now() + self.probe.wait);
+                    }
+                }
+                reply = reply_rx.recv() => match reply {
+                    Some(reply) => {
+                        let iface = self
+                            .local_interfaces
+                            .iter()
+                            .find(|iface| iface.contains(reply.src.ip()));
+                        event = Some(to_event(&reply, iface));
+                    }
+                    None => {
+                        return Err(std::io::Error::other("all discovery sockets closed"));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl DiscoveryBackend for UdpRangeBackend {
+    fn name(&self) -> &'static str {
+        UDP_BACKEND
+    }
+
+    fn run(
+        self: Box<Self>,
+        events: mpsc::Sender<BackendEvent>,
+        stop_rx: watch::Receiver<bool>,
+    ) -> BackendFuture {
+        Box::pin(self.run_loop(events, stop_rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(addr: &str) -> Ipv4Addr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_cidr_without_network_and_broadcast() {
+        let hosts = parse_cidr("192.168.1.77/24").unwrap();
+        assert_eq!(hosts.len(), 254);
+        assert_eq!(hosts[0], ip("192.168.1.1"));
+        assert_eq!(hosts[253], ip("192.168.1.254"));
+
+        assert_eq!(
+            parse_cidr(" 10.0.0.8/30 ").unwrap(),
+            [ip("10.0.0.9"), ip("10.0.0.10")]
+        );
+        assert_eq!(
+            parse_cidr("10.0.0.8/31").unwrap(),
+            [ip("10.0.0.8"), ip("10.0.0.9")]
+        );
+        assert_eq!(parse_cidr("10.0.0.8/32").unwrap(), [ip("10.0.0.8")]);
+    }
+
+    #[test]
+    fn rejects_bad_cidr() {
+        for cidr in [
+            "192.168.1.0",
+            "192.168.1.0/",
+            "192.168.1.0/x",
+            "192.168.1.0/33",
+        ] {
+            assert_eq!(
+                parse_cidr(cidr),
+                Err(RangeError::InvalidCidr(cidr.to_string())),
+                "{}",
+                cidr
+            );
+        }
+        assert_eq!(
+            parse_cidr("192.168.1/24"),
+            Err(RangeError::InvalidAddress("192.168.1".to_string()))
+        );
+    }
+
+    #[test]
+    fn limits_large_prefixes() {
+        assert_eq!(parse_cidr("172.16.0.0/16").unwrap().len(), 65534);
+        assert_eq!(
+            parse_cidr("172.16.0.0/15"),
+            Err(RangeError::TooLarge(131070))
+        );
+        assert_eq!(
+            parse_cidr("0.0.0.0/0"),
+            Err(RangeError::TooLarge(u32::MAX as usize - 1))
+        );
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(
+            parse_range("192.168.1.254", " 192.168.2.1").unwrap(),
+            [
+                ip("192.168.1.254"),
+                ip("192.168.1.255"),
+                ip("192.168.2.0"),
+                ip("192.168.2.1")
+            ]
+        );
+        assert_eq!(
+            parse_range("10.0.0.5", "10.0.0.5").unwrap(),
+            [ip("10.0.0.5")]
+        );
+        assert_eq!(
+            parse_range("10.0.0.6", "10.0.0.5"),
+            Err(RangeError::Reversed)
+        );
+        assert_eq!(
+            parse_range("10.0.0.1", "10.0.0.256"),
+            Err(RangeError::InvalidAddress("10.0.0.256".to_string()))
+        );
+        assert_eq!(
+            parse_range("0.0.0.0", "255.255.255.255"),
+            Err(RangeError::TooLarge(u32::MAX as usize + 1))
+        );
+    }
+
+    #[test]
+    fn collects_hosts_up_to_limit() {
+        assert_eq!(
+            collect_hosts(0, MAX_RANGE_HOSTS as u32 - 1).unwrap().len(),
+            MAX_RANGE_HOSTS
+        );
+        assert_eq!(
+            collect_hosts(0, MAX_RANGE_HOSTS as u32),
+            Err(RangeError::TooLarge(MAX_RANGE_HOSTS + 1))
+        );
+        assert_eq!(
+            collect_hosts(u32::MAX, u32::MAX).unwrap(),
+            [Ipv4Addr::BROADCAST]
+        );
+    }
+
+    #[test]
+    fn builds_probe_from_request() {
+        let probe = RangeProbe::from_request(&ProbeRangeRequest {
+            start: Some("10.0.0.1".to_string()),
+            end: Some("10.0.0.3".to_string()),
+            rate: Some(100_000),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(probe.label, "10.0.0.1-10.0.0.3");
+        assert_eq!(probe.hosts.len(), 3);
+        assert_eq!(probe.rate, MAX_RATE);
+        assert_eq!(probe.wait, Duration::from_millis(DEFAULT_WAIT_MS));
+
+        let missing = RangeProbe::from_request(&ProbeRangeRequest {
+            start: Some("10.0.0.1".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(missing.unwrap_err(), RangeError::Missing);
+    }
+}
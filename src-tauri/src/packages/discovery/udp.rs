@@ -0,0 +1,229 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+use tokio::time::{self, Interval};
+
+use super::backend::{BackendEvent, BackendFuture, DiscoveryBackend};
+use super::interfaces::{self, LocalInterface};
+use super::{protocol, DISCOVERY_MESSAGE, DISCOVERY_PORT};
+
+/// 后端名称
+pub const UDP_BACKEND: &str = "udp";
+
+/// 单个网卡上的扫描 socket
+pub(super) struct ScanSocket {
+    pub(super) iface: Option<LocalInterface>,
+    pub(super) socket: Arc<UdpSocket>,
+    pub(super) target: SocketAddr,
+}
+
+/// 收到的一条原始回复
+pub(super) struct RawReply {
+    pub(super) index: usize,
+    pub(super) data: String,
+    pub(super) src: SocketAddr,
+}
+
+/// 绑定一个允许广播的 UDP socket
+pub(super) fn bind_broadcast_socket(ip: Ipv4Addr) -> std::io::Result<UdpSocket> {
+    let socket = std::net::UdpSocket::bind((ip, 0))?;
+    socket.set_broadcast(true)?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket)
+}
+
+/// 为每个本机 IPv4 网卡绑定一个 socket，向其子网发送定向广播；
+/// 一个网卡都绑定不上时退回到 255.255.255.255
+fn bind_scan_sockets() -> std::io::Result<Vec<ScanSocket>> {
+    let mut sockets = Vec::new();
+    for iface in interfaces::list_ipv4_interfaces() {
+        match bind_broadcast_socket(iface.ip) {
+            Ok(socket) => sockets.push(ScanSocket {
+                target: SocketAddr::from((iface.broadcast(), DISCOVERY_PORT)),
+                socket: Arc::new(socket),
+                iface: Some(iface),
+            }),
+            Err(e) => warn!("Failed to bind {} ({}): {}", iface.name, iface.ip, e),
+        }
+    }
+
+    if sockets.is_empty() {
+        sockets.push(ScanSocket {
+            iface: None,
+            socket: Arc::new(bind_broadcast_socket(Ipv4Addr::UNSPECIFIED)?),
+            target: SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT)),
+        });
+    }
+    Ok(sockets)
+}
+
+/// 解析原始回复并转换为后端事件
+pub(super) fn to_event(reply: &RawReply, iface: Option<&LocalInterface>) -> BackendEvent {
+    match protocol::parse_reply(&reply.data, &reply.src) {
+        Ok(mut arm_ip_intro) => {
+            arm_ip_intro.source = UDP_BACKEND.to_string();
+            BackendEvent::Found(Box::new(arm_ip_intro.with_interface(iface)))
+        }
+        Err(e) => BackendEvent::Invalid(format!("{} from {}", e, reply.src)),
+    }
+}
+
+/// UDP 18355 定向广播后端
+pub struct UdpBroadcastBackend {
+    sockets: Vec<ScanSocket>,
+    interval: Option<Duration>,
+}
+
+impl UdpBroadcastBackend {
+    /// 绑定各网卡的 socket；`interval` 为重复广播间隔，为空时只广播一次
+    ///
+    /// `targets` 为额外单播的地址（例如本机的虚拟机械臂），每次广播时一并发送。
+    pub fn bind(interval: Option<Duration>, targets: &[SocketAddr]) -> std::io::Result<Self> {
+        let mut sockets = bind_scan_sockets()?;
+        for target in targets {
+            let local = match target.ip() {
+                ip if ip.is_loopback() => Ipv4Addr::LOCALHOST,
+                _ => Ipv4Addr::UNSPECIFIED,
+            };
+            match bind_broadcast_socket(local) {
+                Ok(socket) => sockets.push(ScanSocket {
+                    iface: None,
+                    socket: Arc::new(socket),
+                    target: *target,
+                }),
+                Err(e) => warn!("Failed to bind socket for {}: {}", target, e),
+            }
+        }
+        Ok(Self {
+            sockets,
+            interval: interval.filter(|d| !d.is_zero()),
+        })
+    }
+
+    /// 本次广播覆盖的子网
+    pub fn subnets(&self) -> Vec<String> {
+        self.sockets
+            .iter()
+            .filter_map(|s| s.iface.as_ref().map(|iface| iface.subnet()))
+            .collect()
+    }
+
+    async fn run_loop(
+        self,
+        events: mpsc::Sender<BackendEvent>,
+        mut stop_rx: watch::Receiver<bool>,
+    ) -> std::io::Result<()> {
+        let (_receivers, mut reply_rx) = spawn_receivers(&self.sockets);
+
+        send_probes(&self.sockets).await?;
+
+        let mut ticker = self
+            .interval
+            .map(|period| time::interval_at(time::Instant::now() + period, period));
+
+        loop {
+            tokio::select! {
+                _ = stop_rx.changed() => return Ok(()),
+                _ = tick(&mut ticker) => send_probes(&self.sockets).await?,
+                reply = reply_rx.recv() => match reply {
+                    Some(reply) => {
+                        let iface = self.sockets[reply.index].iface.as_ref();
+                        if events.send(to_event(&reply, iface)).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    None => {
+                        return Err(std::io::Error::other("all discovery sockets closed"));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl DiscoveryBackend for UdpBroadcastBackend {
+    fn name(&self) -> &'static str {
+        UDP_BACKEND
+    }
+
+    fn run(
+        self: Box<Self>,
+        events: mpsc::Sender<BackendEvent>,
+        stop_rx: watch::Receiver<bool>,
+    ) -> BackendFuture {
+        Box::pin(self.run_loop(events, stop_rx))
+    }
+}
+
+/// 每个 socket 一个接收任务，统一汇总到 channel；返回的 JoinSet 被 drop 时自动中止
+pub(super) fn spawn_receivers(sockets: &[ScanSocket]) -> (JoinSet<()>, mpsc::Receiver<RawReply>) {
+    let (reply_tx, reply_rx) = mpsc::channel(64);
+    let mut receivers = JoinSet::new();
+    for (index, scan_socket) in sockets.iter().enumerate() {
+        receivers.spawn(receive_replies(
+            index,
+            Arc::clone(&scan_socket.socket),
+            reply_tx.clone(),
+        ));
+    }
+    (receivers, reply_rx)
+}
+
+/// 在所有 socket 上发送发现请求，全部失败时返回最后一个错误
+async fn send_probes(sockets: &[ScanSocket]) -> std::io::Result<()> {
+    let mut last_error = None;
+    let mut sent = 0;
+    for scan_socket in sockets {
+        match scan_socket
+            .socket
+            .send_to(DISCOVERY_MESSAGE.as_bytes(), scan_socket.target)
+            .await
+        {
+            Ok(_) => sent += 1,
+            Err(e) => {
+                warn!("Failed to send broadcast to {}: {}", scan_socket.target, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if sent == 0 => Err(e),
+        _ => Ok(()),
+    }
+}
+
+async fn receive_replies(index: usize, socket: Arc<UdpSocket>, tx: mpsc::Sender<RawReply>) {
+    let mut buf = [0; 1024];
+    loop {
+        match socket.recv_from(&mut buf).await {
+            Ok((amt, src)) => {
+                let data = String::from_utf8_lossy(&buf[..amt]).to_string();
+                info!("Received from {}: {}", src, data);
+                if tx.send(RawReply { index, data, src }).await.is_err() {
+                    return;
+                }
+            }
+            // Windows 上之前的发送收到 ICMP 不可达时会返回 ConnectionReset，忽略即可
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => continue,
+            Err(e) => {
+                warn!("Discovery socket {} closed: {}", index, e);
+                return;
+            }
+        }
+    }
+}
+
+async fn tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
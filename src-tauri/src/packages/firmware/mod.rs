@@ -0,0 +1,751 @@
+//! 控制器固件批量升级
+//!
+//! 每台机械臂依次经过：检查兼容性（发现结果中的 `device_type`、`version`，见 `package`）
+//! → 通过 `FirmwareTransport` 把镜像交给控制器 → 轮询指令端口的版本号，
+//! 直到控制器重启后报告新版本。
+//!
+//! 控制器实际的升级接口尚未确认，目前唯一的上传实现 `UnverifiedHttpUpload` 是占位的，
+//! 使用的接口和请求头均为假设，见 `transport`。
+//!
+//! 多台机械臂按 `max_parallel` 并行升级。某台失败（或手动中止）后不再开始新的升级，
+//! 正在上传的连接直接断开（控制器只在收到完整镜像后安装）；已开始安装的继续等待验证，
+//! 不会丢下正在刷写的控制器。进度通过 `firmware_update_progress` 事件推送，
+//! 结束后通过 `firmware_update_finished` 推送每台机械臂的结果。
+
+pub mod package;
+pub mod transport;
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Emitter, Runtime};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::{self, Instant};
+
+use crate::packages::xarm::client::XArmClient;
+use crate::packages::xarm::protocol::COMMAND_PORT;
+use crate::utils::error::AppError;
+use crate::utils::stop::stopped;
+use package::{Compatibility, Manifest, Version};
+use transport::FirmwareTransport;
+
+/// 单台机械臂的进度推送
+pub const EVENT_FIRMWARE_UPDATE_PROGRESS: &str = "firmware_update_progress";
+/// 整批升级结束
+pub const EVENT_FIRMWARE_UPDATE_FINISHED: &str = "firmware_update_finished";
+
+/// 等待重启时查询版本的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// 同时升级的机械臂数上限
+const MAX_PARALLEL: usize = 16;
+
+/// 要升级的机械臂，可以直接传 `ArmIpIntro` 对象
+#[derive(Deserialize, Clone, Debug)]
+pub struct FirmwareTarget {
+    pub ip: String,
+    pub arm_sn: String,
+    pub device_type: String,
+    /// 发现结果中的当前版本
+    pub version: String,
+    /// 控制器 HTTP 端口，默认 18333
+    #[serde(default)]
+    pub http_port: Option<u16>,
+    /// 指令端口，默认 502
+    #[serde(default)]
+    pub command_port: Option<u16>,
+}
+
+impl FirmwareTarget {
+    fn ip(&self) -> Result<IpAddr, AppError> {
+        self.ip
+            .trim()
+            .parse()
+            .map_err(|_| AppError::InvalidArgument(format!("invalid ip address: {}", self.ip)))
+    }
+
+    fn command_addr(&self) -> Result<SocketAddr, AppError> {
+        Ok(SocketAddr::new(
+            self.ip()?,
+            self.command_port.unwrap_or(COMMAND_PORT),
+        ))
+    }
+}
+
+/// 升级选项
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct UpdateOptions {
+    /// 同时升级的机械臂数
+    pub max_parallel: usize,
+    /// 某台失败后继续升级其余机械臂，默认中止整批
+    pub continue_on_failure: bool,
+    /// 允许降级
+    pub allow_downgrade: bool,
+    /// 上传完成后等待控制器以新版本上线的时长（秒）
+    pub restart_timeout_secs: u64,
+}
+
+impl Default for UpdateOptions {
+    fn default() -> Self {
+        Self {
+            max_parallel: 2,
+            continue_on_failure: false,
+            allow_downgrade: false,
+            restart_timeout_secs: 600,
+        }
+    }
+}
+
+/// 单台机械臂的升级阶段
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Checking,
+    Uploading,
+    /// 上传完成，等待控制器安装并重启
+    Installing,
+    Finished,
+}
+
+/// 单台机械臂的升级结果
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeStatus {
+    Updated,
+    /// 已是目标版本，未上传
+    UpToDate,
+    Incompatible,
+    Failed,
+    /// 整批中止时尚未开始或仍在上传
+    Aborted,
+}
+
+/// 单台机械臂的进度，作为 `firmware_update_progress` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct ArmProgress {
+    pub batch_id: u64,
+    pub arm_sn: String,
+    pub stage: Stage,
+    /// 已上传字节数
+    pub sent: u64,
+    pub total: u64,
+    pub message: Option<String>,
+}
+
+/// 单台机械臂的最终结果
+#[derive(Serialize, Clone, Debug)]
+pub struct ArmOutcome {
+    pub arm_sn: String,
+    pub ip: String,
+    pub status: OutcomeStatus,
+    /// 结束时所处的阶段
+    pub stage: Stage,
+    /// 升级前的版本
+    pub from_version: String,
+    /// 最后一次从控制器读到的版本
+    pub version: Option<String>,
+    pub message: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 整批结果，作为 `firmware_update_finished` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct UpdateReport {
+    pub batch_id: u64,
+    pub version: String,
+    /// 是否因失败或手动中止提前结束
+    pub aborted: bool,
+    pub outcomes: Vec<ArmOutcome>,
+}
+
+/// 已开始的批次
+#[derive(Serialize, Clone, Debug)]
+pub struct BatchInfo {
+    pub batch_id: u64,
+    pub version: String,
+    pub arms: Vec<String>,
+}
+
+type ProgressFn = Arc<dyn Fn(&ArmProgress) + Send + Sync>;
+
+/// 一个批次内共享的数据
+struct UpdateContext {
+    batch_id: u64,
+    transport: Arc<dyn FirmwareTransport>,
+    manifest: Manifest,
+    /// 校验过的镜像
+    image: Arc<Vec<u8>>,
+    options: UpdateOptions,
+    on_progress: ProgressFn,
+}
+
+impl UpdateContext {
+    fn report(&self, arm_sn: &str, stage: Stage, sent: u64, message: Option<String>) {
+        (self.on_progress)(&ArmProgress {
+            batch_id: self.batch_id,
+            arm_sn: arm_sn.to_string(),
+            stage,
+            sent,
+            total: self.image.len() as u64,
+            message,
+        });
+    }
+}
+
+struct Batch {
+    arms: Vec<String>,
+    stop_tx: Arc<watch::Sender<bool>>,
+}
+
+/// 进行中的升级批次
+#[derive(Clone, Default)]
+pub struct FirmwareUpdates {
+    next_id: Arc<AtomicU64>,
+    batches: Arc<Mutex<HashMap<u64, Batch>>>,
+}
+
+impl FirmwareUpdates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 校验固件包后开始升级，`path` 为固件包目录或清单文件；同一台机械臂不能同时在两个批次中
+    pub async fn start<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        transport: Arc<dyn FirmwareTransport>,
+        path: &Path,
+        targets: Vec<FirmwareTarget>,
+        options: UpdateOptions,
+    ) -> Result<BatchInfo, AppError> {
+        if targets.is_empty() {
+            return Err(AppError::InvalidArgument("no arm selected".to_string()));
+        }
+        if !(1..=MAX_PARALLEL).contains(&options.max_parallel) {
+            return Err(AppError::InvalidArgument(format!(
+                "max_parallel must be between 1 and {}",
+                MAX_PARALLEL
+            )));
+        }
+        let mut arms = HashSet::new();
+        for target in &targets {
+            transport.check(target)?;
+            if !arms.insert(target.arm_sn.as_str()) {
+                return Err(AppError::InvalidArgument(format!(
+                    "{} is selected twice",
+                    target.arm_sn
+                )));
+            }
+        }
+
+        let package = package::load(path)?;
+        let read = package.clone();
+        let image = async_runtime::spawn_blocking(move || read.read_image())
+            .await
+            .map_err(|e| AppError::Io(e.to_string()))??;
+
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let stop_tx = Arc::new(stop_tx);
+        let batch_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let arms: Vec<String> = targets.iter().map(|t| t.arm_sn.clone()).collect();
+        {
+            let mut batches = self
+                .batches
+                .lock()
+                .map_err(|e| AppError::Io(e.to_string()))?;
+            if let Some(busy) = batches
+                .values()
+                .flat_map(|batch| &batch.arms)
+                .find(|arm_sn| arms.contains(arm_sn))
+            {
+                return Err(AppError::InvalidArgument(format!(
+                    "{} is already being updated",
+                    busy
+                )));
+            }
+            batches.insert(
+                batch_id,
+                Batch {
+                    arms: arms.clone(),
+                    stop_tx: Arc::clone(&stop_tx),
+                },
+            );
+        }
+
+        let version = package.manifest.version.clone();
+        info!(
+            "Firmware update {} to {} started for {:?} via {}",
+            batch_id,
+            version,
+            arms,
+            transport.name()
+        );
+        let emitter = app.clone();
+        let context = Arc::new(UpdateContext {
+            batch_id,
+            transport,
+            manifest: package.manifest,
+            image: Arc::new(image),
+            options,
+            on_progress: Arc::new(move |progress| {
+                let _ = emitter.emit(EVENT_FIRMWARE_UPDATE_PROGRESS, progress.clone());
+            }),
+        });
+        let batches = Arc::clone(&self.batches);
+        let app = app.clone();
+        async_runtime::spawn(async move {
+            let report = run_batch(context, targets, stop_tx, stop_rx).await;
+            if let Ok(mut batches) = batches.lock() {
+                batches.remove(&batch_id);
+            }
+            for outcome in &report.outcomes {
+                let message = outcome.message.as_deref().unwrap_or("");
+                match outcome.status {
+                    OutcomeStatus::Updated | OutcomeStatus::UpToDate => {
+                        info!(
+                            "Firmware update {} {}: {:?}",
+                            batch_id, outcome.arm_sn, outcome.status
+                        )
+                    }
+                    _ => error!(
+                        "Firmware update {} {}: {:?} at {:?} {}",
+                        batch_id, outcome.arm_sn, outcome.status, outcome.stage, message
+                    ),
+                }
+            }
+            let _ = app.emit(EVENT_FIRMWARE_UPDATE_FINISHED, report);
+        });
+
+        Ok(BatchInfo {
+            batch_id,
+            version,
+            arms,
+        })
+    }
+
+    /// 中止批次，返回批次是否存在；已开始安装的机械臂仍会等待验证
+    pub fn abort(&self, batch_id: u64) -> bool {
+        let batches = match self.batches.lock() {
+            Ok(batches) => batches,
+            Err(_) => return false,
+        };
+        match batches.get(&batch_id) {
+            Some(batch) => {
+                batch.stop_tx.send_replace(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 退出时中止所有批次
+    pub fn stop_all(&self) {
+        if let Ok(batches) = self.batches.lock() {
+            for batch in batches.values() {
+                batch.stop_tx.send_replace(true);
+            }
+        }
+    }
+}
+
+async fn run_batch(
+    context: Arc<UpdateContext>,
+    targets: Vec<FirmwareTarget>,
+    stop_tx: Arc<watch::Sender<bool>>,
+    stop_rx: watch::Receiver<bool>,
+) -> UpdateReport {
+    let mut outcomes: Vec<Option<ArmOutcome>> = vec![None; targets.len()];
+    let mut tasks = JoinSet::new();
+    let mut pending = targets.iter().cloned().enumerate();
+    loop {
+        while tasks.len() < context.options.max_parallel && !*stop_tx.borrow() {
+            let Some((index, target)) = pending.next() else {
+                break;
+            };
+            let context = Arc::clone(&context);
+            let stop_rx = stop_rx.clone();
+            tasks.spawn(async move { (index, update_arm(&context, &target, stop_rx).await) });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        match joined {
+            Ok((index, outcome)) => {
+                if outcome.status == OutcomeStatus::Failed && !context.options.continue_on_failure {
+                    warn!(
+                        "Firmware update {} aborted after {} failed",
+                        context.batch_id, outcome.arm_sn
+                    );
+                    stop_tx.send_replace(true);
+                }
+                outcomes[index] = Some(outcome);
+            }
+            Err(e) => error!("Firmware update task failed: {}", e),
+        }
+    }
+
+    let aborted = *stop_tx.borrow();
+    let outcomes = outcomes
+        .into_iter()
+        .zip(&targets)
+        .map(|(outcome, target)| {
+            outcome.unwrap_or_else(|| {
+                let (status, message) = if aborted {
+                    (OutcomeStatus::Aborted, "not started")
+                } else {
+                    (OutcomeStatus::Failed, "update task panicked")
+                };
+                context.report(
+                    &target.arm_sn,
+                    Stage::Finished,
+                    0,
+                    Some(message.to_string()),
+                );
+                ArmOutcome {
+                    arm_sn: target.arm_sn.clone(),
+                    ip: target.ip.clone(),
+                    status,
+                    stage: Stage::Checking,
+                    from_version: target.version.clone(),
+                    version: None,
+                    message: Some(message.to_string()),
+                    duration_ms: 0,
+                }
+            })
+        })
+        .collect();
+    UpdateReport {
+        batch_id: context.batch_id,
+        version: context.manifest.version.clone(),
+        aborted,
+        outcomes,
+    }
+}
+
+/// 升级单台机械臂
+async fn update_arm(
+    context: &UpdateContext,
+    target: &FirmwareTarget,
+    mut stop_rx: watch::Receiver<bool>,
+) -> ArmOutcome {
+    let started = Instant::now();
+    let mut outcome = ArmOutcome {
+        arm_sn: target.arm_sn.clone(),
+        ip: target.ip.clone(),
+        status: OutcomeStatus::Failed,
+        stage: Stage::Checking,
+        from_version: target.version.clone(),
+        version: None,
+        message: None,
+        duration_ms: 0,
+    };
+    let result = run_stages(context, target, &mut stop_rx, &mut outcome).await;
+    if let Err(e) = result {
+        outcome.message = Some(e.to_string());
+    }
+    outcome.duration_ms = started.elapsed().as_millis() as u64;
+    context.report(&target.arm_sn, Stage::Finished, 0, outcome.message.clone());
+    outcome
+}
+
+async fn run_stages(
+    context: &UpdateContext,
+    target: &FirmwareTarget,
+    stop_rx: &mut watch::Receiver<bool>,
+    outcome: &mut ArmOutcome,
+) -> Result<(), AppError> {
+    if *stop_rx.borrow() {
+        outcome.status = OutcomeStatus::Aborted;
+        outcome.message = Some("not started".to_string());
+        return Ok(());
+    }
+    context.report(&target.arm_sn, Stage::Checking, 0, None);
+    match context.manifest.compatibility(
+        &target.device_type,
+        &target.version,
+        context.options.allow_downgrade,
+    ) {
+        Compatibility::Compatible => {}
+        Compatibility::UpToDate => {
+            outcome.status = OutcomeStatus::UpToDate;
+            outcome.version = Some(target.version.clone());
+            return Ok(());
+        }
+        Compatibility::Incompatible { reason } => {
+            outcome.status = OutcomeStatus::Incompatible;
+            outcome.message = Some(reason);
+            return Ok(());
+        }
+    }
+    let addr = target.command_addr()?;
+
+    outcome.stage = Stage::Uploading;
+    context.report(&target.arm_sn, Stage::Uploading, 0, None);
+    tokio::select! {
+        result = upload(context, target) => result?,
+        _ = stopped(stop_rx) => {
+            outcome.status = OutcomeStatus::Aborted;
+            outcome.message = Some("upload cancelled".to_string());
+            return Ok(());
+        }
+    }
+
+    // 镜像已交给控制器，此后不再响应中止
+    outcome.stage = Stage::Installing;
+    context.report(
+        &target.arm_sn,
+        Stage::Installing,
+        context.image.len() as u64,
+        None,
+    );
+    let timeout = Duration::from_secs(context.options.restart_timeout_secs);
+    wait_for_version(context, addr, timeout, outcome).await?;
+    outcome.status = OutcomeStatus::Updated;
+    Ok(())
+}
+
+/// 上传镜像并按已上传字节数推送进度
+async fn upload(context: &UpdateContext, target: &FirmwareTarget) -> Result<(), AppError> {
+    let on_progress = Arc::clone(&context.on_progress);
+    let batch_id = context.batch_id;
+    let arm_sn = target.arm_sn.clone();
+    let total = context.image.len() as u64;
+    let on_sent = Arc::new(move |sent| {
+        on_progress(&ArmProgress {
+            batch_id,
+            arm_sn: arm_sn.clone(),
+            stage: Stage::Uploading,
+            sent,
+            total,
+            message: None,
+        })
+    });
+    context
+        .transport
+        .upload(
+            target,
+            &context.manifest,
+            Arc::clone(&context.image),
+            on_sent,
+        )
+        .await
+}
+
+/// 轮询指令端口版本号，直到与固件包版本一致或超时
+async fn wait_for_version(
+    context: &UpdateContext,
+    addr: SocketAddr,
+    timeout: Duration,
+    outcome: &mut ArmOutcome,
+) -> Result<(), AppError> {
+    let expected = Version::parse(&context.manifest.version)
+        .ok_or_else(|| AppError::InvalidArgument(context.manifest.version.clone()))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut client = XArmClient::new(addr);
+        let result = client.get_version().await;
+        client.disconnect().await;
+        match result {
+            Ok(version) => {
+                let matched = Version::parse(&version).is_some_and(|v| v == expected);
+                outcome.version = Some(version);
+                if matched {
+                    return Ok(());
+                }
+            }
+            Err(e) => {
+                context.report(
+                    &outcome.arm_sn,
+                    Stage::Installing,
+                    context.image.len() as u64,
+                    Some(e.to_string()),
+                );
+            }
+        }
+        if Instant::now() + POLL_INTERVAL > deadline {
+            return Err(AppError::Timeout(format!(
+                "{} to come back on {} (last seen {})",
+                addr,
+                context.manifest.version,
+                outcome.version.as_deref().unwrap_or("nothing")
+            )));
+        }
+        time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use reqwest::Client;
+
+    use super::transport::{UnverifiedHttpUpload, UPLOAD_PATH};
+    use super::*;
+    use crate::packages::simulator::stand_in::{sim_stand_in, still_config, SimStandIn};
+    use crate::packages::simulator::SimConfig;
+    use crate::utils::http_stand_in::http_stand_in;
+
+    /// 固件上传接口替身，记录收到的字节数后以 `status` 应答
+    async fn upload_stand_in(status: &'static str) -> (u16, Arc<AtomicUsize>) {
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&received);
+        let addr = http_stand_in(move |request| {
+            if request.method != "POST" || request.path != UPLOAD_PATH {
+                return ("404 Not Found", Vec::new());
+            }
+            counter.fetch_add(request.body.len(), Ordering::SeqCst);
+            (status, Vec::new())
+        })
+        .await;
+        (addr.port(), received)
+    }
+
+    /// 以指定版本运行的虚拟控制器
+    async fn controller(version: &str) -> SimStandIn {
+        sim_stand_in(SimConfig {
+            version: version.to_string(),
+            ..still_config()
+        })
+        .await
+    }
+
+    fn context(
+        name: &str,
+        image: &[u8],
+        options: UpdateOptions,
+    ) -> (Arc<UpdateContext>, Arc<Mutex<Vec<ArmProgress>>>) {
+        let dir = package::tests::write_package(name, image);
+        let package = package::load(&dir).unwrap();
+        let image = package.read_image().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let context = Arc::new(UpdateContext {
+            batch_id: 1,
+            transport: Arc::new(UnverifiedHttpUpload::new(Arc::new(Client::new()))),
+            manifest: package.manifest,
+            image: Arc::new(image),
+            options,
+            on_progress: Arc::new(move |progress: &ArmProgress| {
+                sink.lock().unwrap().push(progress.clone())
+            }),
+        });
+        (context, events)
+    }
+
+    fn target(arm_sn: &str, http_port: u16, command_port: u16) -> FirmwareTarget {
+        FirmwareTarget {
+            ip: "127.0.0.1".to_string(),
+            arm_sn: arm_sn.to_string(),
+            device_type: "6".to_string(),
+            version: "2.5.0".to_string(),
+            http_port: Some(http_port),
+            command_port: Some(command_port),
+        }
+    }
+
+    async fn run(context: Arc<UpdateContext>, targets: Vec<FirmwareTarget>) -> UpdateReport {
+        let (stop_tx, stop_rx) = watch::channel(false);
+        run_batch(context, targets, Arc::new(stop_tx), stop_rx).await
+    }
+
+    #[tokio::test]
+    async fn uploads_and_verifies_version() {
+        let image = vec![7u8; 200 * 1024];
+        let (context, events) = context("update", &image, UpdateOptions::default());
+        let (http_port, received) = upload_stand_in("200 OK").await;
+        let arm = controller("2.6.0").await;
+
+        let mut up_to_date = target("XI0002", http_port, arm.command_addr.port());
+        up_to_date.version = "v2.6.0".to_string();
+        let mut lite = target("XI0003", http_port, arm.command_addr.port());
+        lite.device_type = "9".to_string();
+        let report = run(
+            context,
+            vec![
+                target("XI0001", http_port, arm.command_addr.port()),
+                up_to_date,
+                lite,
+            ],
+        )
+        .await;
+
+        assert!(!report.aborted);
+        let statuses: Vec<_> = report.outcomes.iter().map(|o| o.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                OutcomeStatus::Updated,
+                OutcomeStatus::UpToDate,
+                OutcomeStatus::Incompatible
+            ]
+        );
+        assert_eq!(report.outcomes[0].version.as_deref(), Some("v2.6.0"));
+        assert_eq!(received.load(Ordering::SeqCst), image.len());
+
+        let events = events.lock().unwrap();
+        let uploaded = events
+            .iter()
+            .filter(|e| e.arm_sn == "XI0001" && e.stage == Stage::Uploading)
+            .map(|e| e.sent)
+            .max();
+        assert_eq!(uploaded, Some(image.len() as u64));
+    }
+
+    #[tokio::test]
+    async fn aborts_batch_after_failure() {
+        let options = UpdateOptions {
+            max_parallel: 1,
+            restart_timeout_secs: 1,
+            ..Default::default()
+        };
+        let (context, _) = context("abort", b"image", options);
+        let (rejecting, _) = upload_stand_in("500 Internal Server Error").await;
+        let (accepting, received) = upload_stand_in("200 OK").await;
+        let arm = controller("2.6.0").await;
+
+        let report = run(
+            context,
+            vec![
+                target("XI0001", rejecting, arm.command_addr.port()),
+                target("XI0002", accepting, arm.command_addr.port()),
+            ],
+        )
+        .await;
+
+        assert!(report.aborted);
+        assert_eq!(report.outcomes[0].status, OutcomeStatus::Failed);
+        assert_eq!(report.outcomes[0].stage, Stage::Uploading);
+        assert_eq!(report.outcomes[1].status, OutcomeStatus::Aborted);
+        assert_eq!(received.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn times_out_on_old_version() {
+        let options = UpdateOptions {
+            restart_timeout_secs: 0,
+            ..Default::default()
+        };
+        let (context, _) = context("timeout", b"image", options);
+        let (http_port, _) = upload_stand_in("200 OK").await;
+        let arm = controller("2.5.0").await;
+
+        let report = run(
+            context,
+            vec![target("XI0001", http_port, arm.command_addr.port())],
+        )
+        .await;
+        let outcome = &report.outcomes[0];
+        assert_eq!(outcome.status, OutcomeStatus::Failed);
+        assert_eq!(outcome.stage, Stage::Installing);
+        assert_eq!(outcome.version.as_deref(), Some("v2.5.0"));
+    }
+}
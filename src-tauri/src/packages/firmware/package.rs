@@ -0,0 +1,320 @@
+//! 固件包
+//!
+//! 固件包是一个目录，包含 `manifest.json` 和固件镜像：
+//!
+//! ```json
+//! {
+//!   "version": "2.6.0",
+//!   "device_types": [5, 6, 7],
+//!   "min_version": "2.0.0",
+//!   "file": "xarm-2.6.0.bin",
+//!   "size": 73400320,
+//!   "sha256": "…"
+//! }
+//! ```
+//!
+//! `min_version` 为可直接升级的最低版本，缺省时不限制。
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utils::error::AppError;
+
+/// 清单文件名
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// 固件包清单
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Manifest {
+    pub version: String,
+    /// 适用的 `device_type`
+    pub device_types: Vec<u16>,
+    #[serde(default)]
+    pub min_version: Option<String>,
+    /// 镜像文件名，相对清单所在目录
+    pub file: String,
+    pub size: u64,
+    /// 镜像 SHA-256（十六进制）
+    pub sha256: String,
+}
+
+/// 校验通过的固件包
+#[derive(Serialize, Clone, Debug)]
+pub struct FirmwarePackage {
+    pub manifest: Manifest,
+    /// 镜像路径
+    pub image: PathBuf,
+}
+
+/// 点分数字版本号，比较时缺少的段按 0 处理
+#[derive(Clone, Debug)]
+pub struct Version(Vec<u32>);
+
+impl Version {
+    /// 取字符串中最后一段点分数字，兼容 `v2.5.0`、`xArm6-V1.9.10` 等写法
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut last = None;
+        let mut rest = text;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+            let candidate = &rest[start..];
+            let end = candidate
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(candidate.len());
+            let parts: Vec<u32> = candidate[..end]
+                .split('.')
+                .map_while(|part| part.parse().ok())
+                .collect();
+            if parts.len() >= 2 {
+                last = Some(Version(parts));
+            }
+            rest = &candidate[end..];
+        }
+        last
+    }
+
+    fn part(&self, index: usize) -> u32 {
+        self.0.get(index).copied().unwrap_or(0)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (0..self.0.len().max(other.0.len()))
+            .map(|i| self.part(i).cmp(&other.part(i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(|part| part.to_string()).collect();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+/// 目标机械臂与固件包的兼容性
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Compatibility {
+    Compatible,
+    /// 已是该版本
+    UpToDate,
+    Incompatible {
+        reason: String,
+    },
+}
+
+impl Manifest {
+    fn validate(&self) -> Result<(), AppError> {
+        let invalid = |message: String| Err(AppError::InvalidArgument(message));
+        if Version::parse(&self.version).is_none() {
+            return invalid(format!("invalid firmware version {:?}", self.version));
+        }
+        if let Some(min) = &self.min_version {
+            if Version::parse(min).is_none() {
+                return invalid(format!("invalid min_version {:?}", min));
+            }
+        }
+        if self.device_types.is_empty() {
+            return invalid("manifest lists no device_types".to_string());
+        }
+        let mut components = Path::new(&self.file).components();
+        if !matches!(components.next(), Some(Component::Normal(_))) || components.next().is_some() {
+            return invalid(format!("invalid image file name {:?}", self.file));
+        }
+        if self.sha256.len() != 64 || !self.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return invalid(format!("invalid sha256 {:?}", self.sha256));
+        }
+        Ok(())
+    }
+
+    /// 按发现结果中的 `device_type` 和 `version` 判断能否升级；`allow_downgrade` 为假时拒绝降级
+    pub fn compatibility(
+        &self,
+        device_type: &str,
+        current: &str,
+        allow_downgrade: bool,
+    ) -> Compatibility {
+        let incompatible = |reason: String| Compatibility::Incompatible { reason };
+        let Ok(device_type) = device_type.trim().parse::<u16>() else {
+            return incompatible(format!("invalid device_type {:?}", device_type));
+        };
+        if !self.device_types.contains(&device_type) {
+            return incompatible(format!(
+                "firmware {} does not support device_type {}",
+                self.version, device_type
+            ));
+        }
+        let (Some(target), Some(current)) =
+            (Version::parse(&self.version), Version::parse(current))
+        else {
+            return incompatible(format!("unknown current version {:?}", current));
+        };
+        if current == target {
+            return Compatibility::UpToDate;
+        }
+        if current > target && !allow_downgrade {
+            return incompatible(format!(
+                "current version {} is newer than {}",
+                current, target
+            ));
+        }
+        if let Some(min) = self.min_version.as_deref().and_then(Version::parse) {
+            if current < min {
+                return incompatible(format!(
+                    "current version {} is older than {}, update to {} first",
+                    current, min, min
+                ));
+            }
+        }
+        Compatibility::Compatible
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 读取固件包，`path` 可以是固件包目录或其中的清单文件
+pub fn load(path: &Path) -> Result<FirmwarePackage, AppError> {
+    let manifest_path = if path.is_dir() {
+        path.join(MANIFEST_NAME)
+    } else {
+        path.to_path_buf()
+    };
+    let text = fs::read_to_string(&manifest_path)
+        .map_err(|e| AppError::Io(format!("{}: {}", manifest_path.display(), e)))?;
+    let manifest: Manifest = serde_json::from_str(&text)
+        .map_err(|e| AppError::InvalidArgument(format!("invalid manifest: {}", e)))?;
+    manifest.validate()?;
+    let image = manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(&manifest.file);
+    Ok(FirmwarePackage { manifest, image })
+}
+
+impl FirmwarePackage {
+    /// 读入镜像并校验大小和 SHA-256，上传时使用校验过的这份数据
+    pub fn read_image(&self) -> Result<Vec<u8>, AppError> {
+        let mut data = Vec::new();
+        File::open(&self.image)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .map_err(|e| AppError::Io(format!("{}: {}", self.image.display(), e)))?;
+        if data.len() as u64 != self.manifest.size {
+            return Err(AppError::InvalidArgument(format!(
+                "image size {} does not match manifest size {}",
+                data.len(),
+                self.manifest.size
+            )));
+        }
+        let checksum = sha256_hex(&data);
+        if !checksum.eq_ignore_ascii_case(&self.manifest.sha256) {
+            return Err(AppError::InvalidArgument(format!(
+                "image checksum {} does not match manifest",
+                checksum
+            )));
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// 在临时目录生成固件包
+    pub fn write_package(name: &str, image: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("firmware-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("xarm.bin"), image).unwrap();
+        let manifest = Manifest {
+            version: "2.6.0".to_string(),
+            device_types: vec![5, 6, 7],
+            min_version: Some("2.0.0".to_string()),
+            file: "xarm.bin".to_string(),
+            size: image.len() as u64,
+            sha256: sha256_hex(image),
+        };
+        fs::write(
+            dir.join(MANIFEST_NAME),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_versions() {
+        let v = |text: &str| Version::parse(text).unwrap();
+        assert_eq!(v("v2.5.0"), v("2.5"));
+        assert_eq!(v("xArm6-V1.9.10").to_string(), "1.9.10");
+        assert!(v("1.10.0") > v("1.9.10"));
+        assert!(Version::parse("beta").is_none());
+    }
+
+    #[test]
+    fn verifies_checksum() {
+        let dir = write_package("checksum", b"firmware image");
+        let package = load(&dir).unwrap();
+        assert_eq!(package.read_image().unwrap(), b"firmware image");
+
+        fs::write(dir.join("xarm.bin"), b"firmware imagf").unwrap();
+        assert!(matches!(
+            package.read_image(),
+            Err(AppError::InvalidArgument(message)) if message.contains("checksum")
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checks_compatibility() {
+        let dir = write_package("compat", b"image");
+        let manifest = load(&dir.join(MANIFEST_NAME)).unwrap().manifest;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            manifest.compatibility("6", "2.5.0", false),
+            Compatibility::Compatible
+        );
+        assert_eq!(
+            manifest.compatibility("6", "v2.6.0", false),
+            Compatibility::UpToDate
+        );
+        let rejected = |device_type, version, allow_downgrade| {
+            matches!(
+                manifest.compatibility(device_type, version, allow_downgrade),
+                Compatibility::Incompatible { .. }
+            )
+        };
+        // Lite 6
+        assert!(rejected("9", "2.5.0", false));
+        assert!(rejected("6", "1.9.10", false));
+        assert!(rejected("6", "2.7.0", false));
+        assert!(!rejected("6", "2.7.0", true));
+    }
+}
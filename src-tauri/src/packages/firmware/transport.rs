@@ -0,0 +1,145 @@
+//! 把镜像交给控制器的方式
+//!
+//! 控制器实际的升级接口尚未确认，`UnverifiedHttpUpload` 只是占位实现：
+//! 按假设的 `POST /controller/firmware`（附 `X-Firmware-Version`、`X-Firmware-Sha256` 头）
+//! 流式上传，仅与测试替身对过接口，不能用于真实控制器。确认接口后新增实现替换它。
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use reqwest::{Body, Client, Url};
+use tokio::time::Instant;
+
+use super::package::Manifest;
+use super::FirmwareTarget;
+use crate::packages::xarm::CONTROLLER_HTTP_PORT;
+use crate::utils::error::AppError;
+
+/// 占位上传接口
+pub(super) const UPLOAD_PATH: &str = "/controller/firmware";
+/// 上传分块大小
+const CHUNK_SIZE: usize = 64 * 1024;
+/// 上传进度推送间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type UploadFuture<'a> = Pin<Box<dyn Future<Output = Result<(), AppError>> + Send + 'a>>;
+
+/// 已上传字节数回调
+pub type SentFn = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// 镜像上传方式
+///
+/// `upload` 返回 `Ok` 表示控制器已完整接收镜像，之后开始安装并重启；
+/// 中止时上传 future 会被直接丢弃。
+pub trait FirmwareTransport: Send + Sync {
+    /// 名称，写入日志
+    fn name(&self) -> &'static str;
+
+    /// 开始升级前检查目标地址
+    fn check(&self, target: &FirmwareTarget) -> Result<(), AppError>;
+
+    fn upload<'a>(
+        &'a self,
+        target: &'a FirmwareTarget,
+        manifest: &'a Manifest,
+        image: Arc<Vec<u8>>,
+        on_sent: SentFn,
+    ) -> UploadFuture<'a>;
+}
+
+/// 未经确认的 HTTP 上传，见模块说明
+pub struct UnverifiedHttpUpload {
+    client: Arc<Client>,
+}
+
+impl UnverifiedHttpUpload {
+    pub fn new(client: Arc<Client>) -> Self {
+        Self { client }
+    }
+
+    fn url(target: &FirmwareTarget) -> Result<Url, AppError> {
+        let addr = SocketAddr::new(
+            target.ip()?,
+            target.http_port.unwrap_or(CONTROLLER_HTTP_PORT),
+        );
+        Url::parse(&format!("http://{}{}", addr, UPLOAD_PATH))
+            .map_err(|e| AppError::InvalidArgument(e.to_string()))
+    }
+}
+
+impl FirmwareTransport for UnverifiedHttpUpload {
+    fn name(&self) -> &'static str {
+        "unverified-http"
+    }
+
+    fn check(&self, target: &FirmwareTarget) -> Result<(), AppError> {
+        Self::url(target).map(|_| ())
+    }
+
+    fn upload<'a>(
+        &'a self,
+        target: &'a FirmwareTarget,
+        manifest: &'a Manifest,
+        image: Arc<Vec<u8>>,
+        on_sent: SentFn,
+    ) -> UploadFuture<'a> {
+        Box::pin(async move {
+            let url = Self::url(target)?;
+            let total = image.len() as u64;
+            let chunks = stream::unfold((0usize, Instant::now()), move |(offset, mut reported)| {
+                let image = Arc::clone(&image);
+                let on_sent = Arc::clone(&on_sent);
+                async move {
+                    if offset >= image.len() {
+                        return None;
+                    }
+                    let end = (offset + CHUNK_SIZE).min(image.len());
+                    if reported.elapsed() >= PROGRESS_INTERVAL || end == image.len() {
+                        reported = Instant::now();
+                        on_sent(end as u64);
+                    }
+                    Some((
+                        Ok::<_, std::io::Error>(image[offset..end].to_vec()),
+                        (end, reported),
+                    ))
+                }
+            });
+
+            let response = self
+                .client
+                .post(url.clone())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_LENGTH, total)
+                .header("X-Firmware-Version", &manifest.version)
+                .header("X-Firmware-Sha256", &manifest.sha256)
+                .body(Body::wrap_stream(chunks))
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_connect() {
+                        AppError::Connect {
+                            addr: url.to_string(),
+                            message: e.to_string(),
+                        }
+                    } else {
+                        AppError::Io(format!("upload to {} failed: {}", url, e))
+                    }
+                })?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            let body = response.text().await.unwrap_or_default();
+            Err(AppError::Protocol(format!(
+                "controller rejected firmware with HTTP {}: {}",
+                status,
+                body.trim()
+            )))
+        })
+    }
+}
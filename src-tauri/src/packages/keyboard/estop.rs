@@ -0,0 +1,374 @@
+//! 急停快捷键
+//!
+//! 与窗口快捷键不同，急停快捷键在启动时注册，窗口失去焦点也不注销。
+//! 按下（`Pressed`）即向所有已连接的机械臂并行发送停止指令，记录 error 日志，
+//! 再弹出需要确认的阻塞对话框显示结果。快捷键保存在 `app_settings.json` 的 `estop_shortcut` 中。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::Local;
+use log::{error, info};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{async_runtime, AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_store::StoreExt;
+use tokio::task::JoinSet;
+
+use crate::packages::menu::i18n;
+use crate::packages::xarm::client::XArmClient;
+use crate::packages::xarm::XArmClients;
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
+
+/// 急停结果推送
+pub const EVENT_EMERGENCY_STOP: &str = "emergency_stop";
+/// 默认快捷键
+pub const DEFAULT_SHORTCUT: &str = "Ctrl+Shift+E";
+
+const SETTINGS_STORE: &str = "app_settings.json";
+const SHORTCUT_KEY: &str = "estop_shortcut";
+/// 运动状态：停止
+const STATE_STOP: u8 = 4;
+
+/// 单台机械臂的急停结果
+#[derive(Serialize, Clone, Debug)]
+pub struct StopOutcome {
+    pub addr: String,
+    pub ok: bool,
+    pub elapsed_ms: f64,
+    pub message: Option<String>,
+}
+
+/// 一次急停，同时作为 `emergency_stop` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct StopReport {
+    /// 触发来源：`shortcut` 或 `command`
+    pub source: String,
+    /// 触发时间（毫秒时间戳）
+    pub time: i64,
+    pub outcomes: Vec<StopOutcome>,
+}
+
+/// 急停快捷键状态
+#[derive(Clone, Default)]
+pub struct EmergencyStop {
+    shortcut: Arc<Mutex<Option<Shortcut>>>,
+    /// 确认对话框是否已打开，避免连续按键弹出多个
+    dialog_open: Arc<AtomicBool>,
+}
+
+impl EmergencyStop {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前快捷键
+    pub fn shortcut(&self) -> Option<String> {
+        self.shortcut
+            .lock()
+            .ok()
+            .and_then(|shortcut| shortcut.map(|shortcut| shortcut.into_string()))
+    }
+}
+
+/// 按保存的设置注册急停快捷键，失败时回退到默认值
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let saved = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(SHORTCUT_KEY))
+        .and_then(|value| value.as_str().map(str::to_string));
+    if let Some(binding) = saved {
+        match register(app, &binding) {
+            Ok(()) => return,
+            Err(e) => error!(
+                "Failed to register saved e-stop shortcut {}: {}",
+                binding, e
+            ),
+        }
+    }
+    if let Err(e) = register(app, DEFAULT_SHORTCUT) {
+        error!(
+            "Failed to register e-stop shortcut {}: {}",
+            DEFAULT_SHORTCUT, e
+        );
+    }
+}
+
+/// 修改急停快捷键并保存；新快捷键注册成功后才注销旧的，保证任何时刻都有急停键
+pub fn set_binding<R: Runtime>(app: &AppHandle<R>, binding: &str) -> Result<String, AppError> {
+    register(app, binding)?;
+    let shortcut = app.state::<AppState>().estop.shortcut().unwrap_or_default();
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    store.set(SHORTCUT_KEY, json!(shortcut));
+    store.save().map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(shortcut)
+}
+
+fn register<R: Runtime>(app: &AppHandle<R>, binding: &str) -> Result<(), AppError> {
+    let shortcut: Shortcut = binding
+        .parse()
+        .map_err(|e| AppError::InvalidArgument(format!("invalid shortcut {:?}: {}", binding, e)))?;
+    if super::window_handlers::<R>().contains_key(&shortcut) {
+        return Err(AppError::InvalidArgument(format!(
+            "{} is already used by a window shortcut",
+            binding
+        )));
+    }
+
+    let state = app.state::<AppState>();
+    let mut current = state
+        .estop
+        .shortcut
+        .lock()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    if *current == Some(shortcut) {
+        return Ok(());
+    }
+    let manager = app.global_shortcut();
+    manager
+        .on_shortcut(shortcut, |app, _, event| {
+            // 按下立即触发，不等松开
+            if event.state == ShortcutState::Pressed {
+                trigger(app, "shortcut");
+            }
+        })
+        .map_err(|e| AppError::InvalidArgument(format!("{}: {}", binding, e)))?;
+    if let Some(previous) = current.replace(shortcut) {
+        if let Err(e) = manager.unregister(previous) {
+            error!("Failed to unregister e-stop shortcut {}: {}", previous, e);
+        }
+    }
+    info!("E-stop shortcut registered: {}", shortcut);
+    Ok(())
+}
+
+/// 快捷键触发急停，不阻塞快捷键回调
+fn trigger<R: Runtime>(app: &AppHandle<R>, source: &'static str) {
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        run(&app, source).await;
+    });
+}
+
+/// 执行急停：先发送停止指令，再推送事件并弹出确认对话框
+pub async fn run<R: Runtime>(app: &AppHandle<R>, source: &str) -> StopReport {
+    error!("Emergency stop triggered by {}", source);
+    let clients = app.state::<AppState>().arms.clone();
+    let report = stop_all(&clients, source).await;
+    if let Err(e) = app.emit(EVENT_EMERGENCY_STOP, &report) {
+        error!("Failed to emit event: {}", e);
+    }
+    confirm(app, &report);
+    report
+}
+
+/// 向所有机械臂并行发送停止指令
+pub async fn stop_all(clients: &XArmClients, source: &str) -> StopReport {
+    let time = Local::now().timestamp_millis();
+    let mut tasks = JoinSet::new();
+    for (addr, client) in clients.all() {
+        tasks.spawn(async move {
+            let started = Instant::now();
+            // 共享连接正在等待其他应答时，另开一条连接发送，不排队
+            let result = match client.try_lock() {
+                Ok(mut client) => client.set_state(STATE_STOP).await,
+                Err(_) => {
+                    let mut client = XArmClient::new(addr);
+                    let result = client.set_state(STATE_STOP).await;
+                    client.disconnect().await;
+                    result
+                }
+            };
+            outcome(addr.to_string(), started, result)
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => error!("E-stop task failed: {}", e),
+        }
+    }
+    outcomes.sort_by(|a, b| a.addr.cmp(&b.addr));
+    for outcome in &outcomes {
+        match &outcome.message {
+            None => error!(
+                "E-stop sent to {} in {:.1} ms",
+                outcome.addr, outcome.elapsed_ms
+            ),
+            Some(message) => error!("E-stop to {} failed: {}", outcome.addr, message),
+        }
+    }
+    if outcomes.is_empty() {
+        error!("E-stop: no connected arm");
+    }
+    StopReport {
+        source: source.to_string(),
+        time,
+        outcomes,
+    }
+}
+
+fn outcome(addr: String, started: Instant, result: Result<(), AppError>) -> StopOutcome {
+    StopOutcome {
+        addr,
+        ok: result.is_ok(),
+        elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+        message: result.err().map(|e| e.to_string()),
+    }
+}
+
+/// 阻塞式确认对话框，同一时间只显示一个
+fn confirm<R: Runtime>(app: &AppHandle<R>, report: &StopReport) {
+    let estop = app.state::<AppState>().estop.clone();
+    if estop.dialog_open.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let failed: Vec<String> = report
+        .outcomes
+        .iter()
+        .filter(|outcome| !outcome.ok)
+        .map(|outcome| {
+            format!(
+                "{}: {}",
+                outcome.addr,
+                outcome.message.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+    let mut message = if report.outcomes.is_empty() {
+        i18n::tr(
+            "急停已触发，但当前没有已连接的机械臂。",
+            "E-stop triggered, but no arm is connected.",
+        )
+        .to_string()
+    } else {
+        format!(
+            "{} {}/{}",
+            i18n::tr("已向机械臂发送停止指令：", "Stop sent to arms:"),
+            report.outcomes.len() - failed.len(),
+            report.outcomes.len()
+        )
+    };
+    if !failed.is_empty() {
+        message.push_str(&format!(
+            "\n\n{}\n{}",
+            i18n::tr(
+                "以下机械臂停止失败，请立即手动急停：",
+                "Failed to stop, use the hardware e-stop now:"
+            ),
+            failed.join("\n")
+        ));
+    }
+
+    let dialog = app
+        .dialog()
+        .message(message)
+        .title(i18n::tr("急停", "Emergency Stop"))
+        .kind(if failed.is_empty() {
+            MessageDialogKind::Warning
+        } else {
+            MessageDialogKind::Error
+        });
+    async_runtime::spawn_blocking(move || {
+        dialog.blocking_show();
+        estop.dialog_open.store(false, Ordering::SeqCst);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+    use tokio::time;
+
+    use super::*;
+    use crate::packages::simulator::arm::{SimArm, STATE_STOPPED};
+    use crate::packages::simulator::stand_in::{sim_stand_in, still_config, SimStandIn};
+    use crate::packages::xarm::ArmAddress;
+
+    async fn simulator() -> SimStandIn {
+        sim_stand_in(still_config()).await
+    }
+
+    fn state(arm: &Mutex<SimArm>) -> u8 {
+        arm.lock().unwrap().state
+    }
+
+    #[tokio::test]
+    async fn stops_every_arm() {
+        let clients = XArmClients::new();
+        let first = simulator().await;
+        let second = simulator().await;
+        clients.get(&first.address()).unwrap();
+        clients.get(&second.address()).unwrap();
+
+        let report = stop_all(&clients, "command").await;
+        assert_eq!(report.source, "command");
+        assert_eq!(report.outcomes.len(), 2);
+        assert!(report.outcomes.iter().all(|outcome| outcome.ok));
+        assert_eq!(state(&first.arm), STATE_STOPPED);
+        assert_eq!(state(&second.arm), STATE_STOPPED);
+
+        assert!(stop_all(&XArmClients::new(), "shortcut")
+            .await
+            .outcomes
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn bypasses_a_busy_client() {
+        let clients = XArmClients::new();
+        let sim = simulator().await;
+        let client = clients.get(&sim.address()).unwrap();
+        // 共享连接被占用（例如正在等待其他指令的应答）
+        let _busy = client.lock().await;
+
+        let report = time::timeout(Duration::from_secs(1), stop_all(&clients, "command"))
+            .await
+            .unwrap();
+        assert!(report.outcomes[0].ok, "{:?}", report.outcomes[0].message);
+        assert_eq!(state(&sim.arm), STATE_STOPPED);
+    }
+
+    #[tokio::test]
+    async fn unreachable_arm_does_not_delay_others() {
+        let clients = XArmClients::new();
+        let sim = simulator().await;
+        // 接受连接但从不应答
+        let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent_addr = silent.local_addr().unwrap();
+        clients
+            .get(&ArmAddress {
+                ip: silent_addr.ip().to_string(),
+                command_port: Some(silent_addr.port()),
+            })
+            .unwrap();
+        clients.get(&sim.address()).unwrap();
+
+        let report = stop_all(&clients, "command").await;
+        let outcome = |addr: String| {
+            report
+                .outcomes
+                .iter()
+                .find(|outcome| outcome.addr == addr)
+                .unwrap()
+        };
+        let stopped = outcome(sim.command_addr.to_string());
+        let failed = outcome(silent_addr.to_string());
+        assert!(stopped.ok);
+        assert!(!failed.ok && failed.message.is_some());
+        assert!(stopped.elapsed_ms < failed.elapsed_ms / 2.0);
+        assert_eq!(state(&sim.arm), STATE_STOPPED);
+    }
+}
@@ -7,108 +7,33 @@ use tauri::{
 use tauri::{Manager, RunEvent};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 
+pub mod estop;
+
+type WindowHandler<R> = Box<dyn Fn(&AppHandle<R>, &Shortcut) + Send + Sync + 'static>;
+
 /// 初始化键盘
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     PluginBuilder::new("app_keyboard")
+        .setup(|app, _| {
+            // 急停快捷键常驻，不随窗口焦点注销
+            estop::init(app);
+            Ok(())
+        })
         .on_event(move |app, event| match event {
             RunEvent::WindowEvent { event, .. } => match event {
                 WindowEvent::Focused(false) => {
                     let shortcut_manager = app.global_shortcut();
-                    if let Err(e) = shortcut_manager.unregister_all() {
-                        eprintln!("取消注册全局快捷键失败: {}", e);
+                    let shortcuts = window_handlers::<R>().into_keys().collect::<Vec<_>>();
+                    if let Err(e) = shortcut_manager.unregister_multiple(shortcuts) {
+                        log::error!("取消注册全局快捷键失败: {}", e);
                     }
-                    println!("取消注册全局快捷键成功");
+                    log::debug!("取消注册全局快捷键成功");
 
-                    println!("window focused -{}", false);
+                    log::debug!("window focused -{}", false);
                 }
                 WindowEvent::Focused(true) => {
                     let shortcut_manager = app.global_shortcut();
-                    let mut handler_map: HashMap<
-                        Shortcut,
-                        Box<dyn Fn(&AppHandle<R>, &Shortcut) + Send + Sync + 'static>,
-                    > = HashMap::new();
-
-                    // 标准尺寸
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyR),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 标准尺寸
-                            let window = _app.get_webview_window("main").unwrap();
-                            window.set_size(LogicalSize::new(1280.0, 768.0)).unwrap();
-                        }),
-                    );
-                    // 最小化
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyM),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 最小化
-                            let window = _app.get_webview_window("main").unwrap();
-                            window.minimize().unwrap();
-                        }),
-                    );
-                    // 全屏
-                    handler_map.insert(
-                        Shortcut::new(None, Code::F11),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 全屏
-                            let window = _app.get_webview_window("main").unwrap();
-                            if let Ok(is_fullscreen) = window.is_fullscreen() {
-                                let _ = window.set_fullscreen(!is_fullscreen);
-                            } else {
-                                let _ = window.set_fullscreen(true);
-                            }
-                        }),
-                    );
-                    // 搜索
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyF),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 去搜索页面，/app/home
-                            let window = _app.get_webview_window("main").unwrap();
-                            window
-                                .eval(&format!("window.location.href = '/app/home';"))
-                                .unwrap();
-                        }),
-                    );
-                    // 刷新页面
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL), Code::F5),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 刷新页面
-                            let window = _app.get_webview_window("main").unwrap();
-                            window.reload().unwrap();
-                        }),
-                    );
-                    // 调试工具
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyI),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 打开调试工具
-                            let window = _app.get_webview_window("main").unwrap();
-                            window.open_devtools();
-                        }),
-                    );
-
-                    // 打开日志目录
-                    handler_map.insert(
-                        Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyL),
-                        Box::new(|_app, shortcut| {
-                            println!("快捷键 {:?} 被按下！", shortcut);
-                            // 打开日志目录
-                            let app_handle = _app.app_handle();
-                            let log_folder = app_handle
-                                .path()
-                                .app_log_dir()
-                                .unwrap_or_else(|_| PathBuf::from("."));
-                            let _ = opener::open(log_folder);
-                        }),
-                    );
+                    let handler_map = window_handlers::<R>();
 
                     let shortcuts = handler_map.keys().cloned().collect::<Vec<Shortcut>>();
 
@@ -124,7 +49,7 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
                     {
                         log::error!("快捷键监听注册失败: {}", e);
                     } else {
-                        println!("快捷键监听注册成功");
+                        log::debug!("快捷键监听注册成功");
                     }
                 }
                 _ => {}
@@ -133,3 +58,92 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
         })
         .build()
 }
+
+/// 窗口快捷键，仅在窗口获得焦点时注册
+fn window_handlers<R: Runtime>() -> HashMap<Shortcut, WindowHandler<R>> {
+    let mut handler_map: HashMap<Shortcut, WindowHandler<R>> = HashMap::new();
+
+    // 标准尺寸
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyR),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 标准尺寸
+            let window = _app.get_webview_window("main").unwrap();
+            window.set_size(LogicalSize::new(1280.0, 768.0)).unwrap();
+        }),
+    );
+    // 最小化
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyM),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 最小化
+            let window = _app.get_webview_window("main").unwrap();
+            window.minimize().unwrap();
+        }),
+    );
+    // 全屏
+    handler_map.insert(
+        Shortcut::new(None, Code::F11),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 全屏
+            let window = _app.get_webview_window("main").unwrap();
+            if let Ok(is_fullscreen) = window.is_fullscreen() {
+                let _ = window.set_fullscreen(!is_fullscreen);
+            } else {
+                let _ = window.set_fullscreen(true);
+            }
+        }),
+    );
+    // 搜索
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL), Code::KeyF),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 去搜索页面，/app/home
+            let window = _app.get_webview_window("main").unwrap();
+            window
+                .eval(&format!("window.location.href = '/app/home';"))
+                .unwrap();
+        }),
+    );
+    // 刷新页面
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL), Code::F5),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 刷新页面
+            let window = _app.get_webview_window("main").unwrap();
+            window.reload().unwrap();
+        }),
+    );
+    // 调试工具
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyI),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 打开调试工具
+            let window = _app.get_webview_window("main").unwrap();
+            window.open_devtools();
+        }),
+    );
+
+    // 打开日志目录
+    handler_map.insert(
+        Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyL),
+        Box::new(|_app, shortcut| {
+            log::debug!("快捷键 {:?} 被按下！", shortcut);
+            // 打开日志目录
+            let app_handle = _app.app_handle();
+            let log_folder = app_handle
+                .path()
+                .app_log_dir()
+                .unwrap_or_else(|_| PathBuf::from("."));
+            let _ = opener::open(log_folder);
+        }),
+    );
+
+    handler_map
+}
@@ -0,0 +1,596 @@
+//! 逆解
+//!
+//! 六轴机型（xArm6、Lite 6、850）的 J4、J5 轴线相交于腕部中心，先由腕部中心求 J1~J3，
+//! 再从腕部姿态分解出 J4~J6，最多 8 组分支（肩部前/后、肘部 ±、腕部 ±）。
+//! xArm6 的法兰相对 J6 轴线有 76 mm 偏置，腕部中心随 J6 变化，此时在 J6 上一维搜索自洽解。
+//!
+//! xArm5、xArm7 使用阻尼最小二乘数值解，从多个初值出发得到不同分支。
+//! 所有解都会按 ±2π 调整到关节范围内（尽量靠近参考关节角），仍超限的解被丢弃。
+
+use std::f64::consts::{PI, TAU};
+
+use nalgebra::{DMatrix, DVector, Isometry3, Rotation3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::SingularityKind;
+use super::{check_joints, flange, frames, jacobian_matrix, links, rotation_error, Link, Pose};
+use crate::packages::xarm::model::ArmModel;
+use crate::utils::error::AppError;
+
+/// 接受解时的位置误差（mm）
+const POSITION_TOLERANCE: f64 = 1e-4;
+/// 接受解时的姿态误差（rad）
+const ORIENTATION_TOLERANCE: f64 = 1e-7;
+const MAX_ITERATIONS: usize = 200;
+/// 阻尼系数（线速度按 m 计）
+const DAMPING: f64 = 1e-3;
+/// 单步最大关节增量（rad）
+const MAX_STEP: f64 = 0.3;
+/// 数值解的额外初值个数
+const NUMERIC_SEEDS: usize = 24;
+/// J6 一维搜索的采样数
+const WRIST_SAMPLES: usize = 360;
+/// 判断 MDH 参数结构时的容差
+const EPSILON: f64 = 1e-9;
+
+/// 奇异判断阈值
+const SHOULDER_TOLERANCE: f64 = 10.0;
+const ANGLE_TOLERANCE: f64 = 1.0 * PI / 180.0;
+
+/// 求解方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IkMethod {
+    /// 支持解析解的机型用解析解，否则用多初值数值解
+    #[default]
+    Auto,
+    Analytic,
+    /// 从参考关节角出发的单个数值解
+    Numeric,
+}
+
+/// 解析解分支，各项取值 ±1
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Branch {
+    /// 1：J1 朝向腕部中心，-1：背向
+    pub shoulder: i8,
+    pub elbow: i8,
+    pub wrist: i8,
+}
+
+/// 一组逆解
+#[derive(Serialize, Clone, Debug)]
+pub struct IkSolution {
+    pub joints: Vec<f64>,
+    /// 数值解没有分支
+    pub branch: Option<Branch>,
+    /// 正解回代的位置误差（mm）
+    pub position_error: f64,
+    /// 正解回代的姿态误差（rad）
+    pub orientation_error: f64,
+}
+
+/// 逆解，结果按与参考关节角（默认全零）的距离排序
+pub fn solve(
+    model: ArmModel,
+    target: &Pose,
+    method: IkMethod,
+    reference: Option<&[f64]>,
+) -> Result<Vec<IkSolution>, AppError> {
+    if let Some(reference) = reference {
+        check_joints(model, reference)?;
+    }
+    let reference = reference
+        .map(<[f64]>::to_vec)
+        .unwrap_or_else(|| vec![0.0; model.axis()]);
+    let target = target.to_isometry();
+    let links = links(model);
+    let limits = model.limits().joints;
+
+    let mut solutions: Vec<IkSolution> = match method {
+        IkMethod::Analytic if !supports_analytic(links) => {
+            return Err(AppError::InvalidArgument(format!(
+                "analytic IK is not available for {:?}",
+                model
+            )))
+        }
+        IkMethod::Auto | IkMethod::Analytic if supports_analytic(links) => analytic(links, &target)
+            .into_iter()
+            .filter_map(|(joints, branch)| {
+                let joints = fit_limits(&joints, &limits, &reference)?;
+                Some(solution(links, &target, joints, Some(branch)))
+            })
+            .collect(),
+        IkMethod::Numeric => numeric(links, &limits, &target, &reference)
+            .map(|joints| solution(links, &target, joints, None))
+            .into_iter()
+            .collect(),
+        _ => seeds(&limits, &reference)
+            .iter()
+            .filter_map(|seed| numeric(links, &limits, &target, seed))
+            .map(|joints| solution(links, &target, joints, None))
+            .collect(),
+    };
+
+    solutions.retain(|solution| {
+        solution.position_error < POSITION_TOLERANCE * 10.0
+            && solution.orientation_error < ORIENTATION_TOLERANCE * 10.0
+    });
+    solutions.sort_by(|a, b| {
+        distance(&a.joints, &reference).total_cmp(&distance(&b.joints, &reference))
+    });
+    solutions.dedup_by(|a, b| distance(&a.joints, &b.joints) < 1e-6);
+    if solutions.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "pose is unreachable within joint limits".to_string(),
+        ));
+    }
+    Ok(solutions)
+}
+
+fn solution(
+    links: &[Link],
+    target: &Isometry3<f64>,
+    joints: Vec<f64>,
+    branch: Option<Branch>,
+) -> IkSolution {
+    let (position_error, orientation_error) = residual(target, &flange(links, &joints));
+    IkSolution {
+        joints,
+        branch,
+        position_error,
+        orientation_error,
+    }
+}
+
+fn residual(target: &Isometry3<f64>, current: &Isometry3<f64>) -> (f64, f64) {
+    let position = (target.translation.vector - current.translation.vector).norm();
+    let orientation = rotation_error(
+        &target.rotation.to_rotation_matrix(),
+        &current.rotation.to_rotation_matrix(),
+    )
+    .norm();
+    (position, orientation)
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// 归一化到 (-π, π]
+fn wrap(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(TAU) - PI;
+    if wrapped <= -PI {
+        wrapped + TAU
+    } else {
+        wrapped
+    }
+}
+
+/// 按 ±2π 把每个关节调整到范围内，多个候选时取最接近参考值的
+fn fit_limits(joints: &[f64], limits: &[(f64, f64)], reference: &[f64]) -> Option<Vec<f64>> {
+    joints
+        .iter()
+        .zip(limits)
+        .zip(reference)
+        .map(|((value, (min, max)), reference)| {
+            let value = wrap(*value);
+            (-2..=2)
+                .map(|turns| value + turns as f64 * TAU)
+                .filter(|candidate| *candidate >= min - EPSILON && *candidate <= max + EPSILON)
+                .min_by(|a, b| (a - reference).abs().total_cmp(&(b - reference).abs()))
+        })
+        .collect()
+}
+
+/// 阻尼最小二乘，迭代中关节角始终限制在范围内
+fn numeric(
+    links: &[Link],
+    limits: &[(f64, f64)],
+    target: &Isometry3<f64>,
+    seed: &[f64],
+) -> Option<Vec<f64>> {
+    let mut joints = seed.to_vec();
+    let target_rotation = target.rotation.to_rotation_matrix();
+    for _ in 0..MAX_ITERATIONS {
+        let current = flange(links, &joints);
+        let position = target.translation.vector - current.translation.vector;
+        let orientation = rotation_error(&target_rotation, &current.rotation.to_rotation_matrix());
+        if position.norm() < POSITION_TOLERANCE && orientation.norm() < ORIENTATION_TOLERANCE {
+            return Some(joints);
+        }
+
+        let mut jacobian = jacobian_matrix(links, &joints);
+        jacobian.rows_mut(0, 3).scale_mut(0.001);
+        let error = DVector::from_iterator(
+            6,
+            (position * 0.001).iter().chain(orientation.iter()).copied(),
+        );
+        let damped =
+            &jacobian * jacobian.transpose() + DMatrix::identity(6, 6) * (DAMPING * DAMPING);
+        let mut step = jacobian.transpose() * damped.lu().solve(&error)?;
+        let largest = step.amax();
+        if largest > MAX_STEP {
+            step *= MAX_STEP / largest;
+        }
+        for ((value, delta), (min, max)) in joints.iter_mut().zip(step.iter()).zip(limits) {
+            *value = (*value + delta).clamp(*min, *max);
+        }
+    }
+    None
+}
+
+/// 参考关节角加上一组在关节范围内均匀分布的固定初值
+fn seeds(limits: &[(f64, f64)], reference: &[f64]) -> Vec<Vec<f64>> {
+    // 固定的线性同余序列，保证结果可复现
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    let mut seeds = vec![reference.to_vec()];
+    for _ in 0..NUMERIC_SEEDS {
+        seeds.push(
+            limits
+                .iter()
+                .map(|(min, max)| {
+                    let (min, max) = (min.max(-PI), max.min(PI));
+                    min + (max - min) * next()
+                })
+                .collect(),
+        );
+    }
+    seeds
+}
+
+/// 是否满足解析解的结构：J1 竖直，J2、J3 平行且无侧向偏置，J4、J5 轴线相交
+fn supports_analytic(links: &[Link]) -> bool {
+    let zero = |value: f64| value.abs() < EPSILON;
+    let [l1, l2, l3, l4, l5, l6] = links else {
+        return false;
+    };
+    zero(l1.alpha)
+        && zero(l1.a)
+        && zero(l2.a)
+        && zero(l2.d)
+        && zero(l2.alpha.cos())
+        && zero(l3.d)
+        && zero(l3.alpha.sin())
+        && zero(l4.alpha.cos())
+        && zero(l5.a)
+        && zero(l5.d)
+        && zero(l5.alpha.cos())
+        && zero(l6.alpha.cos())
+}
+
+/// J4 原点（腕部中心）在 J3 坐标系下的位置
+fn wrist_in_frame3(links: &[Link]) -> Vector3<f64> {
+    let l4 = &links[3];
+    Vector3::new(l4.a, -l4.d * l4.alpha.sin(), l4.d * l4.alpha.cos())
+}
+
+/// 给定 J6 角时的腕部中心
+fn wrist_center(links: &[Link], target: &Isometry3<f64>, theta6: f64) -> Vector3<f64> {
+    let l6 = &links[5];
+    let offset = Vector3::new(l6.a, -l6.d * l6.alpha.sin(), l6.d * l6.alpha.cos());
+    let local = Rotation3::from_axis_angle(&Vector3::z_axis(), -(theta6 + l6.offset))
+        * Rotation3::from_axis_angle(&Vector3::x_axis(), -l6.alpha)
+        * offset;
+    target.translation.vector - target.rotation * local
+}
+
+/// 所有分支的解析解（未做限位调整）
+fn analytic(links: &[Link], target: &Isometry3<f64>) -> Vec<(Vec<f64>, Branch)> {
+    let mut solutions = Vec::new();
+    for shoulder in [1, -1] {
+        for elbow in [1, -1] {
+            for wrist in [1, -1] {
+                let branch = Branch {
+                    shoulder,
+                    elbow,
+                    wrist,
+                };
+                if links[5].a.abs() < EPSILON {
+                    let center = wrist_center(links, target, 0.0);
+                    solutions
+                        .extend(solve_branch(links, target, center, branch).map(|q| (q, branch)));
+                } else {
+                    solutions.extend(
+                        offset_wrist(links, target, branch)
+                            .into_iter()
+                            .map(|q| (q, branch)),
+                    );
+                }
+            }
+        }
+    }
+    solutions
+}
+
+/// 法兰偏置时在 J6 上搜索 `J6(腕部中心(θ6)) = θ6` 的解
+fn offset_wrist(links: &[Link], target: &Isometry3<f64>, branch: Branch) -> Vec<Vec<f64>> {
+    let gap = |theta6: f64| {
+        solve_branch(links, target, wrist_center(links, target, theta6), branch)
+            .map(|joints| wrap(joints[5] - theta6))
+    };
+
+    let mut roots = Vec::new();
+    let sample = |i: usize| -PI + TAU * i as f64 / WRIST_SAMPLES as f64;
+    let mut previous = (sample(0), gap(sample(0)));
+    for i in 1..=WRIST_SAMPLES {
+        let current = (sample(i), gap(sample(i)));
+        if let ((mut low, Some(mut low_gap)), (mut high, Some(high_gap))) = (previous, current) {
+            // 跨越 ±π 的跳变不是根
+            if low_gap == 0.0 {
+                roots.push(low);
+            } else if low_gap.signum() != high_gap.signum() && (high_gap - low_gap).abs() < 1.0 {
+                for _ in 0..60 {
+                    let middle = (low + high) / 2.0;
+                    match gap(middle) {
+                        Some(middle_gap) if middle_gap.signum() == low_gap.signum() => {
+                            low = middle;
+                            low_gap = middle_gap;
+                        }
+                        Some(_) => high = middle,
+                        None => break,
+                    }
+                }
+                roots.push((low + high) / 2.0);
+            }
+        }
+        previous = current;
+    }
+
+    roots
+        .into_iter()
+        .filter_map(|theta6| {
+            solve_branch(links, target, wrist_center(links, target, theta6), branch)
+        })
+        .collect()
+}
+
+/// 由腕部中心和分支求一组关节角，不可达时返回 None
+fn solve_branch(
+    links: &[Link],
+    target: &Isometry3<f64>,
+    center: Vector3<f64>,
+    branch: Branch,
+) -> Option<Vec<f64>> {
+    let [l1, l2, l3, l4, l5, l6] = links else {
+        return None;
+    };
+
+    // J1：腕部中心位于 J1 转过后的 xz 平面内
+    let horizontal = center.x.hypot(center.y);
+    let facing = if horizontal < EPSILON {
+        0.0
+    } else {
+        center.y.atan2(center.x)
+    };
+    let (t1, r) = if branch.shoulder > 0 {
+        (facing, horizontal)
+    } else {
+        (facing + PI, -horizontal)
+    };
+
+    // 腕部中心在 J2 转动前坐标系下的平面坐标
+    let in_frame1 = Vector3::new(r, 0.0, center.z - l1.d);
+    let plane = Rotation3::from_axis_angle(&Vector3::x_axis(), -l2.alpha) * in_frame1;
+
+    // J3：由肩部到腕部中心的距离确定
+    let w3 = wrist_in_frame3(links);
+    let reach = w3.xy().norm();
+    let cos = (plane.xy().norm_squared() - l3.a * l3.a - reach * reach) / (2.0 * l3.a * reach);
+    if cos.abs() > 1.0 + 1e-9 {
+        return None;
+    }
+    let t3 = branch.elbow as f64 * cos.clamp(-1.0, 1.0).acos() - w3.y.atan2(w3.x);
+
+    // J2：J3 转过后腕部中心在 J2 坐标系下的方向与实际方向之差
+    let sign = l3.alpha.cos().signum();
+    let (s3, c3) = t3.sin_cos();
+    let local = Vector3::new(
+        l3.a + c3 * w3.x - s3 * w3.y,
+        sign * (s3 * w3.x + c3 * w3.y),
+        0.0,
+    );
+    let t2 = plane.y.atan2(plane.x) - local.y.atan2(local.x);
+
+    let mut joints = vec![
+        t1 - l1.offset,
+        t2 - l2.offset,
+        t3 - l3.offset,
+        0.0,
+        0.0,
+        0.0,
+    ];
+
+    // J4~J6：M = Rz(t4) · Rx(α5) · Rz(t5) · Rx(α6) · Rz(t6)
+    let r3 = frames(&links[..3], &joints[..3])[2]
+        .rotation
+        .to_rotation_matrix();
+    let m = Rotation3::from_axis_angle(&Vector3::x_axis(), -l4.alpha)
+        * r3.inverse()
+        * target.rotation.to_rotation_matrix();
+    let m = m.matrix();
+    let (s5, s6) = (l5.alpha.sin(), l6.alpha.sin());
+    let c5 = (-m[(2, 2)] / (s5 * s6)).clamp(-1.0, 1.0);
+    let sin5 = branch.wrist as f64 * (1.0 - c5 * c5).sqrt();
+    let t5 = sin5.atan2(c5);
+    // sin5 由 sqrt 求得，奇异附近有 1e-8 量级的误差
+    let (t4, t6) = if sin5.abs() > 1e-7 {
+        let a = s6 * sin5;
+        let b = s5 * sin5;
+        (
+            (m[(1, 2)] / a).atan2(m[(0, 2)] / a),
+            (-m[(2, 1)] / b).atan2(m[(2, 0)] / b),
+        )
+    } else {
+        // 腕部奇异：J4 取 0，转角全部由 J6 承担
+        let rest = (Rotation3::from_axis_angle(&Vector3::x_axis(), l5.alpha)
+            * Rotation3::from_axis_angle(&Vector3::z_axis(), t5)
+            * Rotation3::from_axis_angle(&Vector3::x_axis(), l6.alpha))
+        .inverse()
+            * Rotation3::from_matrix_unchecked(*m);
+        (0.0, rest[(1, 0)].atan2(rest[(0, 0)]))
+    };
+    joints[3] = t4 - l4.offset;
+    joints[4] = t5 - l5.offset;
+    joints[5] = t6 - l6.offset;
+    Some(joints.into_iter().map(wrap).collect())
+}
+
+/// 按解析解的几何结构判断奇异类型
+pub(super) fn singular_kinds(links: &[Link], joints: &[f64]) -> Vec<SingularityKind> {
+    if !supports_analytic(links) {
+        return Vec::new();
+    }
+    let mut kinds = Vec::new();
+    let center = frames(links, joints)[3].translation.vector;
+    if center.x.hypot(center.y) < SHOULDER_TOLERANCE {
+        kinds.push(SingularityKind::Shoulder);
+    }
+    let w3 = wrist_in_frame3(links);
+    let t3 = joints[2] + links[2].offset;
+    if (t3 + w3.y.atan2(w3.x)).sin().abs() < ANGLE_TOLERANCE.sin() {
+        kinds.push(SingularityKind::Elbow);
+    }
+    if (joints[4] + links[4].offset).sin().abs() < ANGLE_TOLERANCE.sin() {
+        kinds.push(SingularityKind::Wrist);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::kinematics::forward;
+
+    fn assert_reaches(model: ArmModel, solutions: &[IkSolution], target: &Pose) {
+        assert!(!solutions.is_empty());
+        for solution in solutions {
+            let pose = forward(model, &solution.joints).unwrap();
+            let (position, orientation) = residual(&target.to_isometry(), &pose.to_isometry());
+            assert!(position < 1e-3 && orientation < 1e-6, "{:?}", solution);
+            let limits = model.limits().joints;
+            assert!(solution
+                .joints
+                .iter()
+                .zip(&limits)
+                .all(|(value, (min, max))| value >= &(min - 1e-9) && value <= &(max + 1e-9)));
+        }
+    }
+
+    #[test]
+    fn analytic_round_trip() {
+        let joints = [0.4, -0.3, -1.2, 0.6, 0.9, -0.5];
+        for model in [ArmModel::Xarm6, ArmModel::Lite6, ArmModel::Xarm850] {
+            let mut joints = joints;
+            if model == ArmModel::Lite6 {
+                // Lite 6 的 J3 范围为正
+                joints[2] = 1.2;
+            }
+            let target = forward(model, &joints).unwrap();
+            let solutions = solve(model, &target, IkMethod::Analytic, None).unwrap();
+            assert_reaches(model, &solutions, &target);
+            assert!(solutions.len() > 1, "{:?} has a single branch", model);
+            assert!(
+                solutions
+                    .iter()
+                    .any(|s| distance(&s.joints, &joints) < 1e-6),
+                "{:?} misses the original joints",
+                model
+            );
+            assert!(solutions.iter().all(|s| s.branch.is_some()));
+        }
+    }
+
+    #[test]
+    fn analytic_reaches_reference_poses() {
+        for (model, joints, target) in crate::packages::kinematics::tests::reference_poses() {
+            let solutions = solve(model, &target, IkMethod::Analytic, None).unwrap();
+            assert_reaches(model, &solutions, &target);
+            assert!(
+                solutions
+                    .iter()
+                    .any(|s| distance(&s.joints, &joints) < 1e-6),
+                "{:?} misses {:?}",
+                model,
+                joints
+            );
+        }
+    }
+
+    #[test]
+    fn reaches_recorded_controller_poses() {
+        for recorded in crate::packages::kinematics::tests::recorded_poses() {
+            let joints = recorded.joints();
+            let target = recorded.pose();
+            let solutions = solve(recorded.model, &target, IkMethod::Auto, Some(&joints)).unwrap();
+            assert_reaches(recorded.model, &solutions, &target);
+            assert!(
+                distance(&solutions[0].joints, &joints) < 1e-3,
+                "{:?} closest solution {:?}",
+                recorded.model,
+                solutions[0].joints
+            );
+        }
+    }
+
+    #[test]
+    fn analytic_handles_wrist_singularity() {
+        let target = forward(ArmModel::Xarm6, &[0.0; 6]).unwrap();
+        let solutions = solve(ArmModel::Xarm6, &target, IkMethod::Auto, None).unwrap();
+        assert_reaches(ArmModel::Xarm6, &solutions, &target);
+        assert!(distance(&solutions[0].joints, &[0.0; 6]) < 1e-6);
+    }
+
+    #[test]
+    fn numeric_round_trip() {
+        for (model, joints) in [
+            (ArmModel::Xarm5, vec![0.3, -0.2, -1.0, 0.4, 0.6]),
+            (ArmModel::Xarm7, vec![0.3, -0.2, 0.5, 1.0, -0.4, 0.6, 0.2]),
+            (ArmModel::Xarm6, vec![0.4, -0.3, -1.2, 0.6, 0.9, -0.5]),
+        ] {
+            let target = forward(model, &joints).unwrap();
+            // 从附近出发，应收敛到原关节角
+            let seed: Vec<f64> = joints.iter().map(|value| value + 0.05).collect();
+            let solutions = solve(model, &target, IkMethod::Numeric, Some(&seed)).unwrap();
+            assert_reaches(model, &solutions, &target);
+            if model != ArmModel::Xarm7 {
+                assert!(distance(&solutions[0].joints, &joints) < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn auto_falls_back_to_numeric_branches() {
+        let joints = [0.3, -0.2, 0.5, 1.0, -0.4, 0.6, 0.2];
+        let target = forward(ArmModel::Xarm7, &joints).unwrap();
+        let solutions = solve(ArmModel::Xarm7, &target, IkMethod::Auto, Some(&joints)).unwrap();
+        assert_reaches(ArmModel::Xarm7, &solutions, &target);
+        assert!(solutions.len() > 1);
+        assert!(solutions[0].branch.is_none());
+        assert!(solve(ArmModel::Xarm7, &target, IkMethod::Analytic, None).is_err());
+    }
+
+    #[test]
+    fn unreachable_pose() {
+        let target = Pose {
+            x: 2000.0,
+            y: 0.0,
+            z: 200.0,
+            roll: PI,
+            pitch: 0.0,
+            yaw: 0.0,
+        };
+        assert!(solve(ArmModel::Xarm6, &target, IkMethod::Auto, None).is_err());
+    }
+}
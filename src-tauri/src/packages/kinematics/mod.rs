@@ -0,0 +1,464 @@
+//! 运动学
+//!
+//! 各机型使用改进 DH（MDH）参数，相邻坐标系变换为 `Rx(alpha) · Tx(a) · Rz(theta + offset) · Tz(d)`，
+//! 长度单位 mm，角度单位 rad，末端坐标系为法兰中心（不含 TCP 偏移），与控制器上报的位姿一致。
+//!
+//! 提供正解、雅可比和奇异检测；逆解见 `ik`，姿态表示转换见 `rotation`。
+
+pub mod ik;
+pub mod rotation;
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use nalgebra::{DMatrix, Isometry3, Rotation3, Translation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::packages::xarm::model::ArmModel;
+use crate::utils::error::AppError;
+
+/// 雅可比最小奇异值低于该值视为接近奇异（线速度按 m 计）
+const SINGULAR_THRESHOLD: f64 = 0.02;
+
+/// 一行 MDH 参数
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Link {
+    /// 绕 x(i-1) 的扭角（rad）
+    pub alpha: f64,
+    /// 沿 x(i-1) 的连杆长度（mm）
+    pub a: f64,
+    /// 关节零位时的角度偏置（rad）
+    pub offset: f64,
+    /// 沿 z(i) 的偏距（mm）
+    pub d: f64,
+}
+
+const fn link(alpha: f64, a: f64, offset: f64, d: f64) -> Link {
+    Link {
+        alpha,
+        a,
+        offset,
+        d,
+    }
+}
+
+/// xArm 大臂两段（284.5 / 53.5）合成后的偏置角和长度
+const XARM_T2: f64 = 1.384_917_872_870_189_4;
+const XARM_A3: f64 = 289.486_614_543_747_07;
+
+const XARM5: [Link; 5] = [
+    link(0.0, 0.0, 0.0, 267.0),
+    link(-FRAC_PI_2, 0.0, -XARM_T2, 0.0),
+    link(0.0, XARM_A3, 2.733_184_269_115_748, 0.0),
+    // 小臂两段（77.5 / 342.5）合成
+    link(0.0, 351.158_795_988_367_6, -1.348_266_396_245_558_8, 0.0),
+    link(-FRAC_PI_2, 76.0, 0.0, 97.0),
+];
+
+const XARM6: [Link; 6] = [
+    link(0.0, 0.0, 0.0, 267.0),
+    link(-FRAC_PI_2, 0.0, -XARM_T2, 0.0),
+    link(0.0, XARM_A3, XARM_T2, 0.0),
+    link(-FRAC_PI_2, 77.5, 0.0, 342.5),
+    link(FRAC_PI_2, 0.0, 0.0, 0.0),
+    link(-FRAC_PI_2, 76.0, 0.0, 97.0),
+];
+
+const XARM7: [Link; 7] = [
+    link(0.0, 0.0, 0.0, 267.0),
+    link(-FRAC_PI_2, 0.0, 0.0, 0.0),
+    link(FRAC_PI_2, 0.0, 0.0, 293.0),
+    link(FRAC_PI_2, 52.5, 0.0, 0.0),
+    link(FRAC_PI_2, 77.5, 0.0, 342.5),
+    link(FRAC_PI_2, 0.0, 0.0, 0.0),
+    link(-FRAC_PI_2, 76.0, 0.0, 97.0),
+];
+
+const LITE6: [Link; 6] = [
+    link(0.0, 0.0, 0.0, 243.3),
+    link(-FRAC_PI_2, 0.0, -FRAC_PI_2, 0.0),
+    link(PI, 200.0, -FRAC_PI_2, 0.0),
+    link(FRAC_PI_2, 87.0, 0.0, 227.6),
+    link(FRAC_PI_2, 0.0, 0.0, 0.0),
+    link(-FRAC_PI_2, 0.0, 0.0, 61.5),
+];
+
+const XARM850: [Link; 6] = [
+    link(0.0, 0.0, 0.0, 364.0),
+    link(-FRAC_PI_2, 0.0, -FRAC_PI_2, 0.0),
+    link(0.0, 390.0, FRAC_PI_2, 0.0),
+    link(-FRAC_PI_2, 150.0, 0.0, 426.0),
+    link(FRAC_PI_2, 0.0, 0.0, 0.0),
+    link(-FRAC_PI_2, 0.0, 0.0, 90.0),
+];
+
+/// 机型的 MDH 参数
+pub fn links(model: ArmModel) -> &'static [Link] {
+    match model {
+        ArmModel::Xarm5 => &XARM5,
+        ArmModel::Xarm6 => &XARM6,
+        ArmModel::Xarm7 => &XARM7,
+        ArmModel::Lite6 => &LITE6,
+        ArmModel::Xarm850 => &XARM850,
+    }
+}
+
+/// 法兰位姿：位置 mm，RPY 姿态 rad
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub roll: f64,
+    pub pitch: f64,
+    pub yaw: f64,
+}
+
+impl Pose {
+    pub fn to_isometry(self) -> Isometry3<f64> {
+        Isometry3::from_parts(
+            Translation3::new(self.x, self.y, self.z),
+            UnitQuaternion::from_euler_angles(self.roll, self.pitch, self.yaw),
+        )
+    }
+
+    pub fn from_isometry(isometry: &Isometry3<f64>) -> Self {
+        let (roll, pitch, yaw) = isometry.rotation.euler_angles();
+        let position = isometry.translation.vector;
+        Pose {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            roll,
+            pitch,
+            yaw,
+        }
+    }
+}
+
+pub(crate) fn check_joints(model: ArmModel, joints: &[f64]) -> Result<(), AppError> {
+    if joints.len() != model.axis() {
+        return Err(AppError::InvalidArgument(format!(
+            "{:?} expects {} joints, got {}",
+            model,
+            model.axis(),
+            joints.len()
+        )));
+    }
+    if joints.iter().any(|value| !value.is_finite()) {
+        return Err(AppError::InvalidArgument(
+            "joint values must be finite".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn link_transform(link: &Link, theta: f64) -> Isometry3<f64> {
+    Isometry3::rotation(Vector3::x() * link.alpha)
+        * Isometry3::translation(link.a, 0.0, 0.0)
+        * Isometry3::rotation(Vector3::z() * (theta + link.offset))
+        * Isometry3::translation(0.0, 0.0, link.d)
+}
+
+/// 各关节坐标系在基座下的位姿，第 i 个的 z 轴即关节 i 的转轴，最后一个为法兰
+pub(crate) fn frames(links: &[Link], joints: &[f64]) -> Vec<Isometry3<f64>> {
+    let mut current = Isometry3::identity();
+    links
+        .iter()
+        .zip(joints)
+        .map(|(link, theta)| {
+            current *= link_transform(link, *theta);
+            current
+        })
+        .collect()
+}
+
+fn flange(links: &[Link], joints: &[f64]) -> Isometry3<f64> {
+    frames(links, joints)
+        .last()
+        .copied()
+        .unwrap_or_else(Isometry3::identity)
+}
+
+/// 正解
+pub fn forward(model: ArmModel, joints: &[f64]) -> Result<Pose, AppError> {
+    check_joints(model, joints)?;
+    Ok(Pose::from_isometry(&flange(links(model), joints)))
+}
+
+/// 基座坐标系下的几何雅可比（6 × n），前三行线速度（mm/rad），后三行角速度
+fn jacobian_matrix(links: &[Link], joints: &[f64]) -> DMatrix<f64> {
+    let frames = frames(links, joints);
+    let end = frames
+        .last()
+        .map(|frame| frame.translation.vector)
+        .unwrap_or_default();
+    let mut jacobian = DMatrix::zeros(6, frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        let axis = frame.rotation * Vector3::z();
+        let linear = axis.cross(&(end - frame.translation.vector));
+        jacobian.fixed_view_mut::<3, 1>(0, i).copy_from(&linear);
+        jacobian.fixed_view_mut::<3, 1>(3, i).copy_from(&axis);
+    }
+    jacobian
+}
+
+/// 雅可比，按行返回
+pub fn jacobian(model: ArmModel, joints: &[f64]) -> Result<Vec<Vec<f64>>, AppError> {
+    check_joints(model, joints)?;
+    let jacobian = jacobian_matrix(links(model), joints);
+    Ok(jacobian
+        .row_iter()
+        .map(|row| row.iter().copied().collect())
+        .collect())
+}
+
+/// 奇异类型，仅对腕部三轴交于一点的六轴机型判断
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SingularityKind {
+    /// 腕部中心落在 J1 轴线上
+    Shoulder,
+    /// 小臂伸直或完全折叠
+    Elbow,
+    /// J4 与 J6 共线
+    Wrist,
+}
+
+/// 奇异检测结果，奇异值按线速度 m、角速度 rad 计
+#[derive(Serialize, Clone, Debug)]
+pub struct Singularity {
+    pub singular_values: Vec<f64>,
+    /// 可操作度，即奇异值之积
+    pub manipulability: f64,
+    pub condition: f64,
+    pub near: bool,
+    pub kinds: Vec<SingularityKind>,
+}
+
+/// 奇异检测
+pub fn singularity(model: ArmModel, joints: &[f64]) -> Result<Singularity, AppError> {
+    check_joints(model, joints)?;
+    let links = links(model);
+    let mut jacobian = jacobian_matrix(links, joints);
+    jacobian.rows_mut(0, 3).scale_mut(0.001);
+
+    let mut singular_values: Vec<f64> = jacobian.singular_values().iter().copied().collect();
+    singular_values.sort_by(|a, b| b.total_cmp(a));
+    let max = singular_values.first().copied().unwrap_or(0.0);
+    let min = singular_values.last().copied().unwrap_or(0.0);
+    let kinds = ik::singular_kinds(links, joints);
+    Ok(Singularity {
+        manipulability: singular_values.iter().product(),
+        condition: if min > 0.0 { max / min } else { f64::INFINITY },
+        near: min < SINGULAR_THRESHOLD || !kinds.is_empty(),
+        singular_values,
+        kinds,
+    })
+}
+
+/// 姿态误差（基座坐标系下的旋转向量）
+///
+/// 小角度时 `acos` 精度不足，这里用 `atan2(sin, cos)` 求转角，接近 180° 时才退回 nalgebra 的实现。
+fn rotation_error(target: &Rotation3<f64>, current: &Rotation3<f64>) -> Vector3<f64> {
+    let delta = target * current.inverse();
+    let m = delta.matrix();
+    let cos = (m.trace() - 1.0) / 2.0;
+    if cos < 0.0 {
+        return delta.scaled_axis();
+    }
+    let sin_axis = Vector3::new(
+        m[(2, 1)] - m[(1, 2)],
+        m[(0, 2)] - m[(2, 0)],
+        m[(1, 0)] - m[(0, 1)],
+    ) / 2.0;
+    let sin = sin_axis.norm();
+    if sin < f64::EPSILON {
+        return sin_axis;
+    }
+    sin_axis * (sin.atan2(cos) / sin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: [ArmModel; 5] = [
+        ArmModel::Xarm5,
+        ArmModel::Xarm6,
+        ArmModel::Xarm7,
+        ArmModel::Lite6,
+        ArmModel::Xarm850,
+    ];
+
+    fn assert_pose(pose: Pose, expected: [f64; 6]) {
+        let actual = [pose.x, pose.y, pose.z, pose.roll, pose.pitch, pose.yaw];
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            // roll 在 ±180° 处可能取到任意一侧
+            let diff = if i == 3 {
+                (a - e + PI).rem_euclid(2.0 * PI) - PI
+            } else {
+                a - e
+            };
+            assert!(diff.abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    /// 控制器记录的一组关节角（`get_servo_angle`）与法兰位姿（`get_position`），单位 mm、°
+    #[derive(Deserialize)]
+    pub(super) struct RecordedPose {
+        pub model: ArmModel,
+        pub joints: Vec<f64>,
+        pub position: [f64; 6],
+    }
+
+    impl RecordedPose {
+        pub fn joints(&self) -> Vec<f64> {
+            self.joints.iter().map(|value| value.to_radians()).collect()
+        }
+
+        pub fn pose(&self) -> Pose {
+            let [x, y, z, roll, pitch, yaw] = self.position;
+            Pose {
+                x,
+                y,
+                z,
+                roll: roll.to_radians(),
+                pitch: pitch.to_radians(),
+                yaw: yaw.to_radians(),
+            }
+        }
+    }
+
+    /// 真机记录的位姿，见 `controller_poses.json`；新采集的数据直接追加到该文件
+    pub(super) fn recorded_poses() -> Vec<RecordedPose> {
+        serde_json::from_str(include_str!("controller_poses.json")).unwrap()
+    }
+
+    #[test]
+    fn matches_recorded_controller_poses() {
+        for recorded in recorded_poses() {
+            let pose = recorded.pose();
+            assert_pose(
+                forward(recorded.model, &recorded.joints()).unwrap(),
+                [pose.x, pose.y, pose.z, pose.roll, pose.pitch, pose.yaw],
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "controller_poses.json only has zero poses so far"]
+    fn records_non_zero_pose_for_every_model() {
+        let recorded = recorded_poses();
+        for model in MODELS {
+            assert!(
+                recorded
+                    .iter()
+                    .any(|r| r.model == model && r.joints.iter().any(|value| *value != 0.0)),
+                "no non-zero recording for {:?}",
+                model
+            );
+        }
+    }
+
+    /// 参考位姿：关节角与按连杆尺寸直接求出的法兰位姿
+    ///
+    /// 只转 J1、J2、J3、J5 时，J2、J3、J5 的轴线都平行于 J1 坐标系的 y 轴，
+    /// 法兰位置可由零位时各段（大臂、小臂、腕部到法兰）的 x/z 分量逐段旋转得到，不经过 MDH 链。
+    /// 关节角取 J2 + J3 + J5 在 ±90° 内，使 RPY 表示唯一。
+    pub(super) fn reference_poses() -> Vec<(ArmModel, [f64; 6], Pose)> {
+        // 肩部（J2）高度，及零位时大臂、小臂、腕部到法兰三段的 (x, z)
+        let xarm6 = (267.0, [(53.5, 284.5), (77.5, -342.5), (76.0, -97.0)]);
+        let xarm850 = (364.0, [(0.0, 390.0), (150.0, -426.0), (0.0, -90.0)]);
+        let mut poses = Vec::new();
+        for (model, (shoulder, segments)) in
+            [(ArmModel::Xarm6, xarm6), (ArmModel::Xarm850, xarm850)]
+        {
+            for joints in [
+                [0.4, 0.3, -0.6, 0.0, 0.5, 0.0],
+                [-1.0, -0.2, -0.9, 0.0, 0.6, 0.0],
+            ] {
+                let (mut x, mut z, mut pitch): (f64, f64, f64) = (0.0, shoulder, 0.0);
+                for ((dx, dz), angle) in segments.iter().zip([joints[1], joints[2], joints[4]]) {
+                    // 绕 y 轴旋转：x 轴转向 -z
+                    pitch += angle;
+                    x += dx * pitch.cos() + dz * pitch.sin();
+                    z += -dx * pitch.sin() + dz * pitch.cos();
+                }
+                let pose = Pose {
+                    x: x * joints[0].cos(),
+                    y: x * joints[0].sin(),
+                    z,
+                    roll: PI,
+                    pitch,
+                    yaw: joints[0],
+                };
+                poses.push((model, joints, pose));
+            }
+        }
+        poses
+    }
+
+    #[test]
+    fn non_zero_poses_match_link_geometry() {
+        for (model, joints, pose) in reference_poses() {
+            assert_pose(
+                forward(model, &joints).unwrap(),
+                [pose.x, pose.y, pose.z, pose.roll, pose.pitch, pose.yaw],
+            );
+        }
+    }
+
+    #[test]
+    fn joint_one_rotates_about_base() {
+        let mut joints = [0.0; 6];
+        joints[0] = FRAC_PI_2;
+        assert_pose(
+            forward(ArmModel::Xarm6, &joints).unwrap(),
+            [0.0, 207.0, 112.0, PI, 0.0, FRAC_PI_2],
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_axis_count() {
+        assert!(forward(ArmModel::Xarm6, &[0.0; 7]).is_err());
+        assert!(forward(ArmModel::Lite6, &[0.0, 0.0, f64::NAN, 0.0, 0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn jacobian_matches_finite_difference() {
+        let joints = [0.3, -0.4, -0.9, 0.5, 0.7, -0.2, 0.4];
+        let step = 1e-7;
+        for model in MODELS {
+            let joints = &joints[..model.axis()];
+            let links = links(model);
+            let jacobian = jacobian_matrix(links, joints);
+            let base = flange(links, joints);
+            for i in 0..joints.len() {
+                let mut moved = joints.to_vec();
+                moved[i] += step;
+                let moved = flange(links, &moved);
+                let linear = (moved.translation.vector - base.translation.vector) / step;
+                let angular = rotation_error(
+                    &moved.rotation.to_rotation_matrix(),
+                    &base.rotation.to_rotation_matrix(),
+                ) / step;
+                for row in 0..3 {
+                    assert!((jacobian[(row, i)] - linear[row]).abs() < 1e-3);
+                    assert!((jacobian[(row + 3, i)] - angular[row]).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn detects_singularities() {
+        // 全零时 J4 与 J6 共线
+        let zero = singularity(ArmModel::Xarm6, &[0.0; 6]).unwrap();
+        assert!(zero.near);
+        assert!(zero.kinds.contains(&SingularityKind::Wrist));
+
+        let normal = singularity(ArmModel::Xarm6, &[0.0, -0.3, -1.0, 0.0, 0.8, 0.0]).unwrap();
+        assert!(!normal.near, "{:?}", normal);
+        assert!(normal.manipulability > 0.0);
+    }
+}
@@ -0,0 +1,160 @@
+//! 姿态表示之间的转换
+//!
+//! RPY 与控制器一致：`R = Rz(yaw) · Ry(pitch) · Rx(roll)`（绕固定轴 X、Y、Z 依次旋转）。
+//! 轴角为旋转向量 `(rx, ry, rz)`，方向为转轴、长度为转角，与控制器的轴角接口相同。
+//! 四元数按 `w, x, y, z` 排列并取 `w >= 0`。角度均为 rad。
+
+use nalgebra::{Quaternion, Rotation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::error::AppError;
+
+/// 姿态
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Orientation {
+    Rpy { roll: f64, pitch: f64, yaw: f64 },
+    Quaternion { w: f64, x: f64, y: f64, z: f64 },
+    AxisAngle { rx: f64, ry: f64, rz: f64 },
+}
+
+/// 姿态表示方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrientationKind {
+    Rpy,
+    Quaternion,
+    AxisAngle,
+}
+
+impl Orientation {
+    /// 转为旋转矩阵，四元数会先归一化
+    pub fn to_rotation(self) -> Result<Rotation3<f64>, AppError> {
+        match self {
+            Orientation::Rpy { roll, pitch, yaw } => {
+                Ok(Rotation3::from_euler_angles(roll, pitch, yaw))
+            }
+            Orientation::Quaternion { w, x, y, z } => {
+                let quaternion = Quaternion::new(w, x, y, z);
+                let norm = quaternion.norm();
+                if !norm.is_finite() || norm <= f64::EPSILON {
+                    return Err(AppError::InvalidArgument(
+                        "quaternion must not be zero".to_string(),
+                    ));
+                }
+                Ok(UnitQuaternion::from_quaternion(quaternion).to_rotation_matrix())
+            }
+            Orientation::AxisAngle { rx, ry, rz } => Ok(Rotation3::new(Vector3::new(rx, ry, rz))),
+        }
+    }
+
+    pub fn from_rotation(rotation: &Rotation3<f64>, kind: OrientationKind) -> Self {
+        match kind {
+            OrientationKind::Rpy => {
+                let (roll, pitch, yaw) = rotation.euler_angles();
+                Orientation::Rpy { roll, pitch, yaw }
+            }
+            OrientationKind::Quaternion => {
+                let q = UnitQuaternion::from_rotation_matrix(rotation);
+                let sign = if q.w < 0.0 { -1.0 } else { 1.0 };
+                Orientation::Quaternion {
+                    w: q.w * sign,
+                    x: q.i * sign,
+                    y: q.j * sign,
+                    z: q.k * sign,
+                }
+            }
+            OrientationKind::AxisAngle => {
+                let vector = rotation.scaled_axis();
+                Orientation::AxisAngle {
+                    rx: vector.x,
+                    ry: vector.y,
+                    rz: vector.z,
+                }
+            }
+        }
+    }
+
+    /// 转换为另一种表示
+    pub fn convert(self, kind: OrientationKind) -> Result<Self, AppError> {
+        Ok(Self::from_rotation(&self.to_rotation()?, kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn tool_down_pose() {
+        // 末端朝下：roll = 180°
+        let rpy = Orientation::Rpy {
+            roll: PI,
+            pitch: 0.0,
+            yaw: 0.0,
+        };
+        let Orientation::Quaternion { w, x, y, z } =
+            rpy.convert(OrientationKind::Quaternion).unwrap()
+        else {
+            panic!("expected quaternion");
+        };
+        assert!(close(w, 0.0) && close(x.abs(), 1.0) && close(y, 0.0) && close(z, 0.0));
+
+        let Orientation::AxisAngle { rx, ry, rz } =
+            rpy.convert(OrientationKind::AxisAngle).unwrap()
+        else {
+            panic!("expected axis angle");
+        };
+        assert!(close(rx.abs(), PI) && close(ry, 0.0) && close(rz, 0.0));
+    }
+
+    #[test]
+    fn round_trips() {
+        let rpy = Orientation::Rpy {
+            roll: 0.3,
+            pitch: -0.7,
+            yaw: 2.1,
+        };
+        for kind in [OrientationKind::Quaternion, OrientationKind::AxisAngle] {
+            let back = rpy
+                .convert(kind)
+                .unwrap()
+                .convert(OrientationKind::Rpy)
+                .unwrap();
+            let Orientation::Rpy { roll, pitch, yaw } = back else {
+                panic!("expected rpy");
+            };
+            assert!(close(roll, 0.3) && close(pitch, -0.7) && close(yaw, 2.1));
+        }
+
+        // 绕 Z 轴 90°
+        let axis_angle = Orientation::AxisAngle {
+            rx: 0.0,
+            ry: 0.0,
+            rz: FRAC_PI_2,
+        };
+        let Orientation::Rpy { roll, pitch, yaw } =
+            axis_angle.convert(OrientationKind::Rpy).unwrap()
+        else {
+            panic!("expected rpy");
+        };
+        assert!(close(roll, 0.0) && close(pitch, 0.0) && close(yaw, FRAC_PI_2));
+    }
+
+    #[test]
+    fn rejects_zero_quaternion() {
+        let zero = Orientation::Quaternion {
+            w: 0.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        assert!(zero.to_rotation().is_err());
+    }
+}
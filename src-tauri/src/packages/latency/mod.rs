@@ -0,0 +1,438 @@
+//! 延迟统计
+//!
+//! 三类目标：
+//! - `ipc`：Rust 向主窗口发送 `latency_probe` 事件，前端收到后以同一 `rid` 调用 `latency_pong`，测得完整往返；
+//!   主窗口打开控制器网页时没有 Tauri IPC，此时暂停探测，不计丢失；
+//! - `arm:<arm_sn>`：会话心跳（见 `xarm::session`）的往返时间；
+//! - `tool_service`：本地服务 `/check` 接口的 HTTP 往返。
+//!
+//! 每个目标保留最近的样本，计算 p50/p95/p99、抖动（相邻样本差的平均值）和丢包率，
+//! 定期通过 `latency_stats` 事件推送。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use log::{error, info};
+use reqwest::Client;
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Emitter, Manager, Runtime, Url};
+use tokio::sync::watch;
+use tokio::time;
+
+/// 统计结果推送
+pub const EVENT_LATENCY_STATS: &str = "latency_stats";
+/// 请前端回应的 IPC 探测
+pub const EVENT_LATENCY_PROBE: &str = "latency_probe";
+
+pub const TARGET_IPC: &str = "ipc";
+/// 回应 IPC 探测的窗口
+const MAIN_WINDOW: &str = "main";
+pub const TARGET_TOOL_SERVICE: &str = "tool_service";
+
+/// 每个目标保留的样本数
+const WINDOW: usize = 200;
+/// 探测间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+/// 超过该时长未回应视为丢失
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 机械臂目标名
+pub fn arm_target(arm_sn: &str) -> String {
+    format!("arm:{}", arm_sn)
+}
+
+/// 单个目标的统计
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    pub target: String,
+    /// 窗口内样本数（含丢失）
+    pub samples: usize,
+    pub lost: usize,
+    /// 丢包率（0~1）
+    pub loss: f64,
+    pub last_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub jitter_ms: Option<f64>,
+    /// 最近一次样本时间（毫秒时间戳）
+    pub updated: i64,
+}
+
+/// 滚动窗口，`None` 表示丢失
+#[derive(Default)]
+struct Samples {
+    values: Vec<Option<f64>>,
+    next: usize,
+    updated: i64,
+}
+
+impl Samples {
+    fn push(&mut self, sample: Option<f64>) {
+        if self.values.len() < WINDOW {
+            self.values.push(sample);
+        } else {
+            self.values[self.next] = sample;
+        }
+        self.next = (self.next + 1) % WINDOW;
+        self.updated = Local::now().timestamp_millis();
+    }
+
+    /// 按时间顺序遍历
+    fn ordered(&self) -> impl Iterator<Item = &Option<f64>> {
+        let split = if self.values.len() < WINDOW {
+            0
+        } else {
+            self.next
+        };
+        self.values[split..].iter().chain(&self.values[..split])
+    }
+
+    fn stats(&self, target: &str) -> LatencyStats {
+        let received: Vec<f64> = self.ordered().flatten().copied().collect();
+        let lost = self.values.len() - received.len();
+        let jitter = (received.len() > 1).then(|| {
+            received
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .sum::<f64>()
+                / (received.len() - 1) as f64
+        });
+        let mut sorted = received.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        LatencyStats {
+            target: target.to_string(),
+            samples: self.values.len(),
+            lost,
+            loss: if self.values.is_empty() {
+                0.0
+            } else {
+                lost as f64 / self.values.len() as f64
+            },
+            last_ms: self.ordered().last().copied().flatten(),
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+            jitter_ms: jitter,
+            updated: self.updated,
+        }
+    }
+}
+
+/// 最近秩法取百分位
+fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// 延迟统计，各模块通过 `record` 写入样本
+#[derive(Clone, Default)]
+pub struct LatencyTracker {
+    targets: Arc<Mutex<HashMap<String, Samples>>>,
+    /// 等待前端回应的 IPC 探测
+    pending: Arc<Mutex<HashMap<u64, Instant>>>,
+    next_rid: Arc<AtomicU64>,
+    stop_tx: Arc<Mutex<Option<watch::Sender<bool>>>>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次往返，`None` 表示丢失
+    pub fn record(&self, target: &str, rtt: Option<Duration>) {
+        if let Ok(mut targets) = self.targets.lock() {
+            targets
+                .entry(target.to_string())
+                .or_default()
+                .push(rtt.map(|rtt| rtt.as_secs_f64() * 1000.0));
+        }
+    }
+
+    /// 移除目标（例如会话关闭）
+    pub fn remove(&self, target: &str) {
+        if let Ok(mut targets) = self.targets.lock() {
+            targets.remove(target);
+        }
+    }
+
+    /// 所有目标的统计，按名称排序
+    pub fn stats(&self) -> Vec<LatencyStats> {
+        let Ok(targets) = self.targets.lock() else {
+            return Vec::new();
+        };
+        let mut stats: Vec<LatencyStats> = targets
+            .iter()
+            .map(|(target, samples)| samples.stats(target))
+            .collect();
+        stats.sort_by(|a, b| a.target.cmp(&b.target));
+        stats
+    }
+
+    /// 前端回应 IPC 探测，返回 `rid` 是否属于探测
+    pub fn pong(&self, rid: u64) -> bool {
+        let sent = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&rid));
+        match sent {
+            Some(sent) => {
+                self.record(TARGET_IPC, Some(sent.elapsed()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 启动定时探测（已在运行时忽略），`tool_service_url` 见 `services`
+    pub fn start<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        client: Arc<Client>,
+        tool_service_url: String,
+    ) {
+        let Ok(mut stop_tx) = self.stop_tx.lock() else {
+            return;
+        };
+        if stop_tx.is_some() {
+            return;
+        }
+        let (tx, stop_rx) = watch::channel(false);
+        *stop_tx = Some(tx);
+        async_runtime::spawn(run_probes(
+            app.clone(),
+            self.clone(),
+            client,
+            tool_service_url,
+            stop_rx,
+        ));
+        info!("Latency monitor started");
+    }
+
+    pub fn stop(&self) {
+        if let Some(stop_tx) = self.stop_tx.lock().ok().and_then(|mut tx| tx.take()) {
+            let _ = stop_tx.send(true);
+        }
+    }
+
+    /// 发出一次 IPC 探测，主窗口不在应用页面时跳过
+    fn probe_ipc<R: Runtime>(&self, app: &AppHandle<R>) {
+        let serves_app = app
+            .get_webview_window(MAIN_WINDOW)
+            .and_then(|window| window.url().ok())
+            .is_some_and(|url| is_app_page(&url));
+        if !serves_app {
+            self.pause_probes();
+            return;
+        }
+        let Some(rid) = self.next_probe(Instant::now()) else {
+            return;
+        };
+        if let Err(e) = app.emit_to(MAIN_WINDOW, EVENT_LATENCY_PROBE, rid) {
+            error!("Failed to emit event: {}", e);
+        }
+    }
+
+    /// 丢弃等待中的探测，页面切走后它们无法回应，不记为丢失
+    fn pause_probes(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+    }
+
+    /// 登记新探测并返回其 `rid`，同时把超时未回应的探测记为丢失
+    fn next_probe(&self, now: Instant) -> Option<u64> {
+        let rid = self.next_rid.fetch_add(1, Ordering::Relaxed) + 1;
+        let expired = {
+            let mut pending = self.pending.lock().ok()?;
+            let before = pending.len();
+            pending.retain(|_, sent| now.duration_since(*sent) < PROBE_TIMEOUT);
+            let expired = before - pending.len();
+            pending.insert(rid, now);
+            expired
+        };
+        for _ in 0..expired {
+            self.record(TARGET_IPC, None);
+        }
+        Some(rid)
+    }
+}
+
+/// 应用自身的页面（打包后为 `tauri://localhost` 或 `http://tauri.localhost`，开发时为 devUrl）
+fn is_app_page(url: &Url) -> bool {
+    url.scheme() == "tauri" || matches!(url.host_str(), Some("localhost" | "tauri.localhost"))
+}
+
+async fn probe_tool_service(client: &Client, url: &str) -> Option<Duration> {
+    let started = Instant::now();
+    let response = client
+        .get(format!("{}/check", url))
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .ok()?;
+    response.status().is_success().then(|| started.elapsed())
+}
+
+async fn run_probes<R: Runtime>(
+    app: AppHandle<R>,
+    tracker: LatencyTracker,
+    client: Arc<Client>,
+    tool_service_url: String,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut ticker = time::interval(PROBE_INTERVAL);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = ticker.tick() => {}
+        }
+        tracker.probe_ipc(&app);
+        let rtt = tokio::select! {
+            _ = stop_rx.changed() => break,
+            rtt = probe_tool_service(&client, &tool_service_url) => rtt,
+        };
+        tracker.record(TARGET_TOOL_SERVICE, rtt);
+
+        if let Err(e) = app.emit(EVENT_LATENCY_STATS, tracker.stats()) {
+            error!("Failed to emit event: {}", e);
+        }
+    }
+    info!("Latency monitor stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(values: &[Option<f64>]) -> Samples {
+        let mut samples = Samples::default();
+        for value in values {
+            samples.push(*value);
+        }
+        samples
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        assert_eq!(percentile(&[], 0.5), None);
+        assert_eq!(percentile(&[7.0], 0.99), Some(7.0));
+
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 0.50), Some(50.0));
+        assert_eq!(percentile(&sorted, 0.95), Some(95.0));
+        assert_eq!(percentile(&sorted, 0.99), Some(99.0));
+        assert_eq!(percentile(&sorted, 0.0), Some(1.0));
+        assert_eq!(percentile(&sorted, 1.0), Some(100.0));
+    }
+
+    #[test]
+    fn stats_count_loss_and_jitter() {
+        let stats = samples(&[Some(10.0), None, Some(14.0), Some(12.0), None]).stats("ipc");
+        assert_eq!(stats.target, "ipc");
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.lost, 2);
+        assert_eq!(stats.loss, 0.4);
+        assert_eq!(stats.last_ms, None);
+        assert_eq!(stats.p50_ms, Some(12.0));
+        assert_eq!(stats.p99_ms, Some(14.0));
+        // 丢失的样本不参与抖动：|14-10| 与 |12-14| 的平均
+        assert_eq!(stats.jitter_ms, Some(3.0));
+
+        let single = samples(&[Some(5.0)]).stats("ipc");
+        assert_eq!(single.last_ms, Some(5.0));
+        assert_eq!(single.jitter_ms, None);
+
+        let empty = Samples::default().stats("ipc");
+        assert_eq!(empty.loss, 0.0);
+        assert_eq!(empty.p50_ms, None);
+    }
+
+    #[test]
+    fn window_keeps_latest_samples_in_order() {
+        let values: Vec<Option<f64>> = (0..WINDOW + 3).map(|i| Some(i as f64)).collect();
+        let samples = samples(&values);
+        assert_eq!(samples.values.len(), WINDOW);
+
+        let ordered: Vec<f64> = samples.ordered().flatten().copied().collect();
+        assert_eq!(ordered.first(), Some(&3.0));
+        assert_eq!(ordered.last(), Some(&((WINDOW + 2) as f64)));
+        assert!(ordered.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(samples.stats("ipc").last_ms, Some((WINDOW + 2) as f64));
+    }
+
+    #[test]
+    fn pong_matches_pending_probes() {
+        let tracker = LatencyTracker::new();
+        let start = Instant::now();
+        let rid = tracker.next_probe(start).unwrap();
+
+        // 不属于探测的 rid 不计入统计
+        assert!(!tracker.pong(rid + 100));
+        assert!(tracker.stats().is_empty());
+
+        assert!(tracker.pong(rid));
+        assert!(!tracker.pong(rid));
+        let stats = tracker.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].samples, stats[0].lost), (1, 0));
+
+        // 超时未回应的探测在下次探测时记为丢失
+        let stale = tracker.next_probe(start).unwrap();
+        let next = tracker.next_probe(start + PROBE_TIMEOUT).unwrap();
+        assert_ne!(stale, next);
+        assert!(!tracker.pong(stale));
+        assert_eq!(tracker.stats()[0].lost, 1);
+        assert!(tracker.pong(next));
+        assert_eq!(tracker.stats()[0].samples, 3);
+    }
+
+    #[test]
+    fn pauses_probes_off_the_app_page() {
+        for url in [
+            "tauri://localhost/app/home",
+            "http://tauri.localhost/app/studio?url=x",
+            "http://localhost:1430/",
+        ] {
+            assert!(is_app_page(&Url::parse(url).unwrap()), "{}", url);
+        }
+        for url in [
+            "http://192.168.1.100:18333/?channel=prod",
+            "http://127.0.0.1:18333/",
+        ] {
+            assert!(!is_app_page(&Url::parse(url).unwrap()), "{}", url);
+        }
+
+        // 切走页面时等待中的探测不计为丢失
+        let tracker = LatencyTracker::new();
+        let start = Instant::now();
+        let rid = tracker.next_probe(start).unwrap();
+        tracker.pause_probes();
+        assert!(!tracker.pong(rid));
+        tracker.next_probe(start + PROBE_TIMEOUT * 2).unwrap();
+        assert!(tracker.stats().is_empty());
+    }
+
+    #[test]
+    fn stats_are_sorted_by_target() {
+        let tracker = LatencyTracker::new();
+        tracker.record(TARGET_TOOL_SERVICE, Some(Duration::from_millis(3)));
+        tracker.record(&arm_target("XI1"), None);
+        tracker.record(TARGET_IPC, Some(Duration::from_millis(1)));
+        let targets: Vec<String> = tracker.stats().into_iter().map(|s| s.target).collect();
+        assert_eq!(targets, ["arm:XI1", "ipc", "tool_service"]);
+
+        tracker.remove("ipc");
+        assert_eq!(tracker.stats().len(), 2);
+    }
+}
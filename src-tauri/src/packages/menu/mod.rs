@@ -8,6 +8,9 @@ use tauri::{
 };
 use tauri_plugin_dialog::DialogExt;
 
+use crate::packages::xarm::report::ReportKind;
+use crate::state::app_state::AppState;
+
 /// 初始化菜单
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     PluginBuilder::new("app_menu")
@@ -147,6 +150,29 @@ pub fn event_handler<R: Runtime>(app: &AppHandle<R>, event: &MenuEvent) {
             "tool_check_updates" => {
                 window.emit("check_updates", id.to_string()).unwrap();
             }
+            // 录制所有已连接机械臂的实时上报
+            "tool_record_start" => {
+                let state = app.state::<AppState>();
+                let started = state.recorder.record_sessions(
+                    app,
+                    &state.reports,
+                    &state.sessions.list(),
+                    ReportKind::Realtime,
+                );
+                if started == 0 {
+                    app.dialog()
+                        .message(i18n::tr(
+                            "没有可录制的机械臂（未连接或已在录制中）",
+                            "No arm to record (not connected or already recording)",
+                        ))
+                        .title(i18n::tr("开始录制", "Start Recording"))
+                        .show(|_| {});
+                }
+            }
+            // 停止所有录制
+            "tool_record_stop" => {
+                app.state::<AppState>().recorder.stop_recordings();
+            }
             // 刷新页面
             "tool_refresh_page" => {
                 window.reload().unwrap();
@@ -1,4 +1,21 @@
 pub mod app_log;
+pub mod checker;
+#[cfg(feature = "controller-log-download")]
+pub mod controller_logs;
+pub mod discovery;
 pub mod env;
+pub mod firmware;
 pub mod keyboard;
+pub mod kinematics;
+pub mod latency;
 pub mod menu;
+pub mod modbus;
+pub mod parameters;
+pub mod product_config;
+pub mod recorder;
+pub mod registry;
+pub mod services;
+pub mod sidecar;
+pub mod simulator;
+pub mod trajectory;
+pub mod xarm;
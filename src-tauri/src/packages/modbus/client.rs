@@ -0,0 +1,397 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time;
+
+use super::protocol::{self, function, Header, HEADER_LEN};
+use crate::packages::xarm::client::CONNECT_TIMEOUT;
+use crate::utils::error::AppError;
+
+/// 默认应答超时
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Modbus-TCP 客户端
+///
+/// 与 `XArmClient` 一样按需连接，出错后关闭连接，下一次请求自动重连。
+pub struct ModbusClient {
+    addr: SocketAddr,
+    unit_id: u8,
+    stream: Option<TcpStream>,
+    next_tid: u16,
+    timeout: Duration,
+}
+
+impl ModbusClient {
+    pub fn new(addr: SocketAddr, unit_id: u8, timeout: Duration) -> Self {
+        Self {
+            addr,
+            unit_id,
+            stream: None,
+            next_tid: 1,
+            timeout,
+        }
+    }
+
+    pub async fn connect(&mut self) -> Result<(), AppError> {
+        if self.stream.is_some() {
+            return Ok(());
+        }
+        let connect_error = |message: String| AppError::Connect {
+            addr: self.addr.to_string(),
+            message,
+        };
+        let stream = time::timeout(CONNECT_TIMEOUT, TcpStream::connect(self.addr))
+            .await
+            .map_err(|_| connect_error("timed out".to_string()))?
+            .map_err(|e| connect_error(e.to_string()))?;
+        stream.set_nodelay(true)?;
+        info!("Connected to modbus device {}", self.addr);
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    pub async fn disconnect(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            let _ = stream.shutdown().await;
+            info!("Disconnected from modbus device {}", self.addr);
+        }
+    }
+
+    /// 发送 PDU，返回应答中功能码之后的数据
+    pub async fn request(&mut self, pdu: &[u8]) -> Result<Vec<u8>, AppError> {
+        self.connect().await?;
+        let tid = self.next_tid;
+        self.next_tid = self.next_tid.wrapping_add(1);
+
+        let result = match time::timeout(self.timeout, self.exchange(tid, pdu)).await {
+            Ok(result) => result,
+            Err(_) => Err(AppError::Timeout(format!(
+                "modbus function {} reply from {}",
+                pdu[0], self.addr
+            ))),
+        };
+        match result {
+            Ok(Ok(data)) => Ok(data),
+            // 异常应答说明连接本身正常，保留连接
+            Ok(Err(code)) => Err(AppError::Protocol(format!(
+                "modbus exception {} ({}) for function {}",
+                code,
+                protocol::exception_name(code),
+                pdu[0]
+            ))),
+            Err(e) => {
+                warn!("Modbus {} function {} failed: {}", self.addr, pdu[0], e);
+                self.stream = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// 交换一次报文，内层 `Err` 为设备返回的异常码
+    async fn exchange(&mut self, tid: u16, pdu: &[u8]) -> Result<Result<Vec<u8>, u8>, AppError> {
+        let addr = self.addr;
+        let unit_id = self.unit_id;
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| AppError::Disconnected(addr.to_string()))?;
+        stream
+            .write_all(&protocol::encode_frame(tid, unit_id, pdu))
+            .await?;
+
+        loop {
+            let mut header = [0u8; HEADER_LEN];
+            stream.read_exact(&mut header).await?;
+            let header = Header::parse(&header)?;
+            let mut body = vec![0u8; header.len];
+            stream.read_exact(&mut body).await?;
+            if header.tid != tid {
+                debug!(
+                    "Discard stale modbus reply {} from {} (waiting for {})",
+                    header.tid, addr, tid
+                );
+                continue;
+            }
+
+            // body: 单元 ID、功能码、数据
+            let function = body[1];
+            if function == pdu[0] | 0x80 {
+                return Ok(Err(body.get(2).copied().unwrap_or_default()));
+            }
+            if function != pdu[0] {
+                return Err(AppError::Protocol(format!(
+                    "expected modbus function {}, got {}",
+                    pdu[0], function
+                )));
+            }
+            return Ok(Ok(body[2..].to_vec()));
+        }
+    }
+
+    async fn read_bits(
+        &mut self,
+        function: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, AppError> {
+        check_count(count, protocol::MAX_READ_BITS)?;
+        let data = self
+            .request(&protocol::read_request(function, address, count))
+            .await?;
+        let bytes = byte_counted(&data, (count as usize).div_ceil(8))?;
+        Ok(protocol::unpack_bits(bytes, count as usize))
+    }
+
+    async fn read_registers(
+        &mut self,
+        function: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, AppError> {
+        check_count(count, protocol::MAX_READ_REGISTERS)?;
+        let data = self
+            .request(&protocol::read_request(function, address, count))
+            .await?;
+        let bytes = byte_counted(&data, count as usize * 2)?;
+        Ok(protocol::bytes_to_registers(bytes))
+    }
+
+    /// 功能码 1
+    pub async fn read_coils(&mut self, address: u16, count: u16) -> Result<Vec<bool>, AppError> {
+        self.read_bits(function::READ_COILS, address, count).await
+    }
+
+    /// 功能码 2
+    pub async fn read_discrete_inputs(
+        &mut self,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, AppError> {
+        self.read_bits(function::READ_DISCRETE_INPUTS, address, count)
+            .await
+    }
+
+    /// 功能码 3
+    pub async fn read_holding_registers(
+        &mut self,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, AppError> {
+        self.read_registers(function::READ_HOLDING_REGISTERS, address, count)
+            .await
+    }
+
+    /// 功能码 4
+    pub async fn read_input_registers(
+        &mut self,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, AppError> {
+        self.read_registers(function::READ_INPUT_REGISTERS, address, count)
+            .await
+    }
+
+    /// 功能码 5
+    pub async fn write_single_coil(&mut self, address: u16, value: bool) -> Result<(), AppError> {
+        let mut pdu = vec![function::WRITE_SINGLE_COIL];
+        pdu.extend_from_slice(&address.to_be_bytes());
+        pdu.extend_from_slice(&(if value { 0xFF00u16 } else { 0 }).to_be_bytes());
+        let data = self.request(&pdu).await?;
+        expect_echo(&data, &pdu[1..])
+    }
+
+    /// 功能码 6
+    pub async fn write_single_register(
+        &mut self,
+        address: u16,
+        value: u16,
+    ) -> Result<(), AppError> {
+        let mut pdu = vec![function::WRITE_SINGLE_REGISTER];
+        pdu.extend_from_slice(&address.to_be_bytes());
+        pdu.extend_from_slice(&value.to_be_bytes());
+        let data = self.request(&pdu).await?;
+        expect_echo(&data, &pdu[1..])
+    }
+
+    /// 功能码 15
+    pub async fn write_multiple_coils(
+        &mut self,
+        address: u16,
+        values: &[bool],
+    ) -> Result<(), AppError> {
+        let count = write_count(values.len(), protocol::MAX_WRITE_BITS)?;
+        let bytes = protocol::pack_bits(values);
+        let mut pdu = vec![function::WRITE_MULTIPLE_COILS];
+        pdu.extend_from_slice(&address.to_be_bytes());
+        pdu.extend_from_slice(&count.to_be_bytes());
+        pdu.push(bytes.len() as u8);
+        pdu.extend_from_slice(&bytes);
+        let data = self.request(&pdu).await?;
+        expect_echo(&data, &pdu[1..5])
+    }
+
+    /// 功能码 16
+    pub async fn write_multiple_registers(
+        &mut self,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), AppError> {
+        let count = write_count(values.len(), protocol::MAX_WRITE_REGISTERS)?;
+        let mut pdu = vec![function::WRITE_MULTIPLE_REGISTERS];
+        pdu.extend_from_slice(&address.to_be_bytes());
+        pdu.extend_from_slice(&count.to_be_bytes());
+        pdu.push((values.len() * 2) as u8);
+        pdu.extend_from_slice(&protocol::registers_to_bytes(values));
+        let data = self.request(&pdu).await?;
+        expect_echo(&data, &pdu[1..5])
+    }
+
+    /// 功能码 23：先写后读
+    pub async fn read_write_multiple_registers(
+        &mut self,
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, AppError> {
+        check_count(read_count, protocol::MAX_READ_REGISTERS)?;
+        let write_count = write_count(values.len(), protocol::MAX_READ_WRITE_REGISTERS)?;
+        let mut pdu = vec![function::READ_WRITE_MULTIPLE_REGISTERS];
+        pdu.extend_from_slice(&read_address.to_be_bytes());
+        pdu.extend_from_slice(&read_count.to_be_bytes());
+        pdu.extend_from_slice(&write_address.to_be_bytes());
+        pdu.extend_from_slice(&write_count.to_be_bytes());
+        pdu.push((values.len() * 2) as u8);
+        pdu.extend_from_slice(&protocol::registers_to_bytes(values));
+        let data = self.request(&pdu).await?;
+        let bytes = byte_counted(&data, read_count as usize * 2)?;
+        Ok(protocol::bytes_to_registers(bytes))
+    }
+}
+
+fn check_count(count: u16, max: u16) -> Result<(), AppError> {
+    if count == 0 || count > max {
+        return Err(AppError::InvalidArgument(format!(
+            "count must be 1..={}, got {}",
+            max, count
+        )));
+    }
+    Ok(())
+}
+
+fn write_count(len: usize, max: u16) -> Result<u16, AppError> {
+    let count = u16::try_from(len).unwrap_or(u16::MAX);
+    check_count(count, max)?;
+    Ok(count)
+}
+
+/// 读应答：`<字节数 u8><数据...>`
+fn byte_counted(data: &[u8], expected: usize) -> Result<&[u8], AppError> {
+    match data.split_first() {
+        Some((&len, bytes)) if len as usize == expected && bytes.len() >= expected => {
+            Ok(&bytes[..expected])
+        }
+        _ => Err(AppError::Protocol(format!(
+            "modbus reply has {} data bytes, expected {}",
+            data.len().saturating_sub(1),
+            expected
+        ))),
+    }
+}
+
+/// 写应答回显地址和数量（或值）
+fn expect_echo(data: &[u8], expected: &[u8]) -> Result<(), AppError> {
+    if data.get(..expected.len()) != Some(expected) {
+        return Err(AppError::Protocol(
+            "modbus write reply does not match request".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::modbus::mock;
+
+    #[tokio::test]
+    async fn reads_and_writes_bits() {
+        let (addr, memory, _stop) = mock::spawn().await;
+        memory.lock().unwrap().discrete_inputs[10] = true;
+        let mut client = ModbusClient::new(addr, 1, REPLY_TIMEOUT);
+
+        client.write_single_coil(3, true).await.unwrap();
+        client
+            .write_multiple_coils(
+                8,
+                &[true, false, true, true, false, false, false, false, true],
+            )
+            .await
+            .unwrap();
+        let coils = client.read_coils(0, 17).await.unwrap();
+        assert!(coils[3]);
+        assert_eq!(
+            &coils[8..17],
+            &[true, false, true, true, false, false, false, false, true]
+        );
+        let inputs = client.read_discrete_inputs(9, 3).await.unwrap();
+        assert_eq!(inputs, vec![false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_registers() {
+        let (addr, memory, _stop) = mock::spawn().await;
+        memory.lock().unwrap().input_registers[4] = 0xBEEF;
+        let mut client = ModbusClient::new(addr, 1, REPLY_TIMEOUT);
+
+        client.write_single_register(1, 42).await.unwrap();
+        client
+            .write_multiple_registers(2, &[7, 8, 9])
+            .await
+            .unwrap();
+        assert_eq!(
+            client.read_holding_registers(0, 5).await.unwrap(),
+            vec![0, 42, 7, 8, 9]
+        );
+        assert_eq!(
+            client.read_input_registers(4, 1).await.unwrap(),
+            vec![0xBEEF]
+        );
+        let read = client
+            .read_write_multiple_registers(1, 3, 3, &[100, 200])
+            .await
+            .unwrap();
+        assert_eq!(read, vec![42, 7, 100]);
+    }
+
+    #[tokio::test]
+    async fn surfaces_exceptions_and_keeps_connection() {
+        let (addr, _memory, _stop) = mock::spawn().await;
+        let mut client = ModbusClient::new(addr, 1, REPLY_TIMEOUT);
+
+        let error = client
+            .read_holding_registers(mock::SIZE as u16, 2)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("illegal data address"));
+        assert!(client.stream.is_some());
+        assert!(client.read_holding_registers(0, 1).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_counts() {
+        let (addr, _memory, _stop) = mock::spawn().await;
+        let mut client = ModbusClient::new(addr, 1, REPLY_TIMEOUT);
+        assert!(matches!(
+            client.read_holding_registers(0, 126).await,
+            Err(AppError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            client.write_multiple_registers(0, &[]).await,
+            Err(AppError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+//! 寄存器表（JSON）
+//!
+//! ```json
+//! {
+//!   "name": "gripper",
+//!   "host": "192.168.1.20",
+//!   "unit_id": 1,
+//!   "points": [
+//!     { "name": "position", "area": "holding", "address": 0, "type": "u16", "scale": 0.1, "unit": "mm" },
+//!     { "name": "force", "area": "input", "address": 10, "type": "f32", "word_order": "little" },
+//!     { "name": "vacuum_on", "area": "coil", "address": 3 }
+//!   ],
+//!   "groups": [{ "name": "status", "interval_ms": 100, "points": ["position", "vacuum_on"] }]
+//! }
+//! ```
+//!
+//! 工程值 = 原始值 × scale + offset；线圈和离散输入固定为 bool。
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{client, protocol};
+use crate::utils::error::AppError;
+
+/// 数据区
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Area {
+    Coil,
+    DiscreteInput,
+    Holding,
+    Input,
+}
+
+impl Area {
+    pub fn is_bit(self) -> bool {
+        matches!(self, Area::Coil | Area::DiscreteInput)
+    }
+
+    pub fn is_writable(self) -> bool {
+        matches!(self, Area::Coil | Area::Holding)
+    }
+
+    /// 单次读取的上限
+    pub fn max_read(self) -> u16 {
+        if self.is_bit() {
+            protocol::MAX_READ_BITS
+        } else {
+            protocol::MAX_READ_REGISTERS
+        }
+    }
+}
+
+/// 数据类型
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataType {
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+}
+
+impl DataType {
+    /// 占用的寄存器数
+    pub fn words(self) -> u16 {
+        match self {
+            DataType::Bool | DataType::U16 | DataType::I16 => 1,
+            DataType::U32 | DataType::I32 | DataType::F32 => 2,
+        }
+    }
+}
+
+/// 32 位数据的字序
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WordOrder {
+    /// 高字在前
+    #[default]
+    Big,
+    /// 低字在前
+    Little,
+}
+
+/// 点值
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum PointValue {
+    Bool(bool),
+    Number(f64),
+}
+
+impl PointValue {
+    /// 判断点值是否未变：数值按位比较，NaN 与同样的 NaN 视为相同
+    pub fn same(&self, other: &PointValue) -> bool {
+        match (self, other) {
+            (PointValue::Number(a), PointValue::Number(b)) => a.to_bits() == b.to_bits(),
+            _ => self == other,
+        }
+    }
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// 点定义
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Point {
+    pub name: String,
+    pub area: Area,
+    pub address: u16,
+    /// 缺省时线圈/离散输入为 bool，寄存器为 u16
+    #[serde(rename = "type", default)]
+    pub data_type: Option<DataType>,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default)]
+    pub word_order: WordOrder,
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// 缺省时线圈和保持寄存器可写
+    #[serde(default)]
+    pub writable: Option<bool>,
+}
+
+impl Point {
+    pub fn data_type(&self) -> DataType {
+        self.data_type.unwrap_or(if self.area.is_bit() {
+            DataType::Bool
+        } else {
+            DataType::U16
+        })
+    }
+
+    /// 占用的地址数（线圈为 1 位）
+    pub fn len(&self) -> u16 {
+        if self.area.is_bit() {
+            1
+        } else {
+            self.data_type().words()
+        }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.area.is_writable() && self.writable.unwrap_or(true)
+    }
+
+    /// 由寄存器原始值计算工程值
+    pub fn decode_registers(&self, words: &[u16]) -> PointValue {
+        let pair = || {
+            let (high, low) = match self.word_order {
+                WordOrder::Big => (words[0], words[1]),
+                WordOrder::Little => (words[1], words[0]),
+            };
+            ((high as u32) << 16) | low as u32
+        };
+        let raw = match self.data_type() {
+            DataType::Bool => return PointValue::Bool(words[0] != 0),
+            DataType::U16 => words[0] as f64,
+            DataType::I16 => words[0] as i16 as f64,
+            DataType::U32 => pair() as f64,
+            DataType::I32 => pair() as i32 as f64,
+            DataType::F32 => f32::from_bits(pair()) as f64,
+        };
+        PointValue::Number(raw * self.scale + self.offset)
+    }
+
+    /// 由工程值计算要写入的寄存器
+    pub fn encode_registers(&self, value: PointValue) -> Result<Vec<u16>, AppError> {
+        let number = match (self.data_type(), value) {
+            (DataType::Bool, PointValue::Bool(value)) => return Ok(vec![value as u16]),
+            (DataType::Bool, PointValue::Number(_)) | (_, PointValue::Bool(_)) => {
+                return Err(self.type_error(value));
+            }
+            (_, PointValue::Number(number)) => number,
+        };
+        let raw = (number - self.offset) / self.scale;
+        let integer = |min: f64, max: f64| {
+            let rounded = raw.round();
+            if !rounded.is_finite() || rounded < min || rounded > max {
+                Err(AppError::InvalidArgument(format!(
+                    "{}: {} is out of range for {:?}",
+                    self.name,
+                    number,
+                    self.data_type()
+                )))
+            } else {
+                Ok(rounded)
+            }
+        };
+        let split = |bits: u32| {
+            let (high, low) = ((bits >> 16) as u16, bits as u16);
+            match self.word_order {
+                WordOrder::Big => vec![high, low],
+                WordOrder::Little => vec![low, high],
+            }
+        };
+        Ok(match self.data_type() {
+            DataType::U16 => vec![integer(0.0, u16::MAX as f64)? as u16],
+            DataType::I16 => vec![integer(i16::MIN as f64, i16::MAX as f64)? as i16 as u16],
+            DataType::U32 => split(integer(0.0, u32::MAX as f64)? as u32),
+            DataType::I32 => split(integer(i32::MIN as f64, i32::MAX as f64)? as i32 as u32),
+            DataType::F32 => split((raw as f32).to_bits()),
+            DataType::Bool => return Err(self.type_error(value)),
+        })
+    }
+
+    fn type_error(&self, value: PointValue) -> AppError {
+        AppError::InvalidArgument(format!(
+            "{}: {:?} does not match type {:?}",
+            self.name,
+            value,
+            self.data_type()
+        ))
+    }
+}
+
+/// 轮询组
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PollGroup {
+    pub name: String,
+    pub interval_ms: u64,
+    pub points: Vec<String>,
+}
+
+fn default_port() -> u16 {
+    protocol::DEFAULT_PORT
+}
+
+fn default_unit_id() -> u8 {
+    1
+}
+
+fn default_timeout_ms() -> u64 {
+    client::REPLY_TIMEOUT.as_millis() as u64
+}
+
+/// 一台设备的寄存器表
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct RegisterMap {
+    /// 设备名，作为事件和指令中的设备标识
+    pub name: String,
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_unit_id")]
+    pub unit_id: u8,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    pub points: Vec<Point>,
+    #[serde(default)]
+    pub groups: Vec<PollGroup>,
+}
+
+/// 合并后的一次读取
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadSpan {
+    pub area: Area,
+    pub address: u16,
+    pub count: u16,
+}
+
+/// 相邻点之间允许合并读取的最大空隙
+const MAX_GAP: u32 = 8;
+
+impl RegisterMap {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text)
+            .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn socket_addr(&self) -> Result<SocketAddr, AppError> {
+        let ip = self
+            .host
+            .trim()
+            .parse()
+            .map_err(|_| AppError::InvalidArgument(format!("invalid host: {}", self.host)))?;
+        Ok(SocketAddr::new(ip, self.port))
+    }
+
+    pub fn point(&self, name: &str) -> Result<&Point, AppError> {
+        self.points
+            .iter()
+            .find(|point| point.name == name)
+            .ok_or_else(|| {
+                AppError::InvalidArgument(format!("{} has no point {}", self.name, name))
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        let invalid = |message: String| Err(AppError::InvalidArgument(message));
+        if self.name.trim().is_empty() {
+            return invalid("register map needs a name".to_string());
+        }
+        self.socket_addr()?;
+
+        let mut names = HashSet::new();
+        for point in &self.points {
+            if !names.insert(point.name.as_str()) {
+                return invalid(format!("duplicate point {}", point.name));
+            }
+            if point.area.is_bit() != (point.data_type() == DataType::Bool) {
+                return invalid(format!(
+                    "{}: {:?} cannot hold {:?}",
+                    point.name,
+                    point.area,
+                    point.data_type()
+                ));
+            }
+            if point.scale == 0.0 || !point.scale.is_finite() || !point.offset.is_finite() {
+                return invalid(format!("{}: invalid scale or offset", point.name));
+            }
+            if point.address as u32 + point.len() as u32 > 0x10000 {
+                return invalid(format!("{}: address out of range", point.name));
+            }
+        }
+
+        let mut groups = HashSet::new();
+        for group in &self.groups {
+            if !groups.insert(group.name.as_str()) {
+                return invalid(format!("duplicate group {}", group.name));
+            }
+            if group.interval_ms < 10 {
+                return invalid(format!("{}: interval must be at least 10 ms", group.name));
+            }
+            for name in &group.points {
+                self.point(name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 把一组点合并成尽量少的读取
+    pub fn plan_reads(&self, names: &[String]) -> Result<Vec<ReadSpan>, AppError> {
+        let mut points = names
+            .iter()
+            .map(|name| self.point(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        points.sort_by_key(|point| (point.area, point.address));
+
+        let mut spans: Vec<ReadSpan> = Vec::new();
+        for point in points {
+            let end = point.address as u32 + point.len() as u32;
+            if let Some(span) = spans.last_mut() {
+                let span_end = span.address as u32 + span.count as u32;
+                if span.area == point.area
+                    && point.address as u32 <= span_end + MAX_GAP
+                    && end - span.address as u32 <= point.area.max_read() as u32
+                {
+                    span.count = span.count.max((end - span.address as u32) as u16);
+                    continue;
+                }
+            }
+            spans.push(ReadSpan {
+                area: point.area,
+                address: point.address,
+                count: point.len(),
+            });
+        }
+        Ok(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> RegisterMap {
+        serde_json::from_value(serde_json::json!({
+            "name": "io",
+            "host": "127.0.0.1",
+            "points": [
+                { "name": "position", "area": "holding", "address": 0, "scale": 0.1, "offset": -5 },
+                { "name": "force", "area": "holding", "address": 4, "type": "f32", "word_order": "little" },
+                { "name": "count", "area": "holding", "address": 200, "type": "i32" },
+                { "name": "door", "area": "discrete_input", "address": 7 }
+            ],
+            "groups": [{ "name": "all", "interval_ms": 50, "points": ["position", "force", "count", "door"] }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn scales_values_both_ways() {
+        let map = map();
+        map.validate().unwrap();
+        let position = map.point("position").unwrap();
+        let words = position.encode_registers(PointValue::Number(7.3)).unwrap();
+        assert_eq!(words, vec![123]);
+        assert_eq!(
+            position.decode_registers(&words),
+            PointValue::Number(123.0 * 0.1 - 5.0)
+        );
+        assert!(position.encode_registers(PointValue::Number(-6.0)).is_err());
+        assert!(position.encode_registers(PointValue::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn honours_word_order() {
+        let map = map();
+        let force = map.point("force").unwrap();
+        let words = force.encode_registers(PointValue::Number(1.5)).unwrap();
+        assert_eq!(words, vec![0x0000, 0x3FC0]);
+        assert_eq!(force.decode_registers(&words), PointValue::Number(1.5));
+
+        let count = map.point("count").unwrap();
+        let words = count.encode_registers(PointValue::Number(-2.0)).unwrap();
+        assert_eq!(words, vec![0xFFFF, 0xFFFE]);
+        assert_eq!(count.decode_registers(&words), PointValue::Number(-2.0));
+    }
+
+    #[test]
+    fn merges_nearby_points() {
+        let map = map();
+        let names: Vec<String> = map.groups[0].points.clone();
+        assert_eq!(
+            map.plan_reads(&names).unwrap(),
+            vec![
+                ReadSpan {
+                    area: Area::DiscreteInput,
+                    address: 7,
+                    count: 1
+                },
+                ReadSpan {
+                    area: Area::Holding,
+                    address: 0,
+                    count: 6
+                },
+                ReadSpan {
+                    area: Area::Holding,
+                    address: 200,
+                    count: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_maps() {
+        let mut bad = map();
+        bad.points[3].data_type = Some(DataType::U16);
+        assert!(bad.validate().is_err());
+
+        let mut bad = map();
+        bad.groups[0].points.push("missing".to_string());
+        assert!(bad.validate().is_err());
+
+        let mut bad = map();
+        bad.points[1].name = "position".to_string();
+        assert!(bad.validate().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! 测试用的 Modbus-TCP 服务端
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+use super::protocol::{self, function, Header, HEADER_LEN};
+
+/// 每个数据区的地址数
+pub const SIZE: usize = 1000;
+
+pub struct Memory {
+    pub coils: Vec<bool>,
+    pub discrete_inputs: Vec<bool>,
+    pub holding_registers: Vec<u16>,
+    pub input_registers: Vec<u16>,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self {
+            coils: vec![false; SIZE],
+            discrete_inputs: vec![false; SIZE],
+            holding_registers: vec![0; SIZE],
+            input_registers: vec![0; SIZE],
+        }
+    }
+}
+
+/// 在随机端口上启动，返回地址、共享内存和停止信号
+pub async fn spawn() -> (SocketAddr, Arc<Mutex<Memory>>, watch::Sender<bool>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let memory = Arc::new(Mutex::new(Memory::default()));
+    let (stop_tx, mut stop_rx) = watch::channel(false);
+    let shared = Arc::clone(&memory);
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = stop_rx.changed() => break,
+                Ok((stream, _)) = listener.accept() => {
+                    tokio::spawn(serve(stream, Arc::clone(&shared)));
+                }
+            }
+        }
+    });
+    (addr, memory, stop_tx)
+}
+
+async fn serve(mut stream: TcpStream, memory: Arc<Mutex<Memory>>) {
+    loop {
+        let mut header = [0u8; HEADER_LEN];
+        if stream.read_exact(&mut header).await.is_err() {
+            return;
+        }
+        let Ok(parsed) = Header::parse(&header) else {
+            return;
+        };
+        let mut body = vec![0u8; parsed.len];
+        if stream.read_exact(&mut body).await.is_err() {
+            return;
+        }
+        let pdu = match execute(&mut memory.lock().unwrap(), &body[1..]) {
+            Ok(pdu) => pdu,
+            Err(code) => vec![body[1] | 0x80, code],
+        };
+        let frame = protocol::encode_frame(parsed.tid, body[0], &pdu);
+        if stream.write_all(&frame).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn word(pdu: &[u8], at: usize) -> usize {
+    u16::from_be_bytes([pdu[at], pdu[at + 1]]) as usize
+}
+
+fn range(address: usize, count: usize) -> Result<std::ops::Range<usize>, u8> {
+    if address + count > SIZE {
+        return Err(2);
+    }
+    Ok(address..address + count)
+}
+
+fn execute(memory: &mut Memory, pdu: &[u8]) -> Result<Vec<u8>, u8> {
+    let code = pdu[0];
+    let mut reply = vec![code];
+    match code {
+        function::READ_COILS | function::READ_DISCRETE_INPUTS => {
+            let bits = if code == function::READ_COILS {
+                &memory.coils
+            } else {
+                &memory.discrete_inputs
+            };
+            let bytes = protocol::pack_bits(&bits[range(word(pdu, 1), word(pdu, 3))?]);
+            reply.push(bytes.len() as u8);
+            reply.extend(bytes);
+        }
+        function::READ_HOLDING_REGISTERS | function::READ_INPUT_REGISTERS => {
+            let registers = if code == function::READ_HOLDING_REGISTERS {
+                &memory.holding_registers
+            } else {
+                &memory.input_registers
+            };
+            let bytes =
+                protocol::registers_to_bytes(&registers[range(word(pdu, 1), word(pdu, 3))?]);
+            reply.push(bytes.len() as u8);
+            reply.extend(bytes);
+        }
+        function::WRITE_SINGLE_COIL => {
+            let address = range(word(pdu, 1), 1)?.start;
+            memory.coils[address] = word(pdu, 3) == 0xFF00;
+            reply.extend_from_slice(&pdu[1..5]);
+        }
+        function::WRITE_SINGLE_REGISTER => {
+            let address = range(word(pdu, 1), 1)?.start;
+            memory.holding_registers[address] = word(pdu, 3) as u16;
+            reply.extend_from_slice(&pdu[1..5]);
+        }
+        function::WRITE_MULTIPLE_COILS => {
+            let range = range(word(pdu, 1), word(pdu, 3))?;
+            let bits = protocol::unpack_bits(&pdu[6..], range.len());
+            memory.coils[range].copy_from_slice(&bits);
+            reply.extend_from_slice(&pdu[1..5]);
+        }
+        function::WRITE_MULTIPLE_REGISTERS => {
+            let range = range(word(pdu, 1), word(pdu, 3))?;
+            let values = protocol::bytes_to_registers(&pdu[6..]);
+            memory.holding_registers[range].copy_from_slice(&values);
+            reply.extend_from_slice(&pdu[1..5]);
+        }
+        function::READ_WRITE_MULTIPLE_REGISTERS => {
+            let read = range(word(pdu, 1), word(pdu, 3))?;
+            let write = range(word(pdu, 5), word(pdu, 7))?;
+            let values = protocol::bytes_to_registers(&pdu[10..]);
+            memory.holding_registers[write].copy_from_slice(&values);
+            let bytes = protocol::registers_to_bytes(&memory.holding_registers[read]);
+            reply.push(bytes.len() as u8);
+            reply.extend(bytes);
+        }
+        _ => return Err(1),
+    }
+    Ok(reply)
+}
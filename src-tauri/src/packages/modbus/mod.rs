@@ -0,0 +1,616 @@
+//! Modbus-TCP 外设（夹爪、真空控制器、PLC IO 模块等）
+//!
+//! 每台设备由一份寄存器表（见 `map`）描述，按名称读写点位；
+//! 轮询组按各自周期读取，值变化时发送 `modbus_value_changed` 事件。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Emitter, Runtime};
+use tokio::sync::{watch, Mutex as AsyncMutex};
+use tokio::time;
+
+use crate::utils::error::AppError;
+
+pub mod client;
+pub mod map;
+#[cfg(test)]
+mod mock;
+pub mod protocol;
+
+use client::ModbusClient;
+use map::{Area, Point, PointValue, PollGroup, ReadSpan, RegisterMap};
+
+/// 点值变化
+pub const EVENT_MODBUS_VALUE_CHANGED: &str = "modbus_value_changed";
+/// 轮询失败（连续失败只发送一次）
+pub const EVENT_MODBUS_POLL_ERROR: &str = "modbus_poll_error";
+
+/// `modbus_value_changed` 事件内容
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ValueChange {
+    pub device: String,
+    pub group: String,
+    pub point: String,
+    pub value: PointValue,
+    /// 首次读取时为空
+    pub previous: Option<PointValue>,
+    pub timestamp: i64,
+}
+
+/// `modbus_poll_error` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct PollError {
+    pub device: String,
+    pub group: String,
+    pub message: String,
+}
+
+/// 设备信息和最近读取的值
+#[derive(Serialize, Clone, Debug)]
+pub struct DeviceInfo {
+    #[serde(flatten)]
+    pub map: RegisterMap,
+    pub values: HashMap<String, PointValue>,
+}
+
+type Values = Arc<Mutex<HashMap<String, PointValue>>>;
+
+/// 读写设备需要的共享部分
+#[derive(Clone)]
+struct Handle {
+    map: Arc<RegisterMap>,
+    client: Arc<AsyncMutex<ModbusClient>>,
+    values: Values,
+}
+
+struct Device {
+    handle: Handle,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// 按功能码直接访问设备
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum RawRequest {
+    ReadCoils {
+        address: u16,
+        count: u16,
+    },
+    ReadDiscreteInputs {
+        address: u16,
+        count: u16,
+    },
+    ReadHoldingRegisters {
+        address: u16,
+        count: u16,
+    },
+    ReadInputRegisters {
+        address: u16,
+        count: u16,
+    },
+    WriteSingleCoil {
+        address: u16,
+        value: bool,
+    },
+    WriteSingleRegister {
+        address: u16,
+        value: u16,
+    },
+    WriteMultipleCoils {
+        address: u16,
+        values: Vec<bool>,
+    },
+    WriteMultipleRegisters {
+        address: u16,
+        values: Vec<u16>,
+    },
+    ReadWriteMultipleRegisters {
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: Vec<u16>,
+    },
+}
+
+/// 直接访问的结果，写操作为 `null`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum RawReply {
+    Bits(Vec<bool>),
+    Registers(Vec<u16>),
+    Done,
+}
+
+/// 已添加的 Modbus 设备，按设备名索引
+#[derive(Clone, Default)]
+pub struct ModbusDevices {
+    devices: Arc<Mutex<HashMap<String, Device>>>,
+}
+
+impl ModbusDevices {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加设备并启动轮询组；同名设备会被替换，旧设备停止轮询并断开连接
+    pub async fn add<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        map: RegisterMap,
+    ) -> Result<DeviceInfo, AppError> {
+        map.validate()?;
+        let client = ModbusClient::new(
+            map.socket_addr()?,
+            map.unit_id,
+            Duration::from_millis(map.timeout_ms),
+        );
+        let map = Arc::new(map);
+        let client = Arc::new(AsyncMutex::new(client));
+        let values = Values::default();
+        let (stop_tx, stop_rx) = watch::channel(false);
+
+        for group in &map.groups {
+            async_runtime::spawn(run_group(
+                app.clone(),
+                Arc::clone(&map),
+                group.clone(),
+                Arc::clone(&client),
+                Arc::clone(&values),
+                stop_rx.clone(),
+            ));
+        }
+
+        let previous = self
+            .devices
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?
+            .insert(
+                map.name.clone(),
+                Device {
+                    handle: Handle {
+                        map: Arc::clone(&map),
+                        client,
+                        values,
+                    },
+                    stop_tx,
+                },
+            );
+        if let Some(previous) = previous {
+            let _ = previous.stop_tx.send(true);
+            previous.handle.client.lock().await.disconnect().await;
+        }
+        info!(
+            "Modbus device {} added ({} points, {} groups)",
+            map.name,
+            map.points.len(),
+            map.groups.len()
+        );
+        Ok(DeviceInfo {
+            map: RegisterMap::clone(&map),
+            values: HashMap::new(),
+        })
+    }
+
+    /// 移除设备并断开连接，返回之前是否存在
+    pub async fn remove(&self, name: &str) -> Result<bool, AppError> {
+        let device = self
+            .devices
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?
+            .remove(name);
+        match device {
+            Some(device) => {
+                let _ = device.stop_tx.send(true);
+                device.handle.client.lock().await.disconnect().await;
+                info!("Modbus device {} removed", name);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn list(&self) -> Vec<DeviceInfo> {
+        let Ok(devices) = self.devices.lock() else {
+            return Vec::new();
+        };
+        let mut list: Vec<DeviceInfo> = devices
+            .values()
+            .map(|device| DeviceInfo {
+                map: RegisterMap::clone(&device.handle.map),
+                values: device
+                    .handle
+                    .values
+                    .lock()
+                    .map(|values| values.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        list.sort_by(|a, b| a.map.name.cmp(&b.map.name));
+        list
+    }
+
+    pub async fn read_point(&self, device: &str, point: &str) -> Result<PointValue, AppError> {
+        let handle = self.handle(device)?;
+        let mut client = handle.client.lock().await;
+        let read = read_points(&mut client, &handle.map, &[point.to_string()]).await?;
+        let value = read[0].1;
+        if let Ok(mut values) = handle.values.lock() {
+            values.insert(point.to_string(), value);
+        }
+        Ok(value)
+    }
+
+    pub async fn write_point(
+        &self,
+        device: &str,
+        point: &str,
+        value: PointValue,
+    ) -> Result<(), AppError> {
+        let handle = self.handle(device)?;
+        let point = handle.map.point(point)?;
+        let mut client = handle.client.lock().await;
+        write_point(&mut client, point, value).await?;
+        info!("Modbus {}.{} set to {:?}", device, point.name, value);
+        Ok(())
+    }
+
+    /// 按功能码直接读写，用于调试寄存器表之外的地址
+    pub async fn raw(&self, device: &str, request: RawRequest) -> Result<RawReply, AppError> {
+        let handle = self.handle(device)?;
+        let mut client = handle.client.lock().await;
+        Ok(match request {
+            RawRequest::ReadCoils { address, count } => {
+                RawReply::Bits(client.read_coils(address, count).await?)
+            }
+            RawRequest::ReadDiscreteInputs { address, count } => {
+                RawReply::Bits(client.read_discrete_inputs(address, count).await?)
+            }
+            RawRequest::ReadHoldingRegisters { address, count } => {
+                RawReply::Registers(client.read_holding_registers(address, count).await?)
+            }
+            RawRequest::ReadInputRegisters { address, count } => {
+                RawReply::Registers(client.read_input_registers(address, count).await?)
+            }
+            RawRequest::WriteSingleCoil { address, value } => {
+                client.write_single_coil(address, value).await?;
+                RawReply::Done
+            }
+            RawRequest::WriteSingleRegister { address, value } => {
+                client.write_single_register(address, value).await?;
+                RawReply::Done
+            }
+            RawRequest::WriteMultipleCoils { address, values } => {
+                client.write_multiple_coils(address, &values).await?;
+                RawReply::Done
+            }
+            RawRequest::WriteMultipleRegisters { address, values } => {
+                client.write_multiple_registers(address, &values).await?;
+                RawReply::Done
+            }
+            RawRequest::ReadWriteMultipleRegisters {
+                read_address,
+                read_count,
+                write_address,
+                values,
+            } => RawReply::Registers(
+                client
+                    .read_write_multiple_registers(read_address, read_count, write_address, &values)
+                    .await?,
+            ),
+        })
+    }
+
+    /// 停止所有轮询（退出时调用）
+    pub fn stop_all(&self) {
+        if let Ok(mut devices) = self.devices.lock() {
+            for device in devices.values() {
+                let _ = device.stop_tx.send(true);
+            }
+            devices.clear();
+        }
+    }
+
+    fn handle(&self, name: &str) -> Result<Handle, AppError> {
+        let devices = self
+            .devices
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        let device = devices
+            .get(name)
+            .ok_or_else(|| AppError::InvalidArgument(format!("unknown modbus device {}", name)))?;
+        Ok(device.handle.clone())
+    }
+}
+
+/// 一次读取的原始数据
+enum Raw {
+    Bits(Vec<bool>),
+    Words(Vec<u16>),
+}
+
+async fn read_span(client: &mut ModbusClient, span: ReadSpan) -> Result<Raw, AppError> {
+    Ok(match span.area {
+        Area::Coil => Raw::Bits(client.read_coils(span.address, span.count).await?),
+        Area::DiscreteInput => Raw::Bits(
+            client
+                .read_discrete_inputs(span.address, span.count)
+                .await?,
+        ),
+        Area::Holding => Raw::Words(
+            client
+                .read_holding_registers(span.address, span.count)
+                .await?,
+        ),
+        Area::Input => Raw::Words(
+            client
+                .read_input_registers(span.address, span.count)
+                .await?,
+        ),
+    })
+}
+
+/// 按寄存器表读取一组点，合并相邻地址以减少请求
+pub async fn read_points(
+    client: &mut ModbusClient,
+    map: &RegisterMap,
+    names: &[String],
+) -> Result<Vec<(String, PointValue)>, AppError> {
+    let spans = map.plan_reads(names)?;
+    let mut raws = Vec::with_capacity(spans.len());
+    for span in &spans {
+        raws.push(read_span(client, *span).await?);
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            let point = map.point(name)?;
+            let (span, raw) = spans
+                .iter()
+                .zip(&raws)
+                .find(|(span, _)| {
+                    span.area == point.area
+                        && span.address <= point.address
+                        && point.address as u32 + point.len() as u32
+                            <= span.address as u32 + span.count as u32
+                })
+                .ok_or_else(|| AppError::Protocol(format!("{} was not read", name)))?;
+            let at = (point.address - span.address) as usize;
+            let value = match raw {
+                Raw::Bits(bits) => PointValue::Bool(bits[at]),
+                Raw::Words(words) => point.decode_registers(&words[at..at + point.len() as usize]),
+            };
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+/// 写入一个点：线圈用功能码 5，单寄存器用 6，多寄存器用 16
+pub async fn write_point(
+    client: &mut ModbusClient,
+    point: &Point,
+    value: PointValue,
+) -> Result<(), AppError> {
+    if !point.is_writable() {
+        return Err(AppError::InvalidArgument(format!(
+            "{} is read-only",
+            point.name
+        )));
+    }
+    match point.area {
+        Area::Coil => match value {
+            PointValue::Bool(value) => client.write_single_coil(point.address, value).await,
+            PointValue::Number(_) => Err(AppError::InvalidArgument(format!(
+                "{} expects a bool",
+                point.name
+            ))),
+        },
+        _ => match point.encode_registers(value)?.as_slice() {
+            [word] => client.write_single_register(point.address, *word).await,
+            words => client.write_multiple_registers(point.address, words).await,
+        },
+    }
+}
+
+/// 轮询一次，更新缓存并返回变化的点
+async fn poll_once(
+    client: &AsyncMutex<ModbusClient>,
+    map: &RegisterMap,
+    group: &str,
+    names: &[String],
+    values: &Mutex<HashMap<String, PointValue>>,
+) -> Result<Vec<ValueChange>, AppError> {
+    let read = read_points(&mut *client.lock().await, map, names).await?;
+    let timestamp = Local::now().timestamp_millis();
+    let mut values = values.lock().map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(read
+        .into_iter()
+        .filter_map(|(point, value)| {
+            let previous = values.insert(point.clone(), value);
+            (!previous.is_some_and(|previous| previous.same(&value))).then(|| ValueChange {
+                device: map.name.clone(),
+                group: group.to_string(),
+                point,
+                value,
+                previous,
+                timestamp,
+            })
+        })
+        .collect())
+}
+
+async fn run_group<R: Runtime>(
+    app: AppHandle<R>,
+    map: Arc<RegisterMap>,
+    group: PollGroup,
+    client: Arc<AsyncMutex<ModbusClient>>,
+    values: Values,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut ticker = time::interval(Duration::from_millis(group.interval_ms));
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    let mut failing = false;
+
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = ticker.tick() => {}
+        }
+        let result = tokio::select! {
+            _ = stop_rx.changed() => break,
+            result = poll_once(&client, &map, &group.name, &group.points, &values) => result,
+        };
+        match result {
+            Ok(changes) => {
+                failing = false;
+                for change in changes {
+                    if let Err(e) = app.emit(EVENT_MODBUS_VALUE_CHANGED, change) {
+                        error!("Failed to emit event: {}", e);
+                    }
+                }
+            }
+            Err(e) if !failing => {
+                failing = true;
+                warn!(
+                    "Modbus {} group {} poll failed: {}",
+                    map.name, group.name, e
+                );
+                let payload = PollError {
+                    device: map.name.clone(),
+                    group: group.name.clone(),
+                    message: e.to_string(),
+                };
+                if let Err(e) = app.emit(EVENT_MODBUS_POLL_ERROR, payload) {
+                    error!("Failed to emit event: {}", e);
+                }
+            }
+            Err(_) => {}
+        }
+    }
+    info!("Modbus {} group {} stopped", map.name, group.name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(addr: std::net::SocketAddr) -> RegisterMap {
+        serde_json::from_value(serde_json::json!({
+            "name": "gripper",
+            "host": addr.ip().to_string(),
+            "port": addr.port(),
+            "points": [
+                { "name": "position", "area": "holding", "address": 1, "scale": 0.1 },
+                { "name": "target", "area": "holding", "address": 10, "type": "f32" },
+                { "name": "grip", "area": "coil", "address": 0 },
+                { "name": "sensor", "area": "input", "address": 2, "type": "i16", "writable": false }
+            ],
+            "groups": [{ "name": "status", "interval_ms": 20, "points": ["position", "grip", "sensor"] }]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_named_points() {
+        let (addr, memory, _stop) = mock::spawn().await;
+        let map = map(addr);
+        let mut client = ModbusClient::new(addr, 1, client::REPLY_TIMEOUT);
+
+        write_point(
+            &mut client,
+            map.point("position").unwrap(),
+            PointValue::Number(12.5),
+        )
+        .await
+        .unwrap();
+        write_point(
+            &mut client,
+            map.point("target").unwrap(),
+            PointValue::Number(-0.25),
+        )
+        .await
+        .unwrap();
+        write_point(
+            &mut client,
+            map.point("grip").unwrap(),
+            PointValue::Bool(true),
+        )
+        .await
+        .unwrap();
+        assert!(write_point(
+            &mut client,
+            map.point("sensor").unwrap(),
+            PointValue::Number(1.0)
+        )
+        .await
+        .is_err());
+        memory.lock().unwrap().input_registers[2] = (-40i16) as u16;
+        assert_eq!(memory.lock().unwrap().holding_registers[1], 125);
+
+        let names: Vec<String> = ["target", "position", "grip", "sensor"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let read = read_points(&mut client, &map, &names).await.unwrap();
+        assert_eq!(
+            read,
+            vec![
+                ("target".to_string(), PointValue::Number(-0.25)),
+                ("position".to_string(), PointValue::Number(12.5)),
+                ("grip".to_string(), PointValue::Bool(true)),
+                ("sensor".to_string(), PointValue::Number(-40.0)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_only_changed_values() {
+        let (addr, memory, _stop) = mock::spawn().await;
+        let map = map(addr);
+        let client = AsyncMutex::new(ModbusClient::new(addr, 1, client::REPLY_TIMEOUT));
+        let values = Mutex::new(HashMap::new());
+        let names = map.groups[0].points.clone();
+
+        let first = poll_once(&client, &map, "status", &names, &values)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|change| change.previous.is_none()));
+
+        let second = poll_once(&client, &map, "status", &names, &values)
+            .await
+            .unwrap();
+        assert!(second.is_empty());
+
+        memory.lock().unwrap().coils[0] = true;
+        let third = poll_once(&client, &map, "status", &names, &values)
+            .await
+            .unwrap();
+        assert_eq!(third.len(), 1);
+        assert_eq!(third[0].point, "grip");
+        assert_eq!(third[0].previous, Some(PointValue::Bool(false)));
+        assert_eq!(third[0].value, PointValue::Bool(true));
+
+        // f32 NaN 每次读到的位模式相同，不算变化
+        let bits = f32::NAN.to_bits();
+        {
+            let mut memory = memory.lock().unwrap();
+            memory.holding_registers[10] = (bits >> 16) as u16;
+            memory.holding_registers[11] = bits as u16;
+        }
+        let names = vec!["target".to_string()];
+        let nan = poll_once(&client, &map, "status", &names, &values)
+            .await
+            .unwrap();
+        assert_eq!(nan.len(), 1);
+        assert!(poll_once(&client, &map, "status", &names, &values)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Modbus-TCP 报文
+//!
+//! 帧头（MBAP）：`<事务 ID u16><协议 u16 = 0><长度 u16><单元 ID u8>`，长度包含单元 ID 和 PDU。
+//! PDU：`<功能码 u8><数据...>`，异常应答的功能码最高位置 1，数据为 1 字节异常码。
+
+use crate::utils::error::AppError;
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 502;
+/// MBAP 帧头长度（不含单元 ID）
+pub const HEADER_LEN: usize = 6;
+/// PDU 最大长度
+pub const MAX_PDU_LEN: usize = 253;
+
+/// 功能码
+pub mod function {
+    pub const READ_COILS: u8 = 1;
+    pub const READ_DISCRETE_INPUTS: u8 = 2;
+    pub const READ_HOLDING_REGISTERS: u8 = 3;
+    pub const READ_INPUT_REGISTERS: u8 = 4;
+    pub const WRITE_SINGLE_COIL: u8 = 5;
+    pub const WRITE_SINGLE_REGISTER: u8 = 6;
+    pub const WRITE_MULTIPLE_COILS: u8 = 15;
+    pub const WRITE_MULTIPLE_REGISTERS: u8 = 16;
+    pub const READ_WRITE_MULTIPLE_REGISTERS: u8 = 23;
+}
+
+/// 单次读取的线圈/离散输入上限
+pub const MAX_READ_BITS: u16 = 2000;
+/// 单次读取的寄存器上限
+pub const MAX_READ_REGISTERS: u16 = 125;
+/// 单次写入的线圈上限
+pub const MAX_WRITE_BITS: u16 = 1968;
+/// 单次写入的寄存器上限
+pub const MAX_WRITE_REGISTERS: u16 = 123;
+/// 功能码 23 单次写入的寄存器上限
+pub const MAX_READ_WRITE_REGISTERS: u16 = 121;
+
+/// 异常码说明
+pub fn exception_name(code: u8) -> &'static str {
+    match code {
+        1 => "illegal function",
+        2 => "illegal data address",
+        3 => "illegal data value",
+        4 => "server device failure",
+        5 => "acknowledge",
+        6 => "server device busy",
+        8 => "memory parity error",
+        10 => "gateway path unavailable",
+        11 => "gateway target device failed to respond",
+        _ => "unknown exception",
+    }
+}
+
+/// 编码一帧（请求和应答格式相同）
+pub fn encode_frame(tid: u16, unit_id: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + 1 + pdu.len());
+    frame.extend_from_slice(&tid.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&((pdu.len() + 1) as u16).to_be_bytes());
+    frame.push(unit_id);
+    frame.extend_from_slice(pdu);
+    frame
+}
+
+/// MBAP 帧头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub tid: u16,
+    /// 单元 ID 和 PDU 的总长度
+    pub len: usize,
+}
+
+impl Header {
+    pub fn parse(bytes: &[u8; HEADER_LEN]) -> Result<Self, AppError> {
+        let protocol = u16::from_be_bytes([bytes[2], bytes[3]]);
+        if protocol != 0 {
+            return Err(AppError::Protocol(format!(
+                "unexpected modbus protocol id {}",
+                protocol
+            )));
+        }
+        let len = u16::from_be_bytes([bytes[4], bytes[5]]) as usize;
+        if !(2..=MAX_PDU_LEN + 1).contains(&len) {
+            return Err(AppError::Protocol(format!(
+                "invalid modbus frame length {}",
+                len
+            )));
+        }
+        Ok(Self {
+            tid: u16::from_be_bytes([bytes[0], bytes[1]]),
+            len,
+        })
+    }
+}
+
+/// 读请求：功能码 1~4
+pub fn read_request(function: u8, address: u16, count: u16) -> Vec<u8> {
+    let mut pdu = vec![function];
+    pdu.extend_from_slice(&address.to_be_bytes());
+    pdu.extend_from_slice(&count.to_be_bytes());
+    pdu
+}
+
+/// 线圈按低位在前打包
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, bit) in bits.iter().enumerate() {
+        if *bit {
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+    bytes
+}
+
+pub fn unpack_bits(bytes: &[u8], count: usize) -> Vec<bool> {
+    (0..count)
+        .map(|i| {
+            bytes
+                .get(i / 8)
+                .is_some_and(|byte| byte & (1 << (i % 8)) != 0)
+        })
+        .collect()
+}
+
+pub fn registers_to_bytes(registers: &[u16]) -> Vec<u8> {
+    registers.iter().flat_map(|r| r.to_be_bytes()).collect()
+}
+
+pub fn bytes_to_registers(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
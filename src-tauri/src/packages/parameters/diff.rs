@@ -0,0 +1,114 @@
+//! 参数比较
+//!
+//! 两份参数先序列化为 JSON，再按路径展开逐项比较，路径形如 `reduced.joint_ranges[2][1]`。
+//! 参数多为 f32，往返后存在舍入误差，数值按相对误差比较。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ArmParameters;
+
+/// 数值比较的相对误差
+const TOLERANCE: f64 = 1e-4;
+
+/// 一项差异，`before`/`after` 为空表示该项只在一侧存在
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ParameterChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// 比较两份参数，返回从 `before` 变为 `after` 需要修改的项
+pub fn diff(before: &ArmParameters, after: &ArmParameters) -> Vec<ParameterChange> {
+    let before = serde_json::to_value(before).unwrap_or(Value::Null);
+    let after = serde_json::to_value(after).unwrap_or(Value::Null);
+    let mut changes = Vec::new();
+    compare(String::new(), Some(&before), Some(&after), &mut changes);
+    changes
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn compare(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<ParameterChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                compare(child(&path, key), a.get(key), b.get(key), changes);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                compare(format!("{}[{}]", path, i), a.get(i), b.get(i), changes);
+            }
+        }
+        (Some(Value::Number(a)), Some(Value::Number(b))) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            if (a - b).abs() > TOLERANCE * a.abs().max(b.abs()).max(1.0) {
+                push(path, before, after, changes);
+            }
+        }
+        (a, b) if a != b => push(path, a, b, changes),
+        _ => {}
+    }
+}
+
+fn push(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<ParameterChange>,
+) {
+    changes.push(ParameterChange {
+        path,
+        before: before.cloned(),
+        after: after.cloned(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::tests::sample;
+    use super::*;
+
+    #[test]
+    fn reports_changed_paths() {
+        let before = sample();
+        let mut after = sample();
+        assert!(diff(&before, &after).is_empty());
+
+        // f32 舍入误差不算差异
+        after.tcp_offset[2] += 1e-6;
+        after.reduced.joint_ranges[1][0] = -1.5;
+        after.io.output_functions.push(3);
+        after.world_offset = Some([0.0, 0.0, 100.0, 0.0, 0.0, 0.0]);
+        let changes = diff(&before, &after);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "io.output_functions[16]",
+                "reduced.joint_ranges[1][0]",
+                "world_offset[2]"
+            ]
+        );
+        assert_eq!(changes[0].before, None);
+        assert_eq!(changes[0].after, Some(json!(3)));
+    }
+}
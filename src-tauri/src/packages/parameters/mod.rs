@@ -0,0 +1,736 @@
+//! 控制器参数备份、恢复与比较
+//!
+//! 备份内容：TCP 偏移、负载、碰撞/示教灵敏度、重力方向（读自详细上报 30002），
+//! 缩减模式限速和关节范围、安全边界（`GET_REDUCED_STATE`），控制器 IO 功能配置（`CGPIO_GET_STATE`），
+//! 以及用户坐标系（世界坐标偏移）。世界坐标偏移读自详细上报 288..312 字节，旧固件的上报没有这一段，
+//! 此时快照中为空并记入 `missing`；可以在快照文件中手动填写，恢复时写入。
+//!
+//! 快照为带格式版本号的 JSON 文件，保存在 `{app_data_dir}/parameters/<arm_sn>/<时间戳>.json`。
+//! 恢复前可预览差异；恢复时只写入有差异的项，最后保存配置并重新读取核对。
+
+pub mod diff;
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tokio::net::TcpStream;
+use tokio::time;
+
+use crate::packages::xarm::client::XArmClient;
+use crate::packages::xarm::protocol::{bytes_to_f32s, f32s_to_bytes, register};
+use crate::packages::xarm::report::{self, ReportKind};
+use crate::utils::error::AppError;
+use crate::utils::files::{self, plain_name};
+pub use diff::{diff, ParameterChange};
+
+/// 快照格式版本，格式不兼容地变化时递增
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 快照目录名
+const DIR_NAME: &str = "parameters";
+/// 快照文件名格式，按字符串排序即按时间排序
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// 等待详细上报首帧的超时
+const REPORT_TIMEOUT: Duration = Duration::from_secs(3);
+/// 运动中
+const STATE_MOVING: u8 = 1;
+
+/// 缩减模式与安全边界
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReducedConfig {
+    /// 缩减模式开关
+    pub enabled: bool,
+    /// 缩减模式 TCP 限速（mm/s）
+    pub tcp_speed: f32,
+    /// 缩减模式关节限速（rad/s）
+    pub joint_speed: f32,
+    /// 缩减模式关节范围（rad），每个关节 `[min, max]`
+    pub joint_ranges: [[f32; 2]; 7],
+    /// 安全边界 x+/x-/y+/y-/z+/z-（mm）
+    pub boundary: [i32; 6],
+    /// 安全边界开关
+    pub boundary_enabled: bool,
+    /// 碰撞回弹
+    pub collision_rebound: bool,
+}
+
+/// 控制器 IO 功能配置，按 IO 序号排列
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IoConfig {
+    pub input_functions: Vec<u8>,
+    pub output_functions: Vec<u8>,
+}
+
+/// 一台机械臂的参数
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ArmParameters {
+    /// TCP 偏移 x/y/z（mm）和 roll/pitch/yaw（rad）
+    pub tcp_offset: [f32; 6],
+    /// 负载：质量（kg）和质心 x/y/z（mm）
+    pub tcp_load: [f32; 4],
+    /// 碰撞灵敏度 0~5
+    pub collision_sensitivity: u8,
+    /// 示教灵敏度 1~5
+    pub teach_sensitivity: u8,
+    pub gravity_direction: [f32; 3],
+    pub reduced: ReducedConfig,
+    pub io: IoConfig,
+    /// 用户坐标系（世界坐标偏移，mm / rad），旧固件读不到时为空
+    #[serde(default)]
+    pub world_offset: Option<[f32; 6]>,
+}
+
+/// 快照文件内容
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub format_version: u32,
+    pub arm_sn: String,
+    pub device_type: String,
+    /// 备份时的固件版本
+    pub firmware_version: String,
+    /// 备份时间（RFC 3339）
+    pub created: String,
+    #[serde(default)]
+    pub note: String,
+    pub parameters: ArmParameters,
+    /// 备份时没能读到的项，非空表示快照不完整
+    #[serde(default)]
+    pub missing: Vec<String>,
+}
+
+/// 已保存的快照
+#[derive(Serialize, Clone, Debug)]
+pub struct SnapshotFile {
+    pub path: PathBuf,
+    pub arm_sn: String,
+    pub device_type: String,
+    pub firmware_version: String,
+    pub created: String,
+    pub note: String,
+    pub missing: Vec<String>,
+}
+
+impl SnapshotFile {
+    pub fn new(path: PathBuf, snapshot: Snapshot) -> Self {
+        Self {
+            path,
+            arm_sn: snapshot.arm_sn,
+            device_type: snapshot.device_type,
+            firmware_version: snapshot.firmware_version,
+            created: snapshot.created,
+            note: snapshot.note,
+            missing: snapshot.missing,
+        }
+    }
+}
+
+/// 恢复结果
+#[derive(Serialize, Clone, Debug)]
+pub struct RestoreReport {
+    /// 写入的项
+    pub applied: Vec<String>,
+    /// 恢复后重新读取仍不一致的项，正常应为空
+    pub remaining: Vec<ParameterChange>,
+}
+
+fn f32_array<const N: usize>(values: &[f32]) -> Result<[f32; N], AppError> {
+    values
+        .get(..N)
+        .and_then(|values| values.try_into().ok())
+        .ok_or_else(|| AppError::Protocol(format!("expected {} values, got {}", N, values.len())))
+}
+
+/// 从详细上报读取一帧
+async fn read_rich_report(addr: SocketAddr) -> Result<report::ReportStatus, AppError> {
+    let read = async {
+        let mut stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| AppError::Connect {
+                addr: addr.to_string(),
+                message: e.to_string(),
+            })?;
+        let frame = report::read_frame(&mut stream).await?;
+        report::decode(ReportKind::Rich, &frame)
+    };
+    let report = time::timeout(REPORT_TIMEOUT, read)
+        .await
+        .map_err(|_| AppError::Timeout(format!("report from {}", addr)))??;
+    report
+        .status
+        .ok_or_else(|| AppError::Protocol(format!("report from {} has no status", addr)))
+}
+
+async fn read_reduced(client: &mut XArmClient) -> Result<ReducedConfig, AppError> {
+    let data = client
+        .read_register(register::GET_REDUCED_STATE, &[])
+        .await?;
+    if data.len() < 79 {
+        return Err(AppError::Protocol(format!(
+            "reduced state has {} bytes, the firmware is too old",
+            data.len()
+        )));
+    }
+    let mut boundary = [0i32; 6];
+    for (value, bytes) in boundary.iter_mut().zip(data[1..13].chunks_exact(2)) {
+        *value = i16::from_be_bytes([bytes[0], bytes[1]]) as i32;
+    }
+    let speeds = bytes_to_f32s(&data[13..21]);
+    let ranges = bytes_to_f32s(&data[21..77]);
+    let mut joint_ranges = [[0.0; 2]; 7];
+    for (range, values) in joint_ranges.iter_mut().zip(ranges.chunks_exact(2)) {
+        *range = [values[0], values[1]];
+    }
+    Ok(ReducedConfig {
+        enabled: data[0] != 0,
+        tcp_speed: speeds[0],
+        joint_speed: speeds[1],
+        joint_ranges,
+        boundary,
+        boundary_enabled: data[77] != 0,
+        collision_rebound: data[78] != 0,
+    })
+}
+
+async fn read_io(client: &mut XArmClient) -> Result<IoConfig, AppError> {
+    let data = client.read_register(register::CGPIO_GET_STATE, &[]).await?;
+    if data.len() < 34 {
+        return Err(AppError::Protocol(format!(
+            "io state has {} bytes, expected at least 34",
+            data.len()
+        )));
+    }
+    let mut io = IoConfig {
+        input_functions: data[18..26].to_vec(),
+        output_functions: data[26..34].to_vec(),
+    };
+    // 新固件追加 8 路配置
+    if data.len() >= 50 {
+        io.input_functions.extend_from_slice(&data[34..42]);
+        io.output_functions.extend_from_slice(&data[42..50]);
+    }
+    Ok(io)
+}
+
+/// 读取机械臂当前参数，`report_addr` 为详细上报地址
+pub async fn read(
+    client: &mut XArmClient,
+    report_addr: SocketAddr,
+) -> Result<ArmParameters, AppError> {
+    let status = read_rich_report(report_addr).await?;
+    Ok(ArmParameters {
+        tcp_offset: f32_array(&status.tcp_offset)?,
+        tcp_load: f32_array(&status.tcp_load)?,
+        collision_sensitivity: status.collision_sens,
+        teach_sensitivity: status.teach_sens,
+        gravity_direction: f32_array(&status.gravity_direction)?,
+        reduced: read_reduced(client).await?,
+        io: read_io(client).await?,
+        world_offset: status.world_offset.as_deref().map(f32_array).transpose()?,
+    })
+}
+
+/// 写入与 `current` 不同的项，返回写入的项
+async fn apply(
+    client: &mut XArmClient,
+    current: &ArmParameters,
+    target: &ArmParameters,
+) -> Result<Vec<String>, AppError> {
+    let changes = diff(current, target);
+    let changed = |path: &str| {
+        changes.iter().any(|change| {
+            change.path == path
+                || change.path.starts_with(&format!("{}[", path))
+                || change.path.starts_with(&format!("{}.", path))
+        })
+    };
+    let mut applied = Vec::new();
+    let mut write = |name: &str| applied.push(name.to_string());
+
+    if changed("tcp_offset") {
+        client
+            .write_register(register::SET_TCP_OFFSET, &f32s_to_bytes(&target.tcp_offset))
+            .await?;
+        write("tcp_offset");
+    }
+    if changed("tcp_load") {
+        client
+            .write_register(register::SET_LOAD_PARAM, &f32s_to_bytes(&target.tcp_load))
+            .await?;
+        write("tcp_load");
+    }
+    if changed("collision_sensitivity") {
+        client
+            .write_register(register::SET_COLLIS_SENS, &[target.collision_sensitivity])
+            .await?;
+        write("collision_sensitivity");
+    }
+    if changed("teach_sensitivity") {
+        client
+            .write_register(register::SET_TEACH_SENS, &[target.teach_sensitivity])
+            .await?;
+        write("teach_sensitivity");
+    }
+    if changed("gravity_direction") {
+        client
+            .write_register(
+                register::SET_GRAVITY_DIR,
+                &f32s_to_bytes(&target.gravity_direction),
+            )
+            .await?;
+        write("gravity_direction");
+    }
+
+    let to = &target.reduced;
+    if changed("reduced.tcp_speed") {
+        client
+            .write_register(register::SET_REDUCED_TRSV, &f32s_to_bytes(&[to.tcp_speed]))
+            .await?;
+        write("reduced.tcp_speed");
+    }
+    if changed("reduced.joint_speed") {
+        client
+            .write_register(
+                register::SET_REDUCED_P2PV,
+                &f32s_to_bytes(&[to.joint_speed]),
+            )
+            .await?;
+        write("reduced.joint_speed");
+    }
+    if changed("reduced.joint_ranges") {
+        let ranges: Vec<f32> = to.joint_ranges.iter().flatten().copied().collect();
+        client
+            .write_register(register::SET_REDUCED_JRANGE, &f32s_to_bytes(&ranges))
+            .await?;
+        write("reduced.joint_ranges");
+    }
+    if changed("reduced.boundary") {
+        let bytes: Vec<u8> = to.boundary.iter().flat_map(|v| v.to_be_bytes()).collect();
+        client
+            .write_register(register::SET_LIMIT_XYZ, &bytes)
+            .await?;
+        write("reduced.boundary");
+    }
+    if changed("reduced.boundary_enabled") {
+        client
+            .write_register(register::SET_FENSE_ON, &[to.boundary_enabled as u8])
+            .await?;
+        write("reduced.boundary_enabled");
+    }
+    if changed("reduced.collision_rebound") {
+        client
+            .write_register(register::SET_COLLIS_REB, &[to.collision_rebound as u8])
+            .await?;
+        write("reduced.collision_rebound");
+    }
+    // 限值写完后再切换缩减模式
+    if changed("reduced.enabled") {
+        client
+            .write_register(register::SET_REDUCED_MODE, &[to.enabled as u8])
+            .await?;
+        write("reduced.enabled");
+    }
+
+    for (register, name, from, to) in [
+        (
+            register::CGPIO_SET_IN_FUN,
+            "io.input_functions",
+            &current.io.input_functions,
+            &target.io.input_functions,
+        ),
+        (
+            register::CGPIO_SET_OUT_FUN,
+            "io.output_functions",
+            &current.io.output_functions,
+            &target.io.output_functions,
+        ),
+    ] {
+        // 只写控制器实际具备的 IO
+        for (io, (from, to)) in from.iter().zip(to).enumerate() {
+            if from != to {
+                client.write_register(register, &[io as u8, *to]).await?;
+                write(&format!("{}[{}]", name, io));
+            }
+        }
+    }
+
+    if let Some(offset) = target
+        .world_offset
+        .as_ref()
+        .filter(|_| changed("world_offset"))
+    {
+        client
+            .write_register(register::SET_WORLD_OFFSET, &f32s_to_bytes(offset))
+            .await?;
+        write("world_offset");
+    }
+    Ok(applied)
+}
+
+/// 读取当前参数生成快照
+pub async fn snapshot(
+    client: &mut XArmClient,
+    report_addr: SocketAddr,
+    arm_sn: &str,
+    device_type: &str,
+    note: String,
+) -> Result<Snapshot, AppError> {
+    let parameters = read(client, report_addr).await?;
+    let mut missing = Vec::new();
+    if parameters.world_offset.is_none() {
+        missing.push("world_offset".to_string());
+    }
+    Ok(Snapshot {
+        format_version: FORMAT_VERSION,
+        arm_sn: plain_name(arm_sn, "arm_sn")?.to_string(),
+        device_type: device_type.trim().to_string(),
+        firmware_version: client.get_version().await?,
+        created: Local::now().to_rfc3339(),
+        note,
+        parameters,
+        missing,
+    })
+}
+
+/// 比较时忽略只在一侧存在的项：旧固件读不到世界坐标偏移，控制器没有的 IO 不会写入
+fn comparable(change: &ParameterChange) -> bool {
+    let absent =
+        |value: &Option<serde_json::Value>| matches!(value, None | Some(serde_json::Value::Null));
+    let one_sided = absent(&change.before) || absent(&change.after);
+    !(one_sided && (change.path == "world_offset" || change.path.starts_with("io.")))
+}
+
+/// 恢复前预览：从当前参数变为快照参数需要修改的项
+pub fn preview(current: &ArmParameters, snapshot: &Snapshot) -> Vec<ParameterChange> {
+    let mut changes: Vec<ParameterChange> = diff(current, &snapshot.parameters)
+        .into_iter()
+        .filter(comparable)
+        .collect();
+    // 读不到当前值时仍会写入快照中的世界坐标偏移
+    if let (None, Some(offset)) = (&current.world_offset, &snapshot.parameters.world_offset) {
+        changes.push(ParameterChange {
+            path: "world_offset".to_string(),
+            before: None,
+            after: serde_json::to_value(offset).ok(),
+        });
+    }
+    changes
+}
+
+/// 检查快照能否恢复到该机械臂
+pub fn check_target(snapshot: &Snapshot, device_type: &str) -> Result<(), AppError> {
+    if snapshot.device_type.trim() != device_type.trim() {
+        return Err(AppError::InvalidArgument(format!(
+            "snapshot of {} is for device_type {}, the arm is {}",
+            snapshot.arm_sn, snapshot.device_type, device_type
+        )));
+    }
+    Ok(())
+}
+
+/// 把快照恢复到机械臂，运动中拒绝恢复
+pub async fn restore(
+    client: &mut XArmClient,
+    report_addr: SocketAddr,
+    snapshot: &Snapshot,
+) -> Result<RestoreReport, AppError> {
+    if client.get_state().await? == STATE_MOVING {
+        return Err(AppError::InvalidArgument(
+            "the arm is moving, stop it before restoring parameters".to_string(),
+        ));
+    }
+    let current = read(client, report_addr).await?;
+    let applied = apply(client, &current, &snapshot.parameters).await?;
+    if !applied.is_empty() {
+        client.write_register(register::SAVE_CONF, &[]).await?;
+    }
+    info!(
+        "Restored {} parameters from snapshot of {}",
+        applied.len(),
+        snapshot.arm_sn
+    );
+
+    // 上报周期内可能还是旧值，稍等后核对
+    time::sleep(Duration::from_millis(300)).await;
+    let after = read(client, report_addr).await?;
+    let remaining: Vec<ParameterChange> = diff(&after, &snapshot.parameters)
+        .into_iter()
+        .filter(comparable)
+        .collect();
+    if !remaining.is_empty() {
+        warn!(
+            "{} parameters still differ after restoring {}",
+            remaining.len(),
+            snapshot.arm_sn
+        );
+    }
+    Ok(RestoreReport { applied, remaining })
+}
+
+/// 快照根目录，不存在时创建
+pub fn snapshots_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    files::data_dir(app, DIR_NAME)
+}
+
+/// 保存快照，返回文件路径
+pub fn save(root: &Path, snapshot: &Snapshot) -> Result<PathBuf, AppError> {
+    let dir = root.join(plain_name(&snapshot.arm_sn, "arm_sn")?);
+    let text = serde_json::to_string_pretty(snapshot).map_err(|e| AppError::Io(e.to_string()))?;
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let (path, mut file) = files::create_new_file(&dir, &timestamp, ".json")?;
+    file.write_all(text.as_bytes())?;
+    Ok(path)
+}
+
+/// 读取快照文件
+pub fn load(path: &Path) -> Result<Snapshot, AppError> {
+    let text =
+        fs::read_to_string(path).map_err(|e| AppError::Io(format!("{}: {}", path.display(), e)))?;
+    let value: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))?;
+    let version = value
+        .get("format_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    if version == 0 || version > FORMAT_VERSION as u64 {
+        return Err(AppError::InvalidArgument(format!(
+            "{} has unsupported format version {}",
+            path.display(),
+            version
+        )));
+    }
+    serde_json::from_value(value)
+        .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))
+}
+
+/// 列出快照，最新的在前；`arm_sn` 为空时列出所有机械臂；无法解析的文件跳过
+pub fn list(root: &Path, arm_sn: Option<&str>) -> Result<Vec<SnapshotFile>, AppError> {
+    let dirs = match arm_sn {
+        Some(arm_sn) => vec![root.join(plain_name(arm_sn, "arm_sn")?)],
+        None => fs::read_dir(root)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+    };
+    let mut files = Vec::new();
+    for dir in dirs {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            match load(&path) {
+                Ok(snapshot) => files.push(SnapshotFile::new(path, snapshot)),
+                Err(e) => warn!("Skip parameter snapshot: {}", e),
+            }
+        }
+    }
+    files.sort_by(|a, b| b.created.cmp(&a.created));
+    Ok(files)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::packages::simulator::stand_in::{
+        sim_stand_in_with_report, still_config, SimStandIn,
+    };
+
+    /// 与虚拟机械臂默认值一致的参数
+    pub fn sample() -> ArmParameters {
+        let range = [-2.0 * std::f32::consts::PI, 2.0 * std::f32::consts::PI];
+        ArmParameters {
+            tcp_offset: [0.0; 6],
+            tcp_load: [0.0; 4],
+            collision_sensitivity: 3,
+            teach_sensitivity: 3,
+            gravity_direction: [0.0, 0.0, -1.0],
+            reduced: ReducedConfig {
+                enabled: false,
+                tcp_speed: 250.0,
+                joint_speed: std::f32::consts::PI / 3.0,
+                joint_ranges: [range; 7],
+                boundary: [999, -999, 999, -999, 999, -999],
+                boundary_enabled: false,
+                collision_rebound: true,
+            },
+            io: IoConfig {
+                input_functions: vec![0; 16],
+                output_functions: vec![0; 16],
+            },
+            world_offset: Some([0.0; 6]),
+        }
+    }
+
+    fn sample_snapshot(arm_sn: &str, created: &str) -> Snapshot {
+        Snapshot {
+            format_version: FORMAT_VERSION,
+            arm_sn: arm_sn.to_string(),
+            device_type: "6".to_string(),
+            firmware_version: "v2.5.0".to_string(),
+            created: created.to_string(),
+            note: String::new(),
+            parameters: sample(),
+            missing: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saves_and_lists_snapshots() {
+        let root = std::env::temp_dir().join(format!("parameters-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let older = sample_snapshot("XI1303", "2026-01-01T08:00:00+08:00");
+        let newer = sample_snapshot("XI1303", "2026-02-01T08:00:00+08:00");
+        let first = save(&root, &older).unwrap();
+        let second = save(&root, &newer).unwrap();
+        assert_ne!(first, second);
+        save(
+            &root,
+            &sample_snapshot("XI1304", "2026-03-01T08:00:00+08:00"),
+        )
+        .unwrap();
+        assert_eq!(load(&second).unwrap(), newer);
+
+        let files = list(&root, Some("XI1303")).unwrap();
+        let created: Vec<&str> = files.iter().map(|f| f.created.as_str()).collect();
+        assert_eq!(created, [newer.created.as_str(), older.created.as_str()]);
+        assert_eq!(list(&root, None).unwrap().len(), 3);
+        assert!(list(&root, Some("XI1305")).unwrap().is_empty());
+        assert!(save(&root, &sample_snapshot("../x", "")).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_format_version() {
+        let path = std::env::temp_dir().join(format!("parameters-v2-{}.json", std::process::id()));
+        let mut value = serde_json::to_value(sample_snapshot("XI1303", "")).unwrap();
+        value["format_version"] = serde_json::json!(FORMAT_VERSION + 1);
+        fs::write(&path, value.to_string()).unwrap();
+        let result = load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(AppError::InvalidArgument(message)) if message.contains("format version")
+        ));
+    }
+
+    /// 运行虚拟控制器的指令端口和详细上报端口，`kind` 为上报端口发送的帧格式
+    async fn controller(kind: ReportKind) -> (SimStandIn, XArmClient, SocketAddr) {
+        let stand_in = sim_stand_in_with_report(still_config(), kind).await;
+        let client = XArmClient::new(stand_in.command_addr);
+        let report_addr = stand_in.report_addr.unwrap();
+        (stand_in, client, report_addr)
+    }
+
+    #[tokio::test]
+    async fn backs_up_and_restores() {
+        let (stand_in, mut client, report_addr) = controller(ReportKind::Rich).await;
+        let backup = snapshot(&mut client, report_addr, "XI1303", "6", String::new())
+            .await
+            .unwrap();
+        assert!(diff(&backup.parameters, &sample()).is_empty());
+        assert!(backup.missing.is_empty());
+
+        // 修改后恢复
+        let mut modified = sample();
+        modified.tcp_offset[2] = 120.0;
+        modified.tcp_load = [1.5, 0.0, 0.0, 40.0];
+        modified.reduced.enabled = true;
+        modified.reduced.joint_ranges[0] = [-1.0, 1.0];
+        modified.reduced.boundary[0] = 500;
+        modified.io.output_functions[9] = 11;
+        modified.world_offset = Some([0.0, 0.0, 50.0, 0.0, 0.0, 0.0]);
+        let target = Snapshot {
+            parameters: modified,
+            ..backup.clone()
+        };
+        let current = read(&mut client, report_addr).await.unwrap();
+        let paths: Vec<String> = preview(&current, &target)
+            .into_iter()
+            .map(|change| change.path)
+            .collect();
+        assert_eq!(paths.len(), 9);
+        assert!(paths.contains(&"io.output_functions[9]".to_string()));
+
+        let report = restore(&mut client, report_addr, &target).await.unwrap();
+        assert!(report.remaining.is_empty(), "{:?}", report.remaining);
+        assert_eq!(
+            report.applied,
+            [
+                "tcp_offset",
+                "tcp_load",
+                "reduced.joint_ranges",
+                "reduced.boundary",
+                "reduced.enabled",
+                "io.output_functions[9]",
+                "world_offset"
+            ]
+        );
+        {
+            let arm = stand_in.arm.lock().unwrap();
+            assert_eq!(arm.tcp_offset[2], 120.0);
+            assert_eq!(arm.cgpio_outputs[9], 11);
+            assert_eq!(arm.world_offset[2], 50.0);
+        }
+
+        // 再恢复原备份
+        let report = restore(&mut client, report_addr, &backup).await.unwrap();
+        assert!(report.remaining.is_empty());
+        assert_eq!(report.applied.len(), 7);
+        assert!(diff(&read(&mut client, report_addr).await.unwrap(), &sample()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn marks_world_offset_missing_on_old_firmware() {
+        // 旧固件的详细上报与普通上报等长，没有世界坐标偏移
+        let (stand_in, mut client, report_addr) = controller(ReportKind::Normal).await;
+        let backup = snapshot(&mut client, report_addr, "XI1303", "6", String::new())
+            .await
+            .unwrap();
+        assert_eq!(backup.parameters.world_offset, None);
+        assert_eq!(backup.missing, ["world_offset"]);
+        assert_eq!(
+            SnapshotFile::new(PathBuf::new(), backup.clone()).missing,
+            ["world_offset"]
+        );
+
+        // 读不到当前值时照样写入快照中的偏移，核对时不算差异
+        let target = sample_snapshot("XI1303", "");
+        let current = read(&mut client, report_addr).await.unwrap();
+        let changes = preview(&current, &target);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "world_offset");
+        assert_eq!(changes[0].before, None);
+        stand_in.arm.lock().unwrap().world_offset[2] = 50.0;
+        let report = restore(&mut client, report_addr, &target).await.unwrap();
+        assert_eq!(report.applied, ["world_offset"]);
+        assert!(report.remaining.is_empty());
+        assert_eq!(stand_in.arm.lock().unwrap().world_offset, [0.0; 6]);
+    }
+
+    #[tokio::test]
+    async fn refuses_restore_while_moving() {
+        let (stand_in, mut client, report_addr) = controller(ReportKind::Rich).await;
+        let snapshot = sample_snapshot("XI1303", "");
+        stand_in.arm.lock().unwrap().state = STATE_MOVING;
+        assert!(matches!(
+            restore(&mut client, report_addr, &snapshot).await,
+            Err(AppError::InvalidArgument(_))
+        ));
+        assert!(check_target(&snapshot, "9").is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! 产品配置 `releases/uf_product_config.ini`
+//!
+//! 不同产品（xArm、Lite6、850）的安装包附带不同的配置文件，前端据此调整界面：
+//!
+//! ```ini
+//! [product]
+//! model = lite6
+//! name = UFACTORY Lite 6
+//! version = 1.0.0
+//!
+//! [features]
+//! gripper = true
+//! linear_track = false
+//! ```
+//!
+//! 键不区分大小写，`;` 或 `#` 开头的行为注释。其余节和键原样保留在 `sections` 中。
+//! 文件不存在时使用默认配置（xArm）。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::warn;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::utils::error::AppError;
+
+/// 配置变化，负载为新的 `ProductConfig`
+pub const EVENT_PRODUCT_CONFIG_CHANGED: &str = "product_config_changed";
+/// 相对资源目录的配置文件路径
+pub const CONFIG_FILE: &str = "releases/uf_product_config.ini";
+
+/// 产品型号
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ProductModel {
+    #[default]
+    #[serde(rename = "xarm")]
+    XArm,
+    #[serde(rename = "lite6")]
+    Lite6,
+    #[serde(rename = "850")]
+    Uf850,
+}
+
+impl ProductModel {
+    fn parse(text: &str) -> Option<Self> {
+        let text: String = text
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match text.as_str() {
+            "xarm" => Some(ProductModel::XArm),
+            "lite6" | "lite" => Some(ProductModel::Lite6),
+            "850" | "uf850" | "ufactory850" => Some(ProductModel::Uf850),
+            _ => None,
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            ProductModel::XArm => "xArm",
+            ProductModel::Lite6 => "Lite 6",
+            ProductModel::Uf850 => "UFACTORY 850",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Default)]
+pub struct ProductConfig {
+    pub model: ProductModel,
+    /// 显示名称，未配置时按型号生成
+    pub name: String,
+    pub version: Option<String>,
+    /// 功能开关
+    pub features: BTreeMap<String, bool>,
+    /// 全部键值，按节分组，节名和键名为小写
+    pub sections: BTreeMap<String, BTreeMap<String, String>>,
+    /// 读取的文件，使用默认配置时为空
+    pub path: Option<PathBuf>,
+}
+
+impl ProductConfig {
+    fn for_model(model: ProductModel) -> Self {
+        Self {
+            model,
+            name: model.display_name().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 没有配置文件时的默认配置
+    pub fn default_config() -> Self {
+        Self::for_model(ProductModel::default())
+    }
+
+    /// 解析配置文件内容
+    pub fn parse(text: &str) -> Result<Self, AppError> {
+        let sections = parse_ini(text)?;
+        let product = sections.get("product");
+        let value = |key: &str| {
+            product
+                .and_then(|section| section.get(key))
+                .filter(|value| !value.is_empty())
+        };
+        let model = match value("model") {
+            Some(model) => ProductModel::parse(model).ok_or_else(|| {
+                AppError::InvalidArgument(format!("unknown product model {:?}", model))
+            })?,
+            None => ProductModel::default(),
+        };
+        let mut config = Self::for_model(model);
+        if let Some(name) = value("name") {
+            config.name = name.clone();
+        }
+        config.version = value("version").cloned();
+        if let Some(features) = sections.get("features") {
+            for (key, value) in features {
+                let enabled = parse_bool(value).ok_or_else(|| {
+                    AppError::InvalidArgument(format!("features.{} is not a boolean", key))
+                })?;
+                config.features.insert(key.clone(), enabled);
+            }
+        }
+        config.sections = sections;
+        Ok(config)
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 解析 INI，节之前的键归入空节名
+fn parse_ini(text: &str) -> Result<BTreeMap<String, BTreeMap<String, String>>, AppError> {
+    let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut section = String::new();
+    for (number, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.strip_suffix(']').ok_or_else(|| {
+                AppError::InvalidArgument(format!("line {}: unclosed section", number + 1))
+            })?;
+            section = name.trim().to_ascii_lowercase();
+            sections.entry(section.clone()).or_default();
+            continue;
+        }
+        let (key, value) = line.split_once(['=', ':']).ok_or_else(|| {
+            AppError::InvalidArgument(format!("line {}: expected key = value", number + 1))
+        })?;
+        let value = value.trim().trim_matches('"');
+        sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key.trim().to_ascii_lowercase(), value.to_string());
+    }
+    Ok(sections)
+}
+
+/// 读取配置文件，不存在时返回默认配置
+pub fn load(path: &Path) -> Result<ProductConfig, AppError> {
+    match fs::read(path) {
+        Ok(content) => parse_file(path, &content),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ProductConfig::default_config()),
+        Err(e) => Err(AppError::Io(format!("{}: {}", path.display(), e))),
+    }
+}
+
+fn parse_file(path: &Path, content: &[u8]) -> Result<ProductConfig, AppError> {
+    let text = std::str::from_utf8(content)
+        .map_err(|_| AppError::InvalidArgument(format!("{} is not valid UTF-8", path.display())))?;
+    let mut config = ProductConfig::parse(text)
+        .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))?;
+    config.path = Some(path.to_path_buf());
+    Ok(config)
+}
+
+/// 按内容哈希检测配置文件变化
+#[derive(Default)]
+pub struct ChangeDetector {
+    /// 上次读取的内容哈希，文件不存在为 `Some(None)`，尚未读取为 `None`
+    last: Option<Option<Vec<u8>>>,
+}
+
+impl ChangeDetector {
+    /// 内容变化且解析成功时返回新配置；解析失败时记录日志，保留旧配置
+    pub fn poll(&mut self, path: &Path) -> Option<ProductConfig> {
+        let content = match fs::read(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!("Failed to read {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let hash = content
+            .as_ref()
+            .map(|content| Sha256::digest(content).to_vec());
+        if self.last.as_ref() == Some(&hash) {
+            return None;
+        }
+        self.last = Some(hash);
+        match content {
+            Some(content) => match parse_file(path, &content) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    warn!("Ignore invalid product config: {}", e);
+                    None
+                }
+            },
+            None => Some(ProductConfig::default_config()),
+        }
+    }
+}
+
+/// 当前产品配置和监控线程的停止信号
+#[derive(Clone, Default)]
+pub struct ProductConfigs {
+    current: Arc<Mutex<Option<ProductConfig>>>,
+    monitor: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+}
+
+impl ProductConfigs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最近一次读取的配置，监控线程尚未读取时为空
+    pub fn get(&self) -> Option<ProductConfig> {
+        self.current.lock().ok().and_then(|current| current.clone())
+    }
+
+    pub fn set(&self, config: ProductConfig) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = Some(config);
+        }
+    }
+
+    /// 记录监控线程的运行标志，退出时清除
+    pub fn set_monitor(&self, running: Arc<AtomicBool>) {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            if let Some(previous) = monitor.replace(running) {
+                previous.store(false, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn stop_monitor(&self) {
+        if let Some(running) = self.monitor.lock().ok().and_then(|mut m| m.take()) {
+            running.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LITE6: &str = "\u{feff}; Lite6 安装包
+[Product]
+Model = Lite 6
+version = 1.0.0
+
+[features]
+gripper = true
+linear_track = off
+
+[ui]
+home = /app/lite6
+";
+
+    #[test]
+    fn parses_product_config() {
+        let config = ProductConfig::parse(LITE6).unwrap();
+        assert_eq!(config.model, ProductModel::Lite6);
+        assert_eq!(config.name, "Lite 6");
+        assert_eq!(config.version.as_deref(), Some("1.0.0"));
+        assert_eq!(config.features.get("gripper"), Some(&true));
+        assert_eq!(config.features.get("linear_track"), Some(&false));
+        assert_eq!(config.sections["ui"]["home"], "/app/lite6");
+        assert_eq!(
+            serde_json::to_value(config.model).unwrap(),
+            serde_json::json!("lite6")
+        );
+
+        let config = ProductConfig::parse("[product]\nmodel = UF850\nname = \"850 Pro\"").unwrap();
+        assert_eq!(config.model, ProductModel::Uf850);
+        assert_eq!(config.name, "850 Pro");
+        assert_eq!(
+            ProductConfig::parse("").unwrap(),
+            ProductConfig::default_config()
+        );
+
+        assert!(ProductConfig::parse("[product]\nmodel = xarm7pro").is_err());
+        assert!(ProductConfig::parse("[features]\ngripper = maybe").is_err());
+        assert!(ProductConfig::parse("[product\nmodel = xarm").is_err());
+    }
+
+    #[test]
+    fn detects_changes_by_content() {
+        let path =
+            std::env::temp_dir().join(format!("uf_product_config-{}.ini", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut detector = ChangeDetector::default();
+
+        // 首次读取总是返回配置
+        assert_eq!(detector.poll(&path), Some(ProductConfig::default_config()));
+        assert_eq!(detector.poll(&path), None);
+
+        fs::write(&path, LITE6).unwrap();
+        let config = detector.poll(&path).unwrap();
+        assert_eq!(config.model, ProductModel::Lite6);
+        assert_eq!(config.path.as_deref(), Some(path.as_path()));
+        // 内容不变（即使重新写入）不算变化
+        fs::write(&path, LITE6).unwrap();
+        assert_eq!(detector.poll(&path), None);
+
+        // 解析失败时保留旧配置
+        fs::write(&path, "[product]\nmodel = unknown").unwrap();
+        assert_eq!(detector.poll(&path), None);
+        fs::write(&path, "[product]\nmodel = 850").unwrap();
+        assert_eq!(detector.poll(&path).unwrap().model, ProductModel::Uf850);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(detector.poll(&path), Some(ProductConfig::default_config()));
+    }
+}
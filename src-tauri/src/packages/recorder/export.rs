@@ -0,0 +1,324 @@
+//! 录制导出
+//!
+//! - CSV：每帧一行，缺失的附加数据留空；
+//! - Arrow IPC 文件（`.arrow`）：列式存储，pandas / polars 可直接读取或转存为 Parquet。
+//!
+//! 列由上报类型决定：公共部分为时间、状态、模式、缓存指令数、关节角、TCP 位姿和关节力矩；
+//! 常规/详细上报追加错误码、警告码等，实时上报追加关节速度和电流。
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, Float32Array, RecordBatch, TimestampMillisecondArray, UInt16Array, UInt8Array,
+};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use serde::{Deserialize, Serialize};
+
+use super::format::RecordingReader;
+use crate::packages::xarm::report::{ArmReport, ReportKind};
+use crate::utils::error::AppError;
+
+/// Arrow 每批行数
+const BATCH_ROWS: usize = 4096;
+const AXES: usize = 7;
+const POSE: [&str; 6] = ["x", "y", "z", "roll", "pitch", "yaw"];
+
+/// 状态列取值函数
+type StatusField = fn(&ArmReport) -> Option<u8>;
+
+/// 导出格式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Arrow,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ColumnType {
+    Timestamp,
+    UInt8,
+    UInt16,
+    Float32,
+}
+
+type Getter = Box<dyn Fn(&ArmReport) -> Option<f64>>;
+
+struct Column {
+    name: String,
+    ty: ColumnType,
+    value: Getter,
+}
+
+fn column(name: impl Into<String>, ty: ColumnType, value: Getter) -> Column {
+    Column {
+        name: name.into(),
+        ty,
+        value,
+    }
+}
+
+/// 数组中第 `i` 个值
+fn nth(values: &[f32], i: usize) -> Option<f64> {
+    values.get(i).map(|v| *v as f64)
+}
+
+fn columns(kind: ReportKind) -> Vec<Column> {
+    use ColumnType::*;
+
+    let mut columns = vec![
+        column(
+            "timestamp",
+            Timestamp,
+            Box::new(|r| Some(r.timestamp as f64)),
+        ),
+        column("state", UInt8, Box::new(|r| Some(r.state as f64))),
+        column("mode", UInt8, Box::new(|r| Some(r.mode as f64))),
+        column("cmd_num", UInt16, Box::new(|r| Some(r.cmd_num as f64))),
+    ];
+    for i in 0..AXES {
+        columns.push(column(
+            format!("j{}", i + 1),
+            Float32,
+            Box::new(move |r| nth(&r.joints, i)),
+        ));
+    }
+    for (i, name) in POSE.iter().enumerate() {
+        columns.push(column(*name, Float32, Box::new(move |r| nth(&r.pose, i))));
+    }
+    for i in 0..AXES {
+        columns.push(column(
+            format!("torque{}", i + 1),
+            Float32,
+            Box::new(move |r| nth(&r.torques, i)),
+        ));
+    }
+
+    match kind {
+        ReportKind::Normal | ReportKind::Rich => {
+            let status: [(&str, StatusField); 6] = [
+                ("brake", |r| r.status.as_ref().map(|s| s.brake)),
+                ("enabled", |r| r.status.as_ref().map(|s| s.enabled)),
+                ("error_code", |r| r.status.as_ref().map(|s| s.error_code)),
+                ("warn_code", |r| r.status.as_ref().map(|s| s.warn_code)),
+                ("collision_sens", |r| {
+                    r.status.as_ref().map(|s| s.collision_sens)
+                }),
+                ("teach_sens", |r| r.status.as_ref().map(|s| s.teach_sens)),
+            ];
+            for (name, get) in status {
+                columns.push(column(
+                    name,
+                    UInt8,
+                    Box::new(move |r| get(r).map(f64::from)),
+                ));
+            }
+        }
+        ReportKind::Realtime => {
+            for i in 0..AXES {
+                columns.push(column(
+                    format!("speed{}", i + 1),
+                    Float32,
+                    Box::new(move |r| r.dynamics.as_ref().and_then(|d| nth(&d.speeds, i))),
+                ));
+            }
+            for i in 0..AXES {
+                columns.push(column(
+                    format!("current{}", i + 1),
+                    Float32,
+                    Box::new(move |r| r.dynamics.as_ref().and_then(|d| nth(&d.currents, i))),
+                ));
+            }
+        }
+    }
+    columns
+}
+
+/// 导出录制文件，返回导出的帧数
+pub fn export(source: &Path, target: &Path, format: ExportFormat) -> Result<u64, AppError> {
+    let reader = RecordingReader::open(source)?;
+    let output = BufWriter::new(File::create(target)?);
+    match format {
+        ExportFormat::Csv => write_csv(reader, output),
+        ExportFormat::Arrow => write_arrow(reader, output),
+    }
+}
+
+fn write_csv<R: Read, W: Write>(
+    mut reader: RecordingReader<R>,
+    output: W,
+) -> Result<u64, AppError> {
+    let columns = columns(reader.meta().kind);
+    let mut writer = csv::Writer::from_writer(output);
+    let csv_error = |e: csv::Error| AppError::Io(e.to_string());
+
+    writer
+        .write_record(columns.iter().map(|c| c.name.as_str()))
+        .map_err(csv_error)?;
+    let mut frames = 0;
+    while let Some(report) = reader.next_frame()? {
+        let row = columns.iter().map(|c| match (c.value)(&report) {
+            // 浮点列按 f32 输出，避免出现 0.10000000149 这样的尾数
+            Some(value) => match c.ty {
+                ColumnType::Float32 => (value as f32).to_string(),
+                _ => (value as i64).to_string(),
+            },
+            None => String::new(),
+        });
+        writer.write_record(row).map_err(csv_error)?;
+        frames += 1;
+    }
+    writer.flush()?;
+    Ok(frames)
+}
+
+fn write_arrow<R: Read, W: Write>(
+    mut reader: RecordingReader<R>,
+    output: W,
+) -> Result<u64, AppError> {
+    let columns = columns(reader.meta().kind);
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|c| Field::new(&c.name, data_type(c.ty), true))
+            .collect::<Vec<_>>(),
+    ));
+    let arrow_error = |e: arrow_schema::ArrowError| AppError::Io(e.to_string());
+    let mut writer = FileWriter::try_new(output, &schema).map_err(arrow_error)?;
+
+    let mut buffers: Vec<Vec<Option<f64>>> = vec![Vec::with_capacity(BATCH_ROWS); columns.len()];
+    let mut frames = 0;
+    loop {
+        let report = reader.next_frame()?;
+        if let Some(report) = &report {
+            for (buffer, column) in buffers.iter_mut().zip(&columns) {
+                buffer.push((column.value)(report));
+            }
+            frames += 1;
+        }
+        let rows = buffers.first().map_or(0, Vec::len);
+        if rows > 0 && (rows >= BATCH_ROWS || report.is_none()) {
+            let arrays = columns
+                .iter()
+                .zip(buffers.iter_mut())
+                .map(|(column, buffer)| to_array(column.ty, std::mem::take(buffer)))
+                .collect();
+            let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(arrow_error)?;
+            writer.write(&batch).map_err(arrow_error)?;
+        }
+        if report.is_none() {
+            break;
+        }
+    }
+    writer.finish().map_err(arrow_error)?;
+    Ok(frames)
+}
+
+fn data_type(ty: ColumnType) -> DataType {
+    match ty {
+        ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Millisecond, None),
+        ColumnType::UInt8 => DataType::UInt8,
+        ColumnType::UInt16 => DataType::UInt16,
+        ColumnType::Float32 => DataType::Float32,
+    }
+}
+
+fn to_array(ty: ColumnType, values: Vec<Option<f64>>) -> ArrayRef {
+    let values = values.into_iter();
+    match ty {
+        ColumnType::Timestamp => Arc::new(
+            values
+                .map(|v| v.map(|v| v as i64))
+                .collect::<TimestampMillisecondArray>(),
+        ),
+        ColumnType::UInt8 => Arc::new(values.map(|v| v.map(|v| v as u8)).collect::<UInt8Array>()),
+        ColumnType::UInt16 => {
+            Arc::new(values.map(|v| v.map(|v| v as u16)).collect::<UInt16Array>())
+        }
+        ColumnType::Float32 => Arc::new(
+            values
+                .map(|v| v.map(|v| v as f32))
+                .collect::<Float32Array>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::recorder::format::{RecordingMeta, RecordingWriter};
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{report as sim_report, SimConfig};
+    use crate::packages::xarm::report;
+
+    fn recording(kind: ReportKind, frames: usize) -> Vec<u8> {
+        let meta = RecordingMeta {
+            arm_sn: None,
+            ip: "127.0.0.1".to_string(),
+            command_port: None,
+            kind,
+            started: 0,
+            app_version: "0.0.0".to_string(),
+        };
+        let mut data = Vec::new();
+        let mut writer = RecordingWriter::new(&mut data, &meta).unwrap();
+        let mut arm = SimArm::new(&SimConfig::default());
+        for i in 0..frames {
+            arm.step(0.01);
+            let mut report = report::decode(kind, &sim_report::encode(&arm, kind)).unwrap();
+            report.timestamp = i as i64 * 10;
+            writer.write(&report).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn exports_csv_rows() {
+        let data = recording(ReportKind::Realtime, 3);
+        let reader = RecordingReader::new(data.as_slice()).unwrap();
+        let mut output = Vec::new();
+        assert_eq!(write_csv(reader, &mut output).unwrap(), 3);
+
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("timestamp,state,mode,cmd_num,j1,"));
+        assert!(lines[0].ends_with(",current7"));
+        assert!(lines[2].starts_with("10,"));
+        assert_eq!(
+            lines[1].split(',').count(),
+            columns(ReportKind::Realtime).len()
+        );
+    }
+
+    #[test]
+    fn exports_arrow_batches() {
+        let data = recording(ReportKind::Normal, BATCH_ROWS + 5);
+        let reader = RecordingReader::new(data.as_slice()).unwrap();
+        let mut output = Vec::new();
+        assert_eq!(
+            write_arrow(reader, &mut output).unwrap(),
+            (BATCH_ROWS + 5) as u64
+        );
+
+        let file =
+            arrow_ipc::reader::FileReader::try_new(std::io::Cursor::new(output), None).unwrap();
+        assert_eq!(file.num_batches(), 2);
+        assert!(file.schema().field_with_name("error_code").is_ok());
+        let rows: usize = file.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, BATCH_ROWS + 5);
+    }
+}
@@ -0,0 +1,238 @@
+//! 录制文件格式（`.ufrec`）
+//!
+//! 文件头：`<魔数 "UFREC"><版本 u8><元数据长度 u32 大端><元数据 JSON>`；
+//! 之后逐帧追加：`<接收时间 i64 大端，毫秒><上报帧>`，上报帧即控制器原始格式（自带长度前缀，见 `xarm::report`）。
+//! 录制中断时末尾可能有不完整的帧，读取时忽略。
+
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::path::Path;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::packages::xarm::report::{self, ArmReport, ReportKind, COMMON_LEN, MAX_REPORT_LEN};
+use crate::packages::xarm::ArmAddress;
+use crate::utils::error::AppError;
+
+/// 文件扩展名
+pub const EXTENSION: &str = "ufrec";
+
+const MAGIC: &[u8; 5] = b"UFREC";
+const VERSION: u8 = 1;
+/// 元数据长度上限，超过视为文件损坏
+const MAX_META_LEN: usize = 64 * 1024;
+
+/// 录制元数据
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordingMeta {
+    pub arm_sn: Option<String>,
+    pub ip: String,
+    pub command_port: Option<u16>,
+    pub kind: ReportKind,
+    /// 开始时间（毫秒时间戳）
+    pub started: i64,
+    /// 录制时的软件版本
+    pub app_version: String,
+}
+
+impl RecordingMeta {
+    /// 录制来源的机械臂地址
+    pub fn address(&self) -> ArmAddress {
+        ArmAddress {
+            ip: self.ip.clone(),
+            command_port: self.command_port,
+        }
+    }
+}
+
+/// 顺序写入录制文件
+pub struct RecordingWriter<W: Write> {
+    inner: W,
+    frames: u64,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// 写入文件头
+    pub fn new(mut inner: W, meta: &RecordingMeta) -> Result<Self, AppError> {
+        let meta = serde_json::to_vec(meta).map_err(|e| AppError::Io(e.to_string()))?;
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        inner.write_all(&(meta.len() as u32).to_be_bytes())?;
+        inner.write_all(&meta)?;
+        Ok(Self { inner, frames: 0 })
+    }
+
+    /// 追加一帧，时间取 `report.timestamp`
+    pub fn write(&mut self, report: &ArmReport) -> Result<(), AppError> {
+        self.inner.write_all(&report.timestamp.to_be_bytes())?;
+        self.inner.write_all(&report::encode(report))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// 已写入的帧数
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn flush(&mut self) -> Result<(), AppError> {
+        Ok(self.inner.flush()?)
+    }
+}
+
+/// 顺序读取录制文件
+pub struct RecordingReader<R: Read> {
+    inner: R,
+    meta: RecordingMeta,
+}
+
+impl RecordingReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self, AppError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// 读取并校验文件头
+    pub fn new(mut inner: R) -> Result<Self, AppError> {
+        let mut head = [0u8; 10];
+        inner
+            .read_exact(&mut head)
+            .map_err(|_| invalid("missing header"))?;
+        if &head[..5] != MAGIC {
+            return Err(invalid("not a recording file"));
+        }
+        if head[5] != VERSION {
+            return Err(invalid(&format!("unsupported version {}", head[5])));
+        }
+        let len = u32::from_be_bytes([head[6], head[7], head[8], head[9]]) as usize;
+        if len > MAX_META_LEN {
+            return Err(invalid(&format!("metadata too long: {} bytes", len)));
+        }
+        let mut meta = vec![0u8; len];
+        inner
+            .read_exact(&mut meta)
+            .map_err(|_| invalid("truncated metadata"))?;
+        let meta = serde_json::from_slice(&meta).map_err(|e| invalid(&e.to_string()))?;
+        Ok(Self { inner, meta })
+    }
+
+    pub fn meta(&self) -> &RecordingMeta {
+        &self.meta
+    }
+
+    /// 读取下一帧，文件结束（包括不完整的尾帧）时返回 `None`
+    pub fn next_frame(&mut self) -> Result<Option<ArmReport>, AppError> {
+        let mut timestamp = [0u8; 8];
+        match self.inner.read_exact(&mut timestamp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let mut len = [0u8; 4];
+        if !self.read_or_eof(&mut len)? {
+            return Ok(None);
+        }
+        let frame_len = u32::from_be_bytes(len) as usize;
+        if !(COMMON_LEN..=MAX_REPORT_LEN).contains(&frame_len) {
+            return Err(invalid(&format!("invalid frame length {}", frame_len)));
+        }
+
+        let mut frame = vec![0u8; frame_len];
+        frame[..4].copy_from_slice(&len);
+        if !self.read_or_eof(&mut frame[4..])? {
+            return Ok(None);
+        }
+        let mut report = report::decode(self.meta.kind, &frame)?;
+        report.timestamp = i64::from_be_bytes(timestamp);
+        Ok(Some(report))
+    }
+
+    /// 读满缓冲区，遇到文件结束返回 false
+    fn read_or_eof(&mut self, buf: &mut [u8]) -> Result<bool, AppError> {
+        match self.inner.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                warn!("Recording ends with an incomplete frame, ignored");
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn invalid(message: &str) -> AppError {
+    AppError::Protocol(format!("invalid recording: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{report as sim_report, SimConfig};
+
+    fn meta(kind: ReportKind) -> RecordingMeta {
+        RecordingMeta {
+            arm_sn: Some("XS1234".to_string()),
+            ip: "127.0.0.1".to_string(),
+            command_port: None,
+            kind,
+            started: 1_700_000_000_000,
+            app_version: "0.0.0".to_string(),
+        }
+    }
+
+    fn sample(kind: ReportKind, steps: usize, timestamp: i64) -> ArmReport {
+        let mut arm = SimArm::new(&SimConfig::default());
+        for _ in 0..steps {
+            arm.step(0.01);
+        }
+        let mut report = report::decode(kind, &sim_report::encode(&arm, kind)).unwrap();
+        report.timestamp = timestamp;
+        report
+    }
+
+    #[test]
+    fn round_trips_frames() {
+        for kind in [ReportKind::Normal, ReportKind::Realtime] {
+            let reports: Vec<ArmReport> = (0..3)
+                .map(|i| sample(kind, i * 10, 1_700_000_000_000 + i as i64 * 10))
+                .collect();
+            let mut writer = RecordingWriter::new(Vec::new(), &meta(kind)).unwrap();
+            for report in &reports {
+                writer.write(report).unwrap();
+            }
+            assert_eq!(writer.frames(), 3);
+
+            let data = writer.inner;
+            let mut reader = RecordingReader::new(data.as_slice()).unwrap();
+            assert_eq!(reader.meta(), &meta(kind));
+            for report in &reports {
+                assert_eq!(reader.next_frame().unwrap().as_ref(), Some(report));
+            }
+            assert_eq!(reader.next_frame().unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn ignores_truncated_tail() {
+        let mut writer = RecordingWriter::new(Vec::new(), &meta(ReportKind::Realtime)).unwrap();
+        writer.write(&sample(ReportKind::Realtime, 5, 1)).unwrap();
+        writer.write(&sample(ReportKind::Realtime, 6, 2)).unwrap();
+        let mut data = writer.inner;
+        data.truncate(data.len() - 20);
+
+        let mut reader = RecordingReader::new(data.as_slice()).unwrap();
+        assert!(reader.next_frame().unwrap().is_some());
+        assert_eq!(reader.next_frame().unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            RecordingReader::new(b"PK\x03\x04 not a recording".as_slice()),
+            Err(AppError::Protocol(_))
+        ));
+    }
+}
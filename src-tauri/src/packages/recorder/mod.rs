@@ -0,0 +1,784 @@
+//! 状态录制与回放
+//!
+//! 录制：通过 `ReportStreams::open` 拿到全频率上报数据，逐帧写入 `.ufrec` 文件（格式见 `format`），
+//! 文件保存在 `{app_data_dir}/recordings`，可导出为 CSV 或 Arrow（见 `export`）。
+//!
+//! 回放：以录制时的机械臂地址（或指定地址）调用 `ReportStreams::attach` 代替真实连接，
+//! 按原始时间间隔（可倍速）推送，前端和 WebSocket 桥的订阅照常收到数据，无需连接机械臂。
+//! 录制和回放的状态分别通过 `recording_state`、`replay_state` 事件推送。
+
+pub mod export;
+pub mod format;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{error, info, warn};
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Emitter, Runtime};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::time::{self, Instant};
+
+use crate::packages::xarm::report::{ArmReport, ReportKind};
+use crate::packages::xarm::session::{ConnectionState, SessionInfo};
+use crate::packages::xarm::stream::ReportStreams;
+use crate::packages::xarm::ArmAddress;
+use crate::utils::error::AppError;
+use crate::utils::files::{self, plain_name};
+use format::{RecordingMeta, RecordingReader, RecordingWriter};
+
+/// 录制状态推送
+pub const EVENT_RECORDING_STATE: &str = "recording_state";
+/// 回放状态推送
+pub const EVENT_REPLAY_STATE: &str = "replay_state";
+
+/// 录制文件目录名
+const DIR_NAME: &str = "recordings";
+/// 录制文件落盘及状态推送间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// 回放进度推送间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+/// 回放倍速上限
+const MAX_SPEED: f64 = 100.0;
+/// 回放预读的帧数
+const READ_AHEAD: usize = 256;
+
+/// 录制/回放任务状态
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// 回放到文件末尾
+    Finished,
+    /// 手动停止（或上报连接关闭）
+    Stopped,
+    Failed,
+}
+
+/// 录制任务信息，同时作为 `recording_state` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordingInfo {
+    pub id: u64,
+    pub path: PathBuf,
+    pub meta: RecordingMeta,
+    pub frames: u64,
+    pub state: TaskState,
+    pub message: Option<String>,
+}
+
+/// 回放任务信息，同时作为 `replay_state` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct ReplayInfo {
+    pub id: u64,
+    pub path: PathBuf,
+    pub meta: RecordingMeta,
+    /// 回放数据推送到的地址，订阅该地址即可收到
+    pub arm: ArmAddress,
+    pub speed: f64,
+    /// 已推送帧数
+    pub frames: u64,
+    /// 当前帧相对第一帧的时间（毫秒，录制时间轴）
+    pub position_ms: i64,
+    pub state: TaskState,
+    pub message: Option<String>,
+}
+
+/// 已保存的录制文件
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordingFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub meta: RecordingMeta,
+}
+
+struct Task<T> {
+    info: Arc<Mutex<T>>,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// 录制与回放任务
+#[derive(Clone, Default)]
+pub struct Recorder {
+    next_id: Arc<AtomicU64>,
+    recordings: Arc<Mutex<HashMap<u64, Task<RecordingInfo>>>>,
+    replays: Arc<Mutex<HashMap<u64, Task<ReplayInfo>>>>,
+}
+
+/// 录制文件目录，不存在时创建
+pub fn recordings_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    files::data_dir(app, DIR_NAME)
+}
+
+/// 相对路径按录制目录解析
+pub fn resolve<R: Runtime>(app: &AppHandle<R>, file: &Path) -> Result<PathBuf, AppError> {
+    if file.is_absolute() {
+        Ok(file.to_path_buf())
+    } else {
+        Ok(recordings_dir(app)?.join(file))
+    }
+}
+
+/// 列出录制目录中的文件，最新的在前；无法解析的文件跳过
+pub fn list_files<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<RecordingFile>, AppError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(recordings_dir(app)?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(format::EXTENSION) {
+            continue;
+        }
+        match RecordingReader::open(&path) {
+            Ok(reader) => files.push(RecordingFile {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                meta: reader.meta().clone(),
+                path,
+            }),
+            Err(e) => warn!("Skip recording {}: {}", path.display(), e),
+        }
+    }
+    files.sort_by_key(|file| std::cmp::Reverse(file.meta.started));
+    Ok(files)
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始录制某台机械臂的上报数据；同一地址和上报类型只允许一个录制
+    pub fn start_recording<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        reports: &ReportStreams,
+        arm: ArmAddress,
+        arm_sn: Option<String>,
+        kind: ReportKind,
+    ) -> Result<RecordingInfo, AppError> {
+        self.record_into(
+            &recordings_dir(app)?,
+            reports,
+            arm,
+            arm_sn,
+            kind,
+            notifier(app, EVENT_RECORDING_STATE),
+        )
+    }
+
+    /// 录制到 `dir`，状态变化通过 `notify` 通知
+    fn record_into<N>(
+        &self,
+        dir: &Path,
+        reports: &ReportStreams,
+        arm: ArmAddress,
+        arm_sn: Option<String>,
+        kind: ReportKind,
+        notify: N,
+    ) -> Result<RecordingInfo, AppError>
+    where
+        N: Fn(&RecordingInfo) + Send + Sync + 'static,
+    {
+        let addr = arm.report_addr(kind)?;
+        let duplicated = self.recordings().iter().any(|info| {
+            info.meta.kind == kind && info.meta.address().report_addr(kind).ok() == Some(addr)
+        });
+        if duplicated {
+            return Err(AppError::InvalidArgument(format!(
+                "{} {:?} is already being recorded",
+                addr, kind
+            )));
+        }
+
+        let now = Local::now();
+        let meta = RecordingMeta {
+            ip: arm.ip.trim().to_string(),
+            command_port: arm.command_port,
+            arm_sn,
+            kind,
+            started: now.timestamp_millis(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        let label = match &meta.arm_sn {
+            Some(arm_sn) => plain_name(arm_sn, "arm_sn")?.to_string(),
+            None => meta.ip.replace(['.', ':'], "-"),
+        };
+        let stem = format!("{}_{}", label, now.format("%Y%m%d_%H%M%S_%3f"));
+        // 回放中的地址在这里被拒绝，不留下空文件
+        let (rx, lease) = reports.open(addr, kind)?;
+        let (path, writer) = files::create_new_file(dir, &stem, &format!(".{}", format::EXTENSION))
+            .and_then(|(path, file)| Ok((path, RecordingWriter::new(BufWriter::new(file), &meta)?)))
+            .inspect_err(|_| reports.close(lease))?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let info = RecordingInfo {
+            id,
+            path,
+            meta,
+            frames: 0,
+            state: TaskState::Running,
+            message: None,
+        };
+        let shared = Arc::new(Mutex::new(info.clone()));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        if let Ok(mut recordings) = self.recordings.lock() {
+            recordings.insert(
+                id,
+                Task {
+                    info: Arc::clone(&shared),
+                    stop_tx,
+                },
+            );
+        }
+
+        info!("Recording {} started: {} {:?}", id, addr, kind);
+        notify(&info);
+
+        let recorder = self.clone();
+        let reports = reports.clone();
+        async_runtime::spawn(async move {
+            let (state, message) = record(&notify, rx, writer, &shared, stop_rx).await;
+            reports.close(lease);
+            if let Ok(mut recordings) = recorder.recordings.lock() {
+                recordings.remove(&id);
+            }
+            if let Some(info) = update(&shared, |info| {
+                info.state = state;
+                info.message = message;
+            }) {
+                info!(
+                    "Recording {} {:?}: {} frames to {}",
+                    id,
+                    info.state,
+                    info.frames,
+                    info.path.display()
+                );
+                notify(&info);
+            }
+        });
+
+        Ok(info)
+    }
+
+    /// 为所有已连接的会话开始录制（菜单“开始录制”），返回成功的数量
+    pub fn record_sessions<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        reports: &ReportStreams,
+        sessions: &[SessionInfo],
+        kind: ReportKind,
+    ) -> usize {
+        sessions
+            .iter()
+            .filter(|session| {
+                matches!(
+                    session.state,
+                    ConnectionState::Connected | ConnectionState::Degraded
+                )
+            })
+            .filter(|session| {
+                let arm = ArmAddress {
+                    ip: session.ip.clone(),
+                    command_port: Some(session.command_port),
+                };
+                self.start_recording(app, reports, arm, Some(session.arm_sn.clone()), kind)
+                    .map_err(|e| warn!("Failed to record {}: {}", session.arm_sn, e))
+                    .is_ok()
+            })
+            .count()
+    }
+
+    /// 停止录制，返回是否存在该录制
+    pub fn stop_recording(&self, id: u64) -> bool {
+        stop_task(&self.recordings, Some(id)) > 0
+    }
+
+    /// 停止所有录制，返回停止的数量
+    pub fn stop_recordings(&self) -> usize {
+        stop_task(&self.recordings, None)
+    }
+
+    /// 进行中的录制
+    pub fn recordings(&self) -> Vec<RecordingInfo> {
+        snapshot(&self.recordings)
+    }
+
+    /// 开始回放；`arm` 为空时推送到录制时的地址，该地址已有上报连接时报错
+    pub fn start_replay<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        reports: &ReportStreams,
+        path: PathBuf,
+        speed: f64,
+        arm: Option<ArmAddress>,
+    ) -> Result<ReplayInfo, AppError> {
+        self.replay_with(reports, path, speed, arm, notifier(app, EVENT_REPLAY_STATE))
+    }
+
+    /// 开始回放，状态变化通过 `notify` 通知
+    fn replay_with<N>(
+        &self,
+        reports: &ReportStreams,
+        path: PathBuf,
+        speed: f64,
+        arm: Option<ArmAddress>,
+        notify: N,
+    ) -> Result<ReplayInfo, AppError>
+    where
+        N: Fn(&ReplayInfo) + Send + Sync + 'static,
+    {
+        if !speed.is_finite() || speed <= 0.0 || speed > MAX_SPEED {
+            return Err(AppError::InvalidArgument(format!(
+                "invalid replay speed: {}",
+                speed
+            )));
+        }
+        let reader = RecordingReader::open(&path)?;
+        let meta = reader.meta().clone();
+        let arm = arm.unwrap_or_else(|| meta.address());
+        let addr = arm.report_addr(meta.kind)?;
+        let (tx, lease) = reports.attach(addr, meta.kind)?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let info = ReplayInfo {
+            id,
+            path,
+            meta,
+            arm,
+            speed,
+            frames: 0,
+            position_ms: 0,
+            state: TaskState::Running,
+            message: None,
+        };
+        let shared = Arc::new(Mutex::new(info.clone()));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        if let Ok(mut replays) = self.replays.lock() {
+            replays.insert(
+                id,
+                Task {
+                    info: Arc::clone(&shared),
+                    stop_tx,
+                },
+            );
+        }
+
+        info!(
+            "Replay {} started: {} to {} at {}x",
+            id,
+            info.path.display(),
+            addr,
+            speed
+        );
+        notify(&info);
+
+        let recorder = self.clone();
+        let reports = reports.clone();
+        let frames = read_frames(reader);
+        async_runtime::spawn(async move {
+            let (state, message) = replay(&notify, frames, tx, speed, &shared, stop_rx).await;
+            reports.detach(lease);
+            if let Ok(mut replays) = recorder.replays.lock() {
+                replays.remove(&id);
+            }
+            if let Some(info) = update(&shared, |info| {
+                info.state = state;
+                info.message = message;
+            }) {
+                info!(
+                    "Replay {} {:?} after {} frames",
+                    id, info.state, info.frames
+                );
+                notify(&info);
+            }
+        });
+
+        Ok(info)
+    }
+
+    /// 停止回放，返回是否存在该回放
+    pub fn stop_replay(&self, id: u64) -> bool {
+        stop_task(&self.replays, Some(id)) > 0
+    }
+
+    /// 进行中的回放
+    pub fn replays(&self) -> Vec<ReplayInfo> {
+        snapshot(&self.replays)
+    }
+
+    /// 停止所有录制和回放（退出时调用）
+    pub fn stop_all(&self) {
+        stop_task(&self.recordings, None);
+        stop_task(&self.replays, None);
+    }
+}
+
+/// 向任务发送停止信号，`id` 为空时停止全部，返回发送的数量
+fn stop_task<T>(tasks: &Mutex<HashMap<u64, Task<T>>>, id: Option<u64>) -> usize {
+    let Ok(tasks) = tasks.lock() else {
+        return 0;
+    };
+    let mut count = 0;
+    for (_, task) in tasks
+        .iter()
+        .filter(|(task_id, _)| id.is_none_or(|id| id == **task_id))
+    {
+        let _ = task.stop_tx.send(true);
+        count += 1;
+    }
+    count
+}
+
+fn snapshot<T: Clone>(tasks: &Mutex<HashMap<u64, Task<T>>>) -> Vec<T> {
+    let Ok(tasks) = tasks.lock() else {
+        return Vec::new();
+    };
+    let mut list: Vec<(u64, T)> = tasks
+        .iter()
+        .filter_map(|(id, task)| task.info.lock().ok().map(|info| (*id, info.clone())))
+        .collect();
+    list.sort_by_key(|(id, _)| *id);
+    list.into_iter().map(|(_, info)| info).collect()
+}
+
+/// 以事件推送任务状态
+fn notifier<R: Runtime, T: Serialize + Clone>(
+    app: &AppHandle<R>,
+    event: &'static str,
+) -> impl Fn(&T) + Send + Sync + 'static {
+    let app = app.clone();
+    move |payload| {
+        if let Err(e) = app.emit(event, payload.clone()) {
+            error!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// 录制任务：写入每一帧，定期落盘并推送帧数
+async fn record<W: Write>(
+    notify: &impl Fn(&RecordingInfo),
+    mut rx: broadcast::Receiver<Arc<ArmReport>>,
+    mut writer: RecordingWriter<W>,
+    shared: &Mutex<RecordingInfo>,
+    mut stop_rx: watch::Receiver<bool>,
+) -> (TaskState, Option<String>) {
+    let mut ticker = time::interval(FLUSH_INTERVAL);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    let result = loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break (TaskState::Stopped, None),
+            _ = ticker.tick() => {
+                if let Err(e) = writer.flush() {
+                    break (TaskState::Failed, Some(e.to_string()));
+                }
+                if let Some(info) = update(shared, |info| info.frames = writer.frames()) {
+                    notify(&info);
+                }
+            }
+            received = rx.recv() => match received {
+                Ok(report) => {
+                    if let Err(e) = writer.write(&report) {
+                        break (TaskState::Failed, Some(e.to_string()));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Recording lagged, dropped {} frames", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break (TaskState::Stopped, None),
+            },
+        }
+    };
+    let result = match writer.flush() {
+        Err(e) if result.0 != TaskState::Failed => (TaskState::Failed, Some(e.to_string())),
+        _ => result,
+    };
+    update(shared, |info| info.frames = writer.frames());
+    result
+}
+
+/// 在阻塞线程中顺序读取帧，读完、出错或接收端关闭时结束
+fn read_frames<F: Read + Send + 'static>(
+    mut reader: RecordingReader<F>,
+) -> mpsc::Receiver<Result<ArmReport, AppError>> {
+    let (tx, rx) = mpsc::channel(READ_AHEAD);
+    async_runtime::spawn_blocking(move || loop {
+        let frame = match reader.next_frame() {
+            Ok(Some(report)) => Ok(report),
+            Ok(None) => break,
+            Err(e) => Err(e),
+        };
+        let failed = frame.is_err();
+        if tx.blocking_send(frame).is_err() || failed {
+            break;
+        }
+    });
+    rx
+}
+
+/// 回放任务：按录制时间间隔除以倍速推送每一帧
+async fn replay(
+    notify: &impl Fn(&ReplayInfo),
+    mut frames: mpsc::Receiver<Result<ArmReport, AppError>>,
+    tx: broadcast::Sender<Arc<ArmReport>>,
+    speed: f64,
+    shared: &Mutex<ReplayInfo>,
+    mut stop_rx: watch::Receiver<bool>,
+) -> (TaskState, Option<String>) {
+    let started = Instant::now();
+    let mut first: Option<i64> = None;
+    let mut last_progress = started;
+    loop {
+        let report = match frames.recv().await {
+            Some(Ok(report)) => report,
+            Some(Err(e)) => return (TaskState::Failed, Some(e.to_string())),
+            None => return (TaskState::Finished, None),
+        };
+        let first = *first.get_or_insert(report.timestamp);
+        // 系统时间回拨时时间差为负，立即推送
+        let position_ms = (report.timestamp - first).max(0);
+        let due = started + Duration::from_secs_f64(position_ms as f64 / 1000.0 / speed);
+        tokio::select! {
+            _ = stop_rx.changed() => return (TaskState::Stopped, None),
+            _ = time::sleep_until(due) => {}
+        }
+
+        // 没有订阅者时发送失败，忽略即可
+        let _ = tx.send(Arc::new(report));
+        let info = update(shared, |info| {
+            info.frames += 1;
+            info.position_ms = position_ms;
+        });
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            if let Some(info) = info {
+                notify(&info);
+            }
+        }
+    }
+}
+
+fn update<T: Clone>(shared: &Mutex<T>, f: impl FnOnce(&mut T)) -> Option<T> {
+    let mut info = shared.lock().ok()?;
+    f(&mut info);
+    Some(info.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    use super::*;
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{report as sim_report, SimConfig};
+    use crate::packages::xarm::report;
+
+    const KIND: ReportKind = ReportKind::Realtime;
+
+    fn arm(ip: &str) -> ArmAddress {
+        ArmAddress {
+            ip: ip.to_string(),
+            command_port: None,
+        }
+    }
+
+    fn frame(timestamp: i64) -> ArmReport {
+        let arm = SimArm::new(&SimConfig::default());
+        let mut report = report::decode(KIND, &sim_report::encode(&arm, KIND)).unwrap();
+        report.timestamp = timestamp;
+        report
+    }
+
+    /// 按原始频率订阅，收集帧时间戳
+    fn subscribe(reports: &ReportStreams, addr: SocketAddr) -> UnboundedReceiver<i64> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        reports
+            .subscribe(addr, KIND, 0.0, move |_, report| {
+                tx.send(report.timestamp).is_ok()
+            })
+            .unwrap();
+        rx
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("recorder-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 收集任务状态通知
+    fn events<T: Clone + Send + 'static>() -> (impl Fn(&T) + Send + Sync, UnboundedReceiver<T>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (move |info: &T| drop(tx.send(info.clone())), rx)
+    }
+
+    async fn wait_for<T>(rx: &mut UnboundedReceiver<T>, done: impl Fn(&T) -> bool) -> T {
+        time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = rx.recv().await.unwrap();
+                if done(&event) {
+                    return event;
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    fn write_recording(dir: &Path, timestamps: &[i64]) -> PathBuf {
+        let path = dir.join(format!("replay.{}", format::EXTENSION));
+        let meta = RecordingMeta {
+            arm_sn: None,
+            ip: "127.0.0.1".to_string(),
+            command_port: None,
+            kind: KIND,
+            started: 0,
+            app_version: "0.0.0".to_string(),
+        };
+        let mut writer = RecordingWriter::new(fs::File::create(&path).unwrap(), &meta).unwrap();
+        for timestamp in timestamps {
+            writer.write(&frame(*timestamp)).unwrap();
+        }
+        writer.flush().unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn records_until_the_stream_closes() {
+        let dir = temp_dir("record");
+        let reports = ReportStreams::new();
+        let recorder = Recorder::new();
+        let addr: SocketAddr = arm("127.0.0.2").report_addr(KIND).unwrap();
+        let (source, lease) = reports.inject(addr, KIND);
+        let (notify, mut rx) = events();
+
+        let info = recorder
+            .record_into(
+                &dir,
+                &reports,
+                arm("127.0.0.2"),
+                Some("XI1".to_string()),
+                KIND,
+                notify,
+            )
+            .unwrap();
+        assert_eq!(info.state, TaskState::Running);
+        assert!(info.path.starts_with(&dir));
+        assert_eq!(recorder.recordings().len(), 1);
+        let (notify, _) = events();
+        assert!(recorder
+            .record_into(&dir, &reports, arm("127.0.0.2"), None, KIND, notify)
+            .is_err());
+
+        for i in 0..3 {
+            source.send(Arc::new(frame(i * 10))).unwrap();
+        }
+        reports.detach(lease);
+        drop(source);
+
+        let done = wait_for(&mut rx, |info| info.state != TaskState::Running).await;
+        assert_eq!((done.state, done.frames), (TaskState::Stopped, 3));
+        assert!(recorder.recordings().is_empty());
+
+        let mut reader = RecordingReader::open(&done.path).unwrap();
+        assert_eq!(reader.meta().arm_sn.as_deref(), Some("XI1"));
+        for i in 0..3 {
+            assert_eq!(reader.next_frame().unwrap().unwrap().timestamp, i * 10);
+        }
+        assert!(reader.next_frame().unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stops_recording() {
+        let dir = temp_dir("stop");
+        let reports = ReportStreams::new();
+        let recorder = Recorder::new();
+        let addr = arm("127.0.0.3").report_addr(KIND).unwrap();
+        let _source = reports.inject(addr, KIND);
+        let (notify, mut rx) = events();
+
+        let info = recorder
+            .record_into(&dir, &reports, arm("127.0.0.3"), None, KIND, notify)
+            .unwrap();
+        assert!(!recorder.stop_recording(info.id + 1));
+        assert!(recorder.stop_recording(info.id));
+
+        let done = wait_for(&mut rx, |info| info.state != TaskState::Running).await;
+        assert_eq!((done.state, done.frames), (TaskState::Stopped, 0));
+        assert!(recorder.recordings().is_empty());
+        assert!(done.path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_frames_in_order() {
+        let dir = temp_dir("replay");
+        let path = write_recording(&dir, &[1_000, 1_010, 1_020]);
+        let reports = ReportStreams::new();
+        let recorder = Recorder::new();
+        let target = arm("127.0.0.4");
+        let addr = target.report_addr(KIND).unwrap();
+
+        let (notify, _) = events();
+        assert!(recorder
+            .replay_with(&reports, path.clone(), 0.0, None, notify)
+            .is_err());
+
+        let (notify, mut rx) = events();
+        let info = recorder
+            .replay_with(&reports, path, MAX_SPEED, Some(target.clone()), notify)
+            .unwrap();
+        assert_eq!(info.arm.ip, target.ip);
+        assert_eq!(recorder.replays().len(), 1);
+        // 回放中该地址已被占用，也不能录制回放数据
+        assert!(reports.attach(addr, KIND).is_err());
+        let (notify, _) = events();
+        assert!(recorder
+            .record_into(&dir, &reports, target.clone(), None, KIND, notify)
+            .is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let mut frames = subscribe(&reports, addr);
+        for timestamp in [1_000, 1_010, 1_020] {
+            assert_eq!(frames.recv().await, Some(timestamp));
+        }
+        let done = wait_for(&mut rx, |info| info.state != TaskState::Running).await;
+        assert_eq!(done.state, TaskState::Finished);
+        assert_eq!((done.frames, done.position_ms), (3, 20));
+        assert!(recorder.replays().is_empty());
+        assert!(reports.attach(addr, KIND).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stops_replay() {
+        let dir = temp_dir("stop-replay");
+        let path = write_recording(&dir, &[0, 60_000]);
+        let reports = ReportStreams::new();
+        let recorder = Recorder::new();
+        let target = arm("127.0.0.5");
+        let (notify, mut rx) = events();
+
+        let info = recorder
+            .replay_with(&reports, path, 1.0, Some(target.clone()), notify)
+            .unwrap();
+        let mut frames = subscribe(&reports, target.report_addr(KIND).unwrap());
+        assert_eq!(frames.recv().await, Some(0));
+
+        assert!(recorder.stop_replay(info.id));
+        let done = wait_for(&mut rx, |info| info.state != TaskState::Running).await;
+        assert_eq!((done.state, done.frames), (TaskState::Stopped, 1));
+        assert!(recorder.replays().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
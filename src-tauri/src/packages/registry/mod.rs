@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Local;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{async_runtime, AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::packages::discovery::presence::device_key;
+use crate::packages::discovery::ArmIpIntro;
+
+/// 设备登记表文件（位于应用数据目录）
+const REGISTRY_STORE: &str = "device_registry.json";
+/// 发现回复引起的登记更新合并后延迟写入的时长
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+/// 登记表中的一台设备
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct RegistryEntry {
+    /// 登记表键，首次登记时的 arm_sn（缺失时为 control_sn / IP）
+    pub id: String,
+    pub arm_sn: String,
+    pub control_sn: String,
+    /// 用户备注名，例如 "3 号工位左臂"
+    pub nickname: String,
+    pub ip: String,
+    pub port: String,
+    pub device_type: String,
+    pub axis: String,
+    pub version: String,
+    /// 首次发现时间（毫秒时间戳）
+    pub first_seen: i64,
+    /// 最近一次发现时间（毫秒时间戳）
+    pub last_seen: i64,
+    pub tags: Vec<String>,
+    /// 合并进来的设备的序列号，该设备再次被发现时仍归入本条登记
+    pub aliases: Vec<String>,
+}
+
+impl RegistryEntry {
+    fn is_alias(&self, sn: &str) -> bool {
+        self.aliases.iter().any(|alias| alias == sn)
+    }
+
+    fn update_from(&mut self, intro: &ArmIpIntro, now: i64) {
+        if !intro.arm_sn.is_empty() && !self.is_alias(&intro.arm_sn) {
+            self.arm_sn.clone_from(&intro.arm_sn);
+        }
+        if !intro.control_sn.is_empty() && !self.is_alias(&intro.control_sn) {
+            self.control_sn.clone_from(&intro.control_sn);
+        }
+        self.ip.clone_from(&intro.ip);
+        self.port.clone_from(&intro.port);
+        self.device_type.clone_from(&intro.device_type);
+        self.axis.clone_from(&intro.axis);
+        self.version.clone_from(&intro.version);
+        self.last_seen = now;
+    }
+}
+
+type Entries = HashMap<String, RegistryEntry>;
+
+/// 按 arm_sn 查找，找不到时再按 control_sn 查找，合并留下的别名同样匹配；重复时取最近发现的一条
+fn find<'a>(entries: &'a Entries, intro: &ArmIpIntro) -> Option<&'a RegistryEntry> {
+    let latest = |pred: &dyn Fn(&RegistryEntry) -> bool| {
+        entries
+            .values()
+            .filter(|entry| pred(entry))
+            .max_by_key(|entry| entry.last_seen)
+    };
+    (!intro.arm_sn.is_empty())
+        .then(|| latest(&|e| e.arm_sn == intro.arm_sn || e.is_alias(&intro.arm_sn)))
+        .flatten()
+        .or_else(|| {
+            (!intro.control_sn.is_empty())
+                .then(|| {
+                    latest(&|e| e.control_sn == intro.control_sn || e.is_alias(&intro.control_sn))
+                })
+                .flatten()
+        })
+}
+
+/// 把一条发现回复合并进登记表，返回更新后的记录
+fn apply_discovery(entries: &mut Entries, intro: &ArmIpIntro, now: i64) -> RegistryEntry {
+    let mut entry = find(entries, intro).cloned().unwrap_or_else(|| {
+        info!("Register new device {}", device_key(intro));
+        RegistryEntry {
+            id: device_key(intro),
+            first_seen: now,
+            ..Default::default()
+        }
+    });
+    entry.update_from(intro, now);
+    entries.insert(entry.id.clone(), entry.clone());
+    entry
+}
+
+/// 把 `source` 的备注名、标签、序列号和最近信息合并进 `target`，`source` 的序列号记为别名
+fn merge_entries(source: RegistryEntry, mut target: RegistryEntry) -> RegistryEntry {
+    if target.nickname.is_empty() {
+        target.nickname = source.nickname;
+    }
+    let serials = [&source.arm_sn, &source.control_sn]
+        .into_iter()
+        .chain(&source.aliases)
+        .filter(|sn| !sn.is_empty() && **sn != target.arm_sn && **sn != target.control_sn);
+    for sn in serials {
+        if !target.is_alias(sn) {
+            target.aliases.push(sn.clone());
+        }
+    }
+    let mut tags = target.tags;
+    tags.extend(source.tags);
+    target.tags = normalize_tags(tags);
+    target.first_seen = target.first_seen.min(source.first_seen);
+    if source.last_seen > target.last_seen {
+        target.last_seen = source.last_seen;
+        target.ip = source.ip;
+        target.port = source.port;
+        target.version = source.version;
+        target.device_type = source.device_type;
+        target.axis = source.axis;
+    }
+    target
+}
+
+#[derive(Default)]
+struct Cache {
+    /// 首次访问时从 store 载入
+    entries: Option<Entries>,
+    /// 尚未写入 store 的登记键
+    dirty: HashSet<String>,
+    flush_scheduled: bool,
+}
+
+/// 设备登记表
+///
+/// 登记内容缓存在内存中；发现回复只更新缓存，由延迟任务批量写入 store，
+/// 用户修改（备注名、标签、删除、合并）立即写入。
+#[derive(Clone, Default)]
+pub struct DeviceRegistry {
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl DeviceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在已载入的登记表上执行 `f`
+    fn with_entries<R: Runtime, T>(
+        &self,
+        app: &AppHandle<R>,
+        f: impl FnOnce(&mut Entries, &mut HashSet<String>) -> T,
+    ) -> Result<T, String> {
+        let mut cache = self.cache.lock().map_err(|e| e.to_string())?;
+        let Cache { entries, dirty, .. } = &mut *cache;
+        let entries = match entries {
+            Some(entries) => entries,
+            None => entries.insert(load(app)?),
+        };
+        Ok(f(entries, dirty))
+    }
+
+    /// 读取全部登记设备，按最近发现时间倒序
+    pub fn list<R: Runtime>(&self, app: &AppHandle<R>) -> Result<Vec<RegistryEntry>, String> {
+        let mut entries = self.with_entries(app, |entries, _| {
+            entries.values().cloned().collect::<Vec<_>>()
+        })?;
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_seen));
+        Ok(entries)
+    }
+
+    /// 登记（或刷新）一台被发现的设备，稍后批量写入
+    pub fn record<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        intro: &ArmIpIntro,
+    ) -> Result<RegistryEntry, String> {
+        let now = Local::now().timestamp_millis();
+        let entry = self.with_entries(app, |entries, dirty| {
+            let entry = apply_discovery(entries, intro, now);
+            dirty.insert(entry.id.clone());
+            entry
+        })?;
+        self.schedule_flush(app);
+        Ok(entry)
+    }
+
+    /// 用登记信息补充发现结果（只读，不刷新登记表）
+    pub fn annotate<R: Runtime>(&self, app: &AppHandle<R>, intro: &mut ArmIpIntro) {
+        let entry = self.with_entries(app, |entries, _| find(entries, intro).cloned());
+        if let Ok(Some(entry)) = entry {
+            intro.nickname = entry.nickname;
+            intro.tags = entry.tags;
+        }
+    }
+
+    /// 登记设备并把备注名、标签写回发现结果，失败时原样返回
+    pub fn enrich<R: Runtime>(&self, app: &AppHandle<R>, mut intro: ArmIpIntro) -> ArmIpIntro {
+        match self.record(app, &intro) {
+            Ok(entry) => {
+                intro.nickname = entry.nickname;
+                intro.tags = entry.tags;
+            }
+            Err(e) => error!("Failed to update device registry: {}", e),
+        }
+        intro
+    }
+
+    /// 修改备注名
+    pub fn rename<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        nickname: &str,
+    ) -> Result<RegistryEntry, String> {
+        self.update(app, id, |entry| {
+            entry.nickname = nickname.trim().to_string()
+        })
+    }
+
+    /// 设置标签（去重、去空）
+    pub fn set_tags<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        tags: Vec<String>,
+    ) -> Result<RegistryEntry, String> {
+        self.update(app, id, |entry| entry.tags = normalize_tags(tags))
+    }
+
+    /// 删除登记
+    pub fn forget<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Result<bool, String> {
+        let removed = self.with_entries(app, |entries, dirty| {
+            dirty.remove(id);
+            entries.remove(id).is_some()
+        })?;
+        let store = app.store(REGISTRY_STORE).map_err(|e| e.to_string())?;
+        Ok(store.delete(id) || removed)
+    }
+
+    /// 把 `from` 合并进 `into`（例如维修后更换了序列号），`from` 会被删除
+    pub fn merge<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        from: &str,
+        into: &str,
+    ) -> Result<RegistryEntry, String> {
+        if from == into {
+            return Err("Cannot merge a device into itself".to_string());
+        }
+        let source = self.get(app, from)?;
+        let target = merge_entries(source, self.get(app, into)?);
+        self.put(app, &target)?;
+        self.forget(app, from)?;
+        info!("Merged device {} into {}", from, into);
+        Ok(target)
+    }
+
+    /// 立即写入所有待写的登记（退出前调用）
+    pub fn flush<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        let pending: Vec<RegistryEntry> = {
+            let mut cache = self.cache.lock().map_err(|e| e.to_string())?;
+            cache.flush_scheduled = false;
+            let dirty = std::mem::take(&mut cache.dirty);
+            match &cache.entries {
+                Some(entries) => dirty
+                    .iter()
+                    .filter_map(|id| entries.get(id))
+                    .cloned()
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let store = app.store(REGISTRY_STORE).map_err(|e| e.to_string())?;
+        for entry in &pending {
+            store.set(entry.id.clone(), json!(entry));
+        }
+        store.save().map_err(|e| e.to_string())
+    }
+
+    /// 延迟写入，期间的多次发现回复合并为一次写入
+    fn schedule_flush<R: Runtime>(&self, app: &AppHandle<R>) {
+        match self.cache.lock() {
+            Ok(mut cache) if !cache.flush_scheduled => cache.flush_scheduled = true,
+            _ => return,
+        }
+        let registry = self.clone();
+        let app = app.clone();
+        async_runtime::spawn(async move {
+            tokio::time::sleep(FLUSH_DELAY).await;
+            if let Err(e) = registry.flush(&app) {
+                error!("Failed to save device registry: {}", e);
+            }
+        });
+    }
+
+    fn get<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Result<RegistryEntry, String> {
+        self.with_entries(app, |entries, _| entries.get(id).cloned())?
+            .ok_or_else(|| format!("Device {} is not registered", id))
+    }
+
+    fn update<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        f: impl FnOnce(&mut RegistryEntry),
+    ) -> Result<RegistryEntry, String> {
+        let mut entry = self.get(app, id)?;
+        f(&mut entry);
+        self.put(app, &entry)?;
+        Ok(entry)
+    }
+
+    /// 更新缓存并立即写入 store
+    fn put<R: Runtime>(&self, app: &AppHandle<R>, entry: &RegistryEntry) -> Result<(), String> {
+        self.with_entries(app, |entries, dirty| {
+            dirty.remove(&entry.id);
+            entries.insert(entry.id.clone(), entry.clone());
+        })?;
+        let store = app.store(REGISTRY_STORE).map_err(|e| e.to_string())?;
+        store.set(entry.id.clone(), json!(entry));
+        Ok(())
+    }
+}
+
+/// 从 store 读取全部登记
+fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Entries, String> {
+    let store = app.store(REGISTRY_STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .values()
+        .into_iter()
+        .filter_map(|value| serde_json::from_value::<RegistryEntry>(value).ok())
+        .map(|entry| (entry.id.clone(), entry))
+        .collect())
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !result.iter().any(|t| t == tag) {
+            result.push(tag.to_string());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intro(arm_sn: &str, control_sn: &str, ip: &str) -> ArmIpIntro {
+        ArmIpIntro {
+            arm_sn: arm_sn.to_string(),
+            control_sn: control_sn.to_string(),
+            ip: ip.to_string(),
+            port: "18355".to_string(),
+            version: "2.3.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_new_and_refreshes_known_devices() {
+        let mut entries = Entries::new();
+        let entry = apply_discovery(&mut entries, &intro("XI1", "XS1", "10.0.0.1"), 100);
+        assert_eq!(entry.id, "XI1");
+        assert_eq!((entry.first_seen, entry.last_seen), (100, 100));
+
+        entries.get_mut("XI1").unwrap().nickname = "左臂".to_string();
+        let mut moved = intro("XI1", "XS1", "10.0.0.2");
+        moved.version = "2.4.0".to_string();
+        let entry = apply_discovery(&mut entries, &moved, 200);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entry.nickname, "左臂");
+        assert_eq!(entry.ip, "10.0.0.2");
+        assert_eq!(entry.version, "2.4.0");
+        assert_eq!((entry.first_seen, entry.last_seen), (100, 200));
+    }
+
+    #[test]
+    fn falls_back_to_control_sn() {
+        let mut entries = Entries::new();
+        apply_discovery(&mut entries, &intro("XI1", "XS1", "10.0.0.1"), 100);
+
+        // 更换机械臂本体后 arm_sn 变化，控制器不变，仍归入原登记
+        let entry = apply_discovery(&mut entries, &intro("XI2", "XS1", "10.0.0.1"), 200);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entry.id, "XI1");
+        assert_eq!(entry.arm_sn, "XI2");
+
+        // 回复缺少序列号时不覆盖已登记的值
+        let entry = apply_discovery(&mut entries, &intro("", "XS1", "10.0.0.3"), 300);
+        assert_eq!(entry.arm_sn, "XI2");
+        assert_eq!(entry.ip, "10.0.0.3");
+
+        let entry = apply_discovery(&mut entries, &intro("", "", "10.0.0.9"), 400);
+        assert_eq!(entry.id, "10.0.0.9");
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn prefers_latest_duplicate() {
+        let mut entries = Entries::new();
+        for (id, last_seen) in [("old", 100), ("new", 300), ("mid", 200)] {
+            entries.insert(
+                id.to_string(),
+                RegistryEntry {
+                    id: id.to_string(),
+                    arm_sn: "XI1".to_string(),
+                    last_seen,
+                    ..Default::default()
+                },
+            );
+        }
+        let found = find(&entries, &intro("XI1", "", "10.0.0.1")).unwrap();
+        assert_eq!(found.id, "new");
+        assert!(find(&entries, &intro("XI9", "", "10.0.0.1")).is_none());
+    }
+
+    #[test]
+    fn merges_entries() {
+        let source = RegistryEntry {
+            id: "XI2".to_string(),
+            nickname: "新本体".to_string(),
+            ip: "10.0.0.2".to_string(),
+            first_seen: 50,
+            last_seen: 500,
+            tags: vec!["line-1".to_string(), " cell-a ".to_string()],
+            ..Default::default()
+        };
+        let target = RegistryEntry {
+            id: "XI1".to_string(),
+            ip: "10.0.0.1".to_string(),
+            first_seen: 100,
+            last_seen: 400,
+            tags: vec!["cell-a".to_string(), "".to_string()],
+            ..Default::default()
+        };
+        let merged = merge_entries(source, target);
+        assert_eq!(merged.id, "XI1");
+        assert_eq!(merged.nickname, "新本体");
+        assert_eq!(merged.tags, ["cell-a", "line-1"]);
+        assert_eq!((merged.first_seen, merged.last_seen), (50, 500));
+        assert_eq!(merged.ip, "10.0.0.2");
+    }
+
+    #[test]
+    fn merged_device_keeps_resolving_to_target() {
+        let mut entries = Entries::new();
+        apply_discovery(&mut entries, &intro("XI1", "XS1", "10.0.0.1"), 100);
+        let mut replaced = intro("XI2", "XS2", "10.0.0.2");
+        replaced.device_type = "12".to_string();
+        replaced.axis = "6".to_string();
+        apply_discovery(&mut entries, &replaced, 200);
+        assert_eq!(entries.len(), 2);
+
+        // 合并后两台设备都在回复
+        let source = entries.remove("XI2").unwrap();
+        let merged = merge_entries(source, entries.remove("XI1").unwrap());
+        assert_eq!(merged.aliases, ["XI2", "XS2"]);
+        assert_eq!(
+            (merged.device_type.as_str(), merged.axis.as_str()),
+            ("12", "6")
+        );
+        entries.insert(merged.id.clone(), merged);
+
+        let entry = apply_discovery(&mut entries, &replaced, 300);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entry.id, "XI1");
+        assert_eq!(
+            (entry.arm_sn.as_str(), entry.control_sn.as_str()),
+            ("XI1", "XS1")
+        );
+        assert_eq!(entry.ip, "10.0.0.2");
+        assert_eq!(
+            find(&entries, &intro("", "XS2", "10.0.0.2")).unwrap().id,
+            "XI1"
+        );
+        let entry = apply_discovery(&mut entries, &intro("XI1", "XS1", "10.0.0.1"), 400);
+        assert_eq!((entry.id.as_str(), entry.ip.as_str()), ("XI1", "10.0.0.1"));
+        assert_eq!(entries.len(), 1);
+    }
+}
@@ -0,0 +1,264 @@
+//! 本地服务端点登记
+//!
+//! 启动时为本地服务（tool_service 的 HTTP 接口、WebSocket 桥、本地 API）分配回环端口：首选端口空闲时沿用，
+//! 被其他程序占用时改用系统分配的空闲端口并记录冲突。前端通过 `get_service_endpoints` 查询地址，
+//! 子进程通过环境变量 `UF_<服务名>_PORT` / `UF_<服务名>_URL` 获得各服务地址。
+//! tool_service 按这些变量监听自己的 HTTP 接口和本地 API，启动后检查是否真的在监听（见 sidecar）。
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::packages::sidecar::TOOL_SERVICE;
+use crate::packages::xarm::CONTROLLER_HTTP_PORT;
+use crate::utils::error::AppError;
+use crate::utils::websocket;
+
+/// WebSocket 桥服务名
+pub const WEBSOCKET_BRIDGE: &str = "websocket_bridge";
+/// 本地 API 服务名，由 tool_service 提供给脚本和第三方程序
+pub const LOCAL_API: &str = "local_api";
+
+/// 随应用启动登记的服务：名称、协议、首选端口，0 表示没有固定端口；
+/// tool_service 在本机提供控制器页面，首选与控制器相同的端口
+const DEFAULT_SERVICES: [(&str, &str, u16); 3] = [
+    (TOOL_SERVICE, "http", CONTROLLER_HTTP_PORT),
+    (WEBSOCKET_BRIDGE, "ws", websocket::DEFAULT_PORT),
+    (LOCAL_API, "http", 0),
+];
+/// 系统分配的端口与已登记端口重复时的重试次数
+const ALLOCATE_ATTEMPTS: usize = 8;
+
+/// 服务地址
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ServiceEndpoint {
+    pub name: String,
+    pub scheme: String,
+    pub host: IpAddr,
+    pub port: u16,
+    pub url: String,
+    /// 首选端口被占用时为该端口
+    pub conflict: Option<u16>,
+}
+
+impl ServiceEndpoint {
+    fn new(name: &str, scheme: &str, addr: SocketAddr, conflict: Option<u16>) -> Self {
+        Self {
+            name: name.to_string(),
+            scheme: scheme.to_string(),
+            host: addr.ip(),
+            port: addr.port(),
+            url: format!("{}://{}", scheme, addr),
+            conflict,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+
+    fn env_prefix(&self) -> String {
+        format!("UF_{}", self.name.to_ascii_uppercase())
+    }
+}
+
+/// 端口能否在回环地址上监听
+///
+/// 同时试绑通配地址：Windows 上其他程序监听 `0.0.0.0` 时，回环地址仍能绑定成功，
+/// 但连接会被对方抢走。
+fn is_free(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+        && TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok()
+}
+
+/// 本地服务端点
+#[derive(Clone, Default)]
+pub struct ServiceRegistry {
+    endpoints: Arc<Mutex<BTreeMap<String, ServiceEndpoint>>>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为服务分配回环端口，已登记时直接返回；首选端口被占用或已分给其他服务时改用空闲端口
+    pub fn allocate(
+        &self,
+        name: &str,
+        scheme: &str,
+        preferred: u16,
+    ) -> Result<ServiceEndpoint, AppError> {
+        let mut endpoints = self
+            .endpoints
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        if let Some(endpoint) = endpoints.get(name) {
+            return Ok(endpoint.clone());
+        }
+        let taken = |port: u16| endpoints.values().any(|endpoint| endpoint.port == port);
+
+        let (port, conflict) = if preferred != 0 && !taken(preferred) && is_free(preferred) {
+            (preferred, None)
+        } else {
+            let port = (0..ALLOCATE_ATTEMPTS)
+                .find_map(|_| {
+                    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).ok()?;
+                    let port = listener.local_addr().ok()?.port();
+                    drop(listener);
+                    (!taken(port) && is_free(port)).then_some(port)
+                })
+                .ok_or_else(|| AppError::Io(format!("no free port for {}", name)))?;
+            (port, (preferred != 0).then_some(preferred))
+        };
+        if let Some(preferred) = conflict {
+            warn!(
+                "Port {} for {} is in use, using {} instead",
+                preferred, name, port
+            );
+        }
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+        let endpoint = ServiceEndpoint::new(name, scheme, addr, conflict);
+        info!("Service {} at {}", name, endpoint.url);
+        endpoints.insert(name.to_string(), endpoint.clone());
+        Ok(endpoint)
+    }
+
+    /// 启动时为内置服务分配端口，失败的服务不登记
+    pub fn allocate_defaults(&self) -> Vec<ServiceEndpoint> {
+        DEFAULT_SERVICES
+            .iter()
+            .filter_map(
+                |(name, scheme, preferred)| match self.allocate(name, scheme, *preferred) {
+                    Ok(endpoint) => Some(endpoint),
+                    Err(e) => {
+                        warn!("Failed to allocate port for {}: {}", name, e);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<ServiceEndpoint> {
+        self.endpoints.lock().ok()?.get(name).cloned()
+    }
+
+    /// 服务实际监听的地址与分配的不同时（如用户指定端口）更新登记
+    pub fn update(&self, name: &str, addr: SocketAddr) {
+        if let Ok(mut endpoints) = self.endpoints.lock() {
+            let scheme = endpoints
+                .get(name)
+                .map(|endpoint| endpoint.scheme.clone())
+                .unwrap_or_else(|| "tcp".to_string());
+            endpoints.insert(
+                name.to_string(),
+                ServiceEndpoint::new(name, &scheme, addr, None),
+            );
+        }
+    }
+
+    pub fn list(&self) -> Vec<ServiceEndpoint> {
+        self.endpoints
+            .lock()
+            .map(|endpoints| endpoints.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 传给子进程的环境变量，如 `UF_TOOL_SERVICE_PORT=18333`
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.list()
+            .iter()
+            .flat_map(|endpoint| {
+                let prefix = endpoint.env_prefix();
+                [
+                    (format!("{}_PORT", prefix), endpoint.port.to_string()),
+                    (format!("{}_URL", prefix), endpoint.url.clone()),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_free_ports() {
+        let registry = ServiceRegistry::new();
+        // 占用首选端口，模拟其他厂商的工具
+        let busy = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let busy_port = busy.local_addr().unwrap().port();
+
+        let first = registry.allocate("first", "http", busy_port).unwrap();
+        assert_ne!(first.port, busy_port);
+        assert_eq!(first.conflict, Some(busy_port));
+        assert!(is_free(first.port));
+        drop(busy);
+
+        // 首选端口已分给其他服务
+        let second = registry.allocate("second", "ws", first.port).unwrap();
+        assert_ne!(second.port, first.port);
+        assert_eq!(registry.allocate("first", "http", 1).unwrap(), first);
+
+        let free = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let third = registry.allocate("third", "http", free).unwrap();
+        assert_eq!(third.port, free);
+        assert_eq!(third.conflict, None);
+        assert_eq!(third.url, format!("http://127.0.0.1:{}", free));
+        assert_eq!(registry.list().len(), 3);
+    }
+
+    #[test]
+    fn treats_wildcard_listener_as_busy() {
+        let registry = ServiceRegistry::new();
+        let busy = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        let busy_port = busy.local_addr().unwrap().port();
+        assert!(!is_free(busy_port));
+        let endpoint = registry.allocate("tool", "http", busy_port).unwrap();
+        assert_eq!(endpoint.conflict, Some(busy_port));
+    }
+
+    #[test]
+    fn allocates_default_services() {
+        let registry = ServiceRegistry::new();
+        let endpoints = registry.allocate_defaults();
+        let names: Vec<&str> = endpoints.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, [TOOL_SERVICE, WEBSOCKET_BRIDGE, LOCAL_API]);
+        let mut ports: Vec<u16> = endpoints.iter().map(|e| e.port).collect();
+        ports.sort();
+        ports.dedup();
+        assert_eq!(ports.len(), 3);
+        let local_api = registry.get(LOCAL_API).unwrap();
+        assert_eq!(local_api.conflict, None);
+        assert_eq!(
+            local_api.addr(),
+            format!("127.0.0.1:{}", local_api.port).parse().unwrap()
+        );
+        assert!(registry
+            .env()
+            .contains(&("UF_LOCAL_API_PORT".to_string(), local_api.port.to_string())));
+    }
+
+    #[test]
+    fn exports_environment() {
+        let registry = ServiceRegistry::new();
+        registry.update(TOOL_SERVICE, "127.0.0.1:18400".parse().unwrap());
+        registry.allocate(WEBSOCKET_BRIDGE, "ws", 0).unwrap();
+        registry.update(WEBSOCKET_BRIDGE, "127.0.0.1:18401".parse().unwrap());
+        let env = registry.env();
+        assert!(env.contains(&("UF_TOOL_SERVICE_PORT".to_string(), "18400".to_string())));
+        assert!(env.contains(&(
+            "UF_WEBSOCKET_BRIDGE_URL".to_string(),
+            "ws://127.0.0.1:18401".to_string()
+        )));
+    }
+}
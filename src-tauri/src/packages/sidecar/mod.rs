@@ -0,0 +1,811 @@
+//! 本地服务进程（sidecar）守护
+//!
+//! 启动 `tool_service` 等子进程，逐行转发 stdout/stderr 到日志（目标 `tool_service_stdout`），
+//! 异常退出后按指数退避重启，运行超过 `stable_after` 后退避时间复位。
+//! 启动后探测 `ready_addrs` 中分配给它的端口，都能连上才算就绪；超时未监听时在状态中提示。
+//! 状态变化通过 `service_state` 事件推送。
+//!
+//! 子进程在独立的进程组中运行（Windows 上放入 Job Object），停止时先请求退出，
+//! 超时后强制结束整个进程树，避免遗留孙进程占用端口。进程组只在组长尚未回收时发信号，
+//! 此时进程号不会被复用，不会误杀无关进程。
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Emitter, Runtime};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::{oneshot, watch};
+use tokio::time;
+
+use crate::utils::error::AppError;
+use crate::utils::stop::stopped;
+
+/// 服务状态变化
+pub const EVENT_SERVICE_STATE: &str = "service_state";
+
+/// Python 本地服务名
+pub const TOOL_SERVICE: &str = "tool_service";
+/// tool_service 输出的日志目标，`app_log` 按该前缀加 python 标记
+pub const TOOL_SERVICE_LOG_TARGET: &str = "tool_service_stdout";
+/// 指定 tool_service 可执行文件的环境变量，开发时可指向脚本
+const TOOL_SERVICE_ENV: &str = "UF_TOOL_SERVICE";
+/// 就绪探测间隔
+const READY_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 服务状态
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    Starting,
+    Running,
+    /// 异常退出，等待重启
+    Backoff,
+    Stopped,
+    /// 找不到可执行文件等无法启动的情况，不再重试
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ServiceState {
+    pub service: String,
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    /// 已在分配的端口上监听
+    pub ready: bool,
+    /// 自动重启次数
+    pub restarts: u32,
+    /// 上次退出码，被信号结束时为空
+    pub exit_code: Option<i32>,
+    pub message: Option<String>,
+    /// 距下次重启的时间
+    pub retry_in_ms: Option<u64>,
+}
+
+impl ServiceState {
+    fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            status: ServiceStatus::Stopped,
+            pid: None,
+            ready: false,
+            restarts: 0,
+            exit_code: None,
+            message: None,
+            retry_in_ms: None,
+        }
+    }
+}
+
+/// 子进程参数
+#[derive(Clone, Debug)]
+pub struct SidecarConfig {
+    pub name: String,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub envs: Vec<(String, String)>,
+    pub current_dir: Option<PathBuf>,
+    /// 输出转发的日志目标，stderr 追加 `::stderr`
+    pub log_target: String,
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    /// 运行超过该时长视为稳定，退避时间复位
+    pub stable_after: Duration,
+    /// 停止时等待进程退出的时长，超时强制结束
+    pub stop_timeout: Duration,
+    /// 服务应监听的地址，为空时进程启动即视为就绪
+    pub ready_addrs: Vec<SocketAddr>,
+    /// 启动后等待监听的时长
+    pub ready_timeout: Duration,
+}
+
+impl SidecarConfig {
+    pub fn new(name: &str, program: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            program,
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+            log_target: format!("{}_stdout", name),
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            stable_after: Duration::from_secs(60),
+            stop_timeout: Duration::from_secs(5),
+            ready_addrs: Vec::new(),
+            ready_timeout: Duration::from_secs(15),
+        }
+    }
+
+    /// tool_service：环境变量 `UF_TOOL_SERVICE` 指定的程序，否则为主程序同目录下的 `tool_service`
+    pub fn tool_service() -> Result<Self, AppError> {
+        let program = match std::env::var_os(TOOL_SERVICE_ENV) {
+            Some(program) => PathBuf::from(program),
+            None => std::env::current_exe()?
+                .parent()
+                .map(|dir| dir.join(format!("{}{}", TOOL_SERVICE, std::env::consts::EXE_SUFFIX)))
+                .ok_or_else(|| AppError::Io("executable has no parent directory".to_string()))?,
+        };
+        let mut config = Self::new(TOOL_SERVICE, program);
+        config.log_target = TOOL_SERVICE_LOG_TARGET.to_string();
+        config.current_dir = config.program.parent().map(PathBuf::from);
+        // 管道输出时 Python 默认整块缓冲
+        config
+            .envs
+            .push(("PYTHONUNBUFFERED".to_string(), "1".to_string()));
+        Ok(config)
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        #[cfg(unix)]
+        command.process_group(0);
+        #[cfg(windows)]
+        {
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+        command
+    }
+}
+
+type StateFn = Arc<dyn Fn(&ServiceState) + Send + Sync>;
+/// 一行输出，参数为是否来自 stderr
+type OutputFn = Arc<dyn Fn(bool, &str) + Send + Sync>;
+
+struct Supervisor {
+    config: SidecarConfig,
+    state: Arc<Mutex<ServiceState>>,
+    on_state: StateFn,
+    on_output: OutputFn,
+}
+
+impl Supervisor {
+    fn update(&self, change: impl FnOnce(&mut ServiceState)) {
+        let snapshot = match self.state.lock() {
+            Ok(mut state) => {
+                change(&mut state);
+                state.clone()
+            }
+            Err(_) => return,
+        };
+        (self.on_state)(&snapshot);
+    }
+}
+
+struct Running {
+    stop_tx: watch::Sender<bool>,
+    /// 守护任务结束时释放
+    done_rx: oneshot::Receiver<()>,
+    stop_timeout: Duration,
+}
+
+/// 单个本地服务的守护
+#[derive(Clone, Default)]
+pub struct Sidecar {
+    running: Arc<Mutex<Option<Running>>>,
+    state: Arc<Mutex<Option<ServiceState>>>,
+}
+
+impl Sidecar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动服务并守护，已在运行时先停止
+    pub async fn start<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        config: SidecarConfig,
+    ) -> Result<(), AppError> {
+        let emitter = app.clone();
+        let target = config.log_target.clone();
+        let stderr_target = format!("{}::stderr", target);
+        self.launch(
+            config,
+            Arc::new(move |state| {
+                let _ = emitter.emit(EVENT_SERVICE_STATE, state.clone());
+            }),
+            Arc::new(move |stderr, line| {
+                let target: &str = if stderr { &stderr_target } else { &target };
+                info!(target: target, "{}", line);
+            }),
+        )
+        .await
+    }
+
+    async fn launch(
+        &self,
+        config: SidecarConfig,
+        on_state: StateFn,
+        on_output: OutputFn,
+    ) -> Result<(), AppError> {
+        self.stop().await;
+
+        let state = Arc::new(Mutex::new(ServiceState::new(&config.name)));
+        if !config.program.is_file() {
+            let message = format!("{} not found", config.program.display());
+            warn!("Service {} not started: {}", config.name, message);
+            let mut failed = ServiceState::new(&config.name);
+            failed.status = ServiceStatus::Failed;
+            failed.message = Some(message.clone());
+            on_state(&failed);
+            self.set_state(Some(failed));
+            return Err(AppError::Io(message));
+        }
+
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let (done_tx, done_rx) = oneshot::channel();
+        let stop_timeout = config.stop_timeout;
+        let shared = Arc::clone(&self.state);
+        let supervisor = Supervisor {
+            config,
+            state: Arc::clone(&state),
+            on_state: Arc::new(move |state| {
+                if let Ok(mut shared) = shared.lock() {
+                    *shared = Some(state.clone());
+                }
+                on_state(state);
+            }),
+            on_output,
+        };
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some(Running {
+                stop_tx,
+                done_rx,
+                stop_timeout,
+            });
+        }
+        async_runtime::spawn(async move {
+            supervise(supervisor, stop_rx).await;
+            drop(done_tx);
+        });
+        Ok(())
+    }
+
+    /// 停止服务及其子进程，等待守护任务结束
+    pub async fn stop(&self) -> bool {
+        let running = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|mut running| running.take());
+        let Some(running) = running else {
+            return false;
+        };
+        let _ = running.stop_tx.send(true);
+        // 强制结束后再留一点时间回收
+        let wait = running.stop_timeout + Duration::from_secs(2);
+        if time::timeout(wait, running.done_rx).await.is_err() {
+            warn!("Service supervisor did not stop in {:?}", wait);
+        }
+        true
+    }
+
+    /// 当前状态，从未启动时为空
+    pub fn state(&self) -> Option<ServiceState> {
+        self.state.lock().ok().and_then(|state| state.clone())
+    }
+
+    fn set_state(&self, state: Option<ServiceState>) {
+        if let Ok(mut shared) = self.state.lock() {
+            *shared = state;
+        }
+    }
+}
+
+async fn supervise(supervisor: Supervisor, mut stop_rx: watch::Receiver<bool>) {
+    let config = &supervisor.config;
+    let mut backoff = config.min_backoff;
+    let mut restart = false;
+    loop {
+        supervisor.update(|state| {
+            if restart {
+                state.restarts += 1;
+            }
+            state.status = ServiceStatus::Starting;
+            state.pid = None;
+            state.ready = false;
+            state.retry_in_ms = None;
+        });
+        let started = Instant::now();
+        match config.command().spawn() {
+            Ok(child) => {
+                if run(&supervisor, ProcessTree::new(child), &mut stop_rx).await {
+                    break;
+                }
+                if started.elapsed() >= config.stable_after {
+                    backoff = config.min_backoff;
+                }
+            }
+            Err(e) => {
+                error!("Failed to start service {}: {}", config.name, e);
+                supervisor.update(|state| state.message = Some(e.to_string()));
+            }
+        }
+
+        supervisor.update(|state| {
+            state.status = ServiceStatus::Backoff;
+            state.pid = None;
+            state.ready = false;
+            state.retry_in_ms = Some(backoff.as_millis() as u64);
+        });
+        tokio::select! {
+            _ = stopped(&mut stop_rx) => break,
+            _ = time::sleep(backoff) => {}
+        }
+        backoff = (backoff * 2).min(config.max_backoff);
+        restart = true;
+    }
+    supervisor.update(|state| {
+        state.status = ServiceStatus::Stopped;
+        state.pid = None;
+        state.ready = false;
+        state.retry_in_ms = None;
+    });
+    info!("Service {} stopped", config.name);
+}
+
+/// 运行一次子进程直到退出，收到停止信号时返回 `true`
+async fn run(
+    supervisor: &Supervisor,
+    mut tree: ProcessTree,
+    stop_rx: &mut watch::Receiver<bool>,
+) -> bool {
+    let config = &supervisor.config;
+    let pid = tree.pid;
+    if let Some(stdout) = tree.child.stdout.take() {
+        tokio::spawn(forward(stdout, false, Arc::clone(&supervisor.on_output)));
+    }
+    if let Some(stderr) = tree.child.stderr.take() {
+        tokio::spawn(forward(stderr, true, Arc::clone(&supervisor.on_output)));
+    }
+    info!("Service {} started, pid {:?}", config.name, pid);
+    supervisor.update(|state| {
+        state.status = ServiceStatus::Running;
+        state.pid = pid;
+        state.message = None;
+    });
+
+    let ready = wait_ready(&config.ready_addrs, config.ready_timeout);
+    tokio::pin!(ready);
+    let mut probing = true;
+    loop {
+        tokio::select! {
+            _ = stopped(stop_rx) => {
+                tree.terminate(config.stop_timeout).await;
+                return true;
+            }
+            status = tree.wait() => {
+                let (exit_code, message) = match status {
+                    Ok(status) => (status.code(), format!("exited with {}", status)),
+                    Err(e) => (None, e.to_string()),
+                };
+                warn!("Service {} {}", config.name, message);
+                supervisor.update(|state| {
+                    state.exit_code = exit_code;
+                    state.message = Some(message);
+                });
+                return false;
+            }
+            missing = &mut ready, if probing => {
+                probing = false;
+                match missing {
+                    None => {
+                        info!("Service {} is ready", config.name);
+                        supervisor.update(|state| state.ready = true);
+                    }
+                    // 进程仍在运行，只提示端口不对，不重启
+                    Some(addr) => {
+                        let message = format!(
+                            "not listening on {} after {:?}",
+                            addr, config.ready_timeout
+                        );
+                        warn!("Service {} {}", config.name, message);
+                        supervisor.update(|state| state.message = Some(message));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 等待 `addrs` 都能连上，返回超时时仍连不上的第一个地址
+async fn wait_ready(addrs: &[SocketAddr], timeout: Duration) -> Option<SocketAddr> {
+    let deadline = time::Instant::now() + timeout;
+    for &addr in addrs {
+        loop {
+            if TcpStream::connect(addr).await.is_ok() {
+                break;
+            }
+            if time::Instant::now() >= deadline {
+                return Some(addr);
+            }
+            time::sleep(READY_INTERVAL).await;
+        }
+    }
+    None
+}
+
+async fn forward<T: AsyncRead + Unpin>(stream: T, stderr: bool, on_output: OutputFn) {
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&line);
+                let text = text.trim_end();
+                if !text.is_empty() {
+                    on_output(stderr, text);
+                }
+            }
+        }
+    }
+}
+
+/// 子进程及其后代
+struct ProcessTree {
+    child: Child,
+    pid: Option<u32>,
+    #[cfg(windows)]
+    job: Option<job::KillOnClose>,
+}
+
+impl ProcessTree {
+    fn new(child: Child) -> Self {
+        let pid = child.id();
+        #[cfg(windows)]
+        let job = match job::KillOnClose::assign(&child) {
+            Ok(job) => Some(job),
+            Err(e) => {
+                warn!("Failed to put process {:?} in a job object: {}", pid, e);
+                None
+            }
+        };
+        Self {
+            child,
+            pid,
+            #[cfg(windows)]
+            job,
+        }
+    }
+
+    /// 等待子进程退出，结束残留的后代后回收
+    async fn wait(&mut self) -> io::Result<ExitStatus> {
+        #[cfg(unix)]
+        if let Some(pid) = self.pid {
+            exited(pid).await;
+            signal_group(pid, true).await;
+        }
+        #[cfg(windows)]
+        drop(self.job.take());
+        self.child.wait().await
+    }
+
+    /// 请求进程树退出，超时后强制结束并回收
+    async fn terminate(&mut self, timeout: Duration) {
+        #[cfg(unix)]
+        if let Some(pid) = self.pid {
+            signal_group(pid, false).await;
+            if time::timeout(timeout, exited(pid)).await.is_err() {
+                warn!("Process {} did not exit in {:?}, killing it", pid, timeout);
+            }
+            signal_group(pid, true).await;
+        }
+        // Windows 控制台程序不响应关闭请求，直接关闭 Job Object 结束进程树
+        #[cfg(windows)]
+        drop(self.job.take());
+        let _ = self.child.kill().await;
+    }
+}
+
+/// 等待进程退出但不回收，进程号在回收前不会被复用
+#[cfg(unix)]
+async fn exited(pid: u32) {
+    let _ = tokio::task::spawn_blocking(move || loop {
+        // SAFETY: siginfo_t 是普通数据，全零是合法值；waitid 只写入该结构
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return;
+        }
+    })
+    .await;
+}
+
+/// 向以 `pid` 为首的进程组发送 SIGTERM / SIGKILL，调用方保证组长尚未回收
+#[cfg(unix)]
+async fn signal_group(pid: u32, force: bool) {
+    let signal = if force { "KILL" } else { "TERM" };
+    let _ = Command::new("kill")
+        .args(["-s", signal, "--", &format!("-{}", pid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+}
+
+#[cfg(windows)]
+mod job {
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem;
+    use std::ptr;
+
+    use tokio::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// 关闭时结束其中所有进程的 Job Object，子进程创建的进程默认也在其中
+    pub struct KillOnClose(HANDLE);
+
+    // SAFETY: Job Object 句柄可以在线程间传递
+    unsafe impl Send for KillOnClose {}
+    unsafe impl Sync for KillOnClose {}
+
+    impl KillOnClose {
+        pub fn assign(child: &Child) -> io::Result<Self> {
+            let process = child
+                .raw_handle()
+                .ok_or_else(|| io::Error::other("process already exited"))?;
+            // SAFETY: 参数均为有效指针或空指针，句柄由 KillOnClose 负责关闭
+            unsafe {
+                let handle = CreateJobObjectW(ptr::null(), ptr::null());
+                if handle.is_null() {
+                    return Err(io::Error::last_os_error());
+                }
+                let job = Self(handle);
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = mem::zeroed();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                if SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const c_void,
+                    mem::size_of_val(&info) as u32,
+                ) == 0
+                    || AssignProcessToJobObject(job.0, process as HANDLE) == 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(job)
+            }
+        }
+    }
+
+    impl Drop for KillOnClose {
+        fn drop(&mut self) {
+            // SAFETY: 句柄由 CreateJobObjectW 创建且只关闭一次
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    /// 在临时目录生成代替 tool_service 的脚本
+    fn script(name: &str, body: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sidecar-{}-{}.sh", name, std::process::id()));
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn config(name: &str, program: PathBuf) -> SidecarConfig {
+        let mut config = SidecarConfig::new(name, program);
+        config.min_backoff = Duration::from_millis(50);
+        config.max_backoff = Duration::from_millis(200);
+        config.stop_timeout = Duration::from_secs(1);
+        config
+    }
+
+    type Recorded = (
+        Arc<Mutex<Vec<ServiceState>>>,
+        Arc<Mutex<Vec<(bool, String)>>>,
+    );
+
+    async fn launch(sidecar: &Sidecar, config: SidecarConfig) -> Recorded {
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let (state_sink, line_sink) = (Arc::clone(&states), Arc::clone(&lines));
+        sidecar
+            .launch(
+                config,
+                Arc::new(move |state| state_sink.lock().unwrap().push(state.clone())),
+                Arc::new(move |stderr, line| {
+                    line_sink.lock().unwrap().push((stderr, line.to_string()))
+                }),
+            )
+            .await
+            .unwrap();
+        (states, lines)
+    }
+
+    async fn wait_for(what: &str, condition: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("timed out waiting for {}", what);
+    }
+
+    /// 进程存在且不是僵尸进程（容器里 init 不一定回收）
+    fn alive(pid: &str) -> bool {
+        fs::read_to_string(format!("/proc/{}/stat", pid)).is_ok_and(|stat| {
+            stat.rsplit(')')
+                .next()
+                .is_some_and(|rest| !rest.trim_start().starts_with('Z'))
+        })
+    }
+
+    #[tokio::test]
+    async fn restarts_with_backoff_and_forwards_output() {
+        let program = script("crash", "echo started $SIDECAR_MARK\necho oops >&2\nexit 3");
+        let mut config = config("crash", program.clone());
+        config
+            .envs
+            .push(("SIDECAR_MARK".to_string(), "ok".to_string()));
+        let sidecar = Sidecar::new();
+        let (states, lines) = launch(&sidecar, config).await;
+
+        wait_for("restarts", || {
+            sidecar.state().is_some_and(|state| state.restarts >= 3)
+        })
+        .await;
+        assert!(sidecar.stop().await);
+        fs::remove_file(&program).unwrap();
+
+        let states = states.lock().unwrap();
+        let delays: Vec<u64> = states.iter().filter_map(|s| s.retry_in_ms).collect();
+        assert_eq!(delays[..3], [50, 100, 200]);
+        assert!(states.iter().any(|s| s.exit_code == Some(3)));
+        assert_eq!(states.last().unwrap().status, ServiceStatus::Stopped);
+        let lines = lines.lock().unwrap();
+        assert!(lines.contains(&(false, "started ok".to_string())));
+        assert!(lines.contains(&(true, "oops".to_string())));
+    }
+
+    #[tokio::test]
+    async fn stops_process_tree() {
+        // 子进程忽略 SIGTERM，只能强制结束
+        let pid_file = std::env::temp_dir().join(format!("sidecar-tree-{}", std::process::id()));
+        let program = script(
+            "tree",
+            &format!(
+                "sh -c 'trap \"\" TERM; while true; do sleep 1; done' &\necho $! > {}\nwait",
+                pid_file.display()
+            ),
+        );
+        let sidecar = Sidecar::new();
+        let (states, _) = launch(&sidecar, config("tree", program.clone())).await;
+        wait_for("grandchild", || pid_file.exists()).await;
+        wait_for("pid", || {
+            fs::read_to_string(&pid_file).is_ok_and(|pid| !pid.trim().is_empty())
+        })
+        .await;
+        let grandchild = fs::read_to_string(&pid_file).unwrap().trim().to_string();
+        assert!(alive(&grandchild));
+
+        assert!(sidecar.stop().await);
+        wait_for("grandchild to exit", || !alive(&grandchild)).await;
+        fs::remove_file(&program).unwrap();
+        fs::remove_file(&pid_file).unwrap();
+        let states = states.lock().unwrap();
+        assert_eq!(states.last().unwrap().status, ServiceStatus::Stopped);
+        assert_eq!(states.last().unwrap().restarts, 0);
+    }
+
+    #[tokio::test]
+    async fn kills_leftovers_when_service_exits() {
+        let pid_file = std::env::temp_dir().join(format!("sidecar-exit-{}", std::process::id()));
+        let program = script(
+            "exit",
+            &format!(
+                "sh -c 'trap \"\" TERM; while true; do sleep 1; done' &\necho $! > {}.tmp\nmv {}.tmp {}\nsleep 0.3",
+                pid_file.display(),
+                pid_file.display(),
+                pid_file.display()
+            ),
+        );
+        let sidecar = Sidecar::new();
+        launch(&sidecar, config("exit", program.clone())).await;
+        wait_for("grandchild", || pid_file.exists()).await;
+        let grandchild = fs::read_to_string(&pid_file).unwrap().trim().to_string();
+
+        wait_for("restart", || {
+            sidecar.state().is_some_and(|state| state.restarts >= 1)
+        })
+        .await;
+        assert!(!alive(&grandchild));
+        assert!(sidecar.stop().await);
+        fs::remove_file(&program).unwrap();
+        let _ = fs::remove_file(&pid_file);
+    }
+
+    #[tokio::test]
+    async fn reports_readiness_on_assigned_port() {
+        let program = script("listen", "sleep 30");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = {
+            let unused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            unused.local_addr().unwrap()
+        };
+
+        // 在分配的端口上监听
+        let mut listening = config("listen", program.clone());
+        listening.ready_addrs = vec![listener.local_addr().unwrap()];
+        let sidecar = Sidecar::new();
+        launch(&sidecar, listening).await;
+        wait_for("ready", || sidecar.state().is_some_and(|state| state.ready)).await;
+        assert!(sidecar.stop().await);
+        assert!(!sidecar.state().unwrap().ready);
+
+        // 没有监听分配的端口：仍在运行，但给出提示
+        let mut silent = config("listen", program.clone());
+        silent.ready_addrs = vec![closed];
+        silent.ready_timeout = Duration::from_millis(300);
+        launch(&sidecar, silent).await;
+        wait_for("readiness timeout", || {
+            sidecar
+                .state()
+                .and_then(|state| state.message)
+                .is_some_and(|message| message.contains(&closed.to_string()))
+        })
+        .await;
+        let state = sidecar.state().unwrap();
+        assert_eq!(state.status, ServiceStatus::Running);
+        assert!(!state.ready);
+        assert!(sidecar.stop().await);
+        fs::remove_file(&program).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fails_without_program() {
+        let sidecar = Sidecar::new();
+        let config = config("missing", PathBuf::from("/nonexistent/tool_service"));
+        assert!(sidecar
+            .launch(config, Arc::new(|_| {}), Arc::new(|_, _| {}))
+            .await
+            .is_err());
+        assert_eq!(sidecar.state().unwrap().status, ServiceStatus::Failed);
+        assert!(!sidecar.stop().await);
+    }
+}
@@ -0,0 +1,242 @@
+use std::f32::consts::PI;
+
+use super::SimConfig;
+
+/// 运动中
+pub const STATE_MOVING: u8 = 1;
+/// 就绪（无运动指令）
+pub const STATE_READY: u8 = 2;
+/// 暂停
+pub const STATE_PAUSED: u8 = 3;
+/// 停止
+pub const STATE_STOPPED: u8 = 4;
+
+/// 关节最大速度（rad/s）
+const MAX_JOINT_SPEED: f32 = PI / 4.0;
+/// 演示动作周期（秒）
+const DEMO_PERIOD: f32 = 12.0;
+
+/// 缩减模式与安全边界设置
+#[derive(Clone, Debug)]
+pub struct SimReduced {
+    pub enabled: bool,
+    pub tcp_speed: f32,
+    pub joint_speed: f32,
+    /// 7 个关节的 `min, max`
+    pub joint_ranges: [f32; 14],
+    /// x+/x-/y+/y-/z+/z-（mm）
+    pub boundary: [i32; 6],
+    pub fence: bool,
+    pub collision_rebound: bool,
+}
+
+impl Default for SimReduced {
+    fn default() -> Self {
+        let mut joint_ranges = [0.0; 14];
+        for range in joint_ranges.chunks_exact_mut(2) {
+            range.copy_from_slice(&[-2.0 * PI, 2.0 * PI]);
+        }
+        Self {
+            enabled: false,
+            tcp_speed: 250.0,
+            joint_speed: PI / 3.0,
+            joint_ranges,
+            boundary: [999, -999, 999, -999, 999, -999],
+            fence: false,
+            collision_rebound: true,
+        }
+    }
+}
+
+/// 虚拟机械臂状态
+#[derive(Clone, Debug)]
+pub struct SimArm {
+    pub axis: usize,
+    pub state: u8,
+    pub mode: u8,
+    pub motion_enabled: bool,
+    pub error_code: u8,
+    pub warn_code: u8,
+    /// 缓存中的运动指令数
+    pub cmd_num: u16,
+    /// 关节角（rad），未使用的关节为 0
+    pub joints: [f32; 7],
+    /// 关节速度（rad/s）
+    pub speeds: [f32; 7],
+    /// 运动目标，为空时按 `animate` 决定是否自动演示
+    pub target: Option<[f32; 7]>,
+    pub animate: bool,
+    pub tcp_offset: [f32; 6],
+    pub tcp_load: [f32; 4],
+    pub collision_sens: u8,
+    pub teach_sens: u8,
+    pub gravity_direction: [f32; 3],
+    pub reduced: SimReduced,
+    pub world_offset: [f32; 6],
+    /// 控制器 IO 输入、输出功能配置
+    pub cgpio_inputs: [u8; 16],
+    pub cgpio_outputs: [u8; 16],
+    /// 运行时长（秒），用于演示动作
+    elapsed: f32,
+}
+
+impl SimArm {
+    pub fn new(config: &SimConfig) -> Self {
+        Self {
+            axis: config.axis.clamp(1, 7) as usize,
+            state: STATE_READY,
+            mode: 0,
+            motion_enabled: true,
+            error_code: 0,
+            warn_code: 0,
+            cmd_num: 0,
+            joints: [0.0; 7],
+            speeds: [0.0; 7],
+            target: None,
+            animate: config.animate,
+            tcp_offset: [0.0; 6],
+            tcp_load: [0.0; 4],
+            collision_sens: 3,
+            teach_sens: 3,
+            gravity_direction: [0.0, 0.0, -1.0],
+            reduced: SimReduced::default(),
+            world_offset: [0.0; 6],
+            cgpio_inputs: [0; 16],
+            cgpio_outputs: [0; 16],
+            elapsed: 0.0,
+        }
+    }
+
+    /// 能否执行运动
+    fn can_move(&self) -> bool {
+        self.motion_enabled && self.error_code == 0 && self.state != STATE_STOPPED
+    }
+
+    /// 推进 `dt` 秒
+    pub fn step(&mut self, dt: f32) {
+        let previous = self.joints;
+        if self.can_move() && self.state != STATE_PAUSED {
+            self.elapsed += dt;
+            match self.target {
+                Some(target) => self.move_towards(&target, dt),
+                None if self.animate => self.animate_demo(),
+                None => {}
+            }
+        }
+        for ((speed, joint), previous) in self.speeds.iter_mut().zip(self.joints).zip(previous) {
+            *speed = if dt > 0.0 {
+                (joint - previous) / dt
+            } else {
+                0.0
+            };
+        }
+    }
+
+    fn move_towards(&mut self, target: &[f32; 7], dt: f32) {
+        let max_step = MAX_JOINT_SPEED * dt;
+        let mut reached = true;
+        for (joint, target) in self.joints.iter_mut().zip(target).take(self.axis) {
+            let delta = target - *joint;
+            if delta.abs() > max_step {
+                *joint += max_step.copysign(delta);
+                reached = false;
+            } else {
+                *joint = *target;
+            }
+        }
+        self.state = STATE_MOVING;
+        if reached {
+            self.target = None;
+            self.cmd_num = self.cmd_num.saturating_sub(1);
+            if self.cmd_num == 0 {
+                self.state = STATE_READY;
+            }
+        }
+    }
+
+    /// 各关节以不同相位做小幅正弦摆动
+    fn animate_demo(&mut self) {
+        let phase = 2.0 * PI * self.elapsed / DEMO_PERIOD;
+        for i in 0..self.axis {
+            let amplitude = if i == 1 || i == 2 { 0.35 } else { 0.6 };
+            self.joints[i] = amplitude * (phase + i as f32 * PI / 3.0).sin();
+        }
+        self.state = STATE_MOVING;
+    }
+
+    /// 设置运动目标
+    pub fn move_joints(&mut self, target: [f32; 7]) -> bool {
+        if !self.can_move() {
+            return false;
+        }
+        self.target = Some(target);
+        self.cmd_num = self.cmd_num.saturating_add(1);
+        self.state = STATE_MOVING;
+        true
+    }
+
+    /// 对应 `set_state`：0 运动，3 暂停，4 停止
+    pub fn set_state(&mut self, state: u8) {
+        match state {
+            0 if self.state != STATE_MOVING => self.state = STATE_READY,
+            STATE_PAUSED => self.state = STATE_PAUSED,
+            STATE_STOPPED => {
+                self.state = STATE_STOPPED;
+                self.target = None;
+                self.cmd_num = 0;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn set_motion_enabled(&mut self, enabled: bool) {
+        self.motion_enabled = enabled;
+        if !enabled {
+            self.target = None;
+            self.cmd_num = 0;
+            self.state = STATE_STOPPED;
+        }
+    }
+
+    pub fn clear_error(&mut self) {
+        self.error_code = 0;
+    }
+
+    pub fn clear_warn(&mut self) {
+        self.warn_code = 0;
+    }
+
+    /// TCP 位姿（mm / rad），按简化的串联模型近似，仅用于演示
+    pub fn tcp_pose(&self) -> [f32; 6] {
+        let [j1, j2, j3, _, j5, j6, _] = self.joints;
+        let reach = 207.0 + 289.0 * j2.sin() + 300.0 * (j2 + j3).cos();
+        let height = 267.0 + 289.0 * j2.cos() - 300.0 * (j2 + j3).sin();
+        [
+            reach * j1.cos(),
+            reach * j1.sin(),
+            height,
+            PI,
+            j5 * 0.5,
+            j1 + j6,
+        ]
+    }
+
+    /// 关节力矩（N·m），按重力项近似
+    pub fn torques(&self) -> [f32; 7] {
+        let mut torques = [0.0; 7];
+        for (i, torque) in torques.iter_mut().enumerate().take(self.axis) {
+            let load = match i {
+                1 => 18.0,
+                2 => 9.0,
+                _ => 1.5,
+            };
+            *torque = load * self.joints[i].cos() + 0.2 * self.speeds[i];
+        }
+        torques
+    }
+
+    /// 关节电流（A），与力矩成正比
+    pub fn currents(&self) -> [f32; 7] {
+        self.torques().map(|torque| torque * 0.12)
+    }
+}
@@ -0,0 +1,395 @@
+//! 指令端口（502）模拟，帧格式见 `xarm::protocol`
+
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use super::arm::SimArm;
+use super::SimConfig;
+use crate::packages::xarm::protocol::{
+    self, register::*, Header, HEADER_LEN, STATUS_ERROR, STATUS_WARN,
+};
+
+/// 接受连接，每个连接一个任务
+pub(crate) async fn serve(
+    listener: TcpListener,
+    config: SimConfig,
+    arm: Arc<Mutex<SimArm>>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    info!("Simulator command connection from {}", peer);
+                    connections.spawn(handle_connection(stream, config.clone(), Arc::clone(&arm)));
+                }
+                Err(e) => warn!("Simulator failed to accept command connection: {}", e),
+            },
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, config: SimConfig, arm: Arc<Mutex<SimArm>>) {
+    let mut header = [0u8; HEADER_LEN];
+    loop {
+        if stream.read_exact(&mut header).await.is_err() {
+            return;
+        }
+        let header = match Header::parse(&header) {
+            Ok(header) => header,
+            Err(e) => {
+                warn!("Simulator got invalid request: {}", e);
+                return;
+            }
+        };
+        let mut body = vec![0u8; header.len];
+        if stream.read_exact(&mut body).await.is_err() {
+            return;
+        }
+
+        let register = body[0];
+        let (state, data) = {
+            let Ok(mut arm) = arm.lock() else {
+                return;
+            };
+            let data = execute(&mut arm, &config, register, &body[1..]);
+            (status_byte(&arm), data)
+        };
+
+        let frame = protocol::encode_reply(header.tid, register, state, &data);
+        if stream.write_all(&frame).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn status_byte(arm: &SimArm) -> u8 {
+    let mut state = 0;
+    if arm.error_code != 0 {
+        state |= STATUS_ERROR;
+    }
+    if arm.warn_code != 0 {
+        state |= STATUS_WARN;
+    }
+    state
+}
+
+/// 执行一条指令，返回应答数据；未实现的寄存器直接应答空数据
+fn execute(arm: &mut SimArm, config: &SimConfig, register: u8, params: &[u8]) -> Vec<u8> {
+    match register {
+        GET_VERSION => protocol::string_to_bytes(&format!("v{}", config.version)),
+        GET_ROBOT_SN => protocol::string_to_bytes(&config.arm_sn),
+        MOTION_EN => {
+            if let Some(enable) = params.get(1) {
+                arm.set_motion_enabled(*enable != 0);
+            }
+            Vec::new()
+        }
+        SET_STATE => {
+            if let Some(state) = params.first() {
+                arm.set_state(*state);
+            }
+            Vec::new()
+        }
+        GET_STATE => vec![arm.state],
+        GET_CMDNUM => arm.cmd_num.to_be_bytes().to_vec(),
+        GET_ERROR => vec![arm.error_code, arm.warn_code],
+        CLEAN_ERR => {
+            arm.clear_error();
+            Vec::new()
+        }
+        CLEAN_WAR => {
+            arm.clear_warn();
+            Vec::new()
+        }
+        SET_MODE => {
+            if let Some(mode) = params.first() {
+                arm.mode = *mode;
+            }
+            Vec::new()
+        }
+        MOVE_JOINT => {
+            let values = protocol::bytes_to_f32s(params);
+            if values.len() >= 7 {
+                let mut target = [0.0; 7];
+                target.copy_from_slice(&values[..7]);
+                arm.move_joints(target);
+            }
+            Vec::new()
+        }
+        GET_TCP_POSE => protocol::f32s_to_bytes(&arm.tcp_pose()),
+        GET_JOINT_POS => protocol::f32s_to_bytes(&arm.joints),
+        GET_REDUCED_STATE => reduced_state(arm),
+        CGPIO_GET_STATE => cgpio_state(arm),
+        _ => {
+            configure(arm, register, params);
+            Vec::new()
+        }
+    }
+}
+
+/// 写参数类指令，参数不完整时忽略
+fn configure(arm: &mut SimArm, register: u8, params: &[u8]) {
+    let floats = protocol::bytes_to_f32s(params);
+    let flag = params.first().map(|value| *value != 0);
+    match register {
+        SET_TCP_OFFSET if floats.len() >= 6 => arm.tcp_offset.copy_from_slice(&floats[..6]),
+        SET_LOAD_PARAM if floats.len() >= 4 => arm.tcp_load.copy_from_slice(&floats[..4]),
+        SET_COLLIS_SENS if !params.is_empty() => arm.collision_sens = params[0],
+        SET_TEACH_SENS if !params.is_empty() => arm.teach_sens = params[0],
+        SET_GRAVITY_DIR if floats.len() >= 3 => arm.gravity_direction.copy_from_slice(&floats[..3]),
+        SET_REDUCED_TRSV if !floats.is_empty() => arm.reduced.tcp_speed = floats[0],
+        SET_REDUCED_P2PV if !floats.is_empty() => arm.reduced.joint_speed = floats[0],
+        SET_REDUCED_JRANGE if floats.len() >= 14 => {
+            arm.reduced.joint_ranges.copy_from_slice(&floats[..14])
+        }
+        SET_LIMIT_XYZ if params.len() >= 24 => {
+            for (value, bytes) in arm.reduced.boundary.iter_mut().zip(params.chunks_exact(4)) {
+                *value = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+        }
+        SET_REDUCED_MODE => arm.reduced.enabled = flag.unwrap_or(arm.reduced.enabled),
+        SET_FENSE_ON => arm.reduced.fence = flag.unwrap_or(arm.reduced.fence),
+        SET_COLLIS_REB => {
+            arm.reduced.collision_rebound = flag.unwrap_or(arm.reduced.collision_rebound)
+        }
+        SET_WORLD_OFFSET if floats.len() >= 6 => arm.world_offset.copy_from_slice(&floats[..6]),
+        CGPIO_SET_IN_FUN | CGPIO_SET_OUT_FUN if params.len() >= 2 => {
+            let functions = if register == CGPIO_SET_IN_FUN {
+                &mut arm.cgpio_inputs
+            } else {
+                &mut arm.cgpio_outputs
+            };
+            if let Some(function) = functions.get_mut(params[0] as usize) {
+                *function = params[1];
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 缩减模式状态：开关、边界（6 个 i16）、TCP/关节限速、关节范围（14 个 f32）、边界开关、碰撞回弹
+fn reduced_state(arm: &SimArm) -> Vec<u8> {
+    let reduced = &arm.reduced;
+    let mut data = vec![reduced.enabled as u8];
+    for value in reduced.boundary {
+        data.extend_from_slice(
+            &(value.clamp(i16::MIN as i32, i16::MAX as i32) as i16).to_be_bytes(),
+        );
+    }
+    data.extend(protocol::f32s_to_bytes(&[
+        reduced.tcp_speed,
+        reduced.joint_speed,
+    ]));
+    data.extend(protocol::f32s_to_bytes(&reduced.joint_ranges));
+    data.push(reduced.fence as u8);
+    data.push(reduced.collision_rebound as u8);
+    data
+}
+
+/// 控制器 IO 状态，IO 电平和模拟量固定为 0，末尾为 16 路输入、输出功能配置
+fn cgpio_state(arm: &SimArm) -> Vec<u8> {
+    let mut data = vec![0u8; 18];
+    data.extend_from_slice(&arm.cgpio_inputs[..8]);
+    data.extend_from_slice(&arm.cgpio_outputs[..8]);
+    data.extend_from_slice(&arm.cgpio_inputs[8..]);
+    data.extend_from_slice(&arm.cgpio_outputs[8..]);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::packages::simulator::arm::{STATE_MOVING, STATE_STOPPED};
+
+    fn sim() -> (SimConfig, Arc<Mutex<SimArm>>) {
+        let config = SimConfig {
+            animate: false,
+            ..Default::default()
+        };
+        let arm = Arc::new(Mutex::new(SimArm::new(&config)));
+        (config, arm)
+    }
+
+    async fn start(
+        config: SimConfig,
+        arm: Arc<Mutex<SimArm>>,
+    ) -> (SocketAddr, watch::Sender<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        tokio::spawn(serve(listener, config, arm, stop_rx));
+        (addr, stop_tx)
+    }
+
+    /// 读取一条原始应答帧
+    async fn read_reply(stream: &mut TcpStream) -> Vec<u8> {
+        let mut header = [0u8; HEADER_LEN];
+        stream.read_exact(&mut header).await.unwrap();
+        let len = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let mut frame = header.to_vec();
+        frame.resize(HEADER_LEN + len, 0);
+        stream.read_exact(&mut frame[HEADER_LEN..]).await.unwrap();
+        frame
+    }
+
+    #[tokio::test]
+    async fn answers_raw_frames() {
+        let (config, arm) = sim();
+        let (addr, _stop) = start(config, arm).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        stream
+            .write_all(&protocol::encode_request(7, GET_STATE, &[]))
+            .await
+            .unwrap();
+        assert_eq!(
+            read_reply(&mut stream).await,
+            [0, 7, 0, 2, 0, 3, GET_STATE, 0, 2]
+        );
+
+        stream
+            .write_all(&protocol::encode_request(8, GET_VERSION, &[]))
+            .await
+            .unwrap();
+        let frame = read_reply(&mut stream).await;
+        assert_eq!(&frame[..HEADER_LEN], [0, 8, 0, 2, 0, 42]);
+        assert_eq!(frame[HEADER_LEN..HEADER_LEN + 2], [GET_VERSION, 0]);
+        assert_eq!(
+            protocol::bytes_to_string(&frame[HEADER_LEN + 2..]),
+            "v2.5.0"
+        );
+
+        // 未实现的寄存器应答空数据
+        stream
+            .write_all(&protocol::encode_request(9, SAVE_CONF, &[]))
+            .await
+            .unwrap();
+        assert_eq!(
+            read_reply(&mut stream).await,
+            [0, 9, 0, 2, 0, 2, SAVE_CONF, 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn pipelined_requests_keep_order() {
+        let (config, arm) = sim();
+        let (addr, _stop) = start(config, arm.clone()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        let mut batch = protocol::encode_request(1, SET_STATE, &[STATE_STOPPED]);
+        batch.extend(protocol::encode_request(2, GET_STATE, &[]));
+        batch.extend(protocol::encode_request(3, GET_CMDNUM, &[]));
+        stream.write_all(&batch).await.unwrap();
+
+        assert_eq!(
+            read_reply(&mut stream).await,
+            [0, 1, 0, 2, 0, 2, SET_STATE, 0]
+        );
+        assert_eq!(
+            read_reply(&mut stream).await,
+            [0, 2, 0, 2, 0, 3, GET_STATE, 0, STATE_STOPPED]
+        );
+        assert_eq!(
+            read_reply(&mut stream).await,
+            [0, 3, 0, 2, 0, 4, GET_CMDNUM, 0, 0, 0]
+        );
+        assert_eq!(arm.lock().unwrap().state, STATE_STOPPED);
+    }
+
+    #[tokio::test]
+    async fn reports_error_and_warn_in_status_byte() {
+        let (config, arm) = sim();
+        {
+            let mut arm = arm.lock().unwrap();
+            arm.error_code = 22;
+            arm.warn_code = 11;
+        }
+        let (addr, _stop) = start(config, arm).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        stream
+            .write_all(&protocol::encode_request(1, GET_ERROR, &[]))
+            .await
+            .unwrap();
+        let frame = read_reply(&mut stream).await;
+        assert_eq!(frame[HEADER_LEN + 1], STATUS_ERROR | STATUS_WARN);
+        assert_eq!(frame[HEADER_LEN + 2..], [22, 11]);
+
+        stream
+            .write_all(&protocol::encode_request(2, CLEAN_ERR, &[]))
+            .await
+            .unwrap();
+        assert_eq!(read_reply(&mut stream).await[HEADER_LEN + 1], STATUS_WARN);
+    }
+
+    #[tokio::test]
+    async fn closes_connection_on_bad_header() {
+        let (config, arm) = sim();
+        let (addr, _stop) = start(config, arm).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        // 协议号不是 2
+        stream
+            .write_all(&[0, 1, 0, 9, 0, 1, GET_STATE])
+            .await
+            .unwrap();
+        // 未读完的数据可能让对端直接复位连接
+        let mut buf = [0u8; 1];
+        let closed = matches!(stream.read(&mut buf).await, Ok(0) | Err(_));
+        assert!(closed);
+    }
+
+    #[test]
+    fn executes_motion_and_configuration() {
+        let (config, arm) = sim();
+        let mut arm = arm.lock().unwrap();
+
+        let target = [0.1, 0.2, 0.3, 0.0, 0.0, 0.0, 0.0];
+        execute(
+            &mut arm,
+            &config,
+            MOVE_JOINT,
+            &protocol::f32s_to_bytes(&target),
+        );
+        assert_eq!(arm.state, STATE_MOVING);
+        assert_eq!(arm.target, Some(target));
+        assert_eq!(execute(&mut arm, &config, GET_CMDNUM, &[]), [0, 1]);
+
+        let offset = [0.0, 0.0, 120.0, 0.0, 0.0, 0.0];
+        execute(
+            &mut arm,
+            &config,
+            SET_TCP_OFFSET,
+            &protocol::f32s_to_bytes(&offset),
+        );
+        assert_eq!(arm.tcp_offset, offset);
+        // 参数不完整时忽略
+        execute(
+            &mut arm,
+            &config,
+            SET_TCP_OFFSET,
+            &protocol::f32s_to_bytes(&[1.0]),
+        );
+        assert_eq!(arm.tcp_offset, offset);
+
+        execute(&mut arm, &config, CGPIO_SET_OUT_FUN, &[9, 12]);
+        let state = execute(&mut arm, &config, CGPIO_GET_STATE, &[]);
+        assert_eq!(state.len(), 50);
+        assert_eq!(state[42 + 1], 12);
+
+        execute(&mut arm, &config, MOTION_EN, &[8, 0]);
+        assert!(!arm.motion_enabled);
+        assert_eq!(arm.state, STATE_STOPPED);
+        assert_eq!(execute(&mut arm, &config, GET_CMDNUM, &[]), [0, 0]);
+    }
+}
@@ -0,0 +1,107 @@
+use log::{info, warn};
+use tokio::net::UdpSocket;
+use tokio::sync::watch;
+
+use crate::packages::discovery::DISCOVERY_MESSAGE;
+
+use super::SimConfig;
+
+/// 发现回复报文，格式见 `discovery::protocol`
+pub fn reply_message(config: &SimConfig) -> String {
+    format!(
+        "xarm:{},{},{},{},{}:{}",
+        config.axis,
+        config.device_type,
+        config.arm_sn,
+        config.control_sn,
+        config.version,
+        config.addr_type
+    )
+}
+
+/// 应答 `get_xarm_addr` 请求
+pub(super) async fn serve(
+    socket: UdpSocket,
+    config: SimConfig,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let reply = reply_message(&config);
+    let mut buf = [0; 256];
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => return,
+            result = socket.recv_from(&mut buf) => match result {
+                Ok((amt, src)) => {
+                    let request = String::from_utf8_lossy(&buf[..amt]);
+                    if request.trim() != DISCOVERY_MESSAGE {
+                        continue;
+                    }
+                    info!("Simulator {} answering discovery from {}", config.arm_sn, src);
+                    if let Err(e) = socket.send_to(reply.as_bytes(), src).await {
+                        warn!("Simulator failed to reply to {}: {}", src, e);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => continue,
+                Err(e) => {
+                    warn!("Simulator discovery socket closed: {}", e);
+                    return;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::packages::discovery::protocol::{DiscoveryReply, PROTOCOL_STANDARD};
+
+    #[test]
+    fn reply_matches_discovery_protocol() {
+        let config = SimConfig {
+            axis: 7,
+            device_type: 7,
+            ..Default::default()
+        };
+        let reply = DiscoveryReply::parse(&reply_message(&config)).unwrap();
+        assert_eq!(reply.protocol_version, PROTOCOL_STANDARD);
+        assert_eq!(reply.axis, "7");
+        assert_eq!(reply.device_type, "7");
+        assert_eq!(reply.arm_sn, config.arm_sn);
+        assert_eq!(reply.control_sn, config.control_sn);
+        assert_eq!(reply.version, config.version);
+        assert_eq!(reply.addr_type, "LAN");
+    }
+
+    #[tokio::test]
+    async fn answers_only_discovery_requests() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let config = SimConfig::default();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        tokio::spawn(serve(server, config.clone(), stop_rx));
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut buf = [0u8; 256];
+
+        client.send_to(b"hello", addr).await.unwrap();
+        let ignored =
+            tokio::time::timeout(Duration::from_millis(200), client.recv_from(&mut buf)).await;
+        assert!(ignored.is_err());
+
+        client
+            .send_to(format!("{}\n", DISCOVERY_MESSAGE).as_bytes(), addr)
+            .await
+            .unwrap();
+        let (len, src) = tokio::time::timeout(Duration::from_secs(2), client.recv_from(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(src, addr);
+        assert_eq!(&buf[..len], reply_message(&config).as_bytes());
+
+        let _ = stop_tx.send(true);
+    }
+}
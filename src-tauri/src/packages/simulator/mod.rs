@@ -0,0 +1,282 @@
+//! 虚拟 xArm，用于无真机时的演示、培训和自动化测试
+//!
+//! 应答 18355 端口的发现请求，并提供指令端口和三个上报端口。
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time;
+
+use crate::packages::discovery::{DiscoveryService, DISCOVERY_PORT};
+use crate::packages::xarm::report::ReportKind;
+
+pub mod arm;
+pub mod command;
+pub mod discovery;
+pub mod report;
+#[cfg(test)]
+pub mod stand_in;
+
+use arm::SimArm;
+
+/// 演示模式启动参数，可写成 `--demo=<配置文件>`
+pub const DEMO_FLAG: &str = "--demo";
+/// 演示模式环境变量，值为 `1` 或配置文件路径
+pub const DEMO_ENV: &str = "UF_STUDIO_DEMO";
+
+/// 没有权限监听 502 时改用的指令端口
+const FALLBACK_COMMAND_PORT: u16 = 5020;
+/// 运动仿真步长
+const STEP: Duration = Duration::from_millis(10);
+
+/// 虚拟机械臂参数
+///
+/// 指令端口默认 502，与真机一致；Linux/macOS 上没有权限监听时改用 5020 并记录日志，
+/// 实际端口见 `SimulatorStatus.config`。
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct SimConfig {
+    pub axis: u8,
+    pub device_type: u16,
+    pub arm_sn: String,
+    pub control_sn: String,
+    pub version: String,
+    pub addr_type: String,
+    /// 监听地址，默认只监听本机
+    pub host: Ipv4Addr,
+    pub discovery_port: u16,
+    pub command_port: u16,
+    pub report_port: u16,
+    pub rich_report_port: u16,
+    pub realtime_report_port: u16,
+    /// 没有运动指令时自动做演示动作
+    pub animate: bool,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            axis: 6,
+            device_type: 6,
+            arm_sn: "XI1306SIM00001".to_string(),
+            control_sn: "XS1306SIM00001".to_string(),
+            version: "2.5.0".to_string(),
+            addr_type: "LAN".to_string(),
+            host: Ipv4Addr::LOCALHOST,
+            discovery_port: DISCOVERY_PORT,
+            command_port: 502,
+            report_port: ReportKind::Normal.port(),
+            rich_report_port: ReportKind::Rich.port(),
+            realtime_report_port: ReportKind::Realtime.port(),
+            animate: true,
+        }
+    }
+}
+
+/// 运行中的虚拟机械臂信息
+#[derive(Serialize, Clone, Debug)]
+pub struct SimulatorStatus {
+    pub config: SimConfig,
+    pub discovery: String,
+    /// 成功监听的 TCP 端口（指令端口和上报端口）
+    pub listening: Vec<u16>,
+}
+
+struct Running {
+    status: SimulatorStatus,
+    stop_tx: watch::Sender<bool>,
+    tasks: JoinSet<()>,
+}
+
+/// 虚拟机械臂服务，同一时间只运行一台
+#[derive(Clone, Default)]
+pub struct Simulator {
+    running: Arc<Mutex<Option<Running>>>,
+}
+
+impl Simulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动（或以新参数重启）虚拟机械臂，并让发现服务向其单播探测
+    pub async fn start(
+        &self,
+        mut config: SimConfig,
+        discovery: &DiscoveryService,
+    ) -> Result<SimulatorStatus, String> {
+        self.stop(discovery).await;
+
+        let discovery_addr = SocketAddr::from((config.host, config.discovery_port));
+        let socket = UdpSocket::bind(discovery_addr)
+            .await
+            .map_err(|e| format!("Failed to bind {}: {}", discovery_addr, e))?;
+
+        let arm = Arc::new(Mutex::new(SimArm::new(&config)));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let mut tasks = JoinSet::new();
+        tasks.spawn(discovery::serve(socket, config.clone(), stop_rx.clone()));
+        tasks.spawn(run_motion(Arc::clone(&arm), stop_rx.clone()));
+
+        // TCP 端口绑定失败不影响发现应答
+        let mut listening = Vec::new();
+        if let Some(listener) = bind_command(&mut config).await {
+            listening.push(config.command_port);
+            tasks.spawn(command::serve(
+                listener,
+                config.clone(),
+                Arc::clone(&arm),
+                stop_rx.clone(),
+            ));
+        }
+        for (port, kind) in [
+            (config.report_port, ReportKind::Normal),
+            (config.rich_report_port, ReportKind::Rich),
+            (config.realtime_report_port, ReportKind::Realtime),
+        ] {
+            if let Some(listener) = bind_tcp(config.host, port).await {
+                listening.push(port);
+                tasks.spawn(report::serve(
+                    listener,
+                    kind,
+                    Arc::clone(&arm),
+                    stop_rx.clone(),
+                ));
+            }
+        }
+
+        let status = SimulatorStatus {
+            config,
+            discovery: discovery_addr.to_string(),
+            listening,
+        };
+        info!(
+            "Simulator {} started on {} (tcp {:?})",
+            status.config.arm_sn, status.discovery, status.listening
+        );
+
+        let previous = self
+            .running
+            .lock()
+            .map_err(|e| e.to_string())?
+            .replace(Running {
+                status: status.clone(),
+                stop_tx,
+                tasks,
+            });
+        if let Some(previous) = previous {
+            finish(previous, discovery).await;
+        }
+        discovery.add_unicast_target(discovery_addr);
+        Ok(status)
+    }
+
+    /// 停止虚拟机械臂，返回之前是否在运行
+    pub async fn stop(&self, discovery: &DiscoveryService) -> bool {
+        let running = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|mut running| running.take());
+        match running {
+            Some(running) => {
+                finish(running, discovery).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<SimulatorStatus> {
+        self.running
+            .lock()
+            .ok()
+            .and_then(|running| running.as_ref().map(|r| r.status.clone()))
+    }
+}
+
+async fn finish(mut running: Running, discovery: &DiscoveryService) {
+    let _ = running.stop_tx.send(true);
+    while running.tasks.join_next().await.is_some() {}
+    if let Ok(addr) = running.status.discovery.parse() {
+        discovery.remove_unicast_target(addr);
+    }
+    info!("Simulator {} stopped", running.status.config.arm_sn);
+}
+
+/// 监听指令端口，特权端口没有权限时改用 `FALLBACK_COMMAND_PORT` 并更新 `config`
+async fn bind_command(config: &mut SimConfig) -> Option<TcpListener> {
+    match TcpListener::bind((config.host, config.command_port)).await {
+        Ok(listener) => Some(listener),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            warn!(
+                "Simulator has no permission to listen on {}:{}, using port {} instead",
+                config.host, config.command_port, FALLBACK_COMMAND_PORT
+            );
+            config.command_port = FALLBACK_COMMAND_PORT;
+            bind_tcp(config.host, config.command_port).await
+        }
+        Err(e) => {
+            warn!(
+                "Simulator failed to listen on {}:{}: {}",
+                config.host, config.command_port, e
+            );
+            None
+        }
+    }
+}
+
+async fn bind_tcp(host: Ipv4Addr, port: u16) -> Option<TcpListener> {
+    match TcpListener::bind((host, port)).await {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            warn!("Simulator failed to listen on {}:{}: {}", host, port, e);
+            None
+        }
+    }
+}
+
+async fn run_motion(arm: Arc<Mutex<SimArm>>, mut stop_rx: watch::Receiver<bool>) {
+    let mut ticker = time::interval(STEP);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => return,
+            _ = ticker.tick() => {
+                if let Ok(mut arm) = arm.lock() {
+                    arm.step(STEP.as_secs_f32());
+                }
+            }
+        }
+    }
+}
+
+/// 读取演示模式参数：`--demo`、`--demo=<配置文件>` 或环境变量 `UF_STUDIO_DEMO`
+pub fn demo_config() -> Option<SimConfig> {
+    let value = std::env::args()
+        .find_map(|arg| match arg.strip_prefix(DEMO_FLAG)? {
+            "" => Some(String::new()),
+            rest => rest.strip_prefix('=').map(str::to_string),
+        })
+        .or_else(|| std::env::var(DEMO_ENV).ok())?;
+
+    match value.trim() {
+        "" | "1" | "true" => Some(SimConfig::default()),
+        "0" | "false" => None,
+        path => {
+            let config = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+            Some(config.unwrap_or_else(|e| {
+                warn!("Failed to load simulator config {}: {}", path, e);
+                SimConfig::default()
+            }))
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! 上报端口（30001/30002/30003）模拟，帧格式见 `xarm::report`
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time;
+
+use super::arm::SimArm;
+use crate::packages::xarm::protocol::f32s_to_bytes;
+use crate::packages::xarm::report::{ReportKind, WORLD_OFFSET_LEN};
+
+/// 上报周期
+fn period(kind: ReportKind) -> Duration {
+    match kind {
+        ReportKind::Normal | ReportKind::Rich => Duration::from_millis(100),
+        ReportKind::Realtime => Duration::from_millis(10),
+    }
+}
+
+/// 接受连接，按上报周期推送状态
+pub(crate) async fn serve(
+    listener: TcpListener,
+    kind: ReportKind,
+    arm: Arc<Mutex<SimArm>>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    info!("Simulator {:?} report connection from {}", kind, peer);
+                    connections.spawn(push_reports(stream, kind, Arc::clone(&arm)));
+                }
+                Err(e) => warn!("Simulator failed to accept report connection: {}", e),
+            },
+        }
+    }
+}
+
+async fn push_reports(mut stream: TcpStream, kind: ReportKind, arm: Arc<Mutex<SimArm>>) {
+    let mut ticker = time::interval(period(kind));
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+        let frame = match arm.lock() {
+            Ok(arm) => encode(&arm, kind),
+            Err(_) => return,
+        };
+        if stream.write_all(&frame).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// 编码一帧上报数据
+pub fn encode(arm: &SimArm, kind: ReportKind) -> Vec<u8> {
+    let mut frame = vec![0u8; 4];
+    frame.push((arm.state & 0x0F) | (arm.mode << 4));
+    frame.extend_from_slice(&arm.cmd_num.to_be_bytes());
+    frame.extend(f32s_to_bytes(&arm.joints));
+    frame.extend(f32s_to_bytes(&arm.tcp_pose()));
+    frame.extend(f32s_to_bytes(&arm.torques()));
+
+    match kind {
+        ReportKind::Normal | ReportKind::Rich => {
+            let mask = (1u8 << arm.axis) - 1;
+            frame.push(if arm.motion_enabled { mask } else { 0 });
+            frame.push(if arm.motion_enabled { mask } else { 0 });
+            frame.push(arm.error_code);
+            frame.push(arm.warn_code);
+            frame.extend(f32s_to_bytes(&arm.tcp_offset));
+            frame.extend(f32s_to_bytes(&arm.tcp_load));
+            frame.push(arm.collision_sens);
+            frame.push(arm.teach_sens);
+            frame.extend(f32s_to_bytes(&arm.gravity_direction));
+            // 详细上报按新固件的长度，末尾为世界坐标偏移
+            if kind == ReportKind::Rich {
+                frame.resize(WORLD_OFFSET_LEN - 24, 0);
+                frame.extend(f32s_to_bytes(&arm.world_offset));
+            }
+        }
+        ReportKind::Realtime => {
+            frame.extend(f32s_to_bytes(&arm.speeds));
+            frame.extend(f32s_to_bytes(&arm.currents()));
+        }
+    }
+
+    let len = frame.len() as u32;
+    frame[..4].copy_from_slice(&len.to_be_bytes());
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::packages::simulator::SimConfig;
+    use crate::packages::xarm::report::{self, NORMAL_LEN, REALTIME_LEN};
+
+    fn arm() -> SimArm {
+        let mut arm = SimArm::new(&SimConfig {
+            axis: 5,
+            animate: false,
+            ..Default::default()
+        });
+        arm.mode = 2;
+        arm.cmd_num = 0x0102;
+        arm
+    }
+
+    #[test]
+    fn encodes_normal_layout() {
+        let arm = arm();
+        let frame = encode(&arm, ReportKind::Normal);
+        assert_eq!(frame.len(), NORMAL_LEN);
+        assert_eq!(frame[..4], (NORMAL_LEN as u32).to_be_bytes());
+        assert_eq!(frame[4], (2 << 4) | arm.state);
+        assert_eq!(frame[5..7], [0x01, 0x02]);
+        // 5 轴使能位
+        assert_eq!(frame[87..89], [0b1_1111, 0b1_1111]);
+        assert_eq!(frame[131..133], [3, 3]);
+    }
+
+    #[test]
+    fn encodes_world_offset_in_rich_layout() {
+        let mut arm = arm();
+        arm.world_offset = [10.0, -20.0, 30.0, 0.0, 0.0, 1.5];
+        let normal = encode(&arm, ReportKind::Normal);
+        let rich = encode(&arm, ReportKind::Rich);
+        assert_eq!(rich.len(), WORLD_OFFSET_LEN);
+        assert_eq!(rich[..4], (WORLD_OFFSET_LEN as u32).to_be_bytes());
+        assert_eq!(rich[4..NORMAL_LEN], normal[4..]);
+        assert_eq!(rich[288..292], 10.0f32.to_le_bytes());
+
+        let status = report::decode(ReportKind::Rich, &rich).unwrap().status;
+        assert_eq!(
+            status.unwrap().world_offset,
+            Some(arm.world_offset.to_vec())
+        );
+    }
+
+    #[test]
+    fn encodes_realtime_layout() {
+        let mut arm = arm();
+        arm.set_motion_enabled(false);
+        let frame = encode(&arm, ReportKind::Realtime);
+        assert_eq!(frame.len(), REALTIME_LEN);
+
+        let normal = encode(&arm, ReportKind::Normal);
+        assert_eq!(normal[87..89], [0, 0]);
+    }
+
+    #[tokio::test]
+    async fn pushes_frames_to_clients() {
+        let arm = Arc::new(Mutex::new(arm()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        tokio::spawn(serve(listener, ReportKind::Realtime, arm.clone(), stop_rx));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        for _ in 0..3 {
+            let frame = report::read_frame(&mut stream).await.unwrap();
+            let decoded = report::decode(ReportKind::Realtime, &frame).unwrap();
+            assert_eq!(decoded.mode, 2);
+            assert_eq!(decoded.cmd_num, 0x0102);
+            assert_eq!(decoded.dynamics.unwrap().speeds.len(), 7);
+        }
+
+        arm.lock().unwrap().cmd_num = 7;
+        let cmd_num = loop {
+            let frame = report::read_frame(&mut stream).await.unwrap();
+            let decoded = report::decode(ReportKind::Realtime, &frame).unwrap();
+            if decoded.cmd_num != 0x0102 {
+                break decoded.cmd_num;
+            }
+        };
+        assert_eq!(cmd_num, 7);
+        let _ = stop_tx.send(true);
+    }
+}
@@ -0,0 +1,85 @@
+//! 测试用的虚拟控制器：在随机端口上运行指令端口（可选一个上报端口），释放时停止
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use super::arm::SimArm;
+use super::{command, report, SimConfig};
+use crate::packages::xarm::report::ReportKind;
+use crate::packages::xarm::ArmAddress;
+
+/// 运行中的替身
+pub struct SimStandIn {
+    pub arm: Arc<Mutex<SimArm>>,
+    pub command_addr: SocketAddr,
+    /// 上报端口，只在 `sim_stand_in_with_report` 启动时存在
+    pub report_addr: Option<SocketAddr>,
+    stop_tx: watch::Sender<bool>,
+}
+
+impl SimStandIn {
+    /// 指令端口对应的机械臂地址
+    pub fn address(&self) -> ArmAddress {
+        ArmAddress {
+            ip: self.command_addr.ip().to_string(),
+            command_port: Some(self.command_addr.port()),
+        }
+    }
+}
+
+impl Drop for SimStandIn {
+    fn drop(&mut self) {
+        let _ = self.stop_tx.send(true);
+    }
+}
+
+/// 不做演示动作的默认参数，测试在此基础上修改
+pub fn still_config() -> SimConfig {
+    SimConfig {
+        animate: false,
+        ..Default::default()
+    }
+}
+
+/// 启动替身
+pub async fn sim_stand_in(config: SimConfig) -> SimStandIn {
+    start(config, None).await
+}
+
+/// 启动替身，并运行一个发送 `kind` 格式帧的上报端口
+pub async fn sim_stand_in_with_report(config: SimConfig, kind: ReportKind) -> SimStandIn {
+    start(config, Some(kind)).await
+}
+
+async fn start(config: SimConfig, report_kind: Option<ReportKind>) -> SimStandIn {
+    let arm = Arc::new(Mutex::new(SimArm::new(&config)));
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let command_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let command_addr = command_listener.local_addr().unwrap();
+    tokio::spawn(command::serve(
+        command_listener,
+        config,
+        Arc::clone(&arm),
+        stop_rx.clone(),
+    ));
+    let mut report_addr = None;
+    if let Some(kind) = report_kind {
+        let report_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        report_addr = Some(report_listener.local_addr().unwrap());
+        tokio::spawn(report::serve(
+            report_listener,
+            kind,
+            Arc::clone(&arm),
+            stop_rx,
+        ));
+    }
+    SimStandIn {
+        arm,
+        command_addr,
+        report_addr,
+        stop_tx,
+    }
+}
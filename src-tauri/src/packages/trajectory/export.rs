@@ -0,0 +1,161 @@
+//! 轨迹导出
+//!
+//! CSV / JSON 与导入格式相同（关节数按机型截取）；旧版 xArm Studio 格式按 4 ms 周期线性重采样，
+//! 每行 7 个关节角（度），不足 7 轴补 0，控制器的轨迹回放只接受这种固定周期的数据。
+
+use serde::{Deserialize, Serialize};
+
+use super::import::{STUDIO_COLUMNS, STUDIO_PERIOD_MS};
+use super::{ensure_valid, AngleUnit, Space, Trajectory, TrajectoryFormat};
+use crate::packages::xarm::model::ArmModel;
+use crate::utils::error::AppError;
+
+/// 导出选项
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// CSV / JSON 中角度的单位，Studio 格式固定为度
+    pub unit: AngleUnit,
+}
+
+#[derive(Serialize)]
+struct JsonTrajectory<'a> {
+    name: &'a str,
+    space: Space,
+    unit: AngleUnit,
+    points: Vec<JsonPoint>,
+}
+
+#[derive(Serialize)]
+struct JsonPoint {
+    time: f64,
+    values: Vec<f64>,
+}
+
+/// 校验后按格式生成文件内容
+pub fn render(
+    trajectory: &Trajectory,
+    model: ArmModel,
+    format: TrajectoryFormat,
+    options: &ExportOptions,
+) -> Result<String, AppError> {
+    ensure_valid(trajectory, model)?;
+    let width = match trajectory.space {
+        Space::Joint => model.axis(),
+        Space::Cartesian => 6,
+    };
+    match format {
+        TrajectoryFormat::Csv => render_csv(trajectory, width, options.unit),
+        TrajectoryFormat::Json => render_json(trajectory, width, options.unit),
+        TrajectoryFormat::Studio => render_studio(trajectory, width),
+    }
+}
+
+/// 换算为文件单位，位置分量不换算
+fn output_values(space: Space, values: &[f64], width: usize, unit: AngleUnit) -> Vec<f64> {
+    values
+        .iter()
+        .take(width)
+        .enumerate()
+        .map(|(i, value)| match space {
+            Space::Cartesian if i < 3 => *value,
+            _ => unit.rad_to(*value),
+        })
+        .collect()
+}
+
+fn render_csv(trajectory: &Trajectory, width: usize, unit: AngleUnit) -> Result<String, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_error = |e: csv::Error| AppError::Io(e.to_string());
+
+    let mut header = vec!["time".to_string()];
+    match trajectory.space {
+        Space::Joint => header.extend((1..=width).map(|i| format!("j{}", i))),
+        Space::Cartesian => {
+            header.extend(["x", "y", "z", "roll", "pitch", "yaw"].map(str::to_string))
+        }
+    }
+    writer.write_record(&header).map_err(csv_error)?;
+    for point in &trajectory.points {
+        let mut row = vec![point.time.to_string()];
+        row.extend(
+            output_values(trajectory.space, &point.values, width, unit)
+                .iter()
+                .map(f64::to_string),
+        );
+        writer.write_record(&row).map_err(csv_error)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| AppError::Io(e.to_string()))
+}
+
+fn render_json(trajectory: &Trajectory, width: usize, unit: AngleUnit) -> Result<String, AppError> {
+    let file = JsonTrajectory {
+        name: &trajectory.name,
+        space: trajectory.space,
+        unit,
+        points: trajectory
+            .points
+            .iter()
+            .map(|point| JsonPoint {
+                time: point.time,
+                values: output_values(trajectory.space, &point.values, width, unit),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| AppError::Io(e.to_string()))
+}
+
+fn render_studio(trajectory: &Trajectory, width: usize) -> Result<String, AppError> {
+    if trajectory.space != Space::Joint {
+        return Err(AppError::InvalidArgument(
+            "xArm Studio format only supports joint trajectories".to_string(),
+        ));
+    }
+    let mut text = String::new();
+    for values in resample(trajectory, width, STUDIO_PERIOD_MS / 1000.0) {
+        let row: Vec<String> = (0..STUDIO_COLUMNS)
+            .map(|i| format!("{:.4}", values.get(i).copied().unwrap_or(0.0).to_degrees()))
+            .collect();
+        text.push_str(&row.join(","));
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// 按固定周期线性插值，包含起点和终点
+fn resample(trajectory: &Trajectory, width: usize, period: f64) -> Vec<Vec<f64>> {
+    let points = &trajectory.points;
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    let duration = last.time - first.time;
+    let steps = (duration / period).round() as usize;
+
+    let mut samples = Vec::with_capacity(steps + 1);
+    let mut segment = 0;
+    for step in 0..=steps {
+        let time = (first.time + step as f64 * period).min(last.time);
+        while segment + 1 < points.len() - 1 && points[segment + 1].time <= time {
+            segment += 1;
+        }
+        let a = &points[segment];
+        let b = points.get(segment + 1).unwrap_or(a);
+        let ratio = if b.time > a.time {
+            ((time - a.time) / (b.time - a.time)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        samples.push(
+            a.values
+                .iter()
+                .zip(&b.values)
+                .take(width)
+                .map(|(a, b)| a + (b - a) * ratio)
+                .collect(),
+        );
+    }
+    samples
+}
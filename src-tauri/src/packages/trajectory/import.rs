@@ -0,0 +1,483 @@
+//! 轨迹文件解析
+//!
+//! - CSV：第一行必须是表头，列按名称识别而不是按位置，可选 `time`（秒），
+//!   关节轨迹为 `j1..jN`，笛卡尔轨迹为 `x,y,z,roll,pitch,yaw`；`#` 开头的行为注释。
+//! - JSON：`{ "name", "space", "unit", "period_ms", "points": [{ "time", "values" } | [..]] }`，
+//!   `points` 中的元素也可以直接是数值数组（按 `period_ms` 计时）。
+//! - 旧版 xArm Studio（`.traj`）：无表头，每行 7 个关节角（度），逗号或空白分隔，固定 4 ms 周期。
+//!
+//! 没有时间列时按 `period_ms` 计时。格式错误时返回带行号的错误。
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{AngleUnit, Space, Trajectory, TrajectoryFormat, TrajectoryPoint};
+use crate::utils::error::AppError;
+
+/// 旧版 xArm Studio 的采样周期
+pub const STUDIO_PERIOD_MS: f64 = 4.0;
+/// 旧版 xArm Studio 每行的关节数
+pub const STUDIO_COLUMNS: usize = 7;
+/// 最大关节数
+const MAX_JOINTS: usize = 7;
+const POSE_COLUMNS: [&str; 6] = ["x", "y", "z", "roll", "pitch", "yaw"];
+
+/// 导入选项，文件自身声明的单位和周期优先
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ImportOptions {
+    /// CSV 中角度的单位
+    pub unit: AngleUnit,
+    /// 没有时间列时的采样周期（毫秒）
+    pub period_ms: f64,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            unit: AngleUnit::Deg,
+            period_ms: STUDIO_PERIOD_MS,
+        }
+    }
+}
+
+fn line_error(line: usize, message: impl std::fmt::Display) -> AppError {
+    AppError::InvalidArgument(format!("line {}: {}", line, message))
+}
+
+/// 读取并解析轨迹文件，`format` 为空时按扩展名判断
+pub fn load(
+    path: &Path,
+    format: Option<TrajectoryFormat>,
+    options: &ImportOptions,
+) -> Result<Trajectory, AppError> {
+    let format = match format {
+        Some(format) => format,
+        None => TrajectoryFormat::from_path(path)?,
+    };
+    let text = fs::read_to_string(path)?;
+    let name = path
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    parse(&text, &name, format, options)
+}
+
+pub fn parse(
+    text: &str,
+    name: &str,
+    format: TrajectoryFormat,
+    options: &ImportOptions,
+) -> Result<Trajectory, AppError> {
+    if !options.period_ms.is_finite() || options.period_ms <= 0.0 {
+        return Err(AppError::InvalidArgument(format!(
+            "invalid period: {} ms",
+            options.period_ms
+        )));
+    }
+    match format {
+        TrajectoryFormat::Csv => parse_csv(text, name, options),
+        TrajectoryFormat::Json => parse_json(text, name, options),
+        TrajectoryFormat::Studio => parse_studio(text, name),
+    }
+}
+
+/// 表头中的一列
+#[derive(Clone, Copy, PartialEq)]
+enum CsvColumn {
+    Time,
+    /// 关节序号（从 0 开始）
+    Joint(usize),
+    /// 位姿分量序号
+    Pose(usize),
+}
+
+fn csv_column(name: &str) -> Option<CsvColumn> {
+    let name = name.trim().to_ascii_lowercase();
+    if name == "time" || name == "t" {
+        return Some(CsvColumn::Time);
+    }
+    if let Some(joint) = name.strip_prefix('j').and_then(|n| n.parse::<usize>().ok()) {
+        return (1..=MAX_JOINTS)
+            .contains(&joint)
+            .then_some(CsvColumn::Joint(joint - 1));
+    }
+    POSE_COLUMNS
+        .iter()
+        .position(|pose| *pose == name)
+        .map(CsvColumn::Pose)
+}
+
+fn parse_csv(text: &str, name: &str, options: &ImportOptions) -> Result<Trajectory, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let csv_error = |e: csv::Error| {
+        let line = e.position().map_or(0, |p| p.line() as usize);
+        line_error(line, e)
+    };
+
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let header_line = headers.position().map_or(1, |p| p.line() as usize);
+    let mut columns = Vec::with_capacity(headers.len());
+    for header in headers.iter() {
+        let column = csv_column(header).ok_or_else(|| {
+            line_error(
+                header_line,
+                format!(
+                    "unknown column {:?}, expected time, j1..j7 or x/y/z/roll/pitch/yaw",
+                    header
+                ),
+            )
+        })?;
+        if columns.contains(&column) {
+            return Err(line_error(
+                header_line,
+                format!("duplicate column {:?}", header),
+            ));
+        }
+        columns.push(column);
+    }
+
+    let joints = columns
+        .iter()
+        .filter(|c| matches!(c, CsvColumn::Joint(_)))
+        .count();
+    let poses = columns
+        .iter()
+        .filter(|c| matches!(c, CsvColumn::Pose(_)))
+        .count();
+    let (space, width) = match (joints, poses) {
+        (0, 0) => return Err(line_error(header_line, "no joint or pose columns")),
+        (_, 0) => (Space::Joint, joints),
+        (0, _) => (Space::Cartesian, POSE_COLUMNS.len()),
+        _ => {
+            return Err(line_error(
+                header_line,
+                "joint and pose columns cannot be mixed",
+            ))
+        }
+    };
+    // 关节列必须是连续的 j1..jN，位姿列必须齐全
+    let expected: Vec<(CsvColumn, String)> = match space {
+        Space::Joint => (0..width)
+            .map(|i| (CsvColumn::Joint(i), format!("j{}", i + 1)))
+            .collect(),
+        Space::Cartesian => POSE_COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (CsvColumn::Pose(i), name.to_string()))
+            .collect(),
+    };
+    for (column, name) in expected {
+        if !columns.contains(&column) {
+            return Err(line_error(header_line, format!("missing column {}", name)));
+        }
+    }
+
+    let mut points = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        if record.len() != columns.len() {
+            return Err(line_error(
+                line,
+                format!("expected {} fields, got {}", columns.len(), record.len()),
+            ));
+        }
+
+        let mut time = None;
+        let mut values = vec![0.0; width];
+        for ((cell, column), header) in record.iter().zip(&columns).zip(headers.iter()) {
+            let value: f64 = cell
+                .parse()
+                .ok()
+                .filter(|v: &f64| v.is_finite())
+                .ok_or_else(|| line_error(line, format!("invalid {} value {:?}", header, cell)))?;
+            match *column {
+                CsvColumn::Time => time = Some(value),
+                CsvColumn::Joint(i) => values[i] = options.unit.to_rad(value),
+                // x/y/z 为 mm，roll/pitch/yaw 为角度
+                CsvColumn::Pose(i) if i < 3 => values[i] = value,
+                CsvColumn::Pose(i) => values[i] = options.unit.to_rad(value),
+            }
+        }
+        let time = time.unwrap_or(points.len() as f64 * options.period_ms / 1000.0);
+        points.push(TrajectoryPoint { line, time, values });
+    }
+    Ok(Trajectory {
+        name: name.to_string(),
+        space,
+        points,
+    })
+}
+
+/// JSON 文件结构，`points` 的元素单独解析以便报告行号
+#[derive(Deserialize)]
+struct JsonTrajectory {
+    name: Option<String>,
+    space: Option<Space>,
+    unit: Option<AngleUnit>,
+    period_ms: Option<f64>,
+    points: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonPoint {
+    Timed { time: f64, values: Vec<f64> },
+    Values(Vec<f64>),
+}
+
+fn parse_json(text: &str, name: &str, options: &ImportOptions) -> Result<Trajectory, AppError> {
+    let file: JsonTrajectory = serde_json::from_str(text)
+        .map_err(|e| line_error(e.line(), format!("invalid trajectory json: {}", e)))?;
+    let lines = element_lines(text, "points");
+    let space = file.space.unwrap_or(Space::Joint);
+    let unit = file.unit.unwrap_or(options.unit);
+    let period_ms = file.period_ms.unwrap_or(options.period_ms);
+    if !period_ms.is_finite() || period_ms <= 0.0 {
+        return Err(AppError::InvalidArgument(format!(
+            "invalid period: {} ms",
+            period_ms
+        )));
+    }
+
+    let mut points = Vec::with_capacity(file.points.len());
+    for (index, value) in file.points.into_iter().enumerate() {
+        let line = lines.get(index).copied().unwrap_or(0);
+        let point: JsonPoint = serde_json::from_value(value).map_err(|_| {
+            line_error(
+                line,
+                "expected {\"time\", \"values\"} or an array of numbers",
+            )
+        })?;
+        let (time, values) = match point {
+            JsonPoint::Timed { time, values } => (time, values),
+            JsonPoint::Values(values) => (index as f64 * period_ms / 1000.0, values),
+        };
+        let values = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match space {
+                Space::Cartesian if i < 3 => value,
+                _ => unit.to_rad(value),
+            })
+            .collect();
+        points.push(TrajectoryPoint { line, time, values });
+    }
+    Ok(Trajectory {
+        name: file.name.unwrap_or_else(|| name.to_string()),
+        space,
+        points,
+    })
+}
+
+/// 找到根对象中 `key` 数组每个元素起始处的行号
+fn element_lines(text: &str, key: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut line = 1;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string = String::new();
+    // 最近一个根对象中的字符串（可能是键）
+    let mut last_key = String::new();
+    // 目标数组所在深度
+    let mut array_depth = None;
+    let mut expect_element = false;
+
+    for c in text.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+                if depth == 1 {
+                    last_key = std::mem::take(&mut string);
+                }
+            } else if depth == 1 {
+                string.push(c);
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        if expect_element && c != ']' {
+            lines.push(line);
+        }
+        expect_element = false;
+        match c {
+            '"' => {
+                in_string = true;
+                string.clear();
+            }
+            '{' | '[' => {
+                depth += 1;
+                if c == '[' && depth == 2 && last_key == key && array_depth.is_none() {
+                    array_depth = Some(depth);
+                    expect_element = true;
+                }
+            }
+            '}' | ']' => {
+                if array_depth == Some(depth) {
+                    return lines;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            ',' if array_depth == Some(depth) => expect_element = true,
+            ',' if depth == 1 => last_key.clear(),
+            _ => {}
+        }
+    }
+    lines
+}
+
+fn parse_studio(text: &str, name: &str) -> Result<Trajectory, AppError> {
+    let mut points = Vec::new();
+    for (index, row) in text.lines().enumerate() {
+        let line = index + 1;
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') {
+            continue;
+        }
+        let values = row
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|cell| !cell.is_empty())
+            .map(|cell| {
+                cell.parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .map(f64::to_radians)
+                    .ok_or_else(|| line_error(line, format!("invalid joint value {:?}", cell)))
+            })
+            .collect::<Result<Vec<f64>, AppError>>()?;
+        if values.len() != STUDIO_COLUMNS {
+            return Err(line_error(
+                line,
+                format!("expected {} joints, got {}", STUDIO_COLUMNS, values.len()),
+            ));
+        }
+        let time = points.len() as f64 * STUDIO_PERIOD_MS / 1000.0;
+        points.push(TrajectoryPoint { line, time, values });
+    }
+    Ok(Trajectory {
+        name: name.to_string(),
+        space: Space::Joint,
+        points,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(text: &str) -> Result<Trajectory, AppError> {
+        parse(
+            text,
+            "test",
+            TrajectoryFormat::Csv,
+            &ImportOptions::default(),
+        )
+    }
+
+    #[test]
+    fn maps_csv_columns_by_name() {
+        let trajectory = csv("# recorded on site\nj2,time,j1\n10,0,1\n20,0.5,2\n").unwrap();
+        assert_eq!(trajectory.space, Space::Joint);
+        assert_eq!(trajectory.points.len(), 2);
+        let point = &trajectory.points[1];
+        assert_eq!(point.line, 4);
+        assert_eq!(point.time, 0.5);
+        assert!((point.values[0] - 2f64.to_radians()).abs() < 1e-12);
+        assert!((point.values[1] - 20f64.to_radians()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reports_csv_errors_with_lines() {
+        let error = csv("j1,j3\n0,0\n").unwrap_err().to_string();
+        assert!(
+            error.contains("line 1") && error.contains("missing column j2"),
+            "{}",
+            error
+        );
+
+        let error = csv("j1,j2\n0,0\n1,oops\n").unwrap_err().to_string();
+        assert!(
+            error.contains("line 3") && error.contains("\"oops\""),
+            "{}",
+            error
+        );
+
+        let error = csv("j1,speed\n0,0\n").unwrap_err().to_string();
+        assert!(error.contains("unknown column \"speed\""), "{}", error);
+
+        assert!(csv("j1,x\n0,0\n").is_err());
+    }
+
+    #[test]
+    fn uses_period_without_time_column() {
+        let trajectory =
+            csv("x,y,z,roll,pitch,yaw\n300,0,200,180,0,0\n301,0,200,180,0,0\n").unwrap();
+        assert_eq!(trajectory.space, Space::Cartesian);
+        assert_eq!(trajectory.points[1].time, 0.004);
+        assert_eq!(trajectory.points[1].values[0], 301.0);
+        assert!((trajectory.points[1].values[3] - std::f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tracks_json_point_lines() {
+        let text = r#"{
+  "name": "pick",
+  "unit": "rad",
+  "points": [
+    { "time": 0, "values": [0, 0, 0, 0, 0, 0] },
+    [0.1, 0, 0, 0, 0, 0],
+    { "time": "late", "values": [] }
+  ]
+}"#;
+        assert_eq!(element_lines(text, "points"), vec![5, 6, 7]);
+        let error = parse(text, "x", TrajectoryFormat::Json, &ImportOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 7"), "{}", error);
+    }
+
+    #[test]
+    fn reads_studio_rows() {
+        let text = "0,0,0,0,0,0,0\n\n90 0 0 0 0 0 0\n";
+        let trajectory = parse(
+            text,
+            "old",
+            TrajectoryFormat::Studio,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(trajectory.points[1].line, 3);
+        assert_eq!(trajectory.points[1].values.len(), STUDIO_COLUMNS);
+
+        let error = parse(
+            "1,2,3\n",
+            "old",
+            TrajectoryFormat::Studio,
+            &ImportOptions::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("line 1"), "{}", error);
+    }
+}
@@ -0,0 +1,527 @@
+//! 轨迹文件导入、校验和导出
+//!
+//! 支持三种文件格式（见 `import` / `export`）：带表头的 CSV、JSON，以及旧版 xArm Studio 导出的 `.traj`。
+//! 导入后统一为 `Trajectory`：时间单位为秒，角度为 rad，位置为 mm。
+//! 每个点记录其在源文件中的行号，校验结果据此指出出错位置。
+//!
+//! 校验按目标机型（`xarm::model`）检查轴数、关节范围、关节速度和加速度；
+//! 笛卡尔轨迹只检查 TCP 线速度和线加速度。导出前必须通过校验。
+
+pub mod export;
+pub mod import;
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::packages::xarm::model::{ArmModel, MotionLimits};
+use crate::utils::error::AppError;
+
+/// 最多报告的问题数，超过后停止校验
+const MAX_VIOLATIONS: usize = 1000;
+/// 比较上限时的相对容差，避免单位换算误差误报
+const TOLERANCE: f64 = 1e-6;
+
+/// 文件格式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrajectoryFormat {
+    Csv,
+    Json,
+    /// 旧版 xArm Studio 导出
+    Studio,
+}
+
+impl TrajectoryFormat {
+    /// 按扩展名判断格式
+    pub fn from_path(path: &Path) -> Result<Self, AppError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Ok(TrajectoryFormat::Csv),
+            Some("json") => Ok(TrajectoryFormat::Json),
+            Some("traj") => Ok(TrajectoryFormat::Studio),
+            _ => Err(AppError::InvalidArgument(format!(
+                "unknown trajectory format: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// 轨迹空间
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Space {
+    /// 关节角
+    Joint,
+    /// TCP 位姿 x/y/z/roll/pitch/yaw
+    Cartesian,
+}
+
+/// 文件中的角度单位
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AngleUnit {
+    #[default]
+    Deg,
+    Rad,
+}
+
+impl AngleUnit {
+    /// 换算为 rad
+    pub fn to_rad(self, value: f64) -> f64 {
+        match self {
+            AngleUnit::Deg => value.to_radians(),
+            AngleUnit::Rad => value,
+        }
+    }
+
+    /// 由 rad 换算为本单位
+    pub fn rad_to(self, value: f64) -> f64 {
+        match self {
+            AngleUnit::Deg => value.to_degrees(),
+            AngleUnit::Rad => value,
+        }
+    }
+}
+
+/// 轨迹点
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrajectoryPoint {
+    /// 源文件行号（从 1 开始），非文件来源时为 0
+    #[serde(default)]
+    pub line: usize,
+    /// 相对起点的时间（秒）
+    pub time: f64,
+    /// 关节角（rad）或 TCP 位姿（mm / rad）
+    pub values: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Trajectory {
+    pub name: String,
+    pub space: Space,
+    pub points: Vec<TrajectoryPoint>,
+}
+
+/// 问题类型
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    Empty,
+    /// 关节数与机型不符
+    AxisCount,
+    /// 时间不递增
+    Time,
+    JointLimit,
+    Velocity,
+    Acceleration,
+}
+
+/// 校验发现的问题
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// 源文件行号
+    pub line: usize,
+    /// 点序号（从 0 开始）
+    pub index: usize,
+    /// 关节号（从 1 开始），与单个关节无关时为空
+    pub joint: Option<usize>,
+    /// 实际值（rad、rad/s、mm/s 等内部单位）
+    pub value: Option<f64>,
+    pub limit: Option<f64>,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(kind: ViolationKind, index: usize, point: &TrajectoryPoint, message: String) -> Self {
+        Self {
+            kind,
+            line: point.line,
+            index,
+            joint: None,
+            value: None,
+            limit: None,
+            message,
+        }
+    }
+
+    fn joint(mut self, joint: usize, value: f64, limit: f64) -> Self {
+        self.joint = Some(joint + 1);
+        self.value = Some(value);
+        self.limit = Some(limit);
+        self
+    }
+}
+
+fn exceeds(value: f64, limit: f64) -> bool {
+    value.abs() > limit * (1.0 + TOLERANCE)
+}
+
+/// 按机型校验轨迹；关节轨迹多出的关节必须为 0（旧版文件固定 7 列）
+pub fn validate(trajectory: &Trajectory, model: ArmModel) -> Vec<Violation> {
+    if trajectory.points.is_empty() {
+        return vec![Violation {
+            kind: ViolationKind::Empty,
+            line: 0,
+            index: 0,
+            joint: None,
+            value: None,
+            limit: None,
+            message: "trajectory has no points".to_string(),
+        }];
+    }
+    let limits = model.limits();
+    let space = trajectory.space;
+    let width = match space {
+        Space::Joint => model.axis(),
+        Space::Cartesian => 6,
+    };
+
+    let mut violations = Vec::new();
+    // 上一个有效点
+    let mut last: Option<&TrajectoryPoint> = None;
+    // 上一段的速度和时长
+    let mut last_segment: Option<(Vec<f64>, f64)> = None;
+    for (index, point) in trajectory.points.iter().enumerate() {
+        if violations.len() >= MAX_VIOLATIONS {
+            break;
+        }
+        if let Some(violation) = check_width(index, point, width, space) {
+            violations.push(violation);
+            continue;
+        }
+        if space == Space::Joint {
+            check_joint_limits(index, point, &limits, &mut violations);
+        }
+
+        let Some(previous) = last.replace(point) else {
+            continue;
+        };
+        let dt = point.time - previous.time;
+        if dt <= 0.0 {
+            violations.push(Violation::new(
+                ViolationKind::Time,
+                index,
+                point,
+                format!(
+                    "time {} s is not after the previous point ({} s, line {})",
+                    point.time, previous.time, previous.line
+                ),
+            ));
+            // 时间错误的点不参与速度计算
+            last = Some(previous);
+            last_segment = None;
+            continue;
+        }
+
+        let velocity: Vec<f64> = point
+            .values
+            .iter()
+            .zip(&previous.values)
+            .take(width)
+            .map(|(value, previous)| (value - previous) / dt)
+            .collect();
+        check_rate(
+            ViolationKind::Velocity,
+            index,
+            point,
+            space,
+            &velocity,
+            &limits,
+            &mut violations,
+        );
+        if let Some((last_velocity, last_dt)) = &last_segment {
+            // 两段速度分别对应各段中点，间隔为两段时长的平均
+            let span = (dt + last_dt) / 2.0;
+            let acceleration: Vec<f64> = velocity
+                .iter()
+                .zip(last_velocity)
+                .map(|(v, last)| (v - last) / span)
+                .collect();
+            check_rate(
+                ViolationKind::Acceleration,
+                index,
+                point,
+                space,
+                &acceleration,
+                &limits,
+                &mut violations,
+            );
+        }
+        last_segment = Some((velocity, dt));
+    }
+    violations.truncate(MAX_VIOLATIONS);
+    violations
+}
+
+/// 检查数值个数，关节轨迹允许末尾补 0
+fn check_width(
+    index: usize,
+    point: &TrajectoryPoint,
+    width: usize,
+    space: Space,
+) -> Option<Violation> {
+    let count = point.values.len();
+    let padded =
+        space == Space::Joint && point.values[width.min(count)..].iter().all(|v| *v == 0.0);
+    if count == width || (count > width && padded) {
+        return None;
+    }
+    let expected = match space {
+        Space::Joint => format!("{} joints", width),
+        Space::Cartesian => "6 pose values".to_string(),
+    };
+    Some(Violation::new(
+        ViolationKind::AxisCount,
+        index,
+        point,
+        format!("expected {}, got {} values", expected, count),
+    ))
+}
+
+fn check_joint_limits(
+    index: usize,
+    point: &TrajectoryPoint,
+    limits: &MotionLimits,
+    violations: &mut Vec<Violation>,
+) {
+    for (joint, (value, (min, max))) in point.values.iter().zip(&limits.joints).enumerate() {
+        let margin = (max - min) * TOLERANCE;
+        if *value < min - margin || *value > max + margin {
+            let limit = if *value < *min { *min } else { *max };
+            violations.push(
+                Violation::new(
+                    ViolationKind::JointLimit,
+                    index,
+                    point,
+                    format!(
+                        "j{} = {:.3}° is outside [{:.3}°, {:.3}°]",
+                        joint + 1,
+                        value.to_degrees(),
+                        min.to_degrees(),
+                        max.to_degrees()
+                    ),
+                )
+                .joint(joint, *value, limit),
+            );
+        }
+    }
+}
+
+/// 检查速度或加速度：关节逐个比较，笛卡尔比较 x/y/z 的合成值
+fn check_rate(
+    kind: ViolationKind,
+    index: usize,
+    point: &TrajectoryPoint,
+    space: Space,
+    rates: &[f64],
+    limits: &MotionLimits,
+    violations: &mut Vec<Violation>,
+) {
+    let (name, unit) = match kind {
+        ViolationKind::Acceleration => ("acceleration", "/s²"),
+        _ => ("speed", "/s"),
+    };
+    match space {
+        Space::Joint => {
+            let limit = match kind {
+                ViolationKind::Acceleration => limits.joint_acc,
+                _ => limits.joint_speed,
+            };
+            for (joint, rate) in rates.iter().enumerate() {
+                if exceeds(*rate, limit) {
+                    violations.push(
+                        Violation::new(
+                            kind,
+                            index,
+                            point,
+                            format!(
+                                "j{} {} {:.3}°{} exceeds {:.3}°{}",
+                                joint + 1,
+                                name,
+                                rate.abs().to_degrees(),
+                                unit,
+                                limit.to_degrees(),
+                                unit
+                            ),
+                        )
+                        .joint(joint, rate.abs(), limit),
+                    );
+                }
+            }
+        }
+        Space::Cartesian => {
+            let limit = match kind {
+                ViolationKind::Acceleration => limits.linear_acc,
+                _ => limits.linear_speed,
+            };
+            let rate = rates.iter().take(3).map(|v| v * v).sum::<f64>().sqrt();
+            if exceeds(rate, limit) {
+                let mut violation = Violation::new(
+                    kind,
+                    index,
+                    point,
+                    format!(
+                        "TCP {} {:.1} mm{} exceeds {:.1} mm{}",
+                        name, rate, unit, limit, unit
+                    ),
+                );
+                violation.value = Some(rate);
+                violation.limit = Some(limit);
+                violations.push(violation);
+            }
+        }
+    }
+}
+
+/// 校验失败时的错误，带上第一个问题
+pub fn ensure_valid(trajectory: &Trajectory, model: ArmModel) -> Result<(), AppError> {
+    let violations = validate(trajectory, model);
+    match violations.first() {
+        None => Ok(()),
+        Some(first) => Err(AppError::InvalidArgument(format!(
+            "trajectory has {} violation(s), first at line {}: {}",
+            violations.len(),
+            first.line,
+            first.message
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export::{render, ExportOptions};
+    use super::import::{parse, ImportOptions};
+    use super::*;
+
+    fn joint_trajectory(rows: &[(f64, [f64; 6])]) -> Trajectory {
+        Trajectory {
+            name: "test".to_string(),
+            space: Space::Joint,
+            points: rows
+                .iter()
+                .enumerate()
+                .map(|(i, (time, values))| TrajectoryPoint {
+                    line: i + 2,
+                    time: *time,
+                    values: values.iter().map(|v| v.to_radians()).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_smooth_trajectory() {
+        let rows: Vec<(f64, [f64; 6])> = (0..50)
+            .map(|i| {
+                (
+                    i as f64 * 0.01,
+                    [i as f64 * 0.1, 0.0, -10.0, 0.0, 10.0, 0.0],
+                )
+            })
+            .collect();
+        assert_eq!(validate(&joint_trajectory(&rows), ArmModel::Xarm6), vec![]);
+    }
+
+    #[test]
+    fn reports_limits_with_lines() {
+        let trajectory = joint_trajectory(&[
+            (0.0, [0.0; 6]),
+            (1.0, [0.0, 130.0, 0.0, 0.0, 0.0, 0.0]),
+            (1.01, [10.0, 130.0, 0.0, 0.0, 0.0, 0.0]),
+            (1.01, [10.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        ]);
+        let violations = validate(&trajectory, ArmModel::Xarm6);
+        let kinds: Vec<(ViolationKind, usize, Option<usize>)> = violations
+            .iter()
+            .map(|v| (v.kind, v.line, v.joint))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ViolationKind::JointLimit, 3, Some(2)),
+                (ViolationKind::JointLimit, 4, Some(2)),
+                (ViolationKind::Velocity, 4, Some(1)),
+                (ViolationKind::Acceleration, 4, Some(1)),
+                (ViolationKind::Time, 5, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_axis_count() {
+        let mut trajectory = joint_trajectory(&[(0.0, [0.0, 0.0, 0.0, 0.0, 0.0, 5.0])]);
+        assert_eq!(validate(&trajectory, ArmModel::Xarm5).len(), 1);
+        assert_eq!(
+            validate(&trajectory, ArmModel::Xarm7)[0].kind,
+            ViolationKind::AxisCount
+        );
+        // 多出的关节为 0 视为补位
+        trajectory.points[0].values.push(0.0);
+        assert!(validate(&trajectory, ArmModel::Xarm6).is_empty());
+    }
+
+    #[test]
+    fn exports_studio_at_fixed_period() {
+        let trajectory =
+            joint_trajectory(&[(0.0, [0.0; 6]), (0.012, [1.2, 0.0, 0.0, 0.0, 0.0, 0.0])]);
+        let text = render(
+            &trajectory,
+            ArmModel::Xarm6,
+            TrajectoryFormat::Studio,
+            &ExportOptions::default(),
+        )
+        .unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1], "0.4000,0.0000,0.0000,0.0000,0.0000,0.0000,0.0000");
+        assert!(rows[3].starts_with("1.2000,"));
+
+        let imported = parse(
+            &text,
+            "x",
+            TrajectoryFormat::Studio,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+        assert!(validate(&imported, ArmModel::Xarm6).is_empty());
+    }
+
+    #[test]
+    fn round_trips_csv() {
+        let trajectory =
+            joint_trajectory(&[(0.0, [0.0; 6]), (0.5, [10.0, 5.0, -5.0, 0.0, 1.0, 2.0])]);
+        let text = render(
+            &trajectory,
+            ArmModel::Xarm6,
+            TrajectoryFormat::Csv,
+            &ExportOptions::default(),
+        )
+        .unwrap();
+        let imported = parse(
+            &text,
+            "test",
+            TrajectoryFormat::Csv,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+        for (a, b) in imported.points.iter().zip(&trajectory.points) {
+            assert_eq!(a.time, b.time);
+            for (x, y) in a.values.iter().zip(&b.values) {
+                assert!((x - y).abs() < 1e-9);
+            }
+        }
+        assert!(render(
+            &joint_trajectory(&[(0.0, [0.0, 200.0, 0.0, 0.0, 0.0, 0.0])]),
+            ArmModel::Xarm6,
+            TrajectoryFormat::Csv,
+            &ExportOptions::default()
+        )
+        .is_err());
+    }
+}
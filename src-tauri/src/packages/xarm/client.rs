@@ -0,0 +1,329 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time;
+
+use super::protocol::{self, register, Header, Reply, ALL_AXES, HEADER_LEN};
+use crate::utils::error::AppError;
+
+/// 默认连接超时
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// 默认应答超时
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 控制器错误码和警告码
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ControllerErrors {
+    pub error_code: u8,
+    pub warn_code: u8,
+}
+
+/// 当前关节角（rad）和 TCP 位姿（mm / rad）
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ArmPosition {
+    pub joints: Vec<f32>,
+    pub pose: Vec<f32>,
+}
+
+/// 指令端口客户端
+///
+/// 连接断开后下一次调用会自动重连；同一连接上的请求串行执行。
+pub struct XArmClient {
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+    next_tid: u16,
+    timeout: Duration,
+}
+
+impl XArmClient {
+    /// 创建客户端，不立即连接
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            stream: None,
+            next_tid: 1,
+            timeout: REPLY_TIMEOUT,
+        }
+    }
+
+    /// 建立连接（已连接时直接返回）
+    pub async fn connect(&mut self) -> Result<(), AppError> {
+        if self.stream.is_some() {
+            return Ok(());
+        }
+        let connect_error = |message: String| AppError::Connect {
+            addr: self.addr.to_string(),
+            message,
+        };
+        let stream = time::timeout(CONNECT_TIMEOUT, TcpStream::connect(self.addr))
+            .await
+            .map_err(|_| connect_error("timed out".to_string()))?
+            .map_err(|e| connect_error(e.to_string()))?;
+        stream.set_nodelay(true)?;
+        info!("Connected to xArm {}", self.addr);
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// 断开连接
+    pub async fn disconnect(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            let _ = stream.shutdown().await;
+            info!("Disconnected from xArm {}", self.addr);
+        }
+    }
+
+    /// 发送一条请求并等待对应事务 ID 的应答
+    ///
+    /// 出现 IO 错误、超时或报文错误时关闭连接，避免后续请求读到错位的数据。
+    pub async fn call(&mut self, register: u8, params: &[u8]) -> Result<Reply, AppError> {
+        self.connect().await?;
+        let tid = self.next_tid;
+        self.next_tid = self.next_tid.wrapping_add(1);
+
+        let result = time::timeout(self.timeout, self.exchange(tid, register, params)).await;
+        let result = match result {
+            Ok(result) => result,
+            Err(_) => Err(AppError::Timeout(format!(
+                "register {} reply from {}",
+                register, self.addr
+            ))),
+        };
+        if let Err(e) = &result {
+            warn!("xArm {} register {} failed: {}", self.addr, register, e);
+            self.stream = None;
+        }
+        result
+    }
+
+    async fn exchange(&mut self, tid: u16, register: u8, params: &[u8]) -> Result<Reply, AppError> {
+        let addr = self.addr;
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| AppError::Disconnected(addr.to_string()))?;
+        stream
+            .write_all(&protocol::encode_request(tid, register, params))
+            .await?;
+
+        loop {
+            let reply = read_reply(stream, addr).await?;
+            // 之前超时的请求可能迟到，丢弃事务 ID 不匹配的应答
+            if reply.tid != tid {
+                debug!(
+                    "Discard stale reply {} from {} (waiting for {})",
+                    reply.tid, addr, tid
+                );
+                continue;
+            }
+            if reply.register != register {
+                return Err(AppError::Protocol(format!(
+                    "expected register {}, got {}",
+                    register, reply.register
+                )));
+            }
+            return Ok(reply);
+        }
+    }
+
+    /// 读寄存器，返回应答数据
+    pub async fn read_register(
+        &mut self,
+        register: u8,
+        params: &[u8],
+    ) -> Result<Vec<u8>, AppError> {
+        Ok(self.call(register, params).await?.data)
+    }
+
+    /// 写寄存器，返回应答状态字节
+    pub async fn write_register(&mut self, register: u8, params: &[u8]) -> Result<u8, AppError> {
+        Ok(self.call(register, params).await?.status)
+    }
+
+    pub async fn get_version(&mut self) -> Result<String, AppError> {
+        let reply = self.call(register::GET_VERSION, &[]).await?;
+        Ok(protocol::bytes_to_string(&reply.data))
+    }
+
+    pub async fn get_robot_sn(&mut self) -> Result<String, AppError> {
+        let reply = self.call(register::GET_ROBOT_SN, &[]).await?;
+        Ok(protocol::bytes_to_string(&reply.data))
+    }
+
+    /// 运动状态：1 运动中，2 就绪，3 暂停，4 停止
+    pub async fn get_state(&mut self) -> Result<u8, AppError> {
+        let reply = self.call(register::GET_STATE, &[]).await?;
+        Ok(reply.expect_len(1)?[0])
+    }
+
+    pub async fn get_error(&mut self) -> Result<ControllerErrors, AppError> {
+        let reply = self.call(register::GET_ERROR, &[]).await?;
+        let data = reply.expect_len(2)?;
+        Ok(ControllerErrors {
+            error_code: data[0],
+            warn_code: data[1],
+        })
+    }
+
+    pub async fn get_joint_positions(&mut self) -> Result<Vec<f32>, AppError> {
+        let reply = self.call(register::GET_JOINT_POS, &[]).await?;
+        Ok(protocol::bytes_to_f32s(reply.expect_len(28)?))
+    }
+
+    pub async fn get_tcp_pose(&mut self) -> Result<Vec<f32>, AppError> {
+        let reply = self.call(register::GET_TCP_POSE, &[]).await?;
+        Ok(protocol::bytes_to_f32s(reply.expect_len(24)?))
+    }
+
+    pub async fn get_position(&mut self) -> Result<ArmPosition, AppError> {
+        Ok(ArmPosition {
+            joints: self.get_joint_positions().await?,
+            pose: self.get_tcp_pose().await?,
+        })
+    }
+
+    /// 使能/失能全部关节
+    pub async fn motion_enable(&mut self, enable: bool) -> Result<(), AppError> {
+        self.write_register(register::MOTION_EN, &[ALL_AXES, enable as u8])
+            .await?;
+        Ok(())
+    }
+
+    /// 运动模式：0 位置，1 伺服，2 拖动示教 ...
+    pub async fn set_mode(&mut self, mode: u8) -> Result<(), AppError> {
+        self.write_register(register::SET_MODE, &[mode]).await?;
+        Ok(())
+    }
+
+    /// 运动状态：0 运动，3 暂停，4 停止
+    pub async fn set_state(&mut self, state: u8) -> Result<(), AppError> {
+        if !matches!(state, 0 | 3 | 4) {
+            return Err(AppError::InvalidArgument(format!(
+                "state must be 0, 3 or 4, got {}",
+                state
+            )));
+        }
+        self.write_register(register::SET_STATE, &[state]).await?;
+        Ok(())
+    }
+
+    pub async fn clean_error(&mut self) -> Result<(), AppError> {
+        self.write_register(register::CLEAN_ERR, &[]).await?;
+        Ok(())
+    }
+
+    pub async fn clean_warn(&mut self) -> Result<(), AppError> {
+        self.write_register(register::CLEAN_WAR, &[]).await?;
+        Ok(())
+    }
+}
+
+async fn read_reply(stream: &mut TcpStream, addr: SocketAddr) -> Result<Reply, AppError> {
+    let closed = |e: std::io::Error| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => AppError::Disconnected(addr.to_string()),
+        _ => AppError::from(e),
+    };
+    let mut header = [0u8; HEADER_LEN];
+    stream.read_exact(&mut header).await.map_err(closed)?;
+    let header = Header::parse(&header)?;
+    let mut body = vec![0u8; header.len];
+    stream.read_exact(&mut body).await.map_err(closed)?;
+    Reply::from_parts(header, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::packages::simulator::stand_in::{sim_stand_in, still_config, SimStandIn};
+    use crate::packages::simulator::SimConfig;
+
+    async fn mock_server() -> SimStandIn {
+        sim_stand_in(still_config()).await
+    }
+
+    #[test]
+    fn encodes_request_frame() {
+        let frame = protocol::encode_request(0x0102, register::SET_MODE, &[1]);
+        assert_eq!(frame, vec![0x01, 0x02, 0x00, 0x02, 0x00, 0x02, 19, 1]);
+    }
+
+    #[test]
+    fn rejects_foreign_protocol() {
+        let header = [0, 1, 0, 0, 0, 3];
+        assert!(matches!(Header::parse(&header), Err(AppError::Protocol(_))));
+    }
+
+    #[tokio::test]
+    async fn reads_info_from_mock() {
+        let sim = mock_server().await;
+        let addr = sim.command_addr;
+        let mut client = XArmClient::new(addr);
+        client.connect().await.unwrap();
+
+        let defaults = SimConfig::default();
+        assert_eq!(
+            client.get_version().await.unwrap(),
+            format!("v{}", defaults.version)
+        );
+        assert_eq!(client.get_robot_sn().await.unwrap(), defaults.arm_sn);
+        assert_eq!(
+            client.get_error().await.unwrap(),
+            ControllerErrors::default()
+        );
+        let position = client.get_position().await.unwrap();
+        assert_eq!(position.joints.len(), 7);
+        assert_eq!(position.pose.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn controls_motion_state() {
+        let sim = mock_server().await;
+        let addr = sim.command_addr;
+        let mut client = XArmClient::new(addr);
+
+        client.motion_enable(false).await.unwrap();
+        assert_eq!(client.get_state().await.unwrap(), 4);
+        client.motion_enable(true).await.unwrap();
+        client.set_mode(0).await.unwrap();
+        client.set_state(0).await.unwrap();
+        assert_eq!(client.get_state().await.unwrap(), 2);
+        client.clean_error().await.unwrap();
+        assert!(matches!(
+            client.set_state(7).await,
+            Err(AppError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn transaction_ids_wrap() {
+        let sim = mock_server().await;
+        let addr = sim.command_addr;
+        let mut client = XArmClient::new(addr);
+        client.next_tid = u16::MAX;
+        client.get_state().await.unwrap();
+        client.get_state().await.unwrap();
+        assert_eq!(client.next_tid, 1);
+    }
+
+    #[tokio::test]
+    async fn reports_connect_error() {
+        // 绑定后立即释放，得到一个大概率无人监听的端口
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut client = XArmClient::new(addr);
+        assert!(matches!(
+            client.get_state().await,
+            Err(AppError::Connect { .. })
+        ));
+        assert!(client.stream.is_none());
+    }
+}
@@ -0,0 +1,104 @@
+//! xArm 控制器通信
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::utils::error::AppError;
+
+pub mod client;
+pub mod model;
+pub mod protocol;
+pub mod report;
+pub mod session;
+pub mod stream;
+
+use client::XArmClient;
+use report::ReportKind;
+
+/// 控制器 HTTP 服务端口，即首页打开的控制器页面所在的服务，日志下载和固件上传也走这里
+pub const CONTROLLER_HTTP_PORT: u16 = 18333;
+
+/// 前端传入的机械臂地址，可以直接传 `ArmIpIntro` 对象（只用到 `ip`）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArmAddress {
+    pub ip: String,
+    /// 指令端口，默认 502（模拟器可能使用其他端口）
+    #[serde(default)]
+    pub command_port: Option<u16>,
+}
+
+impl ArmAddress {
+    pub fn socket_addr(&self) -> Result<SocketAddr, AppError> {
+        let ip: IpAddr =
+            self.ip.trim().parse().map_err(|_| {
+                AppError::InvalidArgument(format!("invalid ip address: {}", self.ip))
+            })?;
+        Ok(SocketAddr::new(
+            ip,
+            self.command_port.unwrap_or(protocol::COMMAND_PORT),
+        ))
+    }
+
+    /// 上报端口地址
+    pub fn report_addr(&self, kind: ReportKind) -> Result<SocketAddr, AppError> {
+        Ok(SocketAddr::new(self.socket_addr()?.ip(), kind.port()))
+    }
+}
+
+/// 按地址复用的指令客户端
+#[derive(Clone, Default)]
+pub struct XArmClients {
+    clients: Arc<Mutex<HashMap<SocketAddr, Arc<AsyncMutex<XArmClient>>>>>,
+}
+
+impl XArmClients {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取（或创建）某个地址的客户端，连接在首次调用时建立
+    pub fn get(&self, address: &ArmAddress) -> Result<Arc<AsyncMutex<XArmClient>>, AppError> {
+        let addr = address.socket_addr()?;
+        let mut clients = self
+            .clients
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        Ok(Arc::clone(clients.entry(addr).or_insert_with(|| {
+            Arc::new(AsyncMutex::new(XArmClient::new(addr)))
+        })))
+    }
+
+    /// 当前所有客户端
+    pub fn all(&self) -> Vec<(SocketAddr, Arc<AsyncMutex<XArmClient>>)> {
+        self.clients
+            .lock()
+            .map(|clients| {
+                clients
+                    .iter()
+                    .map(|(addr, client)| (*addr, Arc::clone(client)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 断开并移除客户端，返回之前是否存在
+    pub async fn remove(&self, address: &ArmAddress) -> Result<bool, AppError> {
+        let addr = address.socket_addr()?;
+        let client = self
+            .clients
+            .lock()
+            .ok()
+            .and_then(|mut clients| clients.remove(&addr));
+        match client {
+            Some(client) => {
+                client.lock().await.disconnect().await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! 机型参数
+//!
+//! 机型由发现结果中的 `axis` 和 `device_type` 确定：xArm 5/6/7 的 `device_type` 等于轴数，
+//! Lite 6 为 9，850 为 12。关节范围和速度、加速度上限取自各机型的产品规格。
+
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::error::AppError;
+
+/// 支持的机型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ArmModel {
+    Xarm5,
+    Xarm6,
+    Xarm7,
+    Lite6,
+    Xarm850,
+}
+
+/// 前端传入的机型信息，可以直接传 `ArmIpIntro` 对象（只用到 `axis` 和 `device_type`）
+#[derive(Deserialize, Clone, Debug)]
+pub struct ArmIdentity {
+    pub axis: String,
+    pub device_type: String,
+}
+
+impl ArmIdentity {
+    pub fn model(&self) -> Result<ArmModel, AppError> {
+        let parse = |name: &str, value: &str| {
+            value
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| AppError::InvalidArgument(format!("invalid {}: {:?}", name, value)))
+        };
+        ArmModel::from_discovery(
+            parse("axis", &self.axis)?,
+            parse("device_type", &self.device_type)?,
+        )
+    }
+}
+
+/// 运动上限
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MotionLimits {
+    /// 各关节范围（rad）
+    pub joints: Vec<(f64, f64)>,
+    /// 关节最大速度（rad/s）
+    pub joint_speed: f64,
+    /// 关节最大加速度（rad/s²）
+    pub joint_acc: f64,
+    /// TCP 最大线速度（mm/s）
+    pub linear_speed: f64,
+    /// TCP 最大线加速度（mm/s²）
+    pub linear_acc: f64,
+}
+
+const fn deg(value: f64) -> f64 {
+    value * PI / 180.0
+}
+
+/// 无限位关节按 ±360° 处理
+const FULL: (f64, f64) = (deg(-360.0), deg(360.0));
+
+impl ArmModel {
+    pub fn from_discovery(axis: u16, device_type: u16) -> Result<Self, AppError> {
+        match (axis, device_type) {
+            (5, 5) => Ok(ArmModel::Xarm5),
+            (6, 6) => Ok(ArmModel::Xarm6),
+            (7, 7) => Ok(ArmModel::Xarm7),
+            (6, 9) => Ok(ArmModel::Lite6),
+            (6, 12) => Ok(ArmModel::Xarm850),
+            _ => Err(AppError::InvalidArgument(format!(
+                "unsupported arm: axis {}, device_type {}",
+                axis, device_type
+            ))),
+        }
+    }
+
+    pub fn axis(self) -> usize {
+        match self {
+            ArmModel::Xarm5 => 5,
+            ArmModel::Xarm7 => 7,
+            ArmModel::Xarm6 | ArmModel::Lite6 | ArmModel::Xarm850 => 6,
+        }
+    }
+
+    pub fn limits(self) -> MotionLimits {
+        let joints = match self {
+            ArmModel::Xarm5 => vec![
+                FULL,
+                (deg(-118.0), deg(120.0)),
+                (deg(-225.0), deg(11.0)),
+                (deg(-97.0), deg(180.0)),
+                FULL,
+            ],
+            ArmModel::Xarm6 => vec![
+                FULL,
+                (deg(-118.0), deg(120.0)),
+                (deg(-225.0), deg(11.0)),
+                FULL,
+                (deg(-97.0), deg(180.0)),
+                FULL,
+            ],
+            ArmModel::Xarm7 => vec![
+                FULL,
+                (deg(-118.0), deg(120.0)),
+                FULL,
+                (deg(-11.0), deg(225.0)),
+                FULL,
+                (deg(-97.0), deg(180.0)),
+                FULL,
+            ],
+            ArmModel::Lite6 => vec![
+                FULL,
+                (deg(-150.0), deg(150.0)),
+                (deg(-3.5), deg(300.0)),
+                FULL,
+                (deg(-124.0), deg(124.0)),
+                FULL,
+            ],
+            ArmModel::Xarm850 => vec![
+                FULL,
+                (deg(-132.0), deg(132.0)),
+                (deg(-242.0), deg(3.5)),
+                FULL,
+                (deg(-117.0), deg(117.0)),
+                FULL,
+            ],
+        };
+        MotionLimits {
+            joints,
+            joint_speed: PI,
+            joint_acc: 20.0,
+            linear_speed: 1000.0,
+            linear_acc: 50000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_discovery_fields() {
+        let identity = ArmIdentity {
+            axis: "6".to_string(),
+            device_type: " 9".to_string(),
+        };
+        assert_eq!(identity.model().unwrap(), ArmModel::Lite6);
+        assert_eq!(ArmModel::from_discovery(7, 7).unwrap(), ArmModel::Xarm7);
+        assert!(ArmModel::from_discovery(6, 7).is_err());
+    }
+
+    #[test]
+    fn limits_match_axis_count() {
+        for model in [
+            ArmModel::Xarm5,
+            ArmModel::Xarm6,
+            ArmModel::Xarm7,
+            ArmModel::Lite6,
+            ArmModel::Xarm850,
+        ] {
+            let limits = model.limits();
+            assert_eq!(limits.joints.len(), model.axis());
+            assert!(limits.joints.iter().all(|(min, max)| min < max));
+        }
+    }
+}
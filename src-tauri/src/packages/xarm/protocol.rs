@@ -0,0 +1,179 @@
+//! xArm 私有指令协议（类 Modbus-TCP，端口 502）
+//!
+//! 请求：`<事务 ID u16><协议 u16 = 2><长度 u16><寄存器 u8><参数...>`，长度包含寄存器字节。
+//! 应答：`<事务 ID u16><协议 u16><长度 u16><寄存器 u8><状态 u8><数据...>`。
+//! 整数为大端，浮点为小端 f32；状态字节 bit6 表示控制器有错误，bit5 表示有警告。
+
+use crate::utils::error::AppError;
+
+/// 指令端口
+pub const COMMAND_PORT: u16 = 502;
+/// xArm 私有协议标识
+pub const PROTOCOL_ID: u16 = 2;
+/// 帧头长度（事务 ID + 协议 + 长度）
+pub const HEADER_LEN: usize = 6;
+/// 版本、序列号等字符串寄存器的固定长度
+pub const STRING_LEN: usize = 40;
+/// 单帧最大长度，超过视为报文错误
+pub const MAX_FRAME_LEN: usize = 1024;
+
+/// 状态字节：控制器有错误
+pub const STATUS_ERROR: u8 = 0x40;
+/// 状态字节：控制器有警告
+pub const STATUS_WARN: u8 = 0x20;
+
+/// 寄存器编号
+pub mod register {
+    pub const GET_VERSION: u8 = 1;
+    pub const GET_ROBOT_SN: u8 = 2;
+    pub const MOTION_EN: u8 = 11;
+    pub const SET_STATE: u8 = 12;
+    pub const GET_STATE: u8 = 13;
+    pub const GET_CMDNUM: u8 = 14;
+    pub const GET_ERROR: u8 = 15;
+    pub const CLEAN_ERR: u8 = 16;
+    pub const CLEAN_WAR: u8 = 17;
+    pub const SET_MODE: u8 = 19;
+    pub const MOVE_JOINT: u8 = 23;
+    pub const SET_TCP_OFFSET: u8 = 35;
+    pub const SET_LOAD_PARAM: u8 = 36;
+    pub const SET_COLLIS_SENS: u8 = 37;
+    pub const SET_TEACH_SENS: u8 = 38;
+    pub const SAVE_CONF: u8 = 40;
+    pub const GET_TCP_POSE: u8 = 41;
+    pub const GET_JOINT_POS: u8 = 42;
+    pub const SET_REDUCED_TRSV: u8 = 47;
+    pub const SET_REDUCED_P2PV: u8 = 48;
+    pub const SET_REDUCED_MODE: u8 = 50;
+    pub const SET_GRAVITY_DIR: u8 = 51;
+    pub const SET_LIMIT_XYZ: u8 = 52;
+    pub const GET_REDUCED_STATE: u8 = 53;
+    pub const SET_REDUCED_JRANGE: u8 = 58;
+    pub const SET_FENSE_ON: u8 = 59;
+    pub const SET_COLLIS_REB: u8 = 60;
+    pub const SET_WORLD_OFFSET: u8 = 73;
+    pub const CGPIO_SET_IN_FUN: u8 = 137;
+    pub const CGPIO_SET_OUT_FUN: u8 = 138;
+    pub const CGPIO_GET_STATE: u8 = 139;
+}
+
+/// 使能/失能时表示全部关节的轴号
+pub const ALL_AXES: u8 = 8;
+
+/// 编码一条请求
+pub fn encode_request(tid: u16, register: u8, params: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + 1 + params.len());
+    frame.extend_from_slice(&tid.to_be_bytes());
+    frame.extend_from_slice(&PROTOCOL_ID.to_be_bytes());
+    frame.extend_from_slice(&((params.len() + 1) as u16).to_be_bytes());
+    frame.push(register);
+    frame.extend_from_slice(params);
+    frame
+}
+
+/// 编码一条应答（供模拟器使用）
+pub fn encode_reply(tid: u16, register: u8, status: u8, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + 2 + data.len());
+    frame.extend_from_slice(&tid.to_be_bytes());
+    frame.extend_from_slice(&PROTOCOL_ID.to_be_bytes());
+    frame.extend_from_slice(&((data.len() + 2) as u16).to_be_bytes());
+    frame.push(register);
+    frame.push(status);
+    frame.extend_from_slice(data);
+    frame
+}
+
+/// 帧头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub tid: u16,
+    pub protocol: u16,
+    /// 帧头之后的字节数
+    pub len: usize,
+}
+
+impl Header {
+    pub fn parse(bytes: &[u8; HEADER_LEN]) -> Result<Self, AppError> {
+        let header = Self {
+            tid: u16::from_be_bytes([bytes[0], bytes[1]]),
+            protocol: u16::from_be_bytes([bytes[2], bytes[3]]),
+            len: u16::from_be_bytes([bytes[4], bytes[5]]) as usize,
+        };
+        if header.protocol != PROTOCOL_ID {
+            return Err(AppError::Protocol(format!(
+                "unexpected protocol id {}",
+                header.protocol
+            )));
+        }
+        if header.len == 0 || header.len > MAX_FRAME_LEN {
+            return Err(AppError::Protocol(format!(
+                "invalid frame length {}",
+                header.len
+            )));
+        }
+        Ok(header)
+    }
+}
+
+/// 解析后的应答
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub tid: u16,
+    pub register: u8,
+    pub status: u8,
+    pub data: Vec<u8>,
+}
+
+impl Reply {
+    /// 由帧头和帧体组成应答
+    pub fn from_parts(header: Header, body: &[u8]) -> Result<Self, AppError> {
+        if body.len() < 2 {
+            return Err(AppError::Protocol(format!(
+                "reply body too short: {} bytes",
+                body.len()
+            )));
+        }
+        Ok(Self {
+            tid: header.tid,
+            register: body[0],
+            status: body[1],
+            data: body[2..].to_vec(),
+        })
+    }
+
+    /// 确认数据至少有 `len` 字节
+    pub fn expect_len(&self, len: usize) -> Result<&[u8], AppError> {
+        if self.data.len() < len {
+            return Err(AppError::Protocol(format!(
+                "register {} returned {} bytes, expected {}",
+                self.register,
+                self.data.len(),
+                len
+            )));
+        }
+        Ok(&self.data)
+    }
+}
+
+pub fn f32s_to_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn bytes_to_f32s(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// 去掉定长字符串末尾的 NUL
+pub fn bytes_to_string(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).trim().to_string()
+}
+
+/// 编码定长字符串
+pub fn string_to_bytes(value: &str) -> Vec<u8> {
+    let mut data = value.as_bytes().to_vec();
+    data.resize(STRING_LEN, 0);
+    data
+}
@@ -0,0 +1,314 @@
+//! 上报端口（30001/30002/30003）数据解析
+//!
+//! 每帧以 4 字节大端长度开头（包含长度本身），公共部分 87 字节：
+//!
+//! | 偏移 | 内容 |
+//! | --- | --- |
+//! | 0..4 | 帧总长度（u32 大端） |
+//! | 4 | 状态（低 4 位）、模式（高 4 位） |
+//! | 5..7 | 缓存指令数（u16 大端） |
+//! | 7..35 | 7 个关节角（f32 小端，rad） |
+//! | 35..59 | TCP 位姿 x/y/z/roll/pitch/yaw（mm / rad） |
+//! | 59..87 | 7 个关节力矩 |
+//!
+//! 常规/详细上报在其后追加抱闸、使能位，错误码，警告码，TCP 偏移（6 个 f32），
+//! 负载（4 个 f32），碰撞、示教灵敏度，重力方向（3 个 f32），共 145 字节；
+//! 实时上报追加 7 个关节速度和 7 个关节电流，共 143 字节。
+//! 新固件的详细上报更长，其中 288..312 为世界坐标偏移（6 个 f32，mm / rad），
+//! 偏移按 xArm-Python-SDK 的解析方式，只在帧长度足够时读取；其余追加内容解析时忽略。
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::protocol::{bytes_to_f32s, f32s_to_bytes};
+use crate::utils::error::AppError;
+
+/// 公共部分长度
+pub const COMMON_LEN: usize = 87;
+/// 常规/详细上报的最小长度
+pub const NORMAL_LEN: usize = 145;
+/// 实时上报的最小长度
+pub const REALTIME_LEN: usize = 143;
+/// 带世界坐标偏移的详细上报的最小长度
+pub const WORLD_OFFSET_LEN: usize = 312;
+/// 世界坐标偏移的起始偏移
+const WORLD_OFFSET_START: usize = WORLD_OFFSET_LEN - 24;
+/// 单帧最大长度，超过视为数据错位
+pub const MAX_REPORT_LEN: usize = 4096;
+
+/// 上报类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// 常规上报（30001）
+    Normal,
+    /// 详细上报（30002）
+    Rich,
+    /// 实时上报（30003）
+    #[default]
+    Realtime,
+}
+
+impl ReportKind {
+    /// 控制器上的默认端口
+    pub fn port(self) -> u16 {
+        match self {
+            ReportKind::Normal => 30001,
+            ReportKind::Rich => 30002,
+            ReportKind::Realtime => 30003,
+        }
+    }
+}
+
+/// 常规/详细上报附带的控制器状态
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ReportStatus {
+    /// 抱闸状态位（bit0 为第 1 关节）
+    pub brake: u8,
+    /// 使能状态位
+    pub enabled: u8,
+    pub error_code: u8,
+    pub warn_code: u8,
+    pub tcp_offset: Vec<f32>,
+    /// 负载：质量（kg）和质心 x/y/z（mm）
+    pub tcp_load: Vec<f32>,
+    pub collision_sens: u8,
+    pub teach_sens: u8,
+    pub gravity_direction: Vec<f32>,
+    /// 世界坐标偏移（mm / rad），旧固件的上报中没有
+    pub world_offset: Option<Vec<f32>>,
+}
+
+/// 实时上报附带的关节动态数据
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ReportDynamics {
+    /// 关节速度（rad/s）
+    pub speeds: Vec<f32>,
+    /// 关节电流（A）
+    pub currents: Vec<f32>,
+}
+
+/// 一帧上报数据
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ArmReport {
+    pub kind: ReportKind,
+    /// 接收时间（毫秒时间戳）
+    pub timestamp: i64,
+    pub state: u8,
+    pub mode: u8,
+    pub cmd_num: u16,
+    /// 关节角（rad）
+    pub joints: Vec<f32>,
+    /// TCP 位姿（mm / rad）
+    pub pose: Vec<f32>,
+    /// 关节力矩
+    pub torques: Vec<f32>,
+    pub status: Option<ReportStatus>,
+    pub dynamics: Option<ReportDynamics>,
+}
+
+/// 读取一帧完整数据（含长度前缀）
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, AppError> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if !(COMMON_LEN..=MAX_REPORT_LEN).contains(&len) {
+        return Err(AppError::Protocol(format!("invalid report length {}", len)));
+    }
+    let mut frame = vec![0u8; len];
+    frame[..4].copy_from_slice(&(len as u32).to_be_bytes());
+    reader.read_exact(&mut frame[4..]).await?;
+    Ok(frame)
+}
+
+/// 解析一帧数据
+pub fn decode(kind: ReportKind, frame: &[u8]) -> Result<ArmReport, AppError> {
+    if frame.len() < COMMON_LEN {
+        return Err(AppError::Protocol(format!(
+            "report too short: {} bytes",
+            frame.len()
+        )));
+    }
+
+    let status = match kind {
+        ReportKind::Normal | ReportKind::Rich if frame.len() >= NORMAL_LEN => Some(ReportStatus {
+            brake: frame[87],
+            enabled: frame[88],
+            error_code: frame[89],
+            warn_code: frame[90],
+            tcp_offset: bytes_to_f32s(&frame[91..115]),
+            tcp_load: bytes_to_f32s(&frame[115..131]),
+            collision_sens: frame[131],
+            teach_sens: frame[132],
+            gravity_direction: bytes_to_f32s(&frame[133..145]),
+            world_offset: (frame.len() >= WORLD_OFFSET_LEN)
+                .then(|| bytes_to_f32s(&frame[WORLD_OFFSET_START..WORLD_OFFSET_LEN])),
+        }),
+        _ => None,
+    };
+    let dynamics = match kind {
+        ReportKind::Realtime if frame.len() >= REALTIME_LEN => Some(ReportDynamics {
+            speeds: bytes_to_f32s(&frame[87..115]),
+            currents: bytes_to_f32s(&frame[115..143]),
+        }),
+        _ => None,
+    };
+
+    Ok(ArmReport {
+        kind,
+        timestamp: Local::now().timestamp_millis(),
+        state: frame[4] & 0x0F,
+        mode: frame[4] >> 4,
+        cmd_num: u16::from_be_bytes([frame[5], frame[6]]),
+        joints: bytes_to_f32s(&frame[7..35]),
+        pose: bytes_to_f32s(&frame[35..59]),
+        torques: bytes_to_f32s(&frame[59..87]),
+        status,
+        dynamics,
+    })
+}
+
+/// 按控制器格式编码一帧（`decode` 的逆过程），附加数据按 `status`/`dynamics` 是否存在写入
+pub fn encode(report: &ArmReport) -> Vec<u8> {
+    let mut frame = vec![0u8; 4];
+    frame.push((report.state & 0x0F) | (report.mode << 4));
+    frame.extend_from_slice(&report.cmd_num.to_be_bytes());
+    frame.extend(f32s_to_bytes(&padded(&report.joints, 7)));
+    frame.extend(f32s_to_bytes(&padded(&report.pose, 6)));
+    frame.extend(f32s_to_bytes(&padded(&report.torques, 7)));
+
+    if let Some(status) = &report.status {
+        frame.push(status.brake);
+        frame.push(status.enabled);
+        frame.push(status.error_code);
+        frame.push(status.warn_code);
+        frame.extend(f32s_to_bytes(&padded(&status.tcp_offset, 6)));
+        frame.extend(f32s_to_bytes(&padded(&status.tcp_load, 4)));
+        frame.push(status.collision_sens);
+        frame.push(status.teach_sens);
+        frame.extend(f32s_to_bytes(&padded(&status.gravity_direction, 3)));
+        if let Some(offset) = &status.world_offset {
+            // 中间未解析的内容补零
+            frame.resize(WORLD_OFFSET_START, 0);
+            frame.extend(f32s_to_bytes(&padded(offset, 6)));
+        }
+    } else if let Some(dynamics) = &report.dynamics {
+        frame.extend(f32s_to_bytes(&padded(&dynamics.speeds, 7)));
+        frame.extend(f32s_to_bytes(&padded(&dynamics.currents, 7)));
+    }
+
+    let len = frame.len() as u32;
+    frame[..4].copy_from_slice(&len.to_be_bytes());
+    frame
+}
+
+/// 截断或补零到固定个数
+fn padded(values: &[f32], len: usize) -> Vec<f32> {
+    let mut values = values.to_vec();
+    values.resize(len, 0.0);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{report, SimConfig};
+
+    fn moving_arm() -> SimArm {
+        let mut arm = SimArm::new(&SimConfig::default());
+        arm.mode = 1;
+        arm.warn_code = 11;
+        for _ in 0..50 {
+            arm.step(0.01);
+        }
+        arm
+    }
+
+    #[test]
+    fn decodes_normal_report() {
+        let arm = moving_arm();
+        let frame = report::encode(&arm, ReportKind::Normal);
+        assert_eq!(frame.len(), NORMAL_LEN);
+
+        let decoded = decode(ReportKind::Normal, &frame).unwrap();
+        assert_eq!(decoded.state, arm.state);
+        assert_eq!(decoded.mode, 1);
+        assert_eq!(decoded.joints, arm.joints.to_vec());
+        assert_eq!(decoded.pose, arm.tcp_pose().to_vec());
+        assert_eq!(decoded.torques, arm.torques().to_vec());
+        let status = decoded.status.unwrap();
+        assert_eq!(status.warn_code, 11);
+        assert_eq!(status.gravity_direction, vec![0.0, 0.0, -1.0]);
+        // 常规上报（及旧固件的详细上报）没有世界坐标偏移
+        assert_eq!(status.world_offset, None);
+        assert!(decoded.dynamics.is_none());
+    }
+
+    #[test]
+    fn decodes_world_offset_from_long_rich_report() {
+        let mut arm = moving_arm();
+        arm.world_offset = [0.0, 0.0, 50.0, 0.1, 0.0, 0.0];
+        let frame = report::encode(&arm, ReportKind::Rich);
+        let status = decode(ReportKind::Rich, &frame).unwrap().status.unwrap();
+        assert_eq!(status.world_offset, Some(arm.world_offset.to_vec()));
+
+        // 长度不足时不读取
+        let mut short = frame[..WORLD_OFFSET_LEN - 1].to_vec();
+        short[..4].copy_from_slice(&((WORLD_OFFSET_LEN - 1) as u32).to_be_bytes());
+        let status = decode(ReportKind::Rich, &short).unwrap().status.unwrap();
+        assert_eq!(status.world_offset, None);
+    }
+
+    #[test]
+    fn decodes_realtime_report() {
+        let arm = moving_arm();
+        let frame = report::encode(&arm, ReportKind::Realtime);
+        assert_eq!(frame.len(), REALTIME_LEN);
+
+        let decoded = decode(ReportKind::Realtime, &frame).unwrap();
+        let dynamics = decoded.dynamics.unwrap();
+        assert_eq!(dynamics.speeds, arm.speeds.to_vec());
+        assert_eq!(dynamics.currents, arm.currents().to_vec());
+        assert!(decoded.status.is_none());
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let arm = moving_arm();
+        for kind in [ReportKind::Normal, ReportKind::Rich, ReportKind::Realtime] {
+            let frame = report::encode(&arm, kind);
+            let decoded = decode(kind, &frame).unwrap();
+            assert_eq!(encode(&decoded), frame);
+        }
+    }
+
+    #[test]
+    fn rejects_short_report() {
+        assert!(decode(ReportKind::Normal, &[0; 40]).is_err());
+    }
+
+    #[tokio::test]
+    async fn splits_frames_from_stream() {
+        let arm = moving_arm();
+        let mut data = report::encode(&arm, ReportKind::Realtime);
+        data.extend(report::encode(&arm, ReportKind::Realtime));
+        let mut reader = data.as_slice();
+
+        for _ in 0..2 {
+            let frame = read_frame(&mut reader).await.unwrap();
+            assert_eq!(frame.len(), REALTIME_LEN);
+        }
+        assert!(read_frame(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_length_prefix() {
+        let data = [0u8, 0, 0, 10, 1, 2, 3];
+        assert!(matches!(
+            read_frame(&mut data.as_slice()).await,
+            Err(AppError::Protocol(_))
+        ));
+    }
+}
@@ -0,0 +1,516 @@
+//! 机械臂会话管理
+//!
+//! 每台机械臂（按 arm_sn）一个会话，定时读取运动状态作为心跳：
+//! 应答变慢或偶尔丢失时标记为 degraded，连续丢失后标记为 lost 并按指数退避重连。
+//! 状态变化通过 `arm_connection_state` 事件通知前端。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Emitter, Runtime};
+use tokio::sync::{watch, Mutex as AsyncMutex};
+use tokio::time;
+
+use super::client::XArmClient;
+use super::{ArmAddress, XArmClients};
+use crate::packages::latency::{self, LatencyTracker};
+use crate::utils::error::AppError;
+
+/// 会话连接状态变化
+pub const EVENT_ARM_CONNECTION_STATE: &str = "arm_connection_state";
+
+/// 连接状态
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// 正在（重新）连接
+    Connecting,
+    Connected,
+    /// 心跳变慢或偶尔丢失
+    Degraded,
+    /// 连续丢失心跳，等待重连
+    Lost,
+}
+
+/// 会话参数
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct SessionOptions {
+    /// 心跳间隔（毫秒）
+    pub heartbeat_interval_ms: u64,
+    /// 心跳往返超过该时长视为 degraded（毫秒）
+    pub degraded_rtt_ms: u64,
+    /// 连续丢失多少次心跳视为 lost
+    pub lost_after_misses: u32,
+    /// 首次重连等待（毫秒），之后每次翻倍
+    pub backoff_initial_ms: u64,
+    /// 重连等待上限（毫秒）
+    pub backoff_max_ms: u64,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_ms: 1000,
+            degraded_rtt_ms: 200,
+            lost_after_misses: 3,
+            backoff_initial_ms: 500,
+            backoff_max_ms: 30000,
+        }
+    }
+}
+
+impl SessionOptions {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.heartbeat_interval_ms < 100 {
+            return Err(AppError::InvalidArgument(
+                "heartbeat interval must be at least 100 ms".to_string(),
+            ));
+        }
+        if self.lost_after_misses == 0 {
+            return Err(AppError::InvalidArgument(
+                "lost_after_misses must be at least 1".to_string(),
+            ));
+        }
+        if self.backoff_initial_ms == 0 || self.backoff_max_ms < self.backoff_initial_ms {
+            return Err(AppError::InvalidArgument(format!(
+                "invalid backoff {}..{} ms",
+                self.backoff_initial_ms, self.backoff_max_ms
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 会话信息，同时作为 `arm_connection_state` 事件内容
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionInfo {
+    pub arm_sn: String,
+    pub ip: String,
+    pub command_port: u16,
+    pub version: String,
+    pub state: ConnectionState,
+    /// 最近一次心跳往返（毫秒）
+    pub rtt_ms: Option<f64>,
+    /// 当前这轮重连的尝试次数
+    pub reconnect_attempts: u32,
+    /// 进入当前状态的时间（毫秒时间戳）
+    pub since: i64,
+    /// 最近一次错误
+    pub message: Option<String>,
+}
+
+struct Session {
+    address: ArmAddress,
+    info: Arc<Mutex<SessionInfo>>,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// 已登记、尚未启动心跳的会话
+struct OpenedSession {
+    info: SessionInfo,
+    client: Arc<AsyncMutex<XArmClient>>,
+    shared: Arc<Mutex<SessionInfo>>,
+    stop_rx: watch::Receiver<bool>,
+}
+
+/// 多机械臂会话，按 arm_sn 索引
+#[derive(Clone)]
+pub struct ArmSessions {
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+    /// 心跳往返写入延迟统计
+    latency: LatencyTracker,
+}
+
+impl ArmSessions {
+    pub fn new(latency: LatencyTracker) -> Self {
+        Self {
+            sessions: Default::default(),
+            latency,
+        }
+    }
+
+    /// 连接机械臂并开始心跳；同一 arm_sn 已有会话时替换（例如 IP 变化）
+    pub async fn open<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        clients: &XArmClients,
+        address: ArmAddress,
+        options: SessionOptions,
+    ) -> Result<SessionInfo, AppError> {
+        options.validate()?;
+        let opened = self.register(clients, address).await?;
+        emit_state(app, &opened.info);
+        async_runtime::spawn(run_session(
+            app.clone(),
+            opened.client,
+            opened.shared,
+            self.latency.clone(),
+            options,
+            opened.stop_rx,
+        ));
+        Ok(opened.info)
+    }
+
+    /// 连接并确认序列号后登记会话，替换同一 arm_sn 的旧会话
+    async fn register(
+        &self,
+        clients: &XArmClients,
+        address: ArmAddress,
+    ) -> Result<OpenedSession, AppError> {
+        let addr = address.socket_addr()?;
+        let client = clients.get(&address)?;
+        let (arm_sn, version) = {
+            let mut client = client.lock().await;
+            client.connect().await?;
+            (client.get_robot_sn().await?, client.get_version().await?)
+        };
+        if arm_sn.is_empty() {
+            return Err(AppError::Protocol(format!(
+                "{} reported no serial number",
+                addr
+            )));
+        }
+
+        let info = SessionInfo {
+            arm_sn: arm_sn.clone(),
+            ip: addr.ip().to_string(),
+            command_port: addr.port(),
+            version,
+            state: ConnectionState::Connected,
+            rtt_ms: None,
+            reconnect_attempts: 0,
+            since: Local::now().timestamp_millis(),
+            message: None,
+        };
+        let shared = Arc::new(Mutex::new(info.clone()));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let previous = {
+            let mut sessions = self
+                .sessions
+                .lock()
+                .map_err(|e| AppError::Io(e.to_string()))?;
+            sessions.insert(
+                arm_sn.clone(),
+                Session {
+                    address,
+                    info: Arc::clone(&shared),
+                    stop_tx,
+                },
+            )
+        };
+        if let Some(previous) = previous {
+            let _ = previous.stop_tx.send(true);
+            // 地址变化后旧地址的客户端不再使用，断开并移除
+            if previous.address.socket_addr().ok() != Some(addr) {
+                clients.remove(&previous.address).await?;
+            }
+            info!("Replaced session for {}", arm_sn);
+        }
+
+        info!("Session opened for {} at {}", arm_sn, addr);
+        Ok(OpenedSession {
+            info,
+            client,
+            shared,
+            stop_rx,
+        })
+    }
+
+    /// 结束会话并断开连接，返回之前是否存在
+    pub async fn close(&self, clients: &XArmClients, arm_sn: &str) -> Result<bool, AppError> {
+        let session = self
+            .sessions
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?
+            .remove(arm_sn);
+        match session {
+            Some(session) => {
+                let _ = session.stop_tx.send(true);
+                clients.remove(&session.address).await?;
+                self.latency.remove(&latency::arm_target(arm_sn));
+                info!("Session closed for {}", arm_sn);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// 所有会话的当前状态
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let Ok(sessions) = self.sessions.lock() else {
+            return Vec::new();
+        };
+        let mut list: Vec<SessionInfo> = sessions
+            .values()
+            .filter_map(|session| session.info.lock().ok().map(|info| info.clone()))
+            .collect();
+        list.sort_by(|a, b| a.arm_sn.cmp(&b.arm_sn));
+        list
+    }
+
+    /// 停止所有心跳任务（退出时调用）
+    pub fn stop_all(&self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            for session in sessions.values() {
+                let _ = session.stop_tx.send(true);
+            }
+            sessions.clear();
+        }
+    }
+}
+
+fn emit_state<R: Runtime>(app: &AppHandle<R>, info: &SessionInfo) {
+    if let Err(e) = app.emit(EVENT_ARM_CONNECTION_STATE, info) {
+        error!("Failed to emit event: {}", e);
+    }
+}
+
+/// 更新会话状态，状态或重连次数变化时发送事件
+fn transition<R: Runtime>(
+    app: &AppHandle<R>,
+    shared: &Mutex<SessionInfo>,
+    state: ConnectionState,
+    rtt: Option<Duration>,
+    message: Option<String>,
+) {
+    let Ok(mut info) = shared.lock() else {
+        return;
+    };
+    if rtt.is_some() {
+        info.rtt_ms = rtt.map(|rtt| rtt.as_secs_f64() * 1000.0);
+    }
+    let attempts = match state {
+        ConnectionState::Connecting => info.reconnect_attempts + 1,
+        ConnectionState::Lost => info.reconnect_attempts,
+        _ => 0,
+    };
+    let changed = info.state != state || info.reconnect_attempts != attempts;
+    info.reconnect_attempts = attempts;
+    if message.is_some() || state == ConnectionState::Connected {
+        info.message = message;
+    }
+    if changed {
+        if state != info.state {
+            match state {
+                ConnectionState::Lost => warn!("xArm {} connection lost", info.arm_sn),
+                ConnectionState::Connected => info!("xArm {} connected", info.arm_sn),
+                _ => {}
+            }
+        }
+        info.state = state;
+        info.since = Local::now().timestamp_millis();
+        emit_state(app, &info);
+    }
+}
+
+/// 心跳：读取运动状态，返回往返耗时（不含等待锁的时间）
+async fn heartbeat(client: &AsyncMutex<XArmClient>) -> Result<Duration, AppError> {
+    let mut client = client.lock().await;
+    let started = Instant::now();
+    client.get_state().await?;
+    Ok(started.elapsed())
+}
+
+/// 重新连接并确认仍是同一台机械臂
+async fn reconnect(client: &AsyncMutex<XArmClient>, arm_sn: &str) -> Result<(), AppError> {
+    let mut client = client.lock().await;
+    client.disconnect().await;
+    client.connect().await?;
+    let sn = client.get_robot_sn().await?;
+    if sn != arm_sn {
+        client.disconnect().await;
+        return Err(AppError::Protocol(format!(
+            "expected serial {}, found {}",
+            arm_sn, sn
+        )));
+    }
+    Ok(())
+}
+
+async fn run_session<R: Runtime>(
+    app: AppHandle<R>,
+    client: Arc<AsyncMutex<XArmClient>>,
+    shared: Arc<Mutex<SessionInfo>>,
+    tracker: LatencyTracker,
+    options: SessionOptions,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let arm_sn = shared
+        .lock()
+        .map(|info| info.arm_sn.clone())
+        .unwrap_or_default();
+    let target = latency::arm_target(&arm_sn);
+    let interval = Duration::from_millis(options.heartbeat_interval_ms);
+    let degraded_rtt = Duration::from_millis(options.degraded_rtt_ms);
+    let mut misses = 0;
+
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = time::sleep(interval) => {}
+        }
+        let result = tokio::select! {
+            _ = stop_rx.changed() => break,
+            result = heartbeat(&client) => result,
+        };
+        tracker.record(&target, result.as_ref().ok().copied());
+        match result {
+            Ok(rtt) => {
+                misses = 0;
+                let state = if rtt > degraded_rtt {
+                    ConnectionState::Degraded
+                } else {
+                    ConnectionState::Connected
+                };
+                transition(&app, &shared, state, Some(rtt), None);
+                continue;
+            }
+            Err(e) => {
+                misses += 1;
+                if misses < options.lost_after_misses {
+                    transition(
+                        &app,
+                        &shared,
+                        ConnectionState::Degraded,
+                        None,
+                        Some(e.to_string()),
+                    );
+                    continue;
+                }
+                transition(
+                    &app,
+                    &shared,
+                    ConnectionState::Lost,
+                    None,
+                    Some(e.to_string()),
+                );
+            }
+        }
+
+        // 指数退避重连，直到成功或会话被关闭
+        let mut delay = Duration::from_millis(options.backoff_initial_ms);
+        let max_delay = Duration::from_millis(options.backoff_max_ms);
+        loop {
+            tokio::select! {
+                _ = stop_rx.changed() => return,
+                _ = time::sleep(delay) => {}
+            }
+            transition(&app, &shared, ConnectionState::Connecting, None, None);
+            let result = tokio::select! {
+                _ = stop_rx.changed() => return,
+                result = reconnect(&client, &arm_sn) => result,
+            };
+            match result {
+                Ok(()) => {
+                    misses = 0;
+                    transition(&app, &shared, ConnectionState::Connected, None, None);
+                    break;
+                }
+                Err(e) => {
+                    transition(
+                        &app,
+                        &shared,
+                        ConnectionState::Lost,
+                        None,
+                        Some(e.to_string()),
+                    );
+                    delay = (delay * 2).min(max_delay);
+                }
+            }
+        }
+    }
+    info!("Session heartbeat for {} stopped", arm_sn);
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::packages::simulator::stand_in::{sim_stand_in, still_config, SimStandIn};
+    use crate::packages::simulator::SimConfig;
+
+    /// 在随机端口上启动一台模拟器，返回其地址
+    async fn simulator(arm_sn: &str) -> (ArmAddress, SimStandIn) {
+        let config = SimConfig {
+            arm_sn: arm_sn.to_string(),
+            ..still_config()
+        };
+        let sim = sim_stand_in(config).await;
+        (sim.address(), sim)
+    }
+
+    fn client_ports(clients: &XArmClients) -> Vec<u16> {
+        let mut ports: Vec<u16> = clients.all().iter().map(|(addr, _)| addr.port()).collect();
+        ports.sort();
+        ports
+    }
+
+    #[tokio::test]
+    async fn opens_and_closes_sessions() {
+        let sessions = ArmSessions::new(LatencyTracker::new());
+        let clients = XArmClients::new();
+        let (address, _sim) = simulator("XI1306SIM00001").await;
+        let port = address.command_port.unwrap();
+
+        let opened = sessions.register(&clients, address).await.unwrap();
+        assert_eq!(opened.info.arm_sn, "XI1306SIM00001");
+        assert_eq!(opened.info.version, "v2.5.0");
+        assert_eq!(opened.info.command_port, port);
+        assert_eq!(opened.info.state, ConnectionState::Connected);
+        assert_eq!(sessions.list().len(), 1);
+        assert_eq!(client_ports(&clients), [port]);
+
+        assert!(sessions.close(&clients, "XI1306SIM00001").await.unwrap());
+        assert!(*opened.stop_rx.borrow());
+        assert!(sessions.list().is_empty());
+        assert!(clients.all().is_empty());
+        assert!(!sessions.close(&clients, "XI1306SIM00001").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn replaces_session_and_drops_stale_client() {
+        let sessions = ArmSessions::new(LatencyTracker::new());
+        let clients = XArmClients::new();
+        let (old, _old_sim) = simulator("XI1306SIM00001").await;
+        let (new, _new_sim) = simulator("XI1306SIM00001").await;
+        let new_port = new.command_port.unwrap();
+
+        let first = sessions.register(&clients, old.clone()).await.unwrap();
+        // 同一地址重复打开时复用客户端
+        let again = sessions.register(&clients, old).await.unwrap();
+        assert!(*first.stop_rx.borrow());
+        assert_eq!(clients.all().len(), 1);
+
+        // 同一台机械臂换了地址：旧会话停止，旧客户端被移除
+        let moved = sessions.register(&clients, new).await.unwrap();
+        assert!(*again.stop_rx.borrow());
+        assert!(!*moved.stop_rx.borrow());
+        assert_eq!(client_ports(&clients), [new_port]);
+
+        let list = sessions.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].command_port, new_port);
+    }
+
+    #[tokio::test]
+    async fn rejects_unreachable_arm() {
+        let sessions = ArmSessions::new(LatencyTracker::new());
+        let clients = XArmClients::new();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let address = ArmAddress {
+            ip: "127.0.0.1".to_string(),
+            command_port: Some(port),
+        };
+        assert!(sessions.register(&clients, address).await.is_err());
+        assert!(sessions.list().is_empty());
+    }
+}
@@ -0,0 +1,481 @@
+//! 上报数据订阅
+//!
+//! 每个（地址, 上报类型）只建立一条连接，解析后的数据通过 broadcast 分发给各订阅者。
+//! 订阅者（前端 `Channel`、WebSocket 连接）按设定频率接收最新一帧，避免直接处理上百赫兹的数据；
+//! Rust 内部（录制等）可以通过 `open` 拿到全频率数据；回放通过 `attach` 代替真实连接提供数据。
+//! 回放中的地址只能订阅，`open` 会拒绝，避免把回放数据录进新的录制。
+//! 每条连接带代次，`close`/`detach` 只作用于打开时的那一条，回放结束后新开的同地址连接不受影响。
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use tauri::async_runtime;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, watch};
+use tokio::time;
+
+use super::client::CONNECT_TIMEOUT;
+use super::report::{self, ArmReport, ReportKind};
+use crate::utils::error::AppError;
+
+pub type SubscriptionId = u64;
+
+/// 前端订阅的默认推送频率
+pub const DEFAULT_RATE_HZ: f64 = 30.0;
+/// 连接断开后的重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// broadcast 缓冲帧数，慢速订阅者超出后丢帧
+const BUFFER_FRAMES: usize = 256;
+
+type StreamKey = (SocketAddr, ReportKind);
+
+/// 一次 `open`/`attach` 占用的连接，释放时交回
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamLease {
+    pub addr: SocketAddr,
+    pub kind: ReportKind,
+    generation: u64,
+}
+
+impl StreamLease {
+    fn key(&self) -> StreamKey {
+        (self.addr, self.kind)
+    }
+}
+
+struct Stream {
+    tx: broadcast::Sender<Arc<ArmReport>>,
+    stop_tx: watch::Sender<bool>,
+    /// 引用计数，归零时断开连接
+    refs: usize,
+    generation: u64,
+    /// 数据来自回放
+    replay: bool,
+}
+
+struct Subscription {
+    /// 数据源被 `detach` 后为空，不再占用连接
+    lease: Option<StreamLease>,
+    stop_tx: watch::Sender<bool>,
+}
+
+/// 上报数据订阅管理
+#[derive(Clone, Default)]
+pub struct ReportStreams {
+    next_id: Arc<AtomicU64>,
+    next_generation: Arc<AtomicU64>,
+    streams: Arc<Mutex<HashMap<StreamKey, Stream>>>,
+    subscriptions: Arc<Mutex<HashMap<SubscriptionId, Subscription>>>,
+}
+
+impl ReportStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 打开（或复用）一条真实上报连接，返回全频率数据；回放中的地址报错。用完后必须调用 `close`
+    pub fn open(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+    ) -> Result<(broadcast::Receiver<Arc<ArmReport>>, StreamLease), AppError> {
+        self.acquire(addr, kind, false)
+    }
+
+    /// `allow_replay` 为 true 时回放数据源也可以复用
+    fn acquire(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+        allow_replay: bool,
+    ) -> Result<(broadcast::Receiver<Arc<ArmReport>>, StreamLease), AppError> {
+        let mut streams = self
+            .streams
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        let stream = streams.entry((addr, kind)).or_insert_with(|| {
+            let (tx, _) = broadcast::channel(BUFFER_FRAMES);
+            let (stop_tx, stop_rx) = watch::channel(false);
+            async_runtime::spawn(run_stream(addr, kind, tx.clone(), stop_rx));
+            Stream {
+                tx,
+                stop_tx,
+                refs: 0,
+                generation: self.next_generation.fetch_add(1, Ordering::Relaxed) + 1,
+                replay: false,
+            }
+        });
+        if stream.replay && !allow_replay {
+            return Err(AppError::InvalidArgument(format!(
+                "report stream {} {:?} is being replayed",
+                addr, kind
+            )));
+        }
+        stream.refs += 1;
+        let lease = StreamLease {
+            addr,
+            kind,
+            generation: stream.generation,
+        };
+        Ok((stream.tx.subscribe(), lease))
+    }
+
+    /// 释放一次 `open`，最后一个使用者释放时断开连接；连接已被替换时不做任何事
+    pub fn close(&self, lease: StreamLease) {
+        let Ok(mut streams) = self.streams.lock() else {
+            return;
+        };
+        if let Some(stream) = streams.get_mut(&lease.key()) {
+            if stream.generation != lease.generation {
+                return;
+            }
+            stream.refs = stream.refs.saturating_sub(1);
+            if stream.refs == 0 {
+                let _ = stream.stop_tx.send(true);
+                streams.remove(&lease.key());
+            }
+        }
+    }
+
+    /// 以外部数据源（回放）代替上报连接，该地址已有连接时报错；结束后调用 `detach`
+    pub fn attach(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+    ) -> Result<(broadcast::Sender<Arc<ArmReport>>, StreamLease), AppError> {
+        self.insert_source(addr, kind, true)
+    }
+
+    /// 测试用：不连接控制器，由测试代替控制器发送上报数据
+    #[cfg(test)]
+    pub(crate) fn inject(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+    ) -> (broadcast::Sender<Arc<ArmReport>>, StreamLease) {
+        self.insert_source(addr, kind, false).unwrap()
+    }
+
+    fn insert_source(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+        replay: bool,
+    ) -> Result<(broadcast::Sender<Arc<ArmReport>>, StreamLease), AppError> {
+        let mut streams = self
+            .streams
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        if streams.contains_key(&(addr, kind)) {
+            return Err(AppError::InvalidArgument(format!(
+                "report stream {} {:?} is already open",
+                addr, kind
+            )));
+        }
+        let (tx, _) = broadcast::channel(BUFFER_FRAMES);
+        let (stop_tx, _) = watch::channel(false);
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        // 数据源自身占一个引用，订阅者全部退出也不会移除
+        streams.insert(
+            (addr, kind),
+            Stream {
+                tx: tx.clone(),
+                stop_tx,
+                refs: 1,
+                generation,
+                replay,
+            },
+        );
+        Ok((
+            tx,
+            StreamLease {
+                addr,
+                kind,
+                generation,
+            },
+        ))
+    }
+
+    /// 移除 `attach` 的数据源，应先释放数据源的 `Sender`。
+    /// 订阅它的订阅不再占用连接，转发完已缓冲的帧后随 broadcast 关闭结束
+    pub fn detach(&self, lease: StreamLease) {
+        if let Ok(mut streams) = self.streams.lock() {
+            if streams
+                .get(&lease.key())
+                .is_some_and(|stream| stream.generation == lease.generation)
+            {
+                streams.remove(&lease.key());
+            }
+        }
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            for subscription in subscriptions.values_mut() {
+                if subscription.lease == Some(lease) {
+                    subscription.lease = None;
+                }
+            }
+        }
+    }
+
+    /// 订阅上报数据，`rate_hz` 为 0 时按原始频率推送；`sink` 返回 false 时结束订阅
+    pub fn subscribe<F>(
+        &self,
+        addr: SocketAddr,
+        kind: ReportKind,
+        rate_hz: f64,
+        sink: F,
+    ) -> Result<SubscriptionId, AppError>
+    where
+        F: FnMut(SubscriptionId, &ArmReport) -> bool + Send + 'static,
+    {
+        if !rate_hz.is_finite() || rate_hz < 0.0 {
+            return Err(AppError::InvalidArgument(format!(
+                "invalid rate: {}",
+                rate_hz
+            )));
+        }
+        let period = (rate_hz > 0.0).then(|| Duration::from_secs_f64(1.0 / rate_hz));
+
+        let (rx, lease) = self.acquire(addr, kind, true)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (stop_tx, stop_rx) = watch::channel(false);
+        // 在锁内 spawn，保证任务结束时移除自身一定发生在插入之后
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        let streams = self.clone();
+        async_runtime::spawn(async move {
+            forward(id, rx, sink, period, stop_rx).await;
+            streams.release(id);
+        });
+        subscriptions.insert(
+            id,
+            Subscription {
+                lease: Some(lease),
+                stop_tx,
+            },
+        );
+        info!(
+            "Report subscription {} for {} {:?} at {} Hz",
+            id, addr, kind, rate_hz
+        );
+        Ok(id)
+    }
+
+    /// 取消订阅，返回是否存在该订阅
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.subscriptions
+            .lock()
+            .ok()
+            .and_then(|subscriptions| {
+                subscriptions
+                    .get(&id)
+                    .map(|subscription| subscription.stop_tx.send(true))
+            })
+            .is_some()
+    }
+
+    /// 断开所有连接（退出时调用）
+    pub fn stop_all(&self) {
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            for subscription in subscriptions.values() {
+                let _ = subscription.stop_tx.send(true);
+            }
+            subscriptions.clear();
+        }
+        if let Ok(mut streams) = self.streams.lock() {
+            for stream in streams.values() {
+                let _ = stream.stop_tx.send(true);
+            }
+            streams.clear();
+        }
+    }
+
+    /// 订阅任务结束时移除订阅并释放连接
+    fn release(&self, id: SubscriptionId) {
+        let subscription = self
+            .subscriptions
+            .lock()
+            .ok()
+            .and_then(|mut subscriptions| subscriptions.remove(&id));
+        if let Some(Subscription { lease, .. }) = subscription {
+            if let Some(lease) = lease {
+                self.close(lease);
+            }
+            info!("Report subscription {} closed", id);
+        }
+    }
+}
+
+/// 把上报数据转发给订阅者；`period` 为空时逐帧转发，否则按周期只发最新一帧
+async fn forward<F>(
+    id: SubscriptionId,
+    mut rx: broadcast::Receiver<Arc<ArmReport>>,
+    mut sink: F,
+    period: Option<Duration>,
+    mut stop_rx: watch::Receiver<bool>,
+) where
+    F: FnMut(SubscriptionId, &ArmReport) -> bool,
+{
+    let mut ticker = period.map(|period| {
+        let mut ticker = time::interval(period);
+        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+        ticker
+    });
+    let throttled = ticker.is_some();
+    let mut latest: Option<Arc<ArmReport>> = None;
+
+    loop {
+        let report = tokio::select! {
+            _ = stop_rx.changed() => return,
+            _ = tick(&mut ticker) => {
+                match latest.take() {
+                    Some(report) => report,
+                    None => continue,
+                }
+            }
+            received = rx.recv() => match received {
+                Ok(report) if throttled => {
+                    latest = Some(report);
+                    continue;
+                }
+                Ok(report) => report,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Report subscriber lagged, skipped {} frames", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+        };
+        // 窗口或连接关闭后发送失败，结束订阅
+        if !sink(id, &report) {
+            return;
+        }
+    }
+}
+
+async fn tick(ticker: &mut Option<time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// 连接任务：读取并解析上报数据，断开后自动重连
+async fn run_stream(
+    addr: SocketAddr,
+    kind: ReportKind,
+    tx: broadcast::Sender<Arc<ArmReport>>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    loop {
+        let result = tokio::select! {
+            _ = stop_rx.changed() => break,
+            result = read_reports(addr, kind, &tx) => result,
+        };
+        if let Err(e) = result {
+            warn!("Report stream {} {:?} interrupted: {}", addr, kind, e);
+        }
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = time::sleep(RECONNECT_DELAY) => {}
+        }
+    }
+    info!("Report stream {} {:?} stopped", addr, kind);
+}
+
+async fn read_reports(
+    addr: SocketAddr,
+    kind: ReportKind,
+    tx: &broadcast::Sender<Arc<ArmReport>>,
+) -> Result<(), AppError> {
+    let connect_error = |message: String| AppError::Connect {
+        addr: addr.to_string(),
+        message,
+    };
+    let mut stream = time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+        .await
+        .map_err(|_| connect_error("timed out".to_string()))?
+        .map_err(|e| connect_error(e.to_string()))?;
+    info!("Report stream {} {:?} connected", addr, kind);
+
+    loop {
+        let frame = report::read_frame(&mut stream).await?;
+        let report = report::decode(kind, &frame)?;
+        // 没有订阅者时发送失败，忽略即可
+        let _ = tx.send(Arc::new(report));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{report as sim_report, SimConfig};
+
+    const KIND: ReportKind = ReportKind::Normal;
+
+    fn frame(timestamp: i64) -> Arc<ArmReport> {
+        let arm = SimArm::new(&SimConfig::default());
+        let mut report = report::decode(KIND, &sim_report::encode(&arm, KIND)).unwrap();
+        report.timestamp = timestamp;
+        Arc::new(report)
+    }
+
+    fn refs(streams: &ReportStreams, addr: SocketAddr) -> Option<usize> {
+        streams
+            .streams
+            .lock()
+            .unwrap()
+            .get(&(addr, KIND))
+            .map(|stream| stream.refs)
+    }
+
+    #[tokio::test]
+    async fn replay_lease_does_not_touch_later_streams() {
+        let streams = ReportStreams::new();
+        // 没有控制器监听，真实连接只会不断重连
+        let addr: SocketAddr = "127.0.0.1:9".parse().unwrap();
+        let (source, replay) = streams.attach(addr, KIND).unwrap();
+        assert!(streams.open(addr, KIND).is_err());
+
+        // 回放可以订阅，回放结束时订阅随之结束
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let id = streams
+            .subscribe(addr, KIND, 0.0, move |_, report| {
+                tx.send(report.timestamp).is_ok()
+            })
+            .unwrap();
+        source.send(frame(5)).unwrap();
+        source.send(frame(6)).unwrap();
+        drop(source);
+        streams.detach(replay);
+        assert_eq!(rx.recv().await, Some(5));
+        assert_eq!(rx.recv().await, Some(6));
+        assert_eq!(rx.recv().await, None);
+        for _ in 0..100 {
+            if !streams.unsubscribe(id) {
+                break;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!streams.unsubscribe(id));
+
+        // 之后同地址的真实连接不受回放遗留的释放影响
+        let (_rx, live) = streams.open(addr, KIND).unwrap();
+        assert_ne!(live, replay);
+        streams.close(replay);
+        streams.detach(replay);
+        assert_eq!(refs(&streams, addr), Some(1));
+        streams.close(live);
+        assert_eq!(refs(&streams, addr), None);
+    }
+}
@@ -1,20 +1,54 @@
-use std::{
-    net::UdpSocket,
-    sync::{atomic::AtomicBool, Arc, Mutex},
-    thread,
-};
+use std::sync::Arc;
 
 use reqwest::Client;
 
-pub struct UdpState {
-    pub socket: Option<Arc<UdpSocket>>,
-    pub handle: Option<thread::JoinHandle<()>>,
-    pub stop_flag: Arc<AtomicBool>,
-}
+use crate::packages::discovery::DiscoveryService;
+use crate::packages::firmware::FirmwareUpdates;
+use crate::packages::keyboard::estop::EmergencyStop;
+use crate::packages::latency::LatencyTracker;
+use crate::packages::modbus::ModbusDevices;
+use crate::packages::product_config::ProductConfigs;
+use crate::packages::recorder::Recorder;
+use crate::packages::registry::DeviceRegistry;
+use crate::packages::services::ServiceRegistry;
+use crate::packages::sidecar::Sidecar;
+use crate::packages::simulator::Simulator;
+use crate::packages::xarm::session::ArmSessions;
+use crate::packages::xarm::stream::ReportStreams;
+use crate::packages::xarm::XArmClients;
+use crate::utils::websocket::{BridgeContext, WebSocketBridge};
 
 pub struct AppState {
     // pub user_settings: Mutex<UserSettings>,
-    pub udp_state: Mutex<UdpState>,
+    pub discovery: DiscoveryService,
+    /// 设备登记表
+    pub registry: DeviceRegistry,
+    /// 演示模式下的虚拟机械臂
+    pub simulator: Simulator,
+    /// 机械臂指令端口客户端
+    pub arms: XArmClients,
+    /// 机械臂会话（心跳与自动重连），按 arm_sn 索引
+    pub sessions: ArmSessions,
+    /// 上报数据订阅
+    pub reports: ReportStreams,
+    /// 本地 WebSocket 桥
+    pub bridge: WebSocketBridge,
+    /// IPC、机械臂和 tool_service 的延迟统计
+    pub latency: LatencyTracker,
+    /// Modbus-TCP 外设
+    pub modbus: ModbusDevices,
+    /// 上报数据录制与回放
+    pub recorder: Recorder,
+    /// 急停快捷键
+    pub estop: EmergencyStop,
+    /// 控制器固件批量升级
+    pub firmware: FirmwareUpdates,
+    /// Python 本地服务进程守护
+    pub tool_service: Sidecar,
+    /// 本地服务端口
+    pub services: ServiceRegistry,
+    /// 产品配置（`uf_product_config.ini`）
+    pub product_config: ProductConfigs,
     pub client: Arc<Client>,
 }
 
@@ -22,14 +56,35 @@ pub struct AppState {
 impl AppState {
     #[allow(dead_code)]
     pub fn new() -> Self {
+        let latency = LatencyTracker::new();
+        let registry = DeviceRegistry::new();
         AppState {
             // user_settings: Mutex::new(UserSettings::default()),
-            udp_state: Mutex::new(UdpState {
-                socket: None,
-                handle: None,
-                stop_flag: Arc::new(AtomicBool::new(false)),
-            }),
+            discovery: DiscoveryService::new(registry.clone()),
+            registry,
+            simulator: Simulator::new(),
+            arms: XArmClients::new(),
+            sessions: ArmSessions::new(latency.clone()),
+            reports: ReportStreams::new(),
+            bridge: WebSocketBridge::new(),
+            latency,
+            modbus: ModbusDevices::new(),
+            recorder: Recorder::new(),
+            estop: EmergencyStop::new(),
+            firmware: FirmwareUpdates::new(),
+            tool_service: Sidecar::new(),
+            services: ServiceRegistry::new(),
+            product_config: ProductConfigs::new(),
             client: Arc::new(Client::new()),
         }
     }
+
+    /// WebSocket 桥与前端共用同一套连接和订阅
+    pub fn bridge_context(&self) -> BridgeContext {
+        BridgeContext {
+            arms: self.arms.clone(),
+            reports: self.reports.clone(),
+            sessions: self.sessions.clone(),
+        }
+    }
 }
@@ -1,27 +1,31 @@
 use crate::packages::menu::i18n;
+use crate::packages::product_config::{ChangeDetector, CONFIG_FILE, EVENT_PRODUCT_CONFIG_CHANGED};
+use crate::state::app_state::AppState;
+use crate::utils::error::AppError;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{thread, time::Duration};
-use tauri::{path::BaseDirectory, AppHandle, Manager};
+use tauri::{path::BaseDirectory, AppHandle, Emitter, Manager};
 
 /// 监控配置结构体
 #[derive(Clone)]
 pub struct MonitorConfig {
-    #[allow(dead_code)]
-    pub process_name: String,
+    /// 相对资源目录的产品配置文件
+    pub config_file: String,
     pub interval: Duration,
 }
 
 impl Default for MonitorConfig {
     fn default() -> Self {
         Self {
-            process_name: "tool_service".to_string(),
+            config_file: CONFIG_FILE.to_string(),
             interval: Duration::from_secs(3),
         }
     }
 }
 
-/// 启动状态监控线程
+/// 启动状态监控线程：定期读取产品配置文件，内容变化时更新 `AppState::product_config`
+/// 并发送 `product_config_changed` 事件（首次读取也会发送）
 ///
 /// # 参数
 /// * `app_handle` - Tauri 应用句柄
@@ -42,10 +46,30 @@ pub fn spawn_state_monitor(app_handle: &AppHandle, config: MonitorConfig) -> Arc
     let running_clone = running.clone();
 
     thread::spawn(move || {
+        let config_path = match resolve_config_path(&app_handle, &config.config_file) {
+            Ok(path) => path,
+            Err(e) => {
+                log::error!("Failed to resolve {}: {}", config.config_file, e);
+                return;
+            }
+        };
+        let mut detector = ChangeDetector::default();
         while running_clone.load(Ordering::Relaxed) {
-            let config_path = resolve_config_path(&app_handle);
-            // 预计算配置存在状态（假设不常变化）
-            let _ = config_path.as_ref().map(|p| p.exists()).unwrap_or(false);
+            if let Some(product) = detector.poll(&config_path) {
+                log::info!(
+                    "{} {:?} ({})",
+                    i18n::tr("产品配置:", "Product config:"),
+                    product.model,
+                    product.name
+                );
+                app_handle
+                    .state::<AppState>()
+                    .product_config
+                    .set(product.clone());
+                if let Err(e) = app_handle.emit(EVENT_PRODUCT_CONFIG_CHANGED, product) {
+                    log::error!("Failed to emit event: {}", e);
+                }
+            }
 
             thread::sleep(config.interval);
         }
@@ -60,14 +84,14 @@ pub fn spawn_state_monitor(app_handle: &AppHandle, config: MonitorConfig) -> Arc
 }
 
 /// 解析配置文件路径
-fn resolve_config_path(
+pub fn resolve_config_path(
     app_handle: &AppHandle,
-) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
-    let config_path = app_handle
+    config_file: &str,
+) -> Result<std::path::PathBuf, AppError> {
+    app_handle
         .path()
-        .resolve("releases/uf_product_config.ini", BaseDirectory::Resource)
-        .map_err(|e| e.to_string())?;
-    Ok(config_path)
+        .resolve(config_file, BaseDirectory::Resource)
+        .map_err(|e| AppError::Io(e.to_string()))
 }
 
 /// 向后兼容的旧函数（已废弃，建议使用 spawn_state_monitor）
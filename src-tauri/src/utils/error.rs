@@ -0,0 +1,67 @@
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// 命令统一错误类型，序列化为 `{ kind, message }` 供前端区分处理
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// 参数错误
+    InvalidArgument(String),
+    /// 无法建立连接
+    Connect { addr: String, message: String },
+    /// 连接已断开
+    Disconnected(String),
+    /// 等待应答超时
+    Timeout(String),
+    /// 收到的报文不符合协议
+    Protocol(String),
+    /// 其他 IO 错误
+    Io(String),
+}
+
+impl AppError {
+    /// 错误类别，前端据此判断是否需要重连等
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::InvalidArgument(_) => "invalid_argument",
+            AppError::Connect { .. } => "connect",
+            AppError::Disconnected(_) => "disconnected",
+            AppError::Timeout(_) => "timeout",
+            AppError::Protocol(_) => "protocol",
+            AppError::Io(_) => "io",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+            AppError::Connect { addr, message } => {
+                write!(f, "failed to connect to {}: {}", addr, message)
+            }
+            AppError::Disconnected(addr) => write!(f, "connection to {} is closed", addr),
+            AppError::Timeout(what) => write!(f, "timed out waiting for {}", what),
+            AppError::Protocol(message) => write!(f, "protocol error: {}", message),
+            AppError::Io(message) => write!(f, "io error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
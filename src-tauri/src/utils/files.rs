@@ -0,0 +1,127 @@
+//! 应用目录下的文件存取：子目录定位、文件名校验、不覆盖已有文件的新建
+
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::utils::error::AppError;
+
+/// 应用数据目录下的子目录，不存在时创建
+pub fn data_dir<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, AppError> {
+    subdir(app.path().app_data_dir(), name)
+}
+
+/// 应用日志目录下的子目录，不存在时创建
+#[cfg(feature = "controller-log-download")]
+pub fn log_dir<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, AppError> {
+    subdir(app.path().app_log_dir(), name)
+}
+
+fn subdir<E: Display>(base: Result<PathBuf, E>, name: &str) -> Result<PathBuf, AppError> {
+    let dir = base.map_err(|e| AppError::Io(e.to_string()))?.join(name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 只允许单级、不含路径分隔符和盘符的名称，防止写到目标目录之外
+pub fn is_plain_name(name: &str) -> bool {
+    !name.contains(['/', '\\', ':'])
+        && matches!(
+            Path::new(name).components().next(),
+            Some(Component::Normal(_))
+        )
+}
+
+/// 去掉首尾空白后检查名称，`what` 为错误信息中的字段名
+pub fn plain_name<'a>(name: &'a str, what: &str) -> Result<&'a str, AppError> {
+    let trimmed = name.trim();
+    if is_plain_name(trimmed) {
+        Ok(trimmed)
+    } else {
+        Err(AppError::InvalidArgument(format!(
+            "invalid {}: {:?}",
+            what, name
+        )))
+    }
+}
+
+/// 在 `dir` 下新建 `{stem}{extension}` 文件，已存在时依次尝试 `{stem}-1{extension}`、`{stem}-2{extension}`…
+pub fn create_new_file(
+    dir: &Path,
+    stem: &str,
+    extension: &str,
+) -> Result<(PathBuf, File), AppError> {
+    create_new(dir, stem, extension, |path| {
+        OpenOptions::new().write(true).create_new(true).open(path)
+    })
+}
+
+/// 在 `dir` 下新建子目录，规则同 `create_new_file`
+#[cfg(any(test, feature = "controller-log-download"))]
+pub fn create_new_dir(dir: &Path, name: &str) -> Result<PathBuf, AppError> {
+    create_new(dir, name, "", |path| fs::create_dir(path)).map(|(path, ())| path)
+}
+
+fn create_new<T>(
+    dir: &Path,
+    stem: &str,
+    extension: &str,
+    create: impl Fn(&Path) -> io::Result<T>,
+) -> Result<(PathBuf, T), AppError> {
+    fs::create_dir_all(dir)?;
+    let mut path = dir.join(format!("{}{}", stem, extension));
+    let mut suffix = 1;
+    loop {
+        match create(&path) {
+            Ok(created) => return Ok((path, created)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                path = dir.join(format!("{}-{}{}", stem, suffix, extension));
+                suffix += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_plain_names() {
+        for name in ["XS1234", "xarm.log.1", "a b"] {
+            assert!(is_plain_name(name), "{:?}", name);
+        }
+        for name in ["", ".", "..", "a/b", "a\\b", "C:", "/etc"] {
+            assert!(!is_plain_name(name), "{:?}", name);
+        }
+        assert_eq!(plain_name(" XS1234 ", "arm_sn").unwrap(), "XS1234");
+        assert!(plain_name("../x", "arm_sn").is_err());
+    }
+
+    #[test]
+    fn creates_without_overwriting() {
+        let dir = std::env::temp_dir().join(format!("files-unique-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let names: Vec<String> = (0..3)
+            .map(|_| {
+                let (path, _) = create_new_file(&dir, "snapshot", ".json").unwrap();
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            ["snapshot.json", "snapshot-1.json", "snapshot-2.json"]
+        );
+
+        let first = create_new_dir(&dir, "archive").unwrap();
+        let second = create_new_dir(&dir, "archive").unwrap();
+        assert_eq!(second, dir.join("archive-1"));
+        assert!(first.is_dir() && second.is_dir());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1 +1,7 @@
+pub mod error;
+pub mod files;
+#[cfg(test)]
+pub mod http_stand_in;
 pub mod response;
+pub mod stop;
+pub mod websocket;
@@ -0,0 +1,40 @@
+//! 后台任务的停止信号
+
+use tokio::sync::watch;
+
+/// 等待停止信号（值为 true），发送端释放也视为停止
+pub async fn stopped(stop_rx: &mut watch::Receiver<bool>) {
+    while !*stop_rx.borrow_and_update() {
+        if stop_rx.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_on_signal_or_dropped_sender() {
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        assert!(
+            time::timeout(Duration::from_millis(50), stopped(&mut stop_rx))
+                .await
+                .is_err()
+        );
+        stop_tx.send_replace(true);
+        stopped(&mut stop_rx).await;
+        // 已经是 true 时立即返回，不依赖是否读过
+        stopped(&mut stop_rx).await;
+
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        stop_tx.send_replace(false);
+        drop(stop_tx);
+        stopped(&mut stop_rx).await;
+    }
+}
@@ -0,0 +1,754 @@
+//! 本地 WebSocket 桥
+//!
+//! 供同一台电脑上的脚本、看板等外部工具订阅上报数据和调用指令，默认只监听回环地址。
+//! 连接时通过 `?token=` 或 `Authorization: Bearer` 携带令牌，令牌吊销后对应连接立即断开。
+//!
+//! 文本帧均为 JSON：
+//!
+//! ```text
+//! -> {"id": 1, "op": "call", "method": "get_state", "params": {"arm": {"ip": "192.168.1.10"}}}
+//! <- {"id": 1, "result": 2}
+//! -> {"id": 2, "op": "subscribe", "arm": {"ip": "192.168.1.10"}, "kind": "realtime", "rate_hz": 30}
+//! <- {"id": 2, "result": 7}
+//! <- {"subscription": 7, "report": {...}}
+//! -> {"id": 3, "op": "unsubscribe", "subscription": 7}
+//! -> {"id": 4, "op": "sessions"}
+//! ```
+//!
+//! 出错时应答为 `{"id": .., "error": {"kind": .., "message": ..}}`。
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::async_runtime::{self, JoinHandle};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::packages::xarm::report::{ArmReport, ReportKind};
+use crate::packages::xarm::session::ArmSessions;
+use crate::packages::xarm::stream::{ReportStreams, SubscriptionId, DEFAULT_RATE_HZ};
+use crate::packages::xarm::{ArmAddress, XArmClients};
+use crate::utils::error::AppError;
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 18300;
+/// 每个连接待发送的消息上限，超出时丢弃上报帧（应答不丢）
+const OUTBOX_LEN: usize = 256;
+
+/// 启动参数
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct BridgeConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    /// 允许监听非回环地址
+    pub allow_remote: bool,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: DEFAULT_PORT,
+            allow_remote: false,
+        }
+    }
+}
+
+/// 运行状态
+#[derive(Serialize, Clone, Debug)]
+pub struct BridgeStatus {
+    pub addr: String,
+    /// 连接地址（不含令牌，令牌需单独传入，避免随地址被记录或分享）
+    pub url: String,
+    pub token: String,
+    pub connections: usize,
+}
+
+/// 连接处理需要的共享状态
+#[derive(Clone)]
+pub struct BridgeContext {
+    pub arms: XArmClients,
+    pub reports: ReportStreams,
+    pub sessions: ArmSessions,
+}
+
+/// 令牌 → 吊销通知
+type Tokens = Arc<Mutex<HashMap<String, watch::Sender<bool>>>>;
+
+struct Running {
+    addr: SocketAddr,
+    stop_tx: watch::Sender<bool>,
+    /// 监听任务，结束时释放端口
+    task: JoinHandle<()>,
+}
+
+/// WebSocket 桥
+#[derive(Clone, Default)]
+pub struct WebSocketBridge {
+    running: Arc<Mutex<Option<Running>>>,
+    tokens: Tokens,
+    /// 启动时生成的默认令牌
+    default_token: Arc<Mutex<Option<String>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl WebSocketBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动（已在运行时先停止）
+    pub async fn start(
+        &self,
+        config: BridgeConfig,
+        context: BridgeContext,
+    ) -> Result<BridgeStatus, AppError> {
+        if !config.host.is_loopback() && !config.allow_remote {
+            return Err(AppError::InvalidArgument(format!(
+                "{} is not a loopback address, set allow_remote to listen on it",
+                config.host
+            )));
+        }
+        // 等旧的监听任务退出、端口释放后再绑定，避免以相同端口重启时 AddrInUse
+        if let Some(running) = self.take_running() {
+            let _ = running.stop_tx.send(true);
+            if let Err(e) = running.task.await {
+                warn!("WebSocket bridge task failed: {}", e);
+            }
+            info!("WebSocket bridge on {} stopped", running.addr);
+        }
+
+        let listener = TcpListener::bind(SocketAddr::new(config.host, config.port))
+            .await
+            .map_err(|e| {
+                AppError::Io(format!(
+                    "Failed to bind {}:{}: {}",
+                    config.host, config.port, e
+                ))
+            })?;
+        let addr = listener.local_addr()?;
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let task = async_runtime::spawn(serve(
+            listener,
+            context,
+            self.tokens.clone(),
+            Arc::clone(&self.connections),
+            stop_rx,
+        ));
+
+        let default_token = self
+            .default_token
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?
+            .clone();
+        if default_token.is_none() {
+            let token = self.create_token()?;
+            *self
+                .default_token
+                .lock()
+                .map_err(|e| AppError::Io(e.to_string()))? = Some(token);
+        }
+        *self
+            .running
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))? = Some(Running {
+            addr,
+            stop_tx,
+            task,
+        });
+        info!("WebSocket bridge listening on {}", addr);
+        self.status()
+            .ok_or_else(|| AppError::Io("WebSocket bridge stopped unexpectedly".to_string()))
+    }
+
+    fn take_running(&self) -> Option<Running> {
+        self.running
+            .lock()
+            .ok()
+            .and_then(|mut running| running.take())
+    }
+
+    /// 停止监听并断开所有连接，返回之前是否在运行
+    pub fn stop(&self) -> bool {
+        match self.take_running() {
+            Some(running) => {
+                let _ = running.stop_tx.send(true);
+                info!("WebSocket bridge on {} stopped", running.addr);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<BridgeStatus> {
+        let addr = self.running.lock().ok()?.as_ref()?.addr;
+        let token = self.default_token.lock().ok()?.clone().unwrap_or_default();
+        Some(BridgeStatus {
+            addr: addr.to_string(),
+            url: format!("ws://{}/", addr),
+            token,
+            connections: self.connections.load(Ordering::Relaxed),
+        })
+    }
+
+    /// 生成新令牌
+    pub fn create_token(&self) -> Result<String, AppError> {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let (revoke_tx, _) = watch::channel(false);
+        self.tokens
+            .lock()
+            .map_err(|e| AppError::Io(e.to_string()))?
+            .insert(token.clone(), revoke_tx);
+        Ok(token)
+    }
+
+    /// 吊销令牌并断开使用该令牌的连接，返回令牌是否存在
+    pub fn revoke_token(&self, token: &str) -> bool {
+        let revoked = self
+            .tokens
+            .lock()
+            .ok()
+            .and_then(|mut tokens| tokens.remove(token));
+        if let Ok(mut default_token) = self.default_token.lock() {
+            if default_token.as_deref() == Some(token) {
+                *default_token = None;
+            }
+        }
+        match revoked {
+            Some(revoke_tx) => {
+                let _ = revoke_tx.send(true);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn serve(
+    listener: TcpListener,
+    context: BridgeContext,
+    tokens: Tokens,
+    connections: Arc<AtomicUsize>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = stop_rx.changed() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("WebSocket bridge accept failed: {}", e);
+                    continue;
+                }
+            },
+        };
+        let context = context.clone();
+        let tokens = tokens.clone();
+        let connections = Arc::clone(&connections);
+        let stop_rx = stop_rx.clone();
+        async_runtime::spawn(async move {
+            connections.fetch_add(1, Ordering::Relaxed);
+            handle_connection(stream, peer, context, tokens, stop_rx).await;
+            connections.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+/// 从查询参数（URL 编码）或 Authorization 头取出令牌
+fn request_token(request: &Request) -> Option<String> {
+    let from_query = request.uri().query().and_then(|query| {
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "token")
+            .map(|(_, token)| token.into_owned())
+    });
+    from_query.or_else(|| {
+        request
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+    })
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    peer: SocketAddr,
+    context: BridgeContext,
+    tokens: Tokens,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut revoke_rx = None;
+    // 握手回调的签名由 tungstenite 决定
+    #[allow(clippy::result_large_err)]
+    let authorize = |request: &Request, response: Response| {
+        revoke_rx = request_token(request).and_then(|token| {
+            tokens
+                .lock()
+                .ok()
+                .and_then(|tokens| tokens.get(&token).map(|revoke_tx| revoke_tx.subscribe()))
+        });
+        match revoke_rx {
+            Some(_) => Ok(response),
+            None => {
+                let mut response = ErrorResponse::new(Some("invalid token".to_string()));
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(response)
+            }
+        }
+    };
+    let ws = match tokio_tungstenite::accept_hdr_async(stream, authorize).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!("WebSocket handshake from {} rejected: {}", peer, e);
+            return;
+        }
+    };
+    let Some(mut revoke_rx) = revoke_rx else {
+        return;
+    };
+    info!("WebSocket client {} connected", peer);
+
+    let (mut sink, mut source) = ws.split();
+    let (out_tx, mut out_rx) = mpsc::channel::<String>(OUTBOX_LEN);
+    let subscriptions = Arc::new(Mutex::new(Vec::new()));
+
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            _ = revoke_rx.changed() => {
+                info!("WebSocket client {} token revoked", peer);
+                break;
+            }
+            Some(text) = out_rx.recv() => {
+                if sink.send(Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = source.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    // 指令可能耗时，单独执行，避免阻塞上报推送
+                    let connection = Connection {
+                        context: context.clone(),
+                        out_tx: out_tx.clone(),
+                        subscriptions: Arc::clone(&subscriptions),
+                    };
+                    let text = text.to_string();
+                    async_runtime::spawn(async move {
+                        let reply = connection.handle(&text).await;
+                        let _ = connection.out_tx.send(reply).await;
+                    });
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    warn!("WebSocket client {} error: {}", peer, e);
+                    break;
+                }
+            },
+        }
+    }
+
+    let _ = sink.close().await;
+    let ids = subscriptions
+        .lock()
+        .map(|mut ids| std::mem::take(&mut *ids))
+        .unwrap_or_default();
+    for id in ids {
+        context.reports.unsubscribe(id);
+    }
+    info!("WebSocket client {} disconnected", peer);
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Op {
+    Call {
+        method: String,
+        #[serde(default)]
+        params: Value,
+    },
+    Subscribe {
+        arm: ArmAddress,
+        #[serde(default)]
+        kind: ReportKind,
+        rate_hz: Option<f64>,
+    },
+    Unsubscribe {
+        subscription: SubscriptionId,
+    },
+    Sessions,
+}
+
+#[derive(Serialize)]
+struct Reply {
+    id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+#[derive(Serialize)]
+struct Push<'a> {
+    subscription: SubscriptionId,
+    report: &'a ArmReport,
+}
+
+/// 单个连接上执行请求所需的上下文
+struct Connection {
+    context: BridgeContext,
+    out_tx: mpsc::Sender<String>,
+    subscriptions: Arc<Mutex<Vec<SubscriptionId>>>,
+}
+
+impl Connection {
+    async fn handle(&self, text: &str) -> String {
+        let request: Result<Value, _> = serde_json::from_str(text);
+        let id = request
+            .as_ref()
+            .ok()
+            .and_then(|request| request.get("id"))
+            .and_then(Value::as_u64);
+        let result = match request {
+            Ok(request) => match serde_json::from_value::<Op>(request) {
+                Ok(op) => self.execute(op).await,
+                Err(e) => Err(AppError::InvalidArgument(e.to_string())),
+            },
+            Err(e) => Err(AppError::InvalidArgument(e.to_string())),
+        };
+        let reply = match result {
+            Ok(result) => Reply {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => Reply {
+                id,
+                result: None,
+                error: Some(error),
+            },
+        };
+        serde_json::to_string(&reply).unwrap_or_default()
+    }
+
+    async fn execute(&self, op: Op) -> Result<Value, AppError> {
+        match op {
+            Op::Call { method, params } => call(&self.context.arms, &method, &params).await,
+            Op::Subscribe { arm, kind, rate_hz } => {
+                let out_tx = self.out_tx.clone();
+                let id = self.context.reports.subscribe(
+                    arm.report_addr(kind)?,
+                    kind,
+                    rate_hz.unwrap_or(DEFAULT_RATE_HZ),
+                    move |subscription, report| {
+                        let Ok(text) = serde_json::to_string(&Push {
+                            subscription,
+                            report,
+                        }) else {
+                            return true;
+                        };
+                        // 客户端读得慢时丢帧，连接关闭时结束订阅
+                        !matches!(
+                            out_tx.try_send(text),
+                            Err(mpsc::error::TrySendError::Closed(_))
+                        )
+                    },
+                )?;
+                if let Ok(mut subscriptions) = self.subscriptions.lock() {
+                    subscriptions.push(id);
+                }
+                to_value(id)
+            }
+            Op::Unsubscribe { subscription } => {
+                // 只能取消本连接的订阅
+                let owned = self
+                    .subscriptions
+                    .lock()
+                    .map(|mut ids| {
+                        let owned = ids.contains(&subscription);
+                        ids.retain(|id| *id != subscription);
+                        owned
+                    })
+                    .unwrap_or(false);
+                to_value(owned && self.context.reports.unsubscribe(subscription))
+            }
+            Op::Sessions => to_value(self.context.sessions.list()),
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, AppError> {
+    serde_json::to_value(value).map_err(|e| AppError::Protocol(e.to_string()))
+}
+
+/// 从 `params` 中取出字段
+fn param<T: serde::de::DeserializeOwned>(params: &Value, name: &str) -> Result<T, AppError> {
+    let value = params.get(name).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| AppError::InvalidArgument(format!("params.{}: {}", name, e)))
+}
+
+/// 执行一条指令，方法名与 `arm_*` 命令对应
+async fn call(arms: &XArmClients, method: &str, params: &Value) -> Result<Value, AppError> {
+    let arm: ArmAddress = param(params, "arm")?;
+    let client = arms.get(&arm)?;
+    let mut client = client.lock().await;
+    match method {
+        "get_version" => to_value(client.get_version().await?),
+        "get_robot_sn" => to_value(client.get_robot_sn().await?),
+        "get_state" => to_value(client.get_state().await?),
+        "get_error" => to_value(client.get_error().await?),
+        "get_position" => to_value(client.get_position().await?),
+        "motion_enable" => to_value(client.motion_enable(param(params, "enable")?).await?),
+        "set_mode" => to_value(client.set_mode(param(params, "mode")?).await?),
+        "set_state" => to_value(client.set_state(param(params, "state")?).await?),
+        "clean_error" => {
+            client.clean_error().await?;
+            to_value(client.clean_warn().await?)
+        }
+        "read_register" => {
+            let data: Option<Vec<u8>> = param(params, "params")?;
+            let register = param(params, "register")?;
+            to_value(
+                client
+                    .read_register(register, &data.unwrap_or_default())
+                    .await?,
+            )
+        }
+        "write_register" => {
+            let data: Vec<u8> = param(params, "params")?;
+            to_value(
+                client
+                    .write_register(param(params, "register")?, &data)
+                    .await?,
+            )
+        }
+        _ => Err(AppError::InvalidArgument(format!(
+            "unknown method {}",
+            method
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    use super::*;
+    use crate::packages::latency::LatencyTracker;
+    use crate::packages::simulator::arm::SimArm;
+    use crate::packages::simulator::{command, SimConfig};
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    fn context() -> BridgeContext {
+        BridgeContext {
+            arms: XArmClients::new(),
+            reports: ReportStreams::new(),
+            sessions: ArmSessions::new(LatencyTracker::new()),
+        }
+    }
+
+    fn config(port: u16) -> BridgeConfig {
+        BridgeConfig {
+            port,
+            ..Default::default()
+        }
+    }
+
+    async fn start() -> (WebSocketBridge, BridgeStatus) {
+        let bridge = WebSocketBridge::new();
+        let status = bridge.start(config(0), context()).await.unwrap();
+        (bridge, status)
+    }
+
+    async fn connect(status: &BridgeStatus, token: &str) -> Client {
+        let (ws, _) = connect_async(format!("{}?token={}", status.url, token))
+            .await
+            .unwrap();
+        ws
+    }
+
+    /// 发送一条请求并等待应答
+    async fn request(ws: &mut Client, text: &str) -> Value {
+        ws.send(Message::text(text)).await.unwrap();
+        let reply = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(reply.to_text().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reads_token_from_query_or_header() {
+        let request = |uri: &str| Request::builder().uri(uri).body(()).unwrap();
+        assert_eq!(
+            request_token(&request("/?a=1&token=ab%2Bc%20d")).as_deref(),
+            Some("ab+c d")
+        );
+        assert_eq!(request_token(&request("/?tokens=x")), None);
+
+        let mut with_header = request("/");
+        with_header
+            .headers_mut()
+            .insert("authorization", "Bearer  abc ".parse().unwrap());
+        assert_eq!(request_token(&with_header).as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn status_url_has_no_token() {
+        let (bridge, status) = start().await;
+        assert!(!status.token.is_empty());
+        assert_eq!(status.url, format!("ws://{}/", status.addr));
+        assert!(!status.url.contains(&status.token));
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn authenticates_connections() {
+        let (bridge, status) = start().await;
+
+        assert!(connect_async(status.url.as_str()).await.is_err());
+        assert!(connect_async(format!("{}?token=wrong", status.url))
+            .await
+            .is_err());
+
+        let mut ws = connect(&status, &status.token).await;
+        assert_eq!(
+            request(&mut ws, r#"{"id": 1, "op": "sessions"}"#).await,
+            serde_json::json!({"id": 1, "result": []})
+        );
+
+        let token = bridge.create_token().unwrap();
+        let mut with_header = status.url.as_str().into_client_request().unwrap();
+        with_header
+            .headers_mut()
+            .insert(AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+        let (mut by_header, _) = connect_async(with_header).await.unwrap();
+
+        // 吊销后使用该令牌的连接被断开，其他连接不受影响
+        assert!(bridge.revoke_token(&token));
+        let closed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match by_header.next().await {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => {}
+                }
+            }
+        })
+        .await;
+        assert!(closed.is_ok());
+        assert!(!bridge.revoke_token(&token));
+        assert_eq!(
+            request(&mut ws, r#"{"id": 2, "op": "sessions"}"#).await["id"],
+            2
+        );
+
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn answers_protocol_errors() {
+        let (bridge, status) = start().await;
+        let mut ws = connect(&status, &status.token).await;
+
+        let reply = request(&mut ws, "not json").await;
+        assert_eq!(reply["id"], Value::Null);
+        assert_eq!(reply["error"]["kind"], "invalid_argument");
+
+        let reply = request(&mut ws, r#"{"id": 3, "op": "reboot"}"#).await;
+        assert_eq!(reply["id"], 3);
+        assert_eq!(reply["error"]["kind"], "invalid_argument");
+
+        let reply = request(
+            &mut ws,
+            r#"{"id": 4, "op": "call", "method": "fly", "params": {"arm": {"ip": "127.0.0.1"}}}"#,
+        )
+        .await;
+        assert_eq!(reply["error"]["kind"], "invalid_argument");
+
+        let reply = request(
+            &mut ws,
+            r#"{"id": 5, "op": "unsubscribe", "subscription": 42}"#,
+        )
+        .await;
+        assert_eq!(reply, serde_json::json!({"id": 5, "result": false}));
+
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn calls_arm_methods() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sim = SimConfig {
+            animate: false,
+            ..Default::default()
+        };
+        let arm = Arc::new(Mutex::new(SimArm::new(&sim)));
+        let (_stop_sim, stop_rx) = watch::channel(false);
+        tokio::spawn(command::serve(listener, sim, arm, stop_rx));
+
+        let (bridge, status) = start().await;
+        let mut ws = connect(&status, &status.token).await;
+        let arm = format!(r#"{{"ip": "127.0.0.1", "command_port": {}}}"#, port);
+
+        let reply = request(
+            &mut ws,
+            &format!(
+                r#"{{"id": 1, "op": "call", "method": "get_robot_sn", "params": {{"arm": {}}}}}"#,
+                arm
+            ),
+        )
+        .await;
+        assert_eq!(reply["result"], "XI1306SIM00001");
+
+        let reply = request(
+            &mut ws,
+            &format!(r#"{{"id": 2, "op": "call", "method": "set_state", "params": {{"arm": {}, "state": 4}}}}"#, arm),
+        )
+        .await;
+        assert!(reply.get("error").is_none(), "{}", reply);
+
+        let reply = request(
+            &mut ws,
+            &format!(
+                r#"{{"id": 3, "op": "call", "method": "get_state", "params": {{"arm": {}}}}}"#,
+                arm
+            ),
+        )
+        .await;
+        assert_eq!(reply["result"], 4);
+
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn restarts_on_the_same_port() {
+        let (bridge, status) = start().await;
+        let port: SocketAddr = status.addr.parse().unwrap();
+        let _ws = connect(&status, &status.token).await;
+
+        for _ in 0..3 {
+            let restarted = bridge.start(config(port.port()), context()).await.unwrap();
+            assert_eq!(restarted.addr, status.addr);
+        }
+        bridge.stop();
+    }
+}
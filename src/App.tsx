@@ -6,6 +6,8 @@ import { BrowserRouter } from "react-router-dom";
 import RouterConfig from "./router";
 import { useEffect } from "react";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Provider } from "react-redux";
 import { store } from "./store";
 import { ThemeProvider } from "./pages/components/theme/ThemeProvider";
@@ -32,6 +34,16 @@ function App() {
     };
   }, []);
 
+  // 回应后端的 IPC 延迟探测（latency_probe -> latency_pong）
+  useEffect(() => {
+    const unListen = listen<number>("latency_probe", (event) => {
+      invoke("latency_pong", { rid: event.payload }).catch(() => { });
+    });
+    return () => {
+      unListen.then((un) => un());
+    };
+  }, []);
+
   // useKeyPress("ctrl.alt.i", () => {
   //   invoke("open_devtools")
   // });
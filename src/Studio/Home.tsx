@@ -16,6 +16,15 @@ type ArmIpsIntro = {
     version: string;
     addr_type: string;
 }
+
+type ServiceEndpoint = {
+    name: string;
+    port: number;
+}
+
+// 控制器上 Studio 网页的端口
+const CONTROLLER_PORT = 18333;
+
 export default function StudioHome() {
     const navigate = useNavigate();
     const { t, i18n } = useTranslation("home");
@@ -25,6 +34,8 @@ export default function StudioHome() {
     const [armIps, setArmIps] = useState<ArmIpsIntro[]>([]);
     const [showIpList, setShowIpList] = useState<boolean>(false);
     const channel = useRef<string>(localStorage.getItem("channel") || "prod");
+    // 本机 tool_service 的端口，启动时分配，从 get_service_endpoints 读取
+    const localPort = useRef<number>(CONTROLLER_PORT);
     // 延迟显示“正在连接中...”的计时器
     const connectTipTimerRef = useRef<number | null>(null);
     // 自动清除 connectTip 的计时器
@@ -42,6 +53,18 @@ export default function StudioHome() {
         };
     }, []);
 
+    useEffect(() => {
+        invoke<ServiceEndpoint[]>("get_service_endpoints").then((endpoints) => {
+            const toolService = endpoints.find((endpoint) => endpoint.name === "tool_service");
+            if (toolService) {
+                localPort.current = toolService.port;
+            }
+        }).catch((e) => {
+            // 查询失败时沿用默认端口
+            console.log('get_service_endpoints failed', e);
+        });
+    }, []);
+
     useEffect(() => {
         if (selectedIp) {
             localStorage.setItem("selectedIp", selectedIp);
@@ -85,7 +108,7 @@ export default function StudioHome() {
 
     function genAddress() {
         let selected_ip = "127.0.0.1";
-        let selected_port = 18333;
+        let selected_port = localPort.current;
         // 将 i18n 当前语言映射为后端使用的参数值
         const currentLang = i18n.language || "cn";
         const langParam = currentLang.startsWith("en") ? "en" : "cn";
@@ -104,7 +127,9 @@ export default function StudioHome() {
 
         if (match) {
             selected_ip = match[1];
-            selected_port = match[2] ? +match[2] : 18333;
+            // 未写端口时，本机用分配的端口，控制器用固定端口
+            const defaultPort = selected_ip === "127.0.0.1" ? localPort.current : CONTROLLER_PORT;
+            selected_port = match[2] ? +match[2] : defaultPort;
         }
 
         return {
@@ -137,7 +162,7 @@ export default function StudioHome() {
         console.log('selectedIp===>', selectedIp);
         console.log('===========================================');
 
-        if (port === 18333) {
+        if (port === localPort.current || port === CONTROLLER_PORT) {
             const startTime = performance.now();
 
             if (connectTipTimerRef.current !== null) {